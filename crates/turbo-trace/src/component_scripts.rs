@@ -0,0 +1,202 @@
+use std::ops::Range;
+
+/// Blanks out everything in a Vue, Svelte or MDX file except its scripts, so
+/// the result can be parsed as a JavaScript or TypeScript module. Characters
+/// are replaced with spaces rather than removed, so positions in the result
+/// are positions in the original file.
+///
+/// Vue and Svelte scripts are the contents of `<script>` blocks, and MDX
+/// scripts are its `import` and `export` statements. Along with the scripts,
+/// returns the `lang` of the first `<script>` block that sets one, e.g. `ts`.
+pub fn extract_component_scripts<'a>(
+    extension: Option<&str>,
+    contents: &'a str,
+) -> (String, Option<&'a str>) {
+    let (ranges, lang) = match extension {
+        Some("mdx") => (mdx_esm_ranges(contents), None),
+        _ => script_block_ranges(contents),
+    };
+
+    let scripts = contents
+        .char_indices()
+        .map(|(index, c)| {
+            if c == '\n' || ranges.iter().any(|range| range.contains(&index)) {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect();
+
+    (scripts, lang)
+}
+
+/// Finds the contents of each `<script>` block. Blocks with a `src` attribute
+/// instead of contents are skipped.
+fn script_block_ranges(contents: &str) -> (Vec<Range<usize>>, Option<&str>) {
+    let mut ranges = Vec::new();
+    let mut lang = None;
+    let mut index = 0;
+
+    while let Some(start) = contents[index..].find("<script") {
+        let attributes_start = index + start + "<script".len();
+        // Skip tags that only start with `script`, e.g. `<scripts>`
+        if !contents[attributes_start..].starts_with(|c: char| c == '>' || c.is_whitespace()) {
+            index = attributes_start;
+            continue;
+        }
+        let Some(tag_end) = contents[attributes_start..]
+            .find('>')
+            .map(|end| attributes_start + end)
+        else {
+            break;
+        };
+        let attributes = &contents[attributes_start..tag_end];
+        let body_start = tag_end + 1;
+        if attributes.ends_with('/') {
+            index = body_start;
+            continue;
+        }
+
+        let body_end = contents[body_start..]
+            .find("</script>")
+            .map_or(contents.len(), |end| body_start + end);
+        if lang.is_none() {
+            lang = attribute(attributes, "lang");
+        }
+        ranges.push(body_start..body_end);
+        index = body_end;
+    }
+
+    (ranges, lang)
+}
+
+/// Finds the ESM blocks of an MDX file, which start with `import` or `export`
+/// at the start of a line and run until the next blank line. Code blocks are
+/// skipped, since imports in them are only examples.
+fn mdx_esm_ranges(contents: &str) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut in_code_block = false;
+    let mut in_esm = false;
+    let mut line_start = 0;
+
+    for line in contents.split_inclusive('\n') {
+        let line_range = line_start..line_start + line.len();
+        line_start = line_range.end;
+
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            in_esm = false;
+        } else if !in_esm {
+            in_esm = ["import", "export"].iter().any(|keyword| {
+                line.strip_prefix(keyword)
+                    .is_some_and(|rest| rest.starts_with(|c: char| !c.is_alphanumeric()))
+            });
+        }
+
+        if in_esm {
+            match ranges.last_mut() {
+                Some(range) if range.end == line_range.start => range.end = line_range.end,
+                _ => ranges.push(line_range),
+            }
+        }
+    }
+
+    ranges
+}
+
+/// Reads the value of a quoted attribute, e.g. `ts` for `lang="ts"`.
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    attributes.split_whitespace().find_map(|attribute| {
+        let value = attribute.strip_prefix(name)?.strip_prefix('=')?;
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        value[1..].split(quote).next()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::extract_component_scripts;
+
+    #[test]
+    fn test_vue_scripts() {
+        let contents = r#"<template>
+  <img src="./logo.png" />
+</template>
+
+<script setup lang="ts">
+import Button from "./Button.vue";
+</script>
+
+<style scoped>
+@import "./theme.css";
+</style>"#;
+
+        let (scripts, lang) = extract_component_scripts(Some("vue"), contents);
+        assert_eq!(lang, Some("ts"));
+        assert_eq!(scripts.len(), contents.len());
+        assert_eq!(scripts.trim(), r#"import Button from "./Button.vue";"#);
+
+        let import = contents.find("import Button").unwrap();
+        assert_eq!(
+            &scripts[import..import + "import Button".len()],
+            "import Button"
+        );
+    }
+
+    #[test]
+    fn test_svelte_scripts() {
+        let contents = r#"<script context="module">
+  export { load } from "./load.js";
+</script>
+<script src="./external.js" />
+<scripts>not a script</scripts>
+<script>
+  import Counter from "./Counter.svelte";
+</script>
+
+<Counter />"#;
+
+        let (scripts, lang) = extract_component_scripts(Some("svelte"), contents);
+        assert_eq!(lang, None);
+        assert_eq!(
+            scripts.split_whitespace().collect::<Vec<_>>().join(" "),
+            r#"export { load } from "./load.js"; import Counter from "./Counter.svelte";"#
+        );
+    }
+
+    #[test]
+    fn test_mdx_scripts() {
+        let contents = r#"import { Chart } from "./chart.js";
+export const meta = {
+  title: "Example",
+};
+
+# Important things
+
+```js
+import { example } from "./example.js";
+```
+
+exported text isn't an export
+
+<Chart />"#;
+
+        let (scripts, lang) = extract_component_scripts(Some("mdx"), contents);
+        assert_eq!(lang, None);
+        assert_eq!(
+            scripts.trim(),
+            r#"import { Chart } from "./chart.js";
+export const meta = {
+  title: "Example",
+};"#
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use std::ops::Range;
+
+/// Finds the files a CSS file refers to, via `@import` and `url()`, along
+/// with Sass's `@use` and `@forward`. Returns each path along with the byte
+/// range of the rule or `url()` it came from.
+///
+/// This isn't a full CSS parser, but it skips comments and strings so that
+/// commented out imports aren't picked up. URLs with a scheme (e.g. `data:` or
+/// `https:`), absolute paths and fragment references are skipped since they
+/// don't refer to files in the repository.
+pub fn find_css_imports(contents: &str) -> Vec<(String, Range<usize>)> {
+    let mut imports = Vec::new();
+    let bytes = contents.as_bytes();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let rest = &contents[index..];
+        if rest.starts_with("/*") {
+            index = rest
+                .find("*/")
+                .map_or(bytes.len(), |end| index + end + "*/".len());
+        } else if let Some(rule) = ["@import", "@use", "@forward"]
+            .into_iter()
+            .find(|rule| rest.starts_with(rule))
+        {
+            // `@import url(...)` is picked up as a `url()` on the next iteration
+            let start = index;
+            index += rule.len();
+            index += whitespace_len(&contents[index..]);
+            if let Some((import, end)) = read_string(contents, index) {
+                push_import(&mut imports, import, start..end);
+                index = end;
+            }
+        } else if rest.starts_with("url(")
+            && !contents[..index]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            let start = index;
+            index += "url(".len();
+            index += whitespace_len(&contents[index..]);
+            let (import, end) = match read_string(contents, index) {
+                Some((import, end)) => (import, end),
+                None => {
+                    let end = contents[index..]
+                        .find(')')
+                        .map_or(bytes.len(), |end| index + end);
+                    (contents[index..end].trim(), end)
+                }
+            };
+            let end = contents[end..]
+                .find(')')
+                .map_or(bytes.len(), |close| end + close + 1);
+            push_import(&mut imports, import, start..end);
+            index = end;
+        } else if let Some((_, end)) = read_string(contents, index) {
+            index = end;
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    imports
+}
+
+fn push_import(imports: &mut Vec<(String, Range<usize>)>, import: &str, range: Range<usize>) {
+    if import.is_empty()
+        || import.starts_with('/')
+        || import.starts_with('#')
+        || import.contains(':')
+    {
+        return;
+    }
+    // Drop query strings and fragments, e.g. `font.woff2?v=1#iefix`
+    let import = import.split(['?', '#']).next().unwrap_or(import);
+    // `~` is how webpack refers to packages in CSS
+    let import = import.strip_prefix('~').unwrap_or(import);
+
+    imports.push((import.to_string(), range));
+}
+
+fn whitespace_len(contents: &str) -> usize {
+    contents.len() - contents.trim_start().len()
+}
+
+/// Reads a quoted string starting at `index`, returning its contents and the
+/// index after the closing quote.
+fn read_string(contents: &str, index: usize) -> Option<(&str, usize)> {
+    let quote = contents[index..].chars().next()?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+    let start = index + 1;
+    let mut escaped = false;
+    for (offset, c) in contents[start..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return Some((&contents[start..start + offset], start + offset + 1)),
+            _ => {}
+        }
+    }
+
+    Some((&contents[start..], contents.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::find_css_imports;
+
+    fn paths(contents: &str) -> Vec<String> {
+        find_css_imports(contents)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    #[test]
+    fn test_import_and_url_ranges() {
+        let contents = r#"@import "reset.css";
+@import url(theme.css);
+.a { background: url("a.png"); }
+.b { background: url( 'b.png' ); }
+.c { background:url( c.png ); }"#;
+
+        let imports = find_css_imports(contents)
+            .into_iter()
+            .map(|(path, range)| (path, &contents[range]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            imports,
+            vec![
+                ("reset.css".to_string(), r#"@import "reset.css""#),
+                ("theme.css".to_string(), "url(theme.css)"),
+                ("a.png".to_string(), r#"url("a.png")"#),
+                ("b.png".to_string(), "url( 'b.png' )"),
+                ("c.png".to_string(), "url( c.png )"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skips_comments_and_strings() {
+        let contents = r#"/* @import "old.css"; url(old.png) */
+.a::before { content: "url(not-a-file.png)"; }
+.b::before { content: '@import "quoted.css"'; }
+@import "new.css";"#;
+
+        assert_eq!(paths(contents), vec!["new.css"]);
+    }
+
+    #[test]
+    fn test_skips_urls_outside_repository() {
+        let contents = r#"@import "https://example.com/remote.css";
+@import url(//example.com/protocol-relative.css);
+.a { background: url(data:image/png;base64,iVBORw0KGgo=); }
+.b { fill: url(#gradient); }
+.c { background: url("/absolute.png"); }
+.d { background: url(); }"#;
+
+        assert!(paths(contents).is_empty());
+    }
+
+    #[test]
+    fn test_strips_query_and_fragment() {
+        let contents = r#"@font-face { src: url("font.woff2?v=1#iefix"); }
+.a { background: url(icons.svg#home); }"#;
+
+        assert_eq!(paths(contents), vec!["font.woff2", "icons.svg"]);
+    }
+
+    #[test]
+    fn test_strips_package_prefix() {
+        assert_eq!(
+            paths(r#"@import "~bootstrap/dist/css/bootstrap.css";"#),
+            vec!["bootstrap/dist/css/bootstrap.css"]
+        );
+    }
+
+    #[test]
+    fn test_sass_rules() {
+        let contents = r#"@use "sass:math";
+@use "variables" as vars;
+@forward "./mixins";
+@import "theme";"#;
+
+        assert_eq!(paths(contents), vec!["variables", "./mixins", "theme"]);
+    }
+
+    #[test]
+    fn test_skips_identifiers_ending_in_url() {
+        let contents = ".a { background: myurl(a.png) my-url(b.png) my_url(c.png); }";
+
+        assert!(paths(contents).is_empty());
+    }
+}
@@ -1,5 +1,8 @@
-use swc_common::{Span, Spanned};
-use swc_ecma_ast::{Decl, ModuleDecl, Stmt};
+use swc_common::Span;
+use swc_ecma_ast::{
+    CallExpr, Callee, Expr, Lit, MemberProp, MetaPropExpr, MetaPropKind, ModuleDecl, NewExpr, Tpl,
+    TsModuleRef,
+};
 use swc_ecma_visit::{Visit, VisitWith};
 
 use crate::tracer::ImportTraceType;
@@ -15,9 +18,25 @@ pub enum ImportType {
     Value,
 }
 
+/// How to handle dynamic imports and requires of template strings with
+/// expressions, e.g. ``import(`./locales/${locale}.js`)``, which can't be
+/// resolved to a single file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+// Only constructed by the library, see the dead code note on `SeenFile`
+#[allow(dead_code)]
+pub enum TemplateImports {
+    /// Report the import as unresolved
+    #[default]
+    Report,
+    /// Skip the import
+    Ignore,
+}
+
 pub struct ImportFinder {
     import_type: ImportTraceType,
+    template_imports: TemplateImports,
     imports: Vec<(String, Span, ImportType)>,
+    unresolved_imports: Vec<(String, Span)>,
 }
 
 impl Default for ImportFinder {
@@ -30,68 +49,182 @@ impl ImportFinder {
     pub fn new(import_type: ImportTraceType) -> Self {
         Self {
             import_type,
+            template_imports: TemplateImports::default(),
             imports: Vec::new(),
+            unresolved_imports: Vec::new(),
         }
     }
 
+    pub fn with_template_imports(mut self, template_imports: TemplateImports) -> Self {
+        self.template_imports = template_imports;
+        self
+    }
+
     pub fn imports(&self) -> &[(String, Span, ImportType)] {
         &self.imports
     }
+
+    /// Imports of template strings that can't be resolved to a file. The
+    /// string has a `*` in place of each expression in the template.
+    pub fn unresolved_imports(&self) -> &[(String, Span)] {
+        &self.unresolved_imports
+    }
+
+    fn add_import(&mut self, import: &str, span: Span, type_only: bool) {
+        let import_type = if type_only {
+            ImportType::Type
+        } else {
+            ImportType::Value
+        };
+        match self.import_type {
+            ImportTraceType::All => {}
+            ImportTraceType::Types if type_only => {}
+            ImportTraceType::Values if !type_only => {}
+            _ => return,
+        }
+        self.imports.push((import.to_string(), span, import_type));
+    }
+
+    /// Adds the argument of a dynamic import, `require` or `require.resolve`
+    fn add_dynamic_import(&mut self, call_expr: &CallExpr) {
+        let Some(arg) = call_expr.args.first() else {
+            return;
+        };
+        match &*arg.expr {
+            Expr::Lit(Lit::Str(lit_str)) => {
+                self.add_import(&lit_str.value, call_expr.span, false);
+            }
+            Expr::Tpl(tpl) => {
+                if let Some(import) = Self::template_string(tpl) {
+                    self.add_import(&import, call_expr.span, false);
+                } else if self.template_imports == TemplateImports::Report
+                    && self.import_type != ImportTraceType::Types
+                {
+                    let import = tpl
+                        .quasis
+                        .iter()
+                        .map(|quasi| &*quasi.raw)
+                        .collect::<Vec<_>>()
+                        .join("*");
+                    self.unresolved_imports.push((import, call_expr.span));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds the URL of `new URL("./worker.js", import.meta.url)`, which
+    /// bundlers treat as an import
+    fn add_url_import(&mut self, new_expr: &NewExpr) {
+        let (Expr::Ident(callee), Some([url, base, ..])) =
+            (&*new_expr.callee, new_expr.args.as_deref())
+        else {
+            return;
+        };
+        if callee.sym != *"URL" || !Self::is_import_meta_url(&base.expr) {
+            return;
+        }
+        let url = match &*url.expr {
+            Expr::Lit(Lit::Str(lit_str)) => lit_str.value.to_string(),
+            Expr::Tpl(tpl) => match Self::template_string(tpl) {
+                Some(url) => url,
+                None => return,
+            },
+            _ => return,
+        };
+        // URLs with a scheme, e.g. `https:`, don't refer to files
+        if url.contains(':') {
+            return;
+        }
+        // The URL is relative to the module even without a leading `./`
+        let import = if url.starts_with('.') || url.starts_with('/') {
+            url
+        } else {
+            format!("./{url}")
+        };
+        self.add_import(&import, new_expr.span, false);
+    }
+
+    fn is_import_meta_url(expr: &Expr) -> bool {
+        let Expr::Member(member) = expr else {
+            return false;
+        };
+        matches!(
+            &*member.obj,
+            Expr::MetaProp(MetaPropExpr {
+                kind: MetaPropKind::ImportMeta,
+                ..
+            })
+        ) && matches!(&member.prop, MemberProp::Ident(prop) if prop.sym == *"url")
+    }
+
+    /// Returns the string for a template without any expressions
+    fn template_string(tpl: &Tpl) -> Option<String> {
+        match tpl.quasis.as_slice() {
+            [quasi] if tpl.exprs.is_empty() => Some(
+                quasi
+                    .cooked
+                    .as_ref()
+                    .map_or_else(|| quasi.raw.to_string(), |cooked| cooked.to_string()),
+            ),
+            _ => None,
+        }
+    }
 }
 
 impl Visit for ImportFinder {
     fn visit_module_decl(&mut self, decl: &ModuleDecl) {
-        if let ModuleDecl::Import(import) = decl {
-            let import_type = if import.type_only {
-                ImportType::Type
-            } else {
-                ImportType::Value
-            };
-            match self.import_type {
-                ImportTraceType::All => {
-                    self.imports
-                        .push((import.src.value.to_string(), import.span, import_type));
-                }
-                ImportTraceType::Types if import.type_only => {
-                    self.imports
-                        .push((import.src.value.to_string(), import.span, import_type));
+        match decl {
+            ModuleDecl::Import(import) => {
+                self.add_import(&import.src.value, import.span, import.type_only);
+            }
+            // `export * from "./foo"`
+            ModuleDecl::ExportAll(export) => {
+                self.add_import(&export.src.value, export.span, export.type_only);
+            }
+            // `export { foo } from "./foo"`
+            ModuleDecl::ExportNamed(export) => {
+                if let Some(src) = &export.src {
+                    self.add_import(&src.value, export.span, export.type_only);
                 }
-                ImportTraceType::Values if !import.type_only => {
-                    self.imports
-                        .push((import.src.value.to_string(), import.span, import_type));
+            }
+            // `import foo = require("./foo")`
+            ModuleDecl::TsImportEquals(import) => {
+                if let TsModuleRef::TsExternalModuleRef(module_ref) = &import.module_ref {
+                    self.add_import(&module_ref.expr.value, import.span, import.is_type_only);
                 }
-                _ => {}
             }
+            _ => {}
         }
+        decl.visit_children_with(self);
     }
 
-    fn visit_stmt(&mut self, stmt: &Stmt) {
-        if let Stmt::Decl(Decl::Var(var_decl)) = stmt {
-            for decl in &var_decl.decls {
-                if let Some(init) = &decl.init {
-                    if let swc_ecma_ast::Expr::Call(call_expr) = &**init {
-                        if let swc_ecma_ast::Callee::Expr(expr) = &call_expr.callee {
-                            if let swc_ecma_ast::Expr::Ident(ident) = &**expr {
-                                if ident.sym == *"require" {
-                                    if let Some(arg) = call_expr.args.first() {
-                                        if let swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Str(
-                                            lit_str,
-                                        )) = &*arg.expr
-                                        {
-                                            self.imports.push((
-                                                lit_str.value.to_string(),
-                                                expr.span(),
-                                                ImportType::Value,
-                                            ));
-                                        }
-                                    }
-                                }
-                            }
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        match &call_expr.callee {
+            // `import("./foo")`
+            Callee::Import(_) => self.add_dynamic_import(call_expr),
+            Callee::Expr(expr) => match &**expr {
+                // `require("./foo")`
+                Expr::Ident(ident) if ident.sym == *"require" => self.add_dynamic_import(call_expr),
+                // `require.resolve("./foo")`
+                Expr::Member(member) => {
+                    if let (Expr::Ident(obj), MemberProp::Ident(prop)) =
+                        (&*member.obj, &member.prop)
+                    {
+                        if obj.sym == *"require" && prop.sym == *"resolve" {
+                            self.add_dynamic_import(call_expr);
                         }
                     }
                 }
-            }
+                _ => {}
+            },
+            _ => {}
         }
-        stmt.visit_children_with(self);
+        call_expr.visit_children_with(self);
+    }
+
+    fn visit_new_expr(&mut self, new_expr: &NewExpr) {
+        self.add_url_import(new_expr);
+        new_expr.visit_children_with(self);
     }
 }
@@ -1,6 +1,8 @@
 #![deny(clippy::all)]
+mod component_scripts;
+mod css_import_finder;
 mod import_finder;
 mod tracer;
 
-pub use import_finder::{ImportFinder, ImportType};
-pub use tracer::{ImportTraceType, TraceError, TraceResult, Tracer};
+pub use import_finder::{ImportFinder, ImportType, TemplateImports};
+pub use tracer::{FileKind, ImportTraceType, TraceError, TraceResult, Tracer};
@@ -1,3 +1,4 @@
+mod css_import_finder;
 mod import_finder;
 mod tracer;
 
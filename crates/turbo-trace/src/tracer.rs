@@ -4,7 +4,8 @@ use camino::{Utf8Path, Utf8PathBuf};
 use globwalk::WalkType;
 use miette::{Diagnostic, Report, SourceSpan};
 use oxc_resolver::{
-    EnforceExtension, ResolveError, ResolveOptions, Resolver, TsconfigOptions, TsconfigReferences,
+    EnforceExtension, Resolution, ResolveError, ResolveOptions, Resolver, TsconfigOptions,
+    TsconfigReferences,
 };
 use swc_common::{
     comments::SingleThreadedComments,
@@ -20,7 +21,11 @@ use tokio::task::JoinSet;
 use tracing::{debug, error};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, PathError};
 
-use crate::import_finder::ImportFinder;
+use crate::{
+    component_scripts::extract_component_scripts,
+    css_import_finder::find_css_imports,
+    import_finder::{ImportFinder, TemplateImports},
+};
 
 #[derive(Debug, Default)]
 pub struct SeenFile {
@@ -30,6 +35,39 @@ pub struct SeenFile {
     // https://github.com/rust-lang/rust/issues/95513
     #[allow(dead_code)]
     pub ast: Option<swc_ecma_ast::Module>,
+    #[allow(dead_code)]
+    pub kind: FileKind,
+}
+
+/// The kind of a traced file, which determines how its imports are found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileKind {
+    /// JavaScript or TypeScript, parsed with swc
+    #[default]
+    Script,
+    /// Vue, Svelte or MDX, where the embedded scripts are parsed with swc
+    Component,
+    /// CSS, Sass or Less, where `@import` and `url()` refer to other files
+    Style,
+    /// JSON, which doesn't import anything
+    Json,
+    /// Any other file, e.g. an image or a font, which doesn't import anything
+    Asset,
+}
+
+impl FileKind {
+    pub fn from_path(path: &AbsoluteSystemPath) -> Self {
+        match path.extension() {
+            // Files without an extension are usually scripts, e.g. `bin/cli`
+            None | Some("js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts") => {
+                FileKind::Script
+            }
+            Some("vue" | "svelte" | "mdx") => FileKind::Component,
+            Some("css" | "scss" | "sass" | "less") => FileKind::Style,
+            Some("json") => FileKind::Json,
+            Some(_) => FileKind::Asset,
+        }
+    }
 }
 
 pub struct Tracer {
@@ -39,6 +77,7 @@ pub struct Tracer {
     cwd: AbsoluteSystemPathBuf,
     errors: Vec<TraceError>,
     import_type: ImportTraceType,
+    template_imports: TemplateImports,
 }
 
 #[derive(Clone, Debug, Error, Diagnostic)]
@@ -62,6 +101,17 @@ pub enum TraceError {
         #[help]
         reason: String,
     },
+    #[error("failed to resolve import of template `{import}` in `{file_path}`")]
+    TemplateImport {
+        import: String,
+        file_path: String,
+        #[label("import here")]
+        span: SourceSpan,
+        #[source_code]
+        text: String,
+        #[help]
+        reason: String,
+    },
     #[error("failed to walk files")]
     GlobError(Arc<globwalk::WalkError>),
 }
@@ -132,6 +182,7 @@ impl Tracer {
             ts_config,
             cwd,
             import_type: ImportTraceType::All,
+            template_imports: TemplateImports::default(),
             errors: Vec::new(),
             source_map: Arc::new(SourceMap::default()),
         }
@@ -142,6 +193,12 @@ impl Tracer {
         self.import_type = import_type;
     }
 
+    // Only called by the library, see the dead code note on `SeenFile`
+    #[allow(dead_code)]
+    pub fn set_template_imports(&mut self, template_imports: TemplateImports) {
+        self.template_imports = template_imports;
+    }
+
     #[tracing::instrument(skip(resolver, source_map))]
     pub async fn get_imports_from_file(
         source_map: &SourceMap,
@@ -149,6 +206,7 @@ impl Tracer {
         resolver: &Resolver,
         file_path: &AbsoluteSystemPath,
         import_type: ImportTraceType,
+        template_imports: TemplateImports,
     ) -> Option<(Vec<AbsoluteSystemPathBuf>, SeenFile)> {
         // Read the file content
         let Ok(file_content) = tokio::fs::read_to_string(&file_path).await else {
@@ -156,57 +214,53 @@ impl Tracer {
             return None;
         };
 
-        let comments = SingleThreadedComments::default();
-
-        let source_file = source_map.new_source_file(
-            FileName::Custom(file_path.to_string()).into(),
-            file_content.clone(),
-        );
-
-        let syntax = if matches!(file_path.extension(), Some("ts") | Some("tsx")) {
-            Syntax::Typescript(TsSyntax {
-                tsx: file_path.extension() == Some("tsx"),
-                decorators: true,
-                ..Default::default()
-            })
+        let kind = FileKind::from_path(file_path);
+        let (imports, ast) = if kind == FileKind::Style {
+            let imports = find_css_imports(&file_content)
+                .into_iter()
+                .map(|(import, range)| {
+                    (
+                        import,
+                        SourceSpan::new(range.start.into(), range.len().into()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            (imports, None)
         } else {
-            Syntax::Es(EsSyntax {
-                jsx: true,
-                ..Default::default()
-            })
-        };
-
-        let lexer = Lexer::new(
-            syntax,
-            EsVersion::EsNext,
-            StringInput::from(&*source_file),
-            Some(&comments),
-        );
-
-        let mut parser = Parser::new_from(Capturing::new(lexer));
-
-        // Parse the file as a module
-        let module = match parser.parse_module() {
-            Ok(module) => module,
-            Err(err) => {
-                errors.push(TraceError::ParseError(file_path.to_owned(), err));
-                return None;
-            }
+            let (script, lang) = match kind {
+                FileKind::Component => {
+                    extract_component_scripts(file_path.extension(), &file_content)
+                }
+                _ => (file_content.clone(), file_path.extension()),
+            };
+            let (imports, module) = Self::get_imports_from_script(
+                source_map,
+                errors,
+                file_path,
+                &script,
+                lang,
+                import_type,
+                template_imports,
+            )?;
+            (imports, Some(module))
         };
 
-        // Visit the AST and find imports
-        let mut finder = ImportFinder::new(import_type);
-        module.visit_with(&mut finder);
         // Convert found imports/requires to absolute paths and add them to files to
         // visit
         let mut files = Vec::new();
-        for (import, span, _) in finder.imports() {
+        for (import, span) in &imports {
             debug!("processing {} in {}", import, file_path);
             let Some(file_dir) = file_path.parent() else {
                 errors.push(TraceError::RootFile(file_path.to_owned()));
                 continue;
             };
-            match resolver.resolve(file_dir, import) {
+            let resolved = match kind {
+                FileKind::Style => {
+                    Self::resolve_style_import(resolver, file_dir, file_path.extension(), import)
+                }
+                _ => resolver.resolve(file_dir, import),
+            };
+            match resolved {
                 Ok(resolved) => {
                     debug!("resolved {:?}", resolved);
                     match resolved.into_path_buf().try_into().map_err(Arc::new) {
@@ -255,14 +309,10 @@ impl Tracer {
                     }
 
                     debug!("failed to resolve: {:?}", err);
-                    let (start, end) = source_map.span_to_char_offset(&source_file, *span);
-                    let start = start as usize;
-                    let end = end as usize;
-
                     errors.push(TraceError::Resolve {
                         import: import.to_string(),
                         file_path: file_path.to_string(),
-                        span: SourceSpan::new(start.into(), (end - start).into()),
+                        span: *span,
                         text: file_content.clone(),
                         reason: err.to_string(),
                     });
@@ -270,7 +320,128 @@ impl Tracer {
             }
         }
 
-        Some((files, SeenFile { ast: Some(module) }))
+        Some((files, SeenFile { ast, kind }))
+    }
+
+    /// Resolves an import in a stylesheet. CSS urls are relative even without
+    /// a leading `./`, but `@import` can also refer to a package, e.g.
+    /// `@import "tailwindcss"`. Sass and Less imports can also leave out the
+    /// extension, and Sass imports can leave out the `_` of a partial, e.g.
+    /// `@use "variables"` for `_variables.scss`.
+    fn resolve_style_import(
+        resolver: &Resolver,
+        file_dir: &AbsoluteSystemPath,
+        extension: Option<&str>,
+        import: &str,
+    ) -> Result<Resolution, ResolveError> {
+        let relative = if import.starts_with('.') {
+            import.to_string()
+        } else {
+            format!("./{import}")
+        };
+        let relative_path = Utf8Path::new(&relative);
+
+        let mut candidates = vec![relative.clone()];
+        if let Some(extension) = extension.filter(|extension| *extension != "css") {
+            if relative_path.extension().is_none() {
+                candidates.push(format!("{relative}.{extension}"));
+                if let Some(name) = relative_path.file_name() {
+                    candidates.push(
+                        relative_path
+                            .with_file_name(format!("_{name}.{extension}"))
+                            .to_string(),
+                    );
+                }
+            }
+        }
+
+        candidates
+            .iter()
+            .find_map(|candidate| resolver.resolve(file_dir, candidate).ok())
+            .map_or_else(|| resolver.resolve(file_dir, import), Ok)
+    }
+
+    /// Parses a JavaScript or TypeScript file and finds its imports, along
+    /// with the span of each import. `lang` is the file's extension, or the
+    /// language of a component's scripts, e.g. `ts`.
+    fn get_imports_from_script(
+        source_map: &SourceMap,
+        errors: &mut Vec<TraceError>,
+        file_path: &AbsoluteSystemPath,
+        file_content: &str,
+        lang: Option<&str>,
+        import_type: ImportTraceType,
+        template_imports: TemplateImports,
+    ) -> Option<(Vec<(String, SourceSpan)>, swc_ecma_ast::Module)> {
+        let comments = SingleThreadedComments::default();
+
+        let source_file = source_map.new_source_file(
+            FileName::Custom(file_path.to_string()).into(),
+            file_content.to_string(),
+        );
+
+        let syntax = if matches!(lang, Some("ts") | Some("tsx")) {
+            Syntax::Typescript(TsSyntax {
+                tsx: lang == Some("tsx"),
+                decorators: true,
+                ..Default::default()
+            })
+        } else {
+            Syntax::Es(EsSyntax {
+                jsx: true,
+                ..Default::default()
+            })
+        };
+
+        let lexer = Lexer::new(
+            syntax,
+            EsVersion::EsNext,
+            StringInput::from(&*source_file),
+            Some(&comments),
+        );
+
+        let mut parser = Parser::new_from(Capturing::new(lexer));
+
+        // Parse the file as a module
+        let module = match parser.parse_module() {
+            Ok(module) => module,
+            Err(err) => {
+                errors.push(TraceError::ParseError(file_path.to_owned(), err));
+                return None;
+            }
+        };
+
+        // Visit the AST and find imports
+        let mut finder = ImportFinder::new(import_type).with_template_imports(template_imports);
+        module.visit_with(&mut finder);
+        for (import, span) in finder.unresolved_imports() {
+            let (start, end) = source_map.span_to_char_offset(&source_file, *span);
+            let start = start as usize;
+            let end = end as usize;
+
+            errors.push(TraceError::TemplateImport {
+                import: import.clone(),
+                file_path: file_path.to_string(),
+                span: SourceSpan::new(start.into(), (end - start).into()),
+                text: file_content.to_string(),
+                reason: "templates with expressions can't be resolved to a single file".to_string(),
+            });
+        }
+        let imports = finder
+            .imports()
+            .iter()
+            .map(|(import, span, _)| {
+                let (start, end) = source_map.span_to_char_offset(&source_file, *span);
+                let start = start as usize;
+                let end = end as usize;
+                (
+                    import.clone(),
+                    SourceSpan::new(start.into(), (end - start).into()),
+                )
+            })
+            .collect();
+
+        Some((imports, module))
     }
 
     pub async fn trace_file(
@@ -287,9 +458,14 @@ impl Tracer {
             return;
         }
 
-        let entry = seen.entry(file_path.clone()).or_default();
+        let kind = FileKind::from_path(&file_path);
+        let entry = seen.entry(file_path.clone()).or_insert_with(|| SeenFile {
+            kind,
+            ..Default::default()
+        });
 
-        if matches!(file_path.extension(), Some("css") | Some("json")) {
+        // JSON and assets don't import anything, so they're leaves of the graph
+        if matches!(kind, FileKind::Json | FileKind::Asset) {
             return;
         }
 
@@ -299,6 +475,7 @@ impl Tracer {
             resolver,
             &file_path,
             self.import_type,
+            self.template_imports,
         )
         .await
         else {
@@ -410,6 +587,13 @@ impl Tracer {
                 "**/*.jsx".parse().expect("valid glob"),
                 "**/*.ts".parse().expect("valid glob"),
                 "**/*.tsx".parse().expect("valid glob"),
+                "**/*.vue".parse().expect("valid glob"),
+                "**/*.svelte".parse().expect("valid glob"),
+                "**/*.mdx".parse().expect("valid glob"),
+                "**/*.css".parse().expect("valid glob"),
+                "**/*.scss".parse().expect("valid glob"),
+                "**/*.sass".parse().expect("valid glob"),
+                "**/*.less".parse().expect("valid glob"),
             ],
             &[
                 "**/node_modules/**".parse().expect("valid glob"),
@@ -447,6 +631,7 @@ impl Tracer {
                     resolver,
                     &file,
                     shared_self.import_type,
+                    shared_self.template_imports,
                 )
                 .await
                 else {
@@ -486,3 +671,35 @@ impl Tracer {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::FileKind;
+
+    #[test]
+    fn test_file_kind_from_path() {
+        let cwd = AbsoluteSystemPathBuf::cwd().unwrap();
+        for (file, kind) in [
+            ("index.ts", FileKind::Script),
+            ("cli", FileKind::Script),
+            ("Card.vue", FileKind::Component),
+            ("Card.svelte", FileKind::Component),
+            ("README.mdx", FileKind::Component),
+            ("styles.css", FileKind::Style),
+            ("styles.scss", FileKind::Style),
+            ("styles.sass", FileKind::Style),
+            ("styles.less", FileKind::Style),
+            ("package.json", FileKind::Json),
+            ("logo.svg", FileKind::Asset),
+            ("font.woff2", FileKind::Asset),
+        ] {
+            assert_eq!(
+                FileKind::from_path(&cwd.join_component(file)),
+                kind,
+                "{file}"
+            );
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::sync::{atomic::AtomicU8, Arc, Mutex};
 
 use futures::{stream::FuturesUnordered, StreamExt};
 use tokio::sync::{mpsc, oneshot, Semaphore};
-use tracing::{warn, Instrument, Level};
+use tracing::{debug, warn, Instrument, Level};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 use turborepo_analytics::AnalyticsSender;
 use turborepo_api_client::{APIAuth, APIClient};
@@ -25,6 +25,7 @@ enum WorkerRequest {
         key: String,
         duration: u64,
         files: Vec<AnchoredSystemPathBuf>,
+        task_id: Option<String>,
     },
     Flush(oneshot::Sender<()>),
     /// Shutdown the cache. The first oneshot notifies when shutdown starts and
@@ -67,6 +68,7 @@ impl AsyncCache {
                         key,
                         duration,
                         files,
+                        task_id,
                     } => {
                         let permit = semaphore.clone().acquire_owned().await.unwrap();
                         let real_cache = real_cache.clone();
@@ -74,8 +76,9 @@ impl AsyncCache {
                         let worker_span = tracing::span!(Level::TRACE, "cache worker: cache PUT");
                         workers.push(tokio::spawn(
                             async move {
-                                if let Err(err) =
-                                    real_cache.put(&anchor, &key, &files, duration).await
+                                if let Err(err) = real_cache
+                                    .put(&anchor, &key, &files, duration, task_id.as_deref())
+                                    .await
                                 {
                                     let num_warnings =
                                         warnings.load(std::sync::atomic::Ordering::Acquire);
@@ -121,6 +124,17 @@ impl AsyncCache {
                 let _ = worker;
             }
 
+            // Now that every write has landed, bring the local cache back within its limits
+            let prune_cache = real_cache.clone();
+            match tokio::task::spawn_blocking(move || prune_cache.prune_local()).await {
+                Ok(Ok(Some(summary))) if summary.evicted > 0 => debug!(
+                    "evicted {} artifacts ({} bytes) from the local cache",
+                    summary.evicted, summary.evicted_bytes
+                ),
+                Ok(Err(err)) => warn!("failed to prune local cache: {err}"),
+                _ => {}
+            }
+
             if let Some(callback) = shutdown_callback {
                 callback.send(()).ok();
             }
@@ -139,6 +153,7 @@ impl AsyncCache {
         key: String,
         files: Vec<AnchoredSystemPathBuf>,
         duration: u64,
+        task_id: Option<String>,
     ) -> Result<(), CacheError> {
         if self
             .writer_sender
@@ -147,6 +162,7 @@ impl AsyncCache {
                 key,
                 duration,
                 files,
+                task_id,
             })
             .await
             .is_err()
@@ -227,8 +243,8 @@ mod tests {
 
     use crate::{
         test_cases::{get_test_cases, TestCase},
-        AsyncCache, CacheActions, CacheConfig, CacheHitMetadata, CacheOpts, CacheSource,
-        RemoteCacheOpts,
+        AsyncCache, CacheActions, CacheConfig, CacheHitMetadata, CacheLimits, CacheOpts,
+        CacheSource, RemoteCacheOpts,
     };
 
     #[tokio::test]
@@ -268,9 +284,12 @@ mod tests {
             },
             workers: 10,
             remote_cache_opts: Some(RemoteCacheOpts {
-                unused_team_id: Some("my-team".to_string()),
+                team_id: Some("my-team".to_string()),
                 signature: false,
+                backend: Default::default(),
             }),
+            local_limits: CacheLimits::default(),
+            local_layout: Default::default(),
         };
 
         let api_client = APIClient::new(
@@ -303,6 +322,7 @@ mod tests {
                     .map(|f| f.path().to_owned())
                     .collect(),
                 test_case.duration,
+                None,
             )
             .await
             .unwrap();
@@ -357,9 +377,12 @@ mod tests {
             },
             workers: 10,
             remote_cache_opts: Some(RemoteCacheOpts {
-                unused_team_id: Some("my-team".to_string()),
+                team_id: Some("my-team".to_string()),
                 signature: false,
+                backend: Default::default(),
             }),
+            local_limits: CacheLimits::default(),
+            local_layout: Default::default(),
         };
 
         // Initialize client with invalid API url to ensure that we don't hit the
@@ -394,6 +417,7 @@ mod tests {
                     .map(|f| f.path().to_owned())
                     .collect(),
                 test_case.duration,
+                None,
             )
             .await
             .unwrap();
@@ -456,9 +480,12 @@ mod tests {
             },
             workers: 10,
             remote_cache_opts: Some(RemoteCacheOpts {
-                unused_team_id: Some("my-team".to_string()),
+                team_id: Some("my-team".to_string()),
                 signature: false,
+                backend: Default::default(),
             }),
+            local_limits: CacheLimits::default(),
+            local_layout: Default::default(),
         };
 
         let api_client = APIClient::new(
@@ -491,6 +518,7 @@ mod tests {
                     .map(|f| f.path().to_owned())
                     .collect(),
                 test_case.duration,
+                None,
             )
             .await
             .unwrap();
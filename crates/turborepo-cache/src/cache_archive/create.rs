@@ -1,8 +1,8 @@
 use std::{
     backtrace::Backtrace,
     fs,
-    fs::OpenOptions,
-    io::{BufWriter, Read, Write},
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Read, Write},
     path::Path,
 };
 
@@ -12,7 +12,37 @@ use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, IntoUnix};
 use crate::CacheError;
 
 pub struct CacheWriter<'a> {
-    builder: tar::Builder<Box<dyn Write + 'a>>,
+    builder: tar::Builder<ArchiveWriter<'a>>,
+}
+
+/// The writer underneath the tar builder. It's kept as a concrete type so that
+/// `CacheWriter::finish` can finish the zstd frame and flush the buffer itself,
+/// rather than leaving it to `Drop` where errors are lost.
+enum ArchiveWriter<'a> {
+    File(BufWriter<File>),
+    CompressedFile(zstd::Encoder<'static, BufWriter<File>>),
+    Writer(Box<dyn Write + 'a>),
+    CompressedWriter(zstd::Encoder<'static, Box<dyn Write + 'a>>),
+}
+
+impl<'a> Write for ArchiveWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ArchiveWriter::File(writer) => writer.write(buf),
+            ArchiveWriter::CompressedFile(writer) => writer.write(buf),
+            ArchiveWriter::Writer(writer) => writer.write(buf),
+            ArchiveWriter::CompressedWriter(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ArchiveWriter::File(writer) => writer.flush(),
+            ArchiveWriter::CompressedFile(writer) => writer.flush(),
+            ArchiveWriter::Writer(writer) => writer.flush(),
+            ArchiveWriter::CompressedWriter(writer) => writer.flush(),
+        }
+    }
 }
 
 impl<'a> CacheWriter<'a> {
@@ -35,21 +65,34 @@ impl<'a> CacheWriter<'a> {
         Ok(self.builder.append_link(header, path, target)?)
     }
 
-    pub fn finish(mut self) -> Result<(), CacheError> {
-        Ok(self.builder.finish()?)
+    /// Writes the end of the archive, the final zstd frame and any buffered
+    /// data, surfacing any errors that would otherwise be swallowed when the
+    /// writer is dropped. An archive is only complete once this succeeds.
+    pub fn finish(self) -> Result<(), CacheError> {
+        match self.builder.into_inner()? {
+            ArchiveWriter::File(writer) => Self::finish_file(writer),
+            ArchiveWriter::CompressedFile(encoder) => Self::finish_file(encoder.finish()?),
+            ArchiveWriter::Writer(mut writer) => Ok(writer.flush()?),
+            ArchiveWriter::CompressedWriter(encoder) => Ok(encoder.finish()?.flush()?),
+        }
+    }
+
+    fn finish_file(writer: BufWriter<File>) -> Result<(), CacheError> {
+        writer.into_inner().map_err(|err| err.into_error())?;
+        Ok(())
     }
 
     pub fn from_writer(writer: impl Write + 'a, use_compression: bool) -> Result<Self, CacheError> {
-        if use_compression {
-            let zw = zstd::Encoder::new(writer, 0)?.auto_finish();
-            Ok(CacheWriter {
-                builder: tar::Builder::new(Box::new(zw)),
-            })
+        let writer: Box<dyn Write + 'a> = Box::new(writer);
+        let writer = if use_compression {
+            ArchiveWriter::CompressedWriter(zstd::Encoder::new(writer, 0)?)
         } else {
-            Ok(CacheWriter {
-                builder: tar::Builder::new(Box::new(writer)),
-            })
-        }
+            ArchiveWriter::Writer(writer)
+        };
+
+        Ok(CacheWriter {
+            builder: tar::Builder::new(writer),
+        })
     }
 
     // Makes a new CacheArchive at the specified path
@@ -66,17 +109,15 @@ impl<'a> CacheWriter<'a> {
 
         let is_compressed = path.extension() == Some("zst");
 
-        if is_compressed {
-            let zw = zstd::Encoder::new(file_buffer, 0)?.auto_finish();
-
-            Ok(CacheWriter {
-                builder: tar::Builder::new(Box::new(zw)),
-            })
+        let writer = if is_compressed {
+            ArchiveWriter::CompressedFile(zstd::Encoder::new(file_buffer, 0)?)
         } else {
-            Ok(CacheWriter {
-                builder: tar::Builder::new(Box::new(file_buffer)),
-            })
-        }
+            ArchiveWriter::File(file_buffer)
+        };
+
+        Ok(CacheWriter {
+            builder: tar::Builder::new(writer),
+        })
     }
 
     // Adds a user-cached item to the tar
@@ -451,4 +492,34 @@ mod tests {
 
         Ok(())
     }
+
+    /// Accepts nothing, like a disk that has run out of space
+    struct FullWriter;
+
+    impl Write for FullWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_finish_surfaces_write_errors() -> Result<()> {
+        let input_dir = tempdir()?;
+        let input_dir_path = AbsoluteSystemPath::from_std_path(input_dir.path())?;
+        input_dir_path
+            .join_component("out.txt")
+            .create_with_contents("output")?;
+
+        // The small archive fits in the zstd encoder's buffer, so nothing is
+        // written until the final frame
+        let mut archive = CacheWriter::from_writer(FullWriter, true)?;
+        archive.add_file(input_dir_path, AnchoredSystemPath::new("out.txt")?)?;
+        assert!(archive.finish().is_err());
+
+        Ok(())
+    }
 }
@@ -6,4 +6,6 @@ mod restore_regular;
 mod restore_symlink;
 
 pub use create::CacheWriter;
-pub use restore::CacheReader;
+pub use restore::{ArchiveEntry, ArchiveEntryKind, CacheReader};
+pub(crate) use restore_directory::CachedDirTree;
+pub(crate) use restore_symlink::canonicalize_linkname;
@@ -1,6 +1,7 @@
 use std::{backtrace::Backtrace, collections::HashMap, io::Read};
 
 use petgraph::graph::DiGraph;
+use serde::Serialize;
 use sha2::{Digest, Sha512};
 use tar::Entry;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
@@ -20,6 +21,24 @@ pub struct CacheReader<'a> {
     reader: Box<dyn Read + 'a>,
 }
 
+/// A file recorded in a cache archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub kind: ArchiveEntryKind,
+    /// Size of the file contents in bytes. Always 0 for directories and
+    /// symlinks.
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveEntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
 impl<'a> CacheReader<'a> {
     pub fn from_reader(reader: impl Read + 'a, is_compressed: bool) -> Result<Self, CacheError> {
         let reader: Box<dyn Read> = if is_compressed {
@@ -58,6 +77,35 @@ impl<'a> CacheReader<'a> {
         Ok(hasher.finalize().to_vec())
     }
 
+    /// Lists the files in the archive without restoring them.
+    pub fn entries(&mut self) -> Result<Vec<ArchiveEntry>, CacheError> {
+        let mut tr = tar::Archive::new(&mut self.reader);
+        let mut entries = Vec::new();
+
+        for entry in tr.entries()? {
+            let entry = entry?;
+            let header = entry.header();
+            let kind = match header.entry_type() {
+                tar::EntryType::Directory => ArchiveEntryKind::Directory,
+                tar::EntryType::Regular => ArchiveEntryKind::File,
+                tar::EntryType::Symlink => ArchiveEntryKind::Symlink,
+                ty => {
+                    return Err(CacheError::RestoreUnsupportedFileType(
+                        ty,
+                        Backtrace::capture(),
+                    ))
+                }
+            };
+            entries.push(ArchiveEntry {
+                path: entry.path()?.to_string_lossy().into_owned(),
+                kind,
+                size: header.size()?,
+            });
+        }
+
+        Ok(entries)
+    }
+
     pub fn restore(
         &mut self,
         anchor: &AbsoluteSystemPath,
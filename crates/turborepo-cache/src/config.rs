@@ -1,9 +1,9 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use miette::{Diagnostic, SourceSpan};
 use thiserror::Error;
 
-use crate::{CacheActions, CacheConfig};
+use crate::{CacheActions, CacheConfig, CacheLayout};
 
 #[derive(Debug, Error, Diagnostic, PartialEq)]
 pub enum Error {
@@ -49,6 +49,12 @@ pub enum Error {
         #[label]
         span: Option<SourceSpan>,
     },
+    #[error("Invalid cache size `{0}`. Expected a size such as `500MB` or `10GB`.")]
+    InvalidSize(String),
+    #[error("Invalid cache age `{0}`. Expected a duration such as `12h` or `7d`.")]
+    InvalidAge(String),
+    #[error("Invalid cache layout `{0}`. Expected `archive` or `deduplicated`.")]
+    InvalidLayout(String),
 }
 
 impl Error {
@@ -59,6 +65,7 @@ impl Error {
             Self::InvalidCacheTypeAndAction { text, .. } => *text = new_text.into(),
             Self::InvalidCacheAction { text, .. } => *text = new_text.into(),
             Self::InvalidCacheType { text, .. } => *text = new_text.into(),
+            Self::InvalidSize(_) | Self::InvalidAge(_) | Self::InvalidLayout(_) => {}
         }
 
         self
@@ -71,6 +78,7 @@ impl Error {
             Self::InvalidCacheTypeAndAction { span, .. } => *span = Some(new_span),
             Self::InvalidCacheAction { span, .. } => *span = Some(new_span),
             Self::InvalidCacheType { span, .. } => *span = Some(new_span),
+            Self::InvalidSize(_) | Self::InvalidAge(_) | Self::InvalidLayout(_) => {}
         }
 
         self
@@ -203,6 +211,64 @@ impl FromStr for CacheActions {
     }
 }
 
+/// Splits a value such as `10GB` into its number and lowercased unit.
+fn split_unit(s: &str) -> Option<(u64, String)> {
+    let s = s.trim();
+    let unit_start = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(unit_start);
+    Some((number.parse().ok()?, unit.trim().to_ascii_lowercase()))
+}
+
+/// Parses a size such as `500MB` or `10GB` into bytes. Units are binary, i.e.
+/// `1KB` is 1024 bytes, and a bare number is treated as bytes.
+pub fn parse_size(s: &str) -> Result<u64, Error> {
+    let invalid = || Error::InvalidSize(s.to_string());
+    let (number, unit) = split_unit(s).ok_or_else(invalid)?;
+    let exponent = match unit.as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 1,
+        "m" | "mb" | "mib" => 2,
+        "g" | "gb" | "gib" => 3,
+        "t" | "tb" | "tib" => 4,
+        _ => return Err(invalid()),
+    };
+
+    number
+        .checked_mul(1024u64.pow(exponent))
+        .ok_or_else(invalid)
+}
+
+/// Parses an age such as `12h` or `7d`. Supported units are `s`, `m`, `h`,
+/// `d` and `w`.
+pub fn parse_age(s: &str) -> Result<Duration, Error> {
+    let invalid = || Error::InvalidAge(s.to_string());
+    let (number, unit) = split_unit(s).ok_or_else(invalid)?;
+    let seconds = match unit.as_str() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+impl FromStr for CacheLayout {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "archive" => Ok(CacheLayout::Archive),
+            "deduplicated" => Ok(CacheLayout::Deduplicated),
+            _ => Err(Error::InvalidLayout(s.to_string())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use test_case::test_case;
@@ -234,4 +300,30 @@ mod test {
     fn test_cache_config(s: &str, expected: Result<CacheConfig, Error>) {
         assert_eq!(CacheConfig::from_str(s), expected);
     }
+
+    #[test_case("1024", Ok(1024) ; "bare bytes")]
+    #[test_case("500MB", Ok(500 * 1024 * 1024) ; "megabytes")]
+    #[test_case("10 gb", Ok(10 * 1024 * 1024 * 1024) ; "lowercase with space")]
+    #[test_case("2KiB", Ok(2048) ; "binary suffix")]
+    #[test_case("10PB", Err(Error::InvalidSize("10PB".to_string())) ; "unknown unit")]
+    #[test_case("GB", Err(Error::InvalidSize("GB".to_string())) ; "missing number")]
+    fn test_parse_size(s: &str, expected: Result<u64, Error>) {
+        assert_eq!(parse_size(s), expected);
+    }
+
+    #[test_case("30s", Ok(Duration::from_secs(30)) ; "seconds")]
+    #[test_case("12h", Ok(Duration::from_secs(12 * 60 * 60)) ; "hours")]
+    #[test_case("7d", Ok(Duration::from_secs(7 * 24 * 60 * 60)) ; "days")]
+    #[test_case("7", Err(Error::InvalidAge("7".to_string())) ; "missing unit")]
+    #[test_case("1y", Err(Error::InvalidAge("1y".to_string())) ; "unknown unit")]
+    fn test_parse_age(s: &str, expected: Result<Duration, Error>) {
+        assert_eq!(parse_age(s), expected);
+    }
+
+    #[test_case("archive", Ok(CacheLayout::Archive) ; "archive")]
+    #[test_case("deduplicated", Ok(CacheLayout::Deduplicated) ; "deduplicated")]
+    #[test_case("dedup", Err(Error::InvalidLayout("dedup".to_string())) ; "unknown layout")]
+    fn test_cache_layout(s: &str, expected: Result<CacheLayout, Error>) {
+        assert_eq!(CacheLayout::from_str(s), expected);
+    }
 }
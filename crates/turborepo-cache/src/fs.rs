@@ -1,25 +1,90 @@
-use std::{backtrace::Backtrace, fs::OpenOptions};
+mod manifest;
+
+use std::{
+    backtrace::Backtrace,
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{self, ErrorKind, Read},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use camino::Utf8Path;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 use turborepo_analytics::AnalyticsSender;
 use turborepo_api_client::{analytics, analytics::AnalyticsEvent};
 
+use self::manifest::{BlobRefs, Manifest, MANIFEST_SUFFIX};
 use crate::{
-    cache_archive::{CacheReader, CacheWriter},
-    CacheError, CacheHitMetadata, CacheSource,
+    cache_archive::{ArchiveEntry, CacheReader, CacheWriter},
+    CacheError, CacheHitMetadata, CacheLayout, CacheLimits, CacheSource,
 };
 
+/// Artifacts are written here before being renamed into the cache directory
+const TEMP_DIR: &str = ".tmp";
+/// Corrupt artifacts are moved here so they're no longer restored, but can
+/// still be inspected
+const QUARANTINE_DIR: &str = ".quarantine";
+/// The duration of the most recent artifact written for each task, so that
+/// durations can be looked up without reading every artifact's metadata
+const DURATIONS_DIR: &str = "durations";
+/// Temporary files older than this were left behind by a crashed write
+const STALE_TEMP_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
 pub struct FSCache {
     cache_directory: AbsoluteSystemPathBuf,
     analytics_recorder: Option<AnalyticsSender>,
+    layout: CacheLayout,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CacheMetadata {
+    pub hash: String,
+    /// Time saved by restoring the artifact, in milliseconds
+    pub duration: u64,
+    /// The task that produced the artifact. Absent for artifacts written by
+    /// older versions of turbo or restored from the remote cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    /// The signature tag the artifact was uploaded with, if it was stored by
+    /// `turbo cache serve`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// A single artifact in the local cache along with its metadata file.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub hash: String,
+    /// Combined size of the archive or manifest and metadata in bytes. Blobs
+    /// shared with other artifacts aren't included.
+    pub size: u64,
+    /// The last time the artifact was written or restored
+    pub last_used: SystemTime,
+    files: Vec<AbsoluteSystemPathBuf>,
+}
+
+impl CacheEntry {
+    fn is_metadata_only(&self) -> bool {
+        self.files
+            .iter()
+            .all(|file| file.as_str().ends_with("-meta.json"))
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct CacheMetadata {
-    hash: String,
-    duration: u64,
+/// The outcome of evicting artifacts from the local cache
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneSummary {
+    pub evicted: usize,
+    pub evicted_bytes: u64,
+    pub remaining: usize,
+    pub remaining_bytes: u64,
 }
 
 impl CacheMetadata {
@@ -49,9 +114,17 @@ impl FSCache {
         Ok(FSCache {
             cache_directory,
             analytics_recorder,
+            layout: CacheLayout::default(),
         })
     }
 
+    /// Sets how new artifacts are written. Artifacts are restored regardless
+    /// of the layout they were written with.
+    pub fn with_layout(mut self, layout: CacheLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
     fn log_fetch(&self, event: analytics::CacheEvent, hash: &str, duration: u64) {
         // If analytics fails to record, it's not worth failing the cache
         if let Some(analytics_recorder) = &self.analytics_recorder {
@@ -73,31 +146,25 @@ impl FSCache {
         anchor: &AbsoluteSystemPath,
         hash: &str,
     ) -> Result<Option<(CacheHitMetadata, Vec<AnchoredSystemPathBuf>)>, CacheError> {
-        let uncompressed_cache_path = self
-            .cache_directory
-            .join_component(&format!("{}.tar", hash));
-        let compressed_cache_path = self
-            .cache_directory
-            .join_component(&format!("{}.tar.zst", hash));
-
-        let cache_path = if uncompressed_cache_path.exists() {
-            uncompressed_cache_path
-        } else if compressed_cache_path.exists() {
-            compressed_cache_path
-        } else {
+        let Some(restored_files) = self.restore(anchor, hash)? else {
             self.log_fetch(analytics::CacheEvent::Miss, hash, 0);
             return Ok(None);
         };
 
-        let mut cache_reader = CacheReader::open(&cache_path)?;
-
-        let restored_files = cache_reader.restore(anchor)?;
-
-        let meta = CacheMetadata::read(
+        let meta = match CacheMetadata::read(
             &self
                 .cache_directory
                 .join_component(&format!("{}-meta.json", hash)),
-        )?;
+        ) {
+            Ok(meta) => meta,
+            Err(err) => {
+                // Metadata is committed before the archive, so an archive without readable
+                // metadata was left behind by an interrupted write from an older turbo
+                self.quarantine(hash, &err);
+                self.log_fetch(analytics::CacheEvent::Miss, hash, 0);
+                return Ok(None);
+            }
+        };
 
         self.log_fetch(analytics::CacheEvent::Hit, hash, meta.duration);
 
@@ -110,16 +177,54 @@ impl FSCache {
         )))
     }
 
-    #[tracing::instrument(skip_all)]
-    pub(crate) fn exists(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
-        let uncompressed_cache_path = self
-            .cache_directory
-            .join_component(&format!("{}.tar", hash));
-        let compressed_cache_path = self
-            .cache_directory
-            .join_component(&format!("{}.tar.zst", hash));
+    /// Restores an artifact from either its archive or its manifest. Returns
+    /// `None` if the artifact is missing or was found to be corrupt.
+    fn restore(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+    ) -> Result<Option<Vec<AnchoredSystemPathBuf>>, CacheError> {
+        if let Some(cache_path) = self.archive_path(hash) {
+            let restored_files = match CacheReader::open(&cache_path)
+                .and_then(|mut reader| reader.restore(anchor))
+            {
+                Ok(restored_files) => restored_files,
+                Err(err) if Self::is_corrupt(&cache_path) => {
+                    self.quarantine(hash, &err);
+                    return Ok(None);
+                }
+                Err(err) => return Err(err),
+            };
+
+            Self::mark_used(&cache_path);
+            return Ok(Some(restored_files));
+        }
 
-        if !uncompressed_cache_path.exists() && !compressed_cache_path.exists() {
+        let manifest_path = self.manifest_path(hash);
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+
+        // Unlike an archive, a manifest can be checked before restoring, so any
+        // failure while restoring is caused by the destination
+        let manifest = match Manifest::read(&manifest_path)
+            .and_then(|manifest| self.verify_manifest(&manifest).map(|()| manifest))
+        {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                self.quarantine(hash, &err);
+                return Ok(None);
+            }
+        };
+        let restored_files = self.restore_manifest(anchor, &manifest)?;
+
+        Self::mark_used(&manifest_path);
+        Ok(Some(restored_files))
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub fn exists(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
+        if self.archive_path(hash).is_none() && !self.manifest_path(hash).exists() {
             return Ok(None);
         }
 
@@ -144,36 +249,500 @@ impl FSCache {
         hash: &str,
         files: &[AnchoredSystemPathBuf],
         duration: u64,
+        task_id: Option<&str>,
+    ) -> Result<(), CacheError> {
+        let meta = CacheMetadata {
+            hash: hash.to_string(),
+            duration,
+            task_id: task_id.map(|task_id| task_id.to_string()),
+            tag: None,
+        };
+
+        let result = match self.layout {
+            CacheLayout::Archive => {
+                self.commit(&meta, &format!("{}.tar.zst", hash), |cache_path| {
+                    let mut cache_item = CacheWriter::create(cache_path)?;
+
+                    for file in files {
+                        cache_item.add_file(anchor, file)?;
+                    }
+
+                    cache_item.finish()
+                })
+            }
+            CacheLayout::Deduplicated => {
+                // Blobs are written before the manifest that refers to them
+                let manifest = self.store_files(anchor, files)?;
+                self.commit(
+                    &meta,
+                    &format!("{}{}", hash, MANIFEST_SUFFIX),
+                    |manifest_path| manifest.write(manifest_path),
+                )
+            }
+        };
+
+        if let (Ok(()), Some(task_id)) = (&result, task_id) {
+            self.record_duration(task_id, duration);
+        }
+        result
+    }
+
+    /// Looks up the duration, in milliseconds, of the most recent artifact
+    /// written for a task.
+    pub fn task_duration(&self, task_id: &str) -> Option<u64> {
+        self.duration_path(task_id)
+            .read_to_string()
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// Records the duration of a task for `task_duration`. Durations are only
+    /// used for estimates, so failing to record one is logged and otherwise
+    /// ignored.
+    fn record_duration(&self, task_id: &str, duration: u64) {
+        let result = self.temp_path("duration").and_then(|temp_path| {
+            let path = self.duration_path(task_id);
+            let result = path
+                .ensure_dir()
+                .and_then(|()| temp_path.create_with_contents(duration.to_string()))
+                .and_then(|()| temp_path.rename(&path));
+            if result.is_err() {
+                let _ = temp_path.remove_file();
+            }
+            Ok(result?)
+        });
+        if let Err(err) = result {
+            debug!("failed to record duration of {task_id}: {err}");
+        }
+    }
+
+    fn duration_path(&self, task_id: &str) -> AbsoluteSystemPathBuf {
+        // Task ids can contain characters that aren't valid in file names
+        let name = format!("{:x}", Sha256::digest(task_id.as_bytes()));
+        self.cache_directory
+            .join_components(&[DURATIONS_DIR, &name])
+    }
+
+    /// Stores an already compressed archive, such as one uploaded to a
+    /// self-hosted remote cache, along with its signature tag. The archive is
+    /// streamed to disk as it's read.
+    #[tracing::instrument(skip_all)]
+    pub fn put_archive(
+        &self,
+        hash: &str,
+        mut archive: impl Read,
+        duration: u64,
+        tag: Option<&str>,
     ) -> Result<(), CacheError> {
+        Self::validate_hash(hash)?;
+        let meta = CacheMetadata {
+            hash: hash.to_string(),
+            duration,
+            task_id: None,
+            tag: tag.map(|tag| tag.to_string()),
+        };
+
+        self.commit(&meta, &format!("{}.tar.zst", hash), |cache_path| {
+            let mut file = cache_path.create()?;
+            io::copy(&mut archive, &mut file)?;
+            Ok(())
+        })
+    }
+
+    /// Opens the compressed archive for an artifact so it can be read without
+    /// restoring it. Artifacts without readable metadata are treated as
+    /// missing.
+    #[tracing::instrument(skip_all)]
+    pub fn open_archive(&self, hash: &str) -> Result<Option<(File, CacheMetadata)>, CacheError> {
+        Self::validate_hash(hash)?;
         let cache_path = self
             .cache_directory
             .join_component(&format!("{}.tar.zst", hash));
 
-        let mut cache_item = CacheWriter::create(&cache_path)?;
+        let archive = match cache_path.open() {
+            Ok(archive) => archive,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let Some(meta) = self.metadata(hash).ok().flatten() else {
+            return Ok(None);
+        };
 
-        for file in files {
-            cache_item.add_file(anchor, file)?;
-        }
+        Self::mark_used(&cache_path);
+
+        Ok(Some((archive, meta)))
+    }
 
+    /// Writes an artifact to the cache as `file_name`, which is either an
+    /// archive or a manifest. `write_archive` is given the path that it should
+    /// be written to.
+    fn commit(
+        &self,
+        meta: &CacheMetadata,
+        file_name: &str,
+        write_archive: impl FnOnce(&AbsoluteSystemPath) -> Result<(), CacheError>,
+    ) -> Result<(), CacheError> {
+        let hash = &meta.hash;
+        let cache_path = self.cache_directory.join_component(file_name);
         let metadata_path = self
             .cache_directory
             .join_component(&format!("{}-meta.json", hash));
 
-        let meta = CacheMetadata {
-            hash: hash.to_string(),
-            duration,
-        };
+        // Write everything to temporary files first so that neither a crash nor a
+        // concurrent reader can observe a partially written artifact
+        let temp_cache_path = self.temp_path(file_name)?;
+        let temp_metadata_path = self.temp_path(&format!("{}-meta.json", hash))?;
+
+        let result =
+            Self::write_artifact(write_archive, meta, &temp_cache_path, &temp_metadata_path)
+                .and_then(|()| {
+                    // `fetch` looks for the archive or manifest, so it's moved into place last.
+                    // Once it's visible the metadata is guaranteed to be as
+                    // well.
+                    temp_metadata_path.rename(&metadata_path)?;
+                    temp_cache_path.rename(&cache_path)?;
+                    Ok(())
+                });
+
+        if result.is_err() {
+            for path in [&temp_cache_path, &temp_metadata_path] {
+                let _ = path.remove_file();
+            }
+        }
+
+        result
+    }
+
+    fn write_artifact(
+        write_archive: impl FnOnce(&AbsoluteSystemPath) -> Result<(), CacheError>,
+        meta: &CacheMetadata,
+        cache_path: &AbsoluteSystemPath,
+        metadata_path: &AbsoluteSystemPath,
+    ) -> Result<(), CacheError> {
+        write_archive(cache_path)?;
+
+        let mut archive_options = OpenOptions::new();
+        archive_options.write(true);
+        cache_path.open_with_options(archive_options)?.sync_all()?;
 
         let mut metadata_options = OpenOptions::new();
         metadata_options.create(true).write(true).truncate(true);
 
         let metadata_file = metadata_path.open_with_options(metadata_options)?;
 
-        serde_json::to_writer(metadata_file, &meta)
-            .map_err(|e| CacheError::InvalidMetadata(e, Backtrace::capture()))?;
+        serde_json::to_writer(&metadata_file, meta)
+            .map_err(|e| CacheError::MetadataWriteFailure(e, Backtrace::capture()))?;
+        metadata_file.sync_all()?;
+
+        Ok(())
+    }
+
+    /// Returns a unique path in the temporary directory. The file name is
+    /// preserved as a suffix so that compression is still inferred from the
+    /// extension.
+    fn temp_path(&self, file_name: &str) -> Result<AbsoluteSystemPathBuf, CacheError> {
+        let temp_dir = self.cache_directory.join_component(TEMP_DIR);
+        temp_dir.create_dir_all()?;
+
+        let id = TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        Ok(temp_dir.join_component(&format!("{}-{}-{}", std::process::id(), id, file_name)))
+    }
+
+    /// Checks whether an archive that failed to restore is unreadable, as
+    /// opposed to having failed because of the restore destination.
+    fn is_corrupt(cache_path: &AbsoluteSystemPathBuf) -> bool {
+        CacheReader::open(cache_path)
+            .and_then(|mut reader| reader.entries())
+            .is_err()
+    }
+
+    /// Moves an artifact out of the cache so it isn't restored again.
+    fn quarantine(&self, hash: &str, err: &CacheError) {
+        warn!("local cache artifact {hash} is corrupt and will be ignored: {err}");
+
+        let quarantine_dir = self.cache_directory.join_component(QUARANTINE_DIR);
+        if let Err(err) = quarantine_dir.create_dir_all() {
+            debug!("failed to create quarantine directory: {err}");
+        }
+
+        for file_name in [
+            format!("{}.tar.zst", hash),
+            format!("{}.tar", hash),
+            format!("{}{}", hash, MANIFEST_SUFFIX),
+            format!("{}-meta.json", hash),
+        ] {
+            let path = self.cache_directory.join_component(&file_name);
+            match path.rename(&quarantine_dir.join_component(&file_name)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => {
+                    debug!("failed to quarantine {path}: {err}");
+                    // Quarantining is best-effort, but the artifact must not be restored again
+                    let _ = path.remove_file();
+                }
+            }
+        }
+    }
+
+    /// Removes temporary files left behind by writes that never completed.
+    fn remove_stale_temp_files(&self, now: SystemTime) -> Result<(), CacheError> {
+        let temp_dir = self.cache_directory.join_component(TEMP_DIR);
+        let dir_entries = match std::fs::read_dir(temp_dir.as_std_path()) {
+            Ok(dir_entries) => dir_entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        for dir_entry in dir_entries {
+            let dir_entry = dir_entry?;
+            let is_stale = dir_entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| {
+                    now.duration_since(modified)
+                        .is_ok_and(|age| age > STALE_TEMP_FILE_AGE)
+                });
+            if is_stale {
+                match std::fs::remove_file(dir_entry.path()) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == ErrorKind::NotFound => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Bumps the modification time of an artifact so that eviction treats it
+    /// as recently used. Failing to do so only affects eviction order, so
+    /// errors are logged and otherwise ignored.
+    fn mark_used(cache_path: &AbsoluteSystemPath) {
+        let mut options = OpenOptions::new();
+        options.write(true);
+        if let Err(err) = cache_path
+            .open_with_options(options)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            debug!("failed to update last used time for {cache_path}: {err}");
+        }
+    }
+
+    /// Lists every artifact in the cache directory, ordered from least to most
+    /// recently used.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, CacheError> {
+        let mut entries: BTreeMap<String, CacheEntry> = BTreeMap::new();
+
+        for dir_entry in std::fs::read_dir(self.cache_directory.as_std_path())? {
+            let dir_entry = dir_entry?;
+            let Some(file_name) = dir_entry.file_name().to_str().map(|name| name.to_owned()) else {
+                continue;
+            };
+            let Some(hash) = file_name
+                .strip_suffix(".tar.zst")
+                .or_else(|| file_name.strip_suffix(".tar"))
+                .or_else(|| file_name.strip_suffix(MANIFEST_SUFFIX))
+                .or_else(|| file_name.strip_suffix("-meta.json"))
+            else {
+                continue;
+            };
+
+            let metadata = match dir_entry.metadata() {
+                Ok(metadata) => metadata,
+                // Another process may have evicted this entry while we were listing
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+
+            let entry = entries
+                .entry(hash.to_string())
+                .or_insert_with(|| CacheEntry {
+                    hash: hash.to_string(),
+                    size: 0,
+                    last_used: UNIX_EPOCH,
+                    files: Vec::new(),
+                });
+            entry.size += metadata.len();
+            // Restores touch the archive and writes touch both files, so the most recent
+            // modification across them is the last time the artifact was used
+            entry.last_used = entry.last_used.max(modified);
+            entry
+                .files
+                .push(self.cache_directory.join_component(&file_name));
+        }
+
+        let mut entries: Vec<_> = entries.into_values().collect();
+        entries.sort_by(|a, b| a.last_used.cmp(&b.last_used).then(a.hash.cmp(&b.hash)));
+        Ok(entries)
+    }
+
+    /// Reads the metadata for an artifact, if present.
+    pub fn metadata(&self, hash: &str) -> Result<Option<CacheMetadata>, CacheError> {
+        Self::validate_hash(hash)?;
+        let metadata_path = self
+            .cache_directory
+            .join_component(&format!("{}-meta.json", hash));
+        if !metadata_path.exists() {
+            return Ok(None);
+        }
+
+        CacheMetadata::read(&metadata_path).map(Some)
+    }
+
+    /// Lists the files stored in an artifact without restoring them. Returns
+    /// `None` if the artifact isn't in the cache.
+    pub fn archive_entries(&self, hash: &str) -> Result<Option<Vec<ArchiveEntry>>, CacheError> {
+        Self::validate_hash(hash)?;
+        if let Some(cache_path) = self.archive_path(hash) {
+            return CacheReader::open(&cache_path)?.entries().map(Some);
+        }
+
+        let manifest_path = self.manifest_path(hash);
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(Manifest::read(&manifest_path)?.archive_entries()))
+    }
+
+    /// Removes a single artifact. Returns `false` if it wasn't in the cache.
+    pub fn remove(&self, hash: &str) -> Result<bool, CacheError> {
+        Self::validate_hash(hash)?;
+        let entries = self.entries()?;
+        let Some(entry) = entries.iter().find(|entry| entry.hash == hash) else {
+            return Ok(false);
+        };
+
+        let mut refs = self.blob_refs(&entries)?;
+        self.evict(entry, &mut refs)?;
+        Ok(true)
+    }
+
+    /// Removes every artifact from the cache.
+    pub fn clear(&self) -> Result<PruneSummary, CacheError> {
+        self.remove_stale_temp_files(SystemTime::now())?;
+        for dir in [QUARANTINE_DIR, DURATIONS_DIR] {
+            match self.cache_directory.join_component(dir).remove_dir_all() {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let entries = self.entries()?;
+        let mut refs = self.blob_refs(&entries)?;
+        let mut summary = PruneSummary::default();
+        for entry in &entries {
+            let freed = self.evict(entry, &mut refs)?;
+            summary.evicted += 1;
+            summary.evicted_bytes += entry.size + freed;
+        }
+        // Nothing refers to the remaining blobs
+        self.remove_all_blobs()?;
+
+        Ok(summary)
+    }
 
+    /// Rejects hashes that can't be a file name in the cache directory, so a
+    /// user supplied hash like `../foo` can't refer to files outside of it.
+    /// Hashes are also limited in length so that the file names they make
+    /// stay within filesystem limits.
+    pub fn validate_hash(hash: &str) -> Result<(), CacheError> {
+        let is_valid = !hash.is_empty()
+            && hash.len() <= 128
+            && hash
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !is_valid {
+            return Err(CacheError::InvalidHash(
+                hash.to_string(),
+                Backtrace::capture(),
+            ));
+        }
         Ok(())
     }
+
+    fn archive_path(&self, hash: &str) -> Option<AbsoluteSystemPathBuf> {
+        [format!("{}.tar", hash), format!("{}.tar.zst", hash)]
+            .into_iter()
+            .map(|file_name| self.cache_directory.join_component(&file_name))
+            .find(|path| path.exists())
+    }
+
+    /// Evicts least-recently-used artifacts until the cache satisfies
+    /// `limits`.
+    #[tracing::instrument(skip_all)]
+    pub fn prune(&self, limits: &CacheLimits) -> Result<PruneSummary, CacheError> {
+        self.prune_at(limits, SystemTime::now())
+    }
+
+    fn prune_at(&self, limits: &CacheLimits, now: SystemTime) -> Result<PruneSummary, CacheError> {
+        self.remove_stale_temp_files(now)?;
+        let entries = self.entries()?;
+        let mut refs = self.blob_refs(&entries)?;
+        let mut remaining_bytes: u64 =
+            entries.iter().map(|entry| entry.size).sum::<u64>() + refs.total_size();
+        let mut summary = PruneSummary::default();
+
+        for entry in &entries {
+            let age = now.duration_since(entry.last_used).ok();
+            let expired = limits
+                .max_age
+                .is_some_and(|max_age| age.is_some_and(|age| age > max_age));
+            let over_size = limits
+                .max_size
+                .is_some_and(|max_size| remaining_bytes > max_size);
+            // Metadata is written before the archive and removed after it, so
+            // metadata on its own is either from a write that's still in progress
+            // or an eviction that was interrupted
+            let interrupted =
+                entry.is_metadata_only() && age.is_some_and(|age| age > STALE_TEMP_FILE_AGE);
+
+            if expired || over_size || interrupted {
+                let evicted_bytes = entry.size + self.evict(entry, &mut refs)?;
+                remaining_bytes -= evicted_bytes;
+                summary.evicted += 1;
+                summary.evicted_bytes += evicted_bytes;
+            } else {
+                summary.remaining += 1;
+            }
+        }
+
+        self.remove_orphaned_blobs(&refs, now)?;
+        summary.remaining_bytes = remaining_bytes;
+        Ok(summary)
+    }
+
+    /// Removes an artifact, its metadata and any blobs that only it referred
+    /// to. Returns the number of bytes freed from the blob store. The archive
+    /// or manifest is removed first so that concurrent readers see a miss
+    /// rather than an artifact without metadata. The metadata is removed last,
+    /// so an eviction that's interrupted leaves it behind for the next prune
+    /// to finish evicting.
+    fn evict(&self, entry: &CacheEntry, refs: &mut BlobRefs) -> Result<u64, CacheError> {
+        let (archives, metadata): (Vec<_>, Vec<_>) = entry
+            .files
+            .iter()
+            .partition(|file| !file.as_str().ends_with("-meta.json"));
+
+        let remove = |file: &AbsoluteSystemPathBuf| match file.remove_file() {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        };
+        for file in archives {
+            remove(file)?;
+        }
+        let freed = self.release_blobs(refs, &entry.hash)?;
+        for file in metadata {
+            remove(file)?;
+        }
+
+        Ok(freed)
+    }
 }
 
 #[cfg(test)]
@@ -209,6 +778,507 @@ mod test {
         Ok(())
     }
 
+    fn set_last_used(cache: &FSCache, hash: &str, time: SystemTime) -> Result<()> {
+        for file in [format!("{hash}.tar.zst"), format!("{hash}-meta.json")] {
+            let mut options = OpenOptions::new();
+            options.write(true);
+            cache
+                .cache_directory
+                .join_component(&file)
+                .open_with_options(options)?
+                .set_modified(time)?;
+        }
+        Ok(())
+    }
+
+    fn populated_cache(repo_root: &AbsoluteSystemPath, now: SystemTime) -> Result<FSCache> {
+        let cache = FSCache::new(Utf8Path::new("cache"), repo_root, None)?;
+        let file = AnchoredSystemPathBuf::from_raw("out.txt")?;
+        repo_root.resolve(&file).create_with_contents("output")?;
+
+        for (hash, hours_ago) in [("old", 3), ("middle", 2), ("new", 1)] {
+            cache.put(repo_root, hash, &[file.clone()], 0, Some("app#build"))?;
+            set_last_used(&cache, hash, now - Duration::from_secs(hours_ago * 60 * 60))?;
+        }
+
+        Ok(cache)
+    }
+
+    fn hashes(cache: &FSCache) -> Result<Vec<String>> {
+        Ok(cache
+            .entries()?
+            .into_iter()
+            .map(|entry| entry.hash)
+            .collect())
+    }
+
+    #[test]
+    fn test_prune_by_age() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let now = SystemTime::now();
+        let cache = populated_cache(repo_root_path, now)?;
+
+        let limits = CacheLimits {
+            max_size: None,
+            max_age: Some(Duration::from_secs(90 * 60)),
+        };
+        let summary = cache.prune_at(&limits, now)?;
+
+        assert_eq!(summary.evicted, 2);
+        assert_eq!(summary.remaining, 1);
+        assert_eq!(hashes(&cache)?, vec!["new".to_string()]);
+        assert!(cache.fetch(repo_root_path, "old")?.is_none());
+        assert!(!cache
+            .cache_directory
+            .join_component("old-meta.json")
+            .exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_by_size() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let now = SystemTime::now();
+        let cache = populated_cache(repo_root_path, now)?;
+
+        let total: u64 = cache.entries()?.iter().map(|entry| entry.size).sum();
+        let limits = CacheLimits {
+            max_size: Some(total - 1),
+            max_age: None,
+        };
+        let summary = cache.prune_at(&limits, now)?;
+
+        assert_eq!(summary.evicted, 1);
+        assert!(summary.remaining_bytes <= total - 1);
+        assert_eq!(
+            hashes(&cache)?,
+            vec!["middle".to_string(), "new".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_marks_artifact_as_used() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let now = SystemTime::now();
+        let cache = populated_cache(repo_root_path, now)?;
+
+        assert!(cache.fetch(repo_root_path, "old")?.is_some());
+
+        assert_eq!(
+            hashes(&cache)?,
+            vec!["middle".to_string(), "new".to_string(), "old".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_inspect_artifact() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = populated_cache(repo_root_path, SystemTime::now())?;
+
+        let metadata = cache.metadata("old")?.unwrap();
+        assert_eq!(metadata.task_id.as_deref(), Some("app#build"));
+
+        let entries = cache.archive_entries("old")?.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "out.txt");
+        assert_eq!(entries[0].size, "output".len() as u64);
+
+        assert!(cache.metadata("missing")?.is_none());
+        assert!(cache.archive_entries("missing")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_durations() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(Utf8Path::new("cache"), repo_root_path, None)?;
+
+        cache.put(repo_root_path, "first", &[], 3000, Some("@scope/app#build"))?;
+        assert_eq!(cache.task_duration("@scope/app#build"), Some(3000));
+        // The most recent artifact of a task wins
+        cache.put(
+            repo_root_path,
+            "second",
+            &[],
+            1000,
+            Some("@scope/app#build"),
+        )?;
+        assert_eq!(cache.task_duration("@scope/app#build"), Some(1000));
+        assert_eq!(cache.task_duration("app#lint"), None);
+
+        // The durations aren't artifacts
+        assert_eq!(hashes(&cache)?.len(), 2);
+        cache.clear()?;
+        assert_eq!(cache.task_duration("@scope/app#build"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_hashes_outside_of_cache_directory() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = populated_cache(repo_root_path, SystemTime::now())?;
+        let outside = repo_root_path.join_component("outside-meta.json");
+        outside.create_with_contents("{}")?;
+
+        let too_long = "a".repeat(129);
+        for hash in [
+            "../outside",
+            "..",
+            "",
+            "/tmp/outside",
+            "a/b",
+            "a\\b",
+            &too_long,
+        ] {
+            assert!(matches!(
+                cache.metadata(hash),
+                Err(CacheError::InvalidHash(..))
+            ));
+            assert!(matches!(
+                cache.archive_entries(hash),
+                Err(CacheError::InvalidHash(..))
+            ));
+            assert!(matches!(
+                cache.remove(hash),
+                Err(CacheError::InvalidHash(..))
+            ));
+        }
+        assert!(outside.exists());
+        assert_eq!(hashes(&cache)?.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_finishes_interrupted_eviction() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let now = SystemTime::now();
+        let cache = populated_cache(repo_root_path, now)?;
+
+        // Leave only the metadata behind, as an eviction that was interrupted
+        // would. The newer one could still be a write in progress.
+        for hash in ["old", "new"] {
+            cache
+                .cache_directory
+                .join_component(&format!("{hash}.tar.zst"))
+                .remove_file()?;
+        }
+        assert!(cache.exists("old")?.is_none());
+
+        let summary =
+            cache.prune_at(&CacheLimits::default(), now - Duration::from_secs(30 * 60))?;
+        assert_eq!(summary.evicted, 1);
+        assert_eq!(
+            hashes(&cache)?,
+            vec!["middle".to_string(), "new".to_string()]
+        );
+        assert!(!cache
+            .cache_directory
+            .join_component("old-meta.json")
+            .exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_and_clear() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = populated_cache(repo_root_path, SystemTime::now())?;
+
+        assert!(cache.remove("middle")?);
+        assert!(!cache.remove("middle")?);
+        assert_eq!(hashes(&cache)?, vec!["old".to_string(), "new".to_string()]);
+
+        let summary = cache.clear()?;
+        assert_eq!(summary.evicted, 2);
+        assert!(hashes(&cache)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_put_leaves_no_temp_files() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = populated_cache(repo_root_path, SystemTime::now())?;
+
+        let temp_files = std::fs::read_dir(cache.cache_directory.join_component(TEMP_DIR))?;
+        assert_eq!(temp_files.count(), 0);
+        assert_eq!(hashes(&cache)?.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_quarantines_truncated_archive() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = populated_cache(repo_root_path, SystemTime::now())?;
+
+        let archive = cache.cache_directory.join_component("old.tar.zst");
+        let contents = std::fs::read(archive.as_std_path())?;
+        std::fs::write(archive.as_std_path(), &contents[..contents.len() / 2])?;
+
+        assert!(cache.fetch(repo_root_path, "old")?.is_none());
+        assert!(!archive.exists());
+        assert!(cache
+            .cache_directory
+            .join_components(&[QUARANTINE_DIR, "old.tar.zst"])
+            .exists());
+        assert_eq!(
+            hashes(&cache)?,
+            vec!["middle".to_string(), "new".to_string()]
+        );
+
+        // Other artifacts are unaffected
+        assert!(cache.fetch(repo_root_path, "new")?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_quarantines_archive_without_metadata() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = populated_cache(repo_root_path, SystemTime::now())?;
+
+        cache
+            .cache_directory
+            .join_component("old-meta.json")
+            .remove_file()?;
+
+        assert!(cache.fetch(repo_root_path, "old")?.is_none());
+        assert!(cache.fetch(repo_root_path, "old")?.is_none());
+        assert_eq!(
+            hashes(&cache)?,
+            vec!["middle".to_string(), "new".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_put_and_read_archive() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = populated_cache(repo_root_path, SystemTime::now())?;
+
+        let (mut file, metadata) = cache.open_archive("old")?.unwrap();
+        assert_eq!(metadata.tag, None);
+        let mut archive = Vec::new();
+        file.read_to_end(&mut archive)?;
+
+        cache.put_archive("copy", archive.as_slice(), 1234, Some("signature"))?;
+        let (mut file, metadata) = cache.open_archive("copy")?.unwrap();
+        let mut copied_archive = Vec::new();
+        file.read_to_end(&mut copied_archive)?;
+        assert_eq!(copied_archive, archive);
+        assert_eq!(metadata.duration, 1234);
+        assert_eq!(metadata.tag.as_deref(), Some("signature"));
+
+        // Archives stored as-is can still be restored
+        let restore_root = tempdir()?;
+        let restore_root_path = AbsoluteSystemPath::from_std_path(restore_root.path())?;
+        let (_, files) = cache.fetch(restore_root_path, "copy")?.unwrap();
+        assert_eq!(files.len(), 1);
+
+        assert!(cache.open_archive("missing")?.is_none());
+        Ok(())
+    }
+
+    fn blob_count(cache: &FSCache) -> Result<usize> {
+        let mut count = 0;
+        for prefix in std::fs::read_dir(cache.cache_directory.join_component("blobs"))? {
+            count += std::fs::read_dir(prefix?.path())?.count();
+        }
+        Ok(count)
+    }
+
+    fn deduplicated_cache(repo_root: &AbsoluteSystemPath) -> Result<FSCache> {
+        let cache = FSCache::new(Utf8Path::new("cache"), repo_root, None)?
+            .with_layout(CacheLayout::Deduplicated);
+        let files = ["dist", "dist/index.js", "dist/types.d.ts", "dist/link.js"]
+            .into_iter()
+            .map(AnchoredSystemPathBuf::from_raw)
+            .collect::<Result<Vec<_>, _>>()?;
+        repo_root.join_component("dist").create_dir_all()?;
+        repo_root
+            .join_components(&["dist", "types.d.ts"])
+            .create_with_contents("export type Shared = string;")?;
+        repo_root
+            .join_components(&["dist", "link.js"])
+            .symlink_to_file("index.js")?;
+
+        for (hash, contents) in [("first", "one"), ("second", "two")] {
+            repo_root
+                .join_components(&["dist", "index.js"])
+                .create_with_contents(contents)?;
+            cache.put(repo_root, hash, &files, 10, Some("app#build"))?;
+        }
+
+        Ok(cache)
+    }
+
+    #[test]
+    fn test_deduplicated_round_trip() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = deduplicated_cache(repo_root_path)?;
+
+        // The shared types file is only stored once
+        assert_eq!(blob_count(&cache)?, 3);
+        assert!(!cache
+            .cache_directory
+            .join_component("first.tar.zst")
+            .exists());
+        assert!(cache.exists("first")?.is_some());
+
+        let entries = cache.archive_entries("first")?.unwrap();
+        assert_eq!(entries.len(), 4);
+
+        let restore_root = tempdir()?;
+        let restore_root_path = AbsoluteSystemPath::from_std_path(restore_root.path())?;
+        let (status, files) = cache.fetch(restore_root_path, "first")?.unwrap();
+        assert_eq!(status.time_saved, 10);
+        assert_eq!(files.len(), 4);
+        assert_eq!(
+            restore_root_path
+                .join_components(&["dist", "index.js"])
+                .read_to_string()?,
+            "one"
+        );
+        assert_eq!(
+            restore_root_path
+                .join_components(&["dist", "link.js"])
+                .read_to_string()?,
+            "one"
+        );
+
+        // Restoring over existing outputs replaces them
+        cache.fetch(restore_root_path, "second")?.unwrap();
+        assert_eq!(
+            restore_root_path
+                .join_components(&["dist", "index.js"])
+                .read_to_string()?,
+            "two"
+        );
+
+        // Artifacts written with the other layout can still be restored
+        let archive_cache = FSCache::new(Utf8Path::new("cache"), repo_root_path, None)?;
+        assert!(archive_cache.fetch(restore_root_path, "first")?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deduplicated_restore_copies_blobs() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = deduplicated_cache(repo_root_path)?;
+
+        let restore_root = repo_root_path.join_component("restored");
+        cache.fetch(&restore_root, "first")?.unwrap();
+
+        // A build rewriting an output in place must not change the blob that
+        // other artifacts share
+        let types = restore_root.join_components(&["dist", "types.d.ts"]);
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(types.as_std_path())?;
+        std::io::Write::write_all(&mut file, b"export type Other = number;")?;
+        drop(file);
+
+        let other_root = repo_root_path.join_component("other");
+        cache.fetch(&other_root, "second")?.unwrap();
+        assert_eq!(
+            other_root
+                .join_components(&["dist", "types.d.ts"])
+                .read_to_string()?,
+            "export type Shared = string;"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deduplicated_restore_rejects_cyclic_links() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(Utf8Path::new("cache"), repo_root_path, None)?
+            .with_layout(CacheLayout::Deduplicated);
+        repo_root_path.join_component("dist").create_dir_all()?;
+        repo_root_path
+            .join_components(&["dist", "a.js"])
+            .symlink_to_file("b.js")?;
+        repo_root_path
+            .join_components(&["dist", "b.js"])
+            .symlink_to_file("a.js")?;
+        let files = ["dist", "dist/a.js", "dist/b.js"]
+            .into_iter()
+            .map(AnchoredSystemPathBuf::from_raw)
+            .collect::<Result<Vec<_>, _>>()?;
+        cache.put(repo_root_path, "cyclic", &files, 10, None)?;
+
+        let restore_root = tempdir()?;
+        let restore_root_path = AbsoluteSystemPath::from_std_path(restore_root.path())?;
+        assert!(matches!(
+            cache.fetch(restore_root_path, "cyclic"),
+            Err(CacheError::CycleDetected(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_evicting_deduplicated_artifacts_removes_unreferenced_blobs() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = deduplicated_cache(repo_root_path)?;
+
+        assert!(cache.remove("first")?);
+        assert_eq!(blob_count(&cache)?, 2);
+
+        let summary = cache.clear()?;
+        assert_eq!(summary.evicted, 1);
+        assert!(!cache.cache_directory.join_component("blobs").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_counts_shared_blobs_once() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = deduplicated_cache(repo_root_path)?;
+
+        let summary = cache.prune_at(&CacheLimits::default(), SystemTime::now())?;
+        let artifact_bytes: u64 = cache.entries()?.iter().map(|entry| entry.size).sum();
+        let blob_bytes = ("one".len() + "two".len() + "export type Shared = string;".len()) as u64;
+        assert_eq!(summary.remaining, 2);
+        assert_eq!(summary.remaining_bytes, artifact_bytes + blob_bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_quarantines_manifest_with_missing_blob() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = deduplicated_cache(repo_root_path)?;
+
+        cache.remove_all_blobs()?;
+
+        let restore_root = tempdir()?;
+        let restore_root_path = AbsoluteSystemPath::from_std_path(restore_root.path())?;
+        assert!(cache.fetch(restore_root_path, "first")?.is_none());
+        assert!(!restore_root_path.join_component("dist").exists());
+        assert!(cache
+            .cache_directory
+            .join_components(&[QUARANTINE_DIR, "first-manifest.json"])
+            .exists());
+        assert_eq!(hashes(&cache)?, vec!["second".to_string()]);
+        Ok(())
+    }
+
     async fn round_trip_test(test_case: &TestCase, port: u16) -> Result<()> {
         let repo_root = tempdir()?;
         let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
@@ -243,7 +1313,13 @@ mod test {
             .iter()
             .map(|f| f.path().to_owned())
             .collect();
-        cache.put(repo_root_path, test_case.hash, &files, test_case.duration)?;
+        cache.put(
+            repo_root_path,
+            test_case.hash,
+            &files,
+            test_case.duration,
+            None,
+        )?;
 
         let (status, files) = cache.fetch(repo_root_path, test_case.hash)?.unwrap();
 
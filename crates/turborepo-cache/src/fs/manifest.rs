@@ -0,0 +1,519 @@
+//! Storage for the deduplicated cache layout. Instead of an archive, each
+//! artifact has a manifest listing its outputs, and the contents of every file
+//! are stored once under `blobs/`, keyed by their SHA-256 digest.
+
+use std::{
+    backtrace::Backtrace,
+    collections::{HashMap, HashSet},
+    fs,
+    io::{ErrorKind, Read},
+    path::Path,
+    time::SystemTime,
+};
+
+use camino::Utf8Path;
+use petgraph::graph::DiGraph;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf, IntoUnix};
+
+use super::{CacheEntry, FSCache, STALE_TEMP_FILE_AGE};
+use crate::{
+    cache_archive::{canonicalize_linkname, ArchiveEntry, ArchiveEntryKind, CachedDirTree},
+    CacheError,
+};
+
+const BLOBS_DIR: &str = "blobs";
+pub(super) const MANIFEST_SUFFIX: &str = "-manifest.json";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct Manifest {
+    entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum ManifestEntry {
+    Directory {
+        path: String,
+        mode: u32,
+    },
+    File {
+        path: String,
+        digest: String,
+        size: u64,
+        mode: u32,
+    },
+    Symlink {
+        path: String,
+        target: String,
+    },
+}
+
+/// Tracks which blobs each artifact refers to, so that a blob can be removed
+/// once the last artifact referring to it is evicted.
+#[derive(Debug, Default)]
+pub(super) struct BlobRefs {
+    // Number of artifacts referring to each blob along with the blob's size
+    blobs: HashMap<String, (usize, u64)>,
+    artifacts: HashMap<String, Vec<String>>,
+}
+
+impl Manifest {
+    pub(super) fn read(path: &AbsoluteSystemPath) -> Result<Self, CacheError> {
+        serde_json::from_str(&path.read_to_string()?)
+            .map_err(|e| CacheError::InvalidManifest(e, Backtrace::capture()))
+    }
+
+    pub(super) fn write(&self, path: &AbsoluteSystemPath) -> Result<(), CacheError> {
+        let contents = serde_json::to_string(self)
+            .map_err(|e| CacheError::InvalidManifest(e, Backtrace::capture()))?;
+        path.create_with_contents(contents)?;
+        Ok(())
+    }
+
+    /// Lists the files in the manifest in the same form as an archive's
+    /// entries.
+    pub(super) fn archive_entries(&self) -> Vec<ArchiveEntry> {
+        self.entries
+            .iter()
+            .map(|entry| match entry {
+                ManifestEntry::Directory { path, .. } => ArchiveEntry {
+                    path: path.clone(),
+                    kind: ArchiveEntryKind::Directory,
+                    size: 0,
+                },
+                ManifestEntry::File { path, size, .. } => ArchiveEntry {
+                    path: path.clone(),
+                    kind: ArchiveEntryKind::File,
+                    size: *size,
+                },
+                ManifestEntry::Symlink { path, .. } => ArchiveEntry {
+                    path: path.clone(),
+                    kind: ArchiveEntryKind::Symlink,
+                    size: 0,
+                },
+            })
+            .collect()
+    }
+
+    fn blobs(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.entries.iter().filter_map(|entry| match entry {
+            ManifestEntry::File { digest, size, .. } => Some((digest.as_str(), *size)),
+            _ => None,
+        })
+    }
+}
+
+impl BlobRefs {
+    /// Total size in bytes of every referenced blob
+    pub(super) fn total_size(&self) -> u64 {
+        self.blobs.values().map(|(_, size)| size).sum()
+    }
+
+    fn add(&mut self, hash: &str, manifest: &Manifest) {
+        let mut digests = Vec::new();
+        let mut seen = HashSet::new();
+        for (digest, size) in manifest.blobs() {
+            // Digests are used as paths, so anything else is ignored
+            if !is_digest(digest) {
+                continue;
+            }
+            // An artifact holds a single reference to a blob no matter how many of its
+            // files share the contents
+            if !seen.insert(digest) {
+                continue;
+            }
+            self.blobs.entry(digest.to_string()).or_insert((0, size)).0 += 1;
+            digests.push(digest.to_string());
+        }
+        self.artifacts.insert(hash.to_string(), digests);
+    }
+
+    /// Drops the references held by an artifact and returns the blobs that
+    /// are no longer referenced by anything.
+    fn release(&mut self, hash: &str) -> Vec<(String, u64)> {
+        let mut unreferenced = Vec::new();
+        for digest in self.artifacts.remove(hash).unwrap_or_default() {
+            let Some((count, size)) = self.blobs.get_mut(&digest) else {
+                continue;
+            };
+            *count -= 1;
+            if *count == 0 {
+                let size = *size;
+                self.blobs.remove(&digest);
+                unreferenced.push((digest, size));
+            }
+        }
+        unreferenced
+    }
+}
+
+impl FSCache {
+    pub(super) fn manifest_path(&self, hash: &str) -> AbsoluteSystemPathBuf {
+        self.cache_directory
+            .join_component(&format!("{}{}", hash, MANIFEST_SUFFIX))
+    }
+
+    fn blobs_dir(&self) -> AbsoluteSystemPathBuf {
+        self.cache_directory.join_component(BLOBS_DIR)
+    }
+
+    fn blob_path(&self, digest: &str) -> AbsoluteSystemPathBuf {
+        self.cache_directory
+            .join_components(&[BLOBS_DIR, &digest[..2], digest])
+    }
+
+    /// Stores the contents of `files` as blobs and returns a manifest
+    /// describing them.
+    pub(super) fn store_files(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        files: &[AnchoredSystemPathBuf],
+    ) -> Result<Manifest, CacheError> {
+        let mut entries = Vec::with_capacity(files.len());
+
+        for file in files {
+            let source_path = anchor.resolve(file);
+            let file_info = source_path.symlink_metadata()?;
+            let path = file.to_unix().to_string();
+
+            let entry = if file_info.is_symlink() {
+                // Targets are stored as Unix paths to match archives
+                let target = source_path.read_link()?.into_unix();
+                ManifestEntry::Symlink {
+                    path,
+                    target: target.into_string(),
+                }
+            } else if file_info.is_dir() {
+                ManifestEntry::Directory {
+                    path,
+                    mode: file_mode(&file_info),
+                }
+            } else if file_info.is_file() {
+                let (digest, size) = self.store_blob(&source_path)?;
+                ManifestEntry::File {
+                    path,
+                    digest,
+                    size,
+                    mode: file_mode(&file_info),
+                }
+            } else {
+                return Err(CacheError::CreateUnsupportedFileType(Backtrace::capture()));
+            };
+
+            entries.push(entry);
+        }
+
+        Ok(Manifest { entries })
+    }
+
+    /// Copies a file into the blob store, returning its digest and size. The
+    /// digest is computed from the copy so that it always matches the stored
+    /// contents, even if the source is modified concurrently.
+    fn store_blob(&self, source: &AbsoluteSystemPath) -> Result<(String, u64), CacheError> {
+        let temp_path = self.temp_path("blob")?;
+
+        let result = self.write_blob(source, &temp_path);
+        if result.is_err() {
+            let _ = temp_path.remove_file();
+        }
+
+        result
+    }
+
+    fn write_blob(
+        &self,
+        source: &AbsoluteSystemPath,
+        temp_path: &AbsoluteSystemPath,
+    ) -> Result<(String, u64), CacheError> {
+        fs::copy(source.as_std_path(), temp_path.as_std_path())?;
+        let (digest, size) = hash_file(temp_path)?;
+
+        let blob_path = self.blob_path(&digest);
+        if blob_path.exists() {
+            // Another artifact already stored these contents
+            Self::mark_used(&blob_path);
+            temp_path.remove_file()?;
+        } else {
+            blob_path.ensure_dir()?;
+            temp_path.rename(&blob_path)?;
+        }
+
+        Ok((digest, size))
+    }
+
+    /// Checks that every blob referenced by a manifest is present, so that a
+    /// manifest that can't be fully restored is treated as a miss before
+    /// anything is written to the restore destination.
+    pub(super) fn verify_manifest(&self, manifest: &Manifest) -> Result<(), CacheError> {
+        for (digest, size) in manifest.blobs() {
+            let is_valid = is_digest(digest)
+                && self
+                    .blob_path(digest)
+                    .symlink_metadata()
+                    .is_ok_and(|metadata| metadata.len() == size);
+            if !is_valid {
+                return Err(CacheError::MissingBlob(
+                    digest.to_string(),
+                    Backtrace::capture(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn restore_manifest(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        manifest: &Manifest,
+    ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
+        anchor.create_dir_all()?;
+
+        let mut dir_cache = CachedDirTree::new(anchor.to_owned());
+        let mut restored = Vec::with_capacity(manifest.entries.len());
+        let mut symlinks = Vec::new();
+
+        for entry in &manifest.entries {
+            match entry {
+                ManifestEntry::Directory { path, mode } => {
+                    let processed_name = AnchoredSystemPathBuf::from_system_path(Path::new(path))?;
+                    dir_cache.safe_mkdir_all(anchor, &processed_name, *mode)?;
+                    restored.push(processed_name);
+                }
+                ManifestEntry::File {
+                    path, digest, mode, ..
+                } => {
+                    let processed_name = AnchoredSystemPathBuf::from_system_path(Path::new(path))?;
+                    dir_cache.safe_mkdir_file(anchor, &processed_name)?;
+                    self.restore_blob(digest, &anchor.resolve(&processed_name), *mode)?;
+                    restored.push(processed_name);
+                }
+                ManifestEntry::Symlink { path, target } => symlinks.push((path, target)),
+            }
+        }
+
+        // Symlinks are restored last so that targets within the artifact exist
+        for (processed_name, target) in Self::sort_symlinks(anchor, symlinks)? {
+            dir_cache.safe_mkdir_file(anchor, &processed_name)?;
+
+            let symlink_from = anchor.resolve(&processed_name);
+            _ = symlink_from.remove();
+
+            if Utf8Path::new(target).is_dir() {
+                symlink_from.symlink_to_dir(target)?;
+            } else {
+                symlink_from.symlink_to_file(target)?;
+            }
+
+            restored.push(processed_name);
+        }
+
+        Ok(restored)
+    }
+
+    /// Orders symlinks so that links pointing at other links in the artifact
+    /// are restored after them, using the same canonicalization of link
+    /// targets as archive restores. Cyclic links are rejected.
+    fn sort_symlinks<'a>(
+        anchor: &AbsoluteSystemPath,
+        symlinks: Vec<(&String, &'a String)>,
+    ) -> Result<Vec<(AnchoredSystemPathBuf, &'a String)>, CacheError> {
+        let mut graph = DiGraph::new();
+        let mut nodes = HashMap::new();
+        let mut links = HashMap::new();
+
+        for (path, target) in symlinks {
+            let processed_name = AnchoredSystemPathBuf::from_system_path(Path::new(path))?;
+            let processed_sourcename =
+                canonicalize_linkname(anchor, &processed_name, processed_name.as_path())?;
+            let processed_linkname =
+                canonicalize_linkname(anchor, &processed_name, Path::new(target))?;
+
+            let source_node = *nodes
+                .entry(processed_sourcename.clone())
+                .or_insert_with(|| graph.add_node(processed_sourcename.clone()));
+            let link_node = *nodes
+                .entry(processed_linkname.clone())
+                .or_insert_with(|| graph.add_node(processed_linkname));
+            graph.add_edge(source_node, link_node, ());
+
+            links.insert(processed_sourcename, (processed_name, target));
+        }
+
+        let sorted = petgraph::algo::toposort(&graph, None)
+            .map_err(|_| CacheError::CycleDetected(Backtrace::capture()))?;
+
+        // Edges point from a link to its target, so targets come last
+        Ok(sorted
+            .into_iter()
+            .rev()
+            .filter_map(|node| links.remove(&graph[node]))
+            .collect())
+    }
+
+    /// Restores a blob to `destination`.
+    ///
+    /// Blobs are always copied rather than hardlinked, since a build that
+    /// rewrites an output in place would otherwise change the blob for every
+    /// artifact that uses it. On copy-on-write filesystems such as APFS,
+    /// Btrfs and XFS, `fs::copy` clones the blob instead of duplicating its
+    /// contents.
+    // Windows doesn't have file modes, so mode is unused
+    #[allow(unused_variables)]
+    fn restore_blob(
+        &self,
+        digest: &str,
+        destination: &AbsoluteSystemPath,
+        mode: u32,
+    ) -> Result<(), CacheError> {
+        match destination.remove_file() {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let blob_path = self.blob_path(digest);
+        fs::copy(blob_path.as_std_path(), destination.as_std_path())?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(destination.as_std_path(), fs::Permissions::from_mode(mode))?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the manifest of every entry that has one. Unreadable manifests
+    /// are skipped since their artifacts can't be restored anyway.
+    pub(super) fn blob_refs(&self, entries: &[CacheEntry]) -> Result<BlobRefs, CacheError> {
+        let mut refs = BlobRefs::default();
+
+        for entry in entries {
+            let manifest_path = self.manifest_path(&entry.hash);
+            if !entry.files.contains(&manifest_path) {
+                continue;
+            }
+            match Manifest::read(&manifest_path) {
+                Ok(manifest) => refs.add(&entry.hash, &manifest),
+                Err(err) => debug!("failed to read cache manifest {manifest_path}: {err}"),
+            }
+        }
+
+        Ok(refs)
+    }
+
+    /// Drops an evicted artifact's references and removes any blobs that are
+    /// no longer referenced. Returns the number of bytes freed.
+    ///
+    /// A concurrent write may reuse a blob just before it's removed. That
+    /// artifact's blob will then be missing, which is treated as a cache miss.
+    pub(super) fn release_blobs(&self, refs: &mut BlobRefs, hash: &str) -> Result<u64, CacheError> {
+        let mut freed = 0;
+        for (digest, size) in refs.release(hash) {
+            match self.blob_path(&digest).remove_file() {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+            freed += size;
+        }
+
+        Ok(freed)
+    }
+
+    /// Removes blobs that no artifact refers to, such as those left behind by
+    /// a write that never completed. Recently used blobs are kept since they
+    /// may belong to a write that is still in progress.
+    pub(super) fn remove_orphaned_blobs(
+        &self,
+        refs: &BlobRefs,
+        now: SystemTime,
+    ) -> Result<(), CacheError> {
+        let prefixes = match fs::read_dir(self.blobs_dir().as_std_path()) {
+            Ok(prefixes) => prefixes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        for prefix in prefixes {
+            let prefix = prefix?;
+            if !prefix.file_type()?.is_dir() {
+                continue;
+            }
+
+            for blob in fs::read_dir(prefix.path())? {
+                let blob = blob?;
+                let is_referenced = blob
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|digest| refs.blobs.contains_key(digest));
+                let is_stale = blob
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .is_ok_and(|modified| {
+                        now.duration_since(modified)
+                            .is_ok_and(|age| age > STALE_TEMP_FILE_AGE)
+                    });
+                if !is_referenced && is_stale {
+                    match fs::remove_file(blob.path()) {
+                        Ok(()) => {}
+                        Err(err) if err.kind() == ErrorKind::NotFound => {}
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes every blob, referenced or not.
+    pub(super) fn remove_all_blobs(&self) -> Result<(), CacheError> {
+        match self.blobs_dir().remove_dir_all() {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+fn hash_file(path: &AbsoluteSystemPath) -> Result<(String, u64), CacheError> {
+    let mut file = path.open()?;
+    let mut hasher = Sha256::new();
+    let mut size = 0;
+    let mut buffer = [0; 8192];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        size += n as u64;
+    }
+
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+fn is_digest(digest: &str) -> bool {
+    digest.len() == 64 && digest.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+// Matches the mode stored in archives, see `CacheWriter::create_header`
+#[allow(unused_variables)]
+fn file_mode(file_info: &fs::Metadata) -> u32 {
+    let mode: u32;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        mode = file_info.mode();
+    }
+    #[cfg(windows)]
+    {
+        mode = 0o755;
+    }
+    mode
+}
@@ -16,6 +16,7 @@ use turborepo_api_client::{
 
 use crate::{
     cache_archive::{CacheReader, CacheWriter},
+    object_store::ObjectStore,
     signature_authentication::ArtifactSignatureAuthenticator,
     upload_progress::{UploadProgress, UploadProgressQuery},
     CacheError, CacheHitMetadata, CacheOpts, CacheSource,
@@ -24,14 +25,37 @@ use crate::{
 pub type UploadMap = HashMap<String, UploadProgressQuery<10, 100>>;
 
 pub struct HTTPCache {
-    client: APIClient,
+    backend: Backend,
     signer_verifier: Option<ArtifactSignatureAuthenticator>,
     repo_root: AbsoluteSystemPathBuf,
-    api_auth: APIAuth,
     analytics_recorder: Option<AnalyticsSender>,
     uploads: Arc<Mutex<UploadMap>>,
 }
 
+enum Backend {
+    Vercel {
+        client: APIClient,
+        api_auth: APIAuth,
+    },
+    ObjectStore(ObjectStore),
+}
+
+impl Backend {
+    fn duration_header(&self) -> &'static str {
+        match self {
+            Backend::Vercel { .. } => "x-artifact-duration",
+            Backend::ObjectStore(store) => store.duration_header(),
+        }
+    }
+
+    fn tag_header(&self) -> &'static str {
+        match self {
+            Backend::Vercel { .. } => "x-artifact-tag",
+            Backend::ObjectStore(store) => store.tag_header(),
+        }
+    }
+}
+
 impl HTTPCache {
     #[tracing::instrument(skip_all)]
     pub fn new(
@@ -40,6 +64,38 @@ impl HTTPCache {
         repo_root: AbsoluteSystemPathBuf,
         api_auth: APIAuth,
         analytics_recorder: Option<AnalyticsSender>,
+    ) -> HTTPCache {
+        let team_id = api_auth.team_id.clone();
+        Self::with_backend(
+            Backend::Vercel { client, api_auth },
+            team_id.as_deref(),
+            opts,
+            repo_root,
+            analytics_recorder,
+        )
+    }
+
+    /// Creates a cache backed by an object store instead of the Vercel API.
+    /// Analytics are only recorded for the Vercel API.
+    #[tracing::instrument(skip_all)]
+    pub(crate) fn with_object_store(
+        store: ObjectStore,
+        opts: &CacheOpts,
+        repo_root: AbsoluteSystemPathBuf,
+    ) -> HTTPCache {
+        let team_id = opts
+            .remote_cache_opts
+            .as_ref()
+            .and_then(|remote_cache_opts| remote_cache_opts.team_id());
+        Self::with_backend(Backend::ObjectStore(store), team_id, opts, repo_root, None)
+    }
+
+    fn with_backend(
+        backend: Backend,
+        team_id: Option<&str>,
+        opts: &CacheOpts,
+        repo_root: AbsoluteSystemPathBuf,
+        analytics_recorder: Option<AnalyticsSender>,
     ) -> HTTPCache {
         let signer_verifier = if opts
             .remote_cache_opts
@@ -47,12 +103,7 @@ impl HTTPCache {
             .is_some_and(|remote_cache_opts| remote_cache_opts.signature)
         {
             Some(ArtifactSignatureAuthenticator {
-                team_id: api_auth
-                    .team_id
-                    .as_deref()
-                    .unwrap_or_default()
-                    .as_bytes()
-                    .to_vec(),
+                team_id: team_id.unwrap_or_default().as_bytes().to_vec(),
                 secret_key_override: None,
             })
         } else {
@@ -60,11 +111,10 @@ impl HTTPCache {
         };
 
         HTTPCache {
-            client,
+            backend,
             signer_verifier,
             repo_root,
             uploads: Arc::new(Mutex::new(HashMap::new())),
-            api_auth,
             analytics_recorder,
         }
     }
@@ -105,19 +155,26 @@ impl HTTPCache {
 
         tracing::debug!("uploading {}", hash);
 
-        self.client
-            .put_artifact(
-                hash,
-                progress,
-                bytes,
-                duration,
-                tag.as_deref(),
-                &self.api_auth.token,
-                self.api_auth.team_id.as_deref(),
-                self.api_auth.team_slug.as_deref(),
-            )
-            .await
-            .map_err(|err| Self::convert_api_error(hash, err))?;
+        match &self.backend {
+            Backend::Vercel { client, api_auth } => client
+                .put_artifact(
+                    hash,
+                    progress,
+                    bytes,
+                    duration,
+                    tag.as_deref(),
+                    &api_auth.token,
+                    api_auth.team_id.as_deref(),
+                    api_auth.team_slug.as_deref(),
+                )
+                .await
+                .map_err(|err| Self::convert_api_error(hash, err))?,
+            Backend::ObjectStore(store) => {
+                store
+                    .put(hash, progress, bytes, duration, tag.as_deref())
+                    .await?
+            }
+        }
         tracing::debug!("uploaded {}", hash);
         Ok(())
     }
@@ -134,25 +191,29 @@ impl HTTPCache {
             cache_archive.add_file(anchor, file)?;
         }
 
-        Ok(())
+        cache_archive.finish()
     }
 
     #[tracing::instrument(skip_all)]
     pub async fn exists(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
-        let Some(response) = self
-            .client
-            .artifact_exists(
-                hash,
-                &self.api_auth.token,
-                self.api_auth.team_id.as_deref(),
-                self.api_auth.team_slug.as_deref(),
-            )
-            .await?
-        else {
+        let response = match &self.backend {
+            Backend::Vercel { client, api_auth } => {
+                client
+                    .artifact_exists(
+                        hash,
+                        &api_auth.token,
+                        api_auth.team_id.as_deref(),
+                        api_auth.team_slug.as_deref(),
+                    )
+                    .await?
+            }
+            Backend::ObjectStore(store) => store.exists(hash).await?,
+        };
+        let Some(response) = response else {
             return Ok(None);
         };
 
-        let duration = Self::get_duration_from_response(&response)?;
+        let duration = self.get_duration_from_response(&response)?;
 
         Ok(Some(CacheHitMetadata {
             source: CacheSource::Remote,
@@ -160,8 +221,8 @@ impl HTTPCache {
         }))
     }
 
-    fn get_duration_from_response(response: &Response) -> Result<u64, CacheError> {
-        if let Some(duration_value) = response.headers().get("x-artifact-duration") {
+    fn get_duration_from_response(&self, response: &Response) -> Result<u64, CacheError> {
+        if let Some(duration_value) = response.headers().get(self.backend.duration_header()) {
             let duration = duration_value
                 .to_str()
                 .map_err(|_| CacheError::InvalidDuration(Backtrace::capture()))?;
@@ -194,26 +255,30 @@ impl HTTPCache {
         &self,
         hash: &str,
     ) -> Result<Option<(CacheHitMetadata, Vec<AnchoredSystemPathBuf>)>, CacheError> {
-        let Some(response) = self
-            .client
-            .fetch_artifact(
-                hash,
-                &self.api_auth.token,
-                self.api_auth.team_id.as_deref(),
-                self.api_auth.team_slug.as_deref(),
-            )
-            .await?
-        else {
+        let response = match &self.backend {
+            Backend::Vercel { client, api_auth } => {
+                client
+                    .fetch_artifact(
+                        hash,
+                        &api_auth.token,
+                        api_auth.team_id.as_deref(),
+                        api_auth.team_slug.as_deref(),
+                    )
+                    .await?
+            }
+            Backend::ObjectStore(store) => store.fetch(hash).await?,
+        };
+        let Some(response) = response else {
             self.log_fetch(analytics::CacheEvent::Miss, hash, 0);
             return Ok(None);
         };
 
-        let duration = Self::get_duration_from_response(&response)?;
+        let duration = self.get_duration_from_response(&response)?;
 
         let body = if let Some(signer_verifier) = &self.signer_verifier {
             let expected_tag = response
                 .headers()
-                .get("x-artifact-tag")
+                .get(self.backend.tag_header())
                 .ok_or(CacheError::ArtifactTagMissing(Backtrace::capture()))?;
 
             let expected_tag = expected_tag
@@ -268,7 +333,7 @@ impl HTTPCache {
         cache_reader.restore(root)
     }
 
-    fn convert_api_error(hash: &str, err: turborepo_api_client::Error) -> CacheError {
+    pub(crate) fn convert_api_error(hash: &str, err: turborepo_api_client::Error) -> CacheError {
         match err {
             turborepo_api_client::Error::ReqwestError(e) if e.is_timeout() => {
                 CacheError::TimeoutError(hash.to_string())
@@ -342,6 +407,8 @@ mod test {
             cache: Default::default(),
             workers: 0,
             remote_cache_opts: None,
+            local_limits: Default::default(),
+            local_layout: Default::default(),
         };
         let api_auth = APIAuth {
             team_id: Some("my-team".to_string()),
@@ -15,6 +15,8 @@ pub mod http;
 /// A wrapper that allows reads and writes from the file system and remote
 /// cache.
 mod multiplexer;
+/// Remote cache backends that aren't the Vercel API
+mod object_store;
 /// Cache signature authentication lets users provide a private key to sign
 /// their cache payloads.
 pub mod signature_authentication;
@@ -22,7 +24,7 @@ pub mod signature_authentication;
 mod test_cases;
 mod upload_progress;
 
-use std::{backtrace, backtrace::Backtrace};
+use std::{backtrace, backtrace::Backtrace, time::Duration};
 
 pub use async_cache::AsyncCache;
 use camino::Utf8PathBuf;
@@ -80,12 +82,24 @@ pub enum CacheError {
     InvalidMetadata(serde_json::Error, #[backtrace] Backtrace),
     #[error("Failed to write cache metadata file")]
     MetadataWriteFailure(serde_json::Error, #[backtrace] Backtrace),
+    #[error("Invalid cache manifest file")]
+    InvalidManifest(serde_json::Error, #[backtrace] Backtrace),
+    #[error("invalid artifact hash `{0}`")]
+    InvalidHash(String, #[backtrace] Backtrace),
+    #[error("cache blob {0} is missing or corrupt")]
+    MissingBlob(String, #[backtrace] Backtrace),
     #[error("Unable to perform write as cache is shutting down")]
     CacheShuttingDown,
     #[error("Unable to determine config cache base")]
     ConfigCacheInvalidBase,
     #[error("Unable to hash config cache inputs")]
     ConfigCacheError,
+    #[error("invalid remote cache URL: {0}")]
+    InvalidRemoteCacheUrl(String),
+    #[error("remote cache token is not a valid header value")]
+    InvalidRemoteCacheToken,
+    #[error("remote cache responded with {0} for {1}")]
+    RemoteStoreStatus(reqwest::StatusCode, String, #[backtrace] Backtrace),
     #[error("Insufficient permissions to write to remote cache. Please verify that your role has write access for Remote Cache Artifact at https://vercel.com/docs/accounts/team-members-and-roles/access-roles/team-level-roles?resource=Remote+Cache+Artifact")]
     ForbiddenRemoteCacheWrite,
 }
@@ -178,19 +192,92 @@ pub struct CacheOpts {
     pub cache: CacheConfig,
     pub workers: u32,
     pub remote_cache_opts: Option<RemoteCacheOpts>,
+    pub local_limits: CacheLimits,
+    pub local_layout: CacheLayout,
+}
+
+/// How artifacts are stored in the local filesystem cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheLayout {
+    /// Each artifact is a compressed tarball of its outputs
+    #[default]
+    Archive,
+    /// Each artifact is a manifest of its outputs, with file contents stored
+    /// once by content hash and shared between artifacts
+    Deduplicated,
+}
+
+/// Bounds on the local filesystem cache. Once either bound is exceeded,
+/// artifacts are evicted in least-recently-used order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CacheLimits {
+    /// Maximum total size of the cache directory in bytes
+    pub max_size: Option<u64>,
+    /// Maximum time since an artifact was last written or restored
+    pub max_age: Option<Duration>,
+}
+
+impl CacheLimits {
+    pub fn is_unbounded(&self) -> bool {
+        self.max_size.is_none() && self.max_age.is_none()
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RemoteCacheOpts {
-    unused_team_id: Option<String>,
+    team_id: Option<String>,
     signature: bool,
+    backend: RemoteCacheBackend,
 }
 
 impl RemoteCacheOpts {
-    pub fn new(unused_team_id: Option<String>, signature: bool) -> Self {
+    pub fn new(team_id: Option<String>, signature: bool, backend: RemoteCacheBackend) -> Self {
         Self {
-            unused_team_id,
+            team_id,
             signature,
+            backend,
         }
     }
+
+    /// The configured team id. Only used to sign artifacts stored outside of
+    /// the Vercel API, which otherwise uses the team that the repo is linked
+    /// to.
+    pub fn team_id(&self) -> Option<&str> {
+        self.team_id.as_deref()
+    }
+
+    pub fn backend(&self) -> &RemoteCacheBackend {
+        &self.backend
+    }
+}
+
+/// Where remote cache artifacts are stored.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RemoteCacheBackend {
+    /// The Vercel artifacts API, or a server implementing it
+    #[default]
+    Vercel,
+    /// An S3-compatible object store
+    S3 {
+        bucket: String,
+        region: String,
+        /// Custom endpoint for S3-compatible stores such as MinIO. Objects are
+        /// addressed path-style when set.
+        endpoint: Option<String>,
+        /// Prefix prepended to every object key
+        prefix: Option<String>,
+    },
+    /// A server that stores artifacts with plain `PUT` and serves them with
+    /// `GET`, such as a WebDAV server
+    Http { url: String },
+}
+
+impl RemoteCacheBackend {
+    /// Whether the backend is the Vercel API, which requires the repository
+    /// to be linked.
+    pub fn is_vercel(&self) -> bool {
+        matches!(self, RemoteCacheBackend::Vercel)
+    }
 }
@@ -9,9 +9,10 @@ use turborepo_analytics::AnalyticsSender;
 use turborepo_api_client::{APIAuth, APIClient};
 
 use crate::{
-    fs::FSCache,
+    fs::{FSCache, PruneSummary},
     http::{HTTPCache, UploadMap},
-    CacheConfig, CacheError, CacheHitMetadata, CacheOpts,
+    object_store::ObjectStore,
+    CacheConfig, CacheError, CacheHitMetadata, CacheLimits, CacheOpts,
 };
 
 pub struct CacheMultiplexer {
@@ -24,6 +25,7 @@ pub struct CacheMultiplexer {
     // being read-only
     should_print_skipping_remote_put: AtomicBool,
     cache_config: CacheConfig,
+    local_limits: CacheLimits,
     fs: Option<FSCache>,
     http: Option<HTTPCache>,
 }
@@ -48,26 +50,50 @@ impl CacheMultiplexer {
         }
 
         let fs_cache = use_fs_cache
-            .then(|| FSCache::new(&opts.cache_dir, repo_root, analytics_recorder.clone()))
+            .then(|| {
+                FSCache::new(&opts.cache_dir, repo_root, analytics_recorder.clone())
+                    .map(|cache| cache.with_layout(opts.local_layout))
+            })
             .transpose()?;
 
-        let http_cache = use_http_cache
-            .then_some(api_auth)
-            .flatten()
-            .map(|api_auth| {
-                HTTPCache::new(
-                    api_client,
-                    opts,
-                    repo_root.to_owned(),
-                    api_auth,
-                    analytics_recorder.clone(),
-                )
-            });
+        let backend = opts
+            .remote_cache_opts
+            .as_ref()
+            .map(|remote_cache_opts| remote_cache_opts.backend())
+            .cloned()
+            .unwrap_or_default();
+        let object_store = use_http_cache
+            .then(|| ObjectStore::new(&backend))
+            .transpose()?
+            .flatten();
+
+        // Object stores authenticate on their own, only the Vercel API needs
+        // the repo to be linked.
+        let http_cache = match object_store {
+            Some(store) => Some(HTTPCache::with_object_store(
+                store,
+                opts,
+                repo_root.to_owned(),
+            )),
+            None => use_http_cache
+                .then_some(api_auth)
+                .flatten()
+                .map(|api_auth| {
+                    HTTPCache::new(
+                        api_client,
+                        opts,
+                        repo_root.to_owned(),
+                        api_auth,
+                        analytics_recorder.clone(),
+                    )
+                }),
+        };
 
         Ok(CacheMultiplexer {
             should_print_skipping_remote_put: AtomicBool::new(true),
             should_use_http_cache: AtomicBool::new(http_cache.is_some()),
             cache_config: opts.cache,
+            local_limits: opts.local_limits,
             fs: fs_cache,
             http: http_cache,
         })
@@ -87,6 +113,18 @@ impl CacheMultiplexer {
         self.http.as_ref().map(|http| http.requests())
     }
 
+    /// Evicts artifacts from the local cache until it is within the configured
+    /// limits. Returns `None` if there is no local cache or it is unbounded.
+    #[tracing::instrument(skip_all)]
+    pub fn prune_local(&self) -> Result<Option<PruneSummary>, CacheError> {
+        match &self.fs {
+            Some(fs) if self.cache_config.local.write && !self.local_limits.is_unbounded() => {
+                fs.prune(&self.local_limits).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn put(
         &self,
@@ -94,11 +132,12 @@ impl CacheMultiplexer {
         key: &str,
         files: &[AnchoredSystemPathBuf],
         duration: u64,
+        task_id: Option<&str>,
     ) -> Result<(), CacheError> {
         if self.cache_config.local.write {
             self.fs
                 .as_ref()
-                .map(|fs| fs.put(anchor, key, files, duration))
+                .map(|fs| fs.put(anchor, key, files, duration, task_id))
                 .transpose()?;
         }
 
@@ -165,7 +204,7 @@ impl CacheMultiplexer {
                     // optimization.
                     if self.cache_config.local.write {
                         if let Some(fs) = &self.fs {
-                            let _ = fs.put(anchor, key, &files, time_saved);
+                            let _ = fs.put(anchor, key, &files, time_saved, None);
                         }
                     }
 
@@ -0,0 +1,267 @@
+//! Automatic fixes for the problems found by `turbo boundaries --fix`
+use std::collections::{BTreeMap, HashMap};
+
+use itertools::Itertools;
+use miette::SourceSpan;
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+use turborepo_repository::{package_graph::PackageName, package_manager::PackageManager};
+
+use super::{BoundariesDiagnostic, BoundariesResult, Error};
+use crate::{rewrite_json, run::Run};
+
+impl Run {
+    /// Fixes the diagnostics that can be fixed automatically and returns the
+    /// ones that can't be.
+    ///
+    /// - Undeclared imports of packages are added to the `dependencies` of the
+    ///   importing package.
+    /// - Value imports from `@types` packages are made type-only.
+    pub async fn fix_boundaries(
+        &self,
+        result: BoundariesResult,
+    ) -> Result<BoundariesResult, Error> {
+        let mut missing_dependencies: BTreeMap<AbsoluteSystemPathBuf, BTreeMap<String, String>> =
+            BTreeMap::new();
+        let mut type_only_imports: HashMap<AbsoluteSystemPathBuf, Vec<BoundariesDiagnostic>> =
+            HashMap::new();
+        let mut unfixed = Vec::new();
+
+        for diagnostic in result.diagnostics {
+            match &diagnostic {
+                BoundariesDiagnostic::PackageNotFound { name, text, .. } => {
+                    let file_path = AbsoluteSystemPathBuf::new(text.name())?;
+                    let package_json_path = self.package_for_path(&file_path).and_then(|package| {
+                        let package_info = self.pkg_dep_graph().package_info(package)?;
+                        Some(self.repo_root().resolve(package_info.package_json_path()))
+                    });
+                    match (package_json_path, self.dependency_specifier(name)) {
+                        (Some(package_json_path), Some(specifier)) => {
+                            missing_dependencies
+                                .entry(package_json_path)
+                                .or_default()
+                                .insert(name.clone(), specifier);
+                        }
+                        _ => unfixed.push(diagnostic),
+                    }
+                }
+                BoundariesDiagnostic::NotTypeOnlyImport { text, .. } => {
+                    let file_path = AbsoluteSystemPathBuf::new(text.name())?;
+                    type_only_imports
+                        .entry(file_path)
+                        .or_default()
+                        .push(diagnostic);
+                }
+                _ => unfixed.push(diagnostic),
+            }
+        }
+
+        for (package_json_path, dependencies) in missing_dependencies {
+            let mut contents = read_file(&package_json_path).await?;
+            for (name, specifier) in dependencies {
+                let specifier =
+                    serde_json::to_string(&specifier).expect("strings can be serialized");
+                contents = rewrite_json::set_path(&contents, &["dependencies", &name], &specifier)?;
+            }
+            write_file(&package_json_path, contents).await?;
+        }
+
+        for (file_path, diagnostics) in type_only_imports {
+            let mut contents = read_file(&file_path).await?;
+            let mut changed = false;
+            // Imports are rewritten from the end of the file so that the spans
+            // of earlier imports are still valid after each rewrite
+            for diagnostic in diagnostics
+                .into_iter()
+                .sorted_by_key(|diagnostic| match diagnostic {
+                    BoundariesDiagnostic::NotTypeOnlyImport { span, .. } => span.offset(),
+                    _ => 0,
+                })
+                .rev()
+            {
+                let BoundariesDiagnostic::NotTypeOnlyImport { span, .. } = &diagnostic else {
+                    continue;
+                };
+                if make_type_only(&mut contents, *span) {
+                    changed = true;
+                } else {
+                    debug!("unable to make import in {} type-only", file_path);
+                    unfixed.push(diagnostic);
+                }
+            }
+            if changed {
+                write_file(&file_path, contents).await?;
+            }
+        }
+
+        Ok(BoundariesResult {
+            source_map: result.source_map,
+            diagnostics: unfixed,
+            suppressed: result.suppressed,
+        })
+    }
+
+    /// The version specifier to use when adding `name` as a dependency.
+    /// Workspace packages use the workspace protocol and external packages
+    /// use the version that the lockfile resolves for other packages in the
+    /// repository.
+    fn dependency_specifier(&self, name: &str) -> Option<String> {
+        let package_graph = self.pkg_dep_graph();
+        if package_graph
+            .package_info(&PackageName::from(name))
+            .is_some()
+        {
+            let specifier = match package_graph.package_manager() {
+                // npm and Yarn 1 don't support the workspace protocol
+                PackageManager::Npm | PackageManager::Yarn => "*",
+                _ => "workspace:*",
+            };
+            return Some(specifier.to_string());
+        }
+
+        let lockfile = package_graph.lockfile()?;
+        package_graph
+            .packages()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .find_map(|(_, package_info)| {
+                let version = package_info
+                    .unresolved_external_dependencies
+                    .as_ref()?
+                    .get(name)?;
+                let workspace_path = package_info.package_path().to_unix();
+                let package = lockfile
+                    .resolve_package(workspace_path.as_str(), name, version)
+                    .ok()??;
+                // pnpm appends peer dependency information to versions
+                let version = package.version.split('(').next()?;
+                version
+                    .starts_with(|c: char| c.is_ascii_digit())
+                    .then(|| format!("^{version}"))
+            })
+    }
+}
+
+async fn read_file(path: &AbsoluteSystemPath) -> Result<String, Error> {
+    tokio::fs::read_to_string(path)
+        .await
+        .map_err(|_| Error::FileNotFound(path.to_owned()))
+}
+
+async fn write_file(path: &AbsoluteSystemPath, contents: String) -> Result<(), Error> {
+    tokio::fs::write(path, contents)
+        .await
+        .map_err(|_| Error::FileNotWritten(path.to_owned()))
+}
+
+// Spans from the import finder are measured in characters
+fn byte_range(contents: &str, span: SourceSpan) -> Option<(usize, usize)> {
+    let mut offsets = contents
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain(std::iter::once(contents.len()));
+    let start = offsets.nth(span.offset())?;
+    let end = if span.len() == 0 {
+        start
+    } else {
+        offsets.nth(span.len() - 1)?
+    };
+    Some((start, end))
+}
+
+// Returns the part of an import declaration before the module source
+fn strip_source(declaration: &str) -> Option<&str> {
+    let declaration = declaration.trim_end().trim_end_matches(';').trim_end();
+    let quote = declaration
+        .chars()
+        .last()
+        .filter(|c| matches!(c, '"' | '\''))?;
+    let source_start = declaration[..declaration.len() - 1].rfind(quote)?;
+    Some(&declaration[..source_start])
+}
+
+/// Adds `type` to the import declaration at `span`. Returns false if the
+/// import can't be made type-only, e.g. side effect imports, `require` calls
+/// or imports with both a default import and named imports.
+fn make_type_only(contents: &mut String, span: SourceSpan) -> bool {
+    let Some((start, end)) = byte_range(contents, span) else {
+        return false;
+    };
+    let declaration = &contents[start..end];
+    let Some(rest) = declaration.strip_prefix("import") else {
+        return false;
+    };
+    if !rest.starts_with(char::is_whitespace) {
+        return false;
+    }
+    let Some(before_source) = strip_source(rest) else {
+        return false;
+    };
+    let Some(clause) = before_source.trim_end().strip_suffix("from") else {
+        return false;
+    };
+    let clause = clause.trim();
+    if clause.is_empty() || clause.starts_with("type ") {
+        return false;
+    }
+    let has_default_import = !clause.starts_with(['{', '*']);
+    if has_default_import && clause.contains(',') {
+        return false;
+    }
+
+    contents.insert_str(start + "import".len(), " type");
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(
+        r#"import { Foo } from "@types/foo";"#,
+        Some(r#"import type { Foo } from "@types/foo";"#)
+        ; "named"
+    )]
+    #[test_case(
+        "import Foo from '@types/foo'",
+        Some("import type Foo from '@types/foo'")
+        ; "default"
+    )]
+    #[test_case(
+        r#"import * as foo from "@types/foo";"#,
+        Some(r#"import type * as foo from "@types/foo";"#)
+        ; "namespace"
+    )]
+    #[test_case(
+        r#"import {
+  Foo,
+  Bar,
+} from "@types/foo";"#,
+        Some(r#"import type {
+  Foo,
+  Bar,
+} from "@types/foo";"#)
+        ; "multiline"
+    )]
+    #[test_case(r#"import "@types/foo";"#, None ; "side effect")]
+    #[test_case(r#"import Foo, { Bar } from "@types/foo";"#, None ; "default and named")]
+    #[test_case(r#"require("@types/foo")"#, None ; "require")]
+    fn test_make_type_only(declaration: &str, expected: Option<&str>) {
+        let source = format!("const a = 'ü';\n{declaration}\nfoo();\n");
+        let offset = source.chars().position(|c| c == '\n').unwrap() + 1;
+        let span = SourceSpan::new(offset.into(), declaration.chars().count().into());
+        let mut contents = source.clone();
+        let fixed = make_type_only(&mut contents, span);
+        match expected {
+            Some(expected) => {
+                assert!(fixed);
+                assert_eq!(contents, format!("const a = 'ü';\n{expected}\nfoo();\n"));
+            }
+            None => {
+                assert!(!fixed);
+                assert_eq!(contents, source);
+            }
+        }
+    }
+}
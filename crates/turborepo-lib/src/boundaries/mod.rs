@@ -0,0 +1,925 @@
+mod fix;
+mod tags;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use git2::Repository;
+use itertools::Itertools;
+use miette::{Diagnostic, NamedSource, Report, SourceSpan};
+use oxc_resolver::{ResolveError, Resolver};
+use regex::Regex;
+use swc_common::{
+    comments::SingleThreadedComments,
+    errors::{ColorConfig, Handler},
+    input::StringInput,
+    FileName, SourceMap, Span,
+};
+use swc_ecma_ast::EsVersion;
+use swc_ecma_parser::{lexer::Lexer, Capturing, EsSyntax, Parser, Syntax, TsSyntax};
+use swc_ecma_visit::VisitWith;
+use thiserror::Error;
+use turbo_trace::{ImportFinder, ImportType, Tracer};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, PathRelation, RelativeUnixPath};
+use turborepo_repository::{
+    package_graph::{PackageName, PackageNode},
+    package_json::PackageJson,
+};
+use wax::Program;
+
+use self::tags::{PackageTags, TagViolation};
+use crate::{config, run::Run};
+
+#[derive(Clone, Debug, Error, Diagnostic)]
+pub enum BoundariesDiagnostic {
+    #[error(
+        "importing from a type declaration package, but import is not declared as a type-only \
+         import"
+    )]
+    #[help("add `type` to the import declaration")]
+    NotTypeOnlyImport {
+        import: String,
+        #[label("package imported here")]
+        span: SourceSpan,
+        #[source_code]
+        text: Arc<NamedSource>,
+    },
+    #[error("cannot import package `{name}` because it is not a dependency")]
+    PackageNotFound {
+        name: String,
+        #[label("package imported here")]
+        span: SourceSpan,
+        #[source_code]
+        text: Arc<NamedSource>,
+    },
+    #[error("cannot import file `{import}` because it leaves the package")]
+    ImportLeavesPackage {
+        import: String,
+        #[label("file imported here")]
+        span: SourceSpan,
+        #[source_code]
+        text: Arc<NamedSource>,
+    },
+    #[error(
+        "package `{package_name}` cannot depend on `{dependency}` because a `deny` rule forbids \
+         it: {reason}"
+    )]
+    DeniedTag {
+        package_name: String,
+        dependency: String,
+        reason: String,
+        #[label("dependency found here")]
+        span: SourceSpan,
+        #[source_code]
+        text: Arc<NamedSource>,
+    },
+    #[error(
+        "package `{package_name}` cannot depend on `{dependency}` because no `allow` rule permits \
+         it: {reason}"
+    )]
+    TagNotAllowed {
+        package_name: String,
+        dependency: String,
+        reason: String,
+        #[label("dependency found here")]
+        span: SourceSpan,
+        #[source_code]
+        text: Arc<NamedSource>,
+    },
+    #[error("failed to parse file {0}")]
+    ParseError(AbsoluteSystemPathBuf, swc_ecma_parser::error::Error),
+}
+
+impl BoundariesDiagnostic {
+    /// The path of the file that the diagnostic was found in
+    pub fn path(&self) -> &str {
+        match self {
+            BoundariesDiagnostic::NotTypeOnlyImport { text, .. }
+            | BoundariesDiagnostic::PackageNotFound { text, .. }
+            | BoundariesDiagnostic::ImportLeavesPackage { text, .. }
+            | BoundariesDiagnostic::DeniedTag { text, .. }
+            | BoundariesDiagnostic::TagNotAllowed { text, .. } => text.name(),
+            BoundariesDiagnostic::ParseError(path, _) => path.as_str(),
+        }
+    }
+
+    fn from_tag_violation(
+        violation: TagViolation,
+        package_name: &PackageName,
+        dependency: &PackageName,
+        span: SourceSpan,
+        text: Arc<NamedSource>,
+    ) -> Self {
+        let package_name = package_name.to_string();
+        let dependency = dependency.to_string();
+        match violation {
+            TagViolation::Denied(reason) => BoundariesDiagnostic::DeniedTag {
+                package_name,
+                dependency,
+                reason,
+                span,
+                text,
+            },
+            TagViolation::NotAllowed(reason) => BoundariesDiagnostic::TagNotAllowed {
+                package_name,
+                dependency,
+                reason,
+                span,
+                text,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum Error {
+    #[error("file `{0}` does not have a parent directory")]
+    NoParentDir(AbsoluteSystemPathBuf),
+    #[error(transparent)]
+    Path(#[from] turbopath::PathError),
+    #[error(transparent)]
+    Lockfiles(#[from] turborepo_lockfiles::Error),
+    #[error(transparent)]
+    GlobWalk(#[from] globwalk::WalkError),
+    #[error("failed to read file: {0}")]
+    FileNotFound(AbsoluteSystemPathBuf),
+    #[error("failed to write file: {0}")]
+    FileNotWritten(AbsoluteSystemPathBuf),
+    #[error(transparent)]
+    Rewrite(#[from] crate::rewrite_json::RewriteError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Config(#[from] config::Error),
+    #[error("invalid boundaries ignore glob `{glob}`: {err}")]
+    InvalidIgnoreGlob {
+        glob: String,
+        err: Box<wax::BuildError>,
+    },
+}
+
+static PACKAGE_NAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(@[a-z0-9-~][a-z0-9-._~]*\/)?[a-z0-9-~][a-z0-9-._~]*$").unwrap()
+});
+
+// Comment that suppresses the diagnostics for the import on the next line
+const IGNORE_COMMENT: &str = "@boundaries-ignore";
+
+/// A diagnostic that was suppressed by a `@boundaries-ignore` comment or by
+/// the `ignore` globs in the root turbo.json
+#[derive(Clone, Debug)]
+pub struct SuppressedDiagnostic {
+    pub diagnostic: BoundariesDiagnostic,
+    pub reason: String,
+}
+
+pub struct BoundariesResult {
+    pub source_map: Arc<SourceMap>,
+    pub diagnostics: Vec<BoundariesDiagnostic>,
+    pub suppressed: Vec<SuppressedDiagnostic>,
+}
+
+impl BoundariesResult {
+    pub fn is_ok(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn emit(&self) {
+        let handler = Handler::with_tty_emitter(
+            ColorConfig::Auto,
+            true,
+            false,
+            Some(self.source_map.clone()),
+        );
+
+        for diagnostic in self.diagnostics.clone() {
+            match diagnostic {
+                BoundariesDiagnostic::ParseError(_, e) => {
+                    e.clone().into_diagnostic(&handler).emit();
+                }
+                e => {
+                    eprintln!("{:?}", Report::new(e.to_owned()));
+                }
+            }
+        }
+
+        if !self.suppressed.is_empty() {
+            eprintln!("{} diagnostic(s) suppressed", self.suppressed.len());
+        }
+    }
+}
+
+impl Run {
+    pub async fn check_boundaries(&self) -> Result<BoundariesResult, Error> {
+        let packages = self.pkg_dep_graph().packages();
+        let repo = Repository::discover(self.repo_root()).ok().map(Mutex::new);
+        let package_tags = self.package_tags()?;
+        let ignore_globs = self.boundaries_ignore_globs()?;
+        let mut diagnostics = vec![];
+        let mut suppressed = vec![];
+        let source_map = SourceMap::default();
+        for (package_name, package_info) in packages {
+            if !self.filtered_pkgs().contains(package_name)
+                || matches!(package_name, PackageName::Root)
+            {
+                continue;
+            }
+
+            let package_root = self.repo_root().resolve(package_info.package_path());
+
+            let internal_dependencies = self
+                .pkg_dep_graph()
+                .immediate_dependencies(&PackageNode::Workspace(package_name.to_owned()))
+                .unwrap_or_default();
+            let unresolved_external_dependencies =
+                package_info.unresolved_external_dependencies.as_ref();
+
+            diagnostics.extend(
+                self.check_package_tags(
+                    package_name,
+                    &self.repo_root().resolve(package_info.package_json_path()),
+                    &internal_dependencies,
+                    &package_tags,
+                )
+                .await?,
+            );
+
+            let (package_diagnostics, package_suppressed) = self
+                .check_package(
+                    &repo,
+                    package_name,
+                    &package_root,
+                    &package_info.package_json,
+                    internal_dependencies,
+                    unresolved_external_dependencies,
+                    &package_tags,
+                    &source_map,
+                )
+                .await?;
+
+            diagnostics.extend(package_diagnostics);
+            suppressed.extend(package_suppressed);
+        }
+
+        let mut unsuppressed = Vec::new();
+        for diagnostic in diagnostics {
+            match self.matching_ignore_glob(&ignore_globs, diagnostic.path()) {
+                Some(glob) => suppressed.push(SuppressedDiagnostic {
+                    diagnostic,
+                    reason: format!("file matches ignore glob `{glob}`"),
+                }),
+                None => unsuppressed.push(diagnostic),
+            }
+        }
+
+        Ok(BoundariesResult {
+            source_map: Arc::new(source_map),
+            diagnostics: unsuppressed,
+            suppressed,
+        })
+    }
+
+    fn boundaries_ignore_globs(&self) -> Result<Vec<(&str, wax::Glob<'_>)>, Error> {
+        self.root_turbo_json()
+            .boundaries
+            .as_ref()
+            .and_then(|boundaries| boundaries.ignore.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|glob| {
+                let matcher = wax::Glob::new(glob).map_err(|err| Error::InvalidIgnoreGlob {
+                    glob: glob.clone(),
+                    err: Box::new(err),
+                })?;
+                Ok((glob.as_str(), matcher))
+            })
+            .collect()
+    }
+
+    /// Returns the first ignore glob that matches `path`
+    fn matching_ignore_glob<'a>(
+        &self,
+        ignore_globs: &'a [(&'a str, wax::Glob<'a>)],
+        path: &str,
+    ) -> Option<&'a str> {
+        let path = AbsoluteSystemPath::new(path).ok()?;
+        let path = self.repo_root().anchor(path).ok()?.to_unix();
+        ignore_globs
+            .iter()
+            .find(|(_, matcher)| matcher.is_match(std::path::Path::new(path.as_str())))
+            .map(|(glob, _)| *glob)
+    }
+
+    /// Returns the reasons given by `@boundaries-ignore` comments, keyed by
+    /// the line that each comment applies to. A comment on its own line applies
+    /// to the next line, while a comment after code applies to its own line.
+    fn ignore_comments(
+        comments: &SingleThreadedComments,
+        source_map: &SourceMap,
+    ) -> HashMap<usize, String> {
+        let (leading, trailing) = comments.borrow_all();
+        leading
+            .values()
+            .chain(trailing.values())
+            .flatten()
+            .filter_map(|comment| {
+                let reason = comment.text.trim().strip_prefix(IGNORE_COMMENT)?;
+                if !reason.is_empty() && !reason.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                let reason = match reason.trim() {
+                    "" => format!("`{IGNORE_COMMENT}` comment"),
+                    reason => reason.to_string(),
+                };
+                let start = source_map.lookup_char_pos(comment.span.lo);
+                let follows_code = start
+                    .file
+                    .get_line(start.line - 1)
+                    .is_some_and(|line| line.chars().take(start.col.0).any(|c| !c.is_whitespace()));
+                let line = if follows_code {
+                    start.line
+                } else {
+                    source_map.lookup_char_pos(comment.span.hi).line + 1
+                };
+                Some((line, reason))
+            })
+            .collect()
+    }
+
+    fn is_potential_package_name(import: &str) -> bool {
+        PACKAGE_NAME_REGEX.is_match(import)
+    }
+
+    /// Collects the tags of every package along with the tag rules from the
+    /// root turbo.json
+    fn package_tags(&self) -> Result<PackageTags<'_>, Error> {
+        let mut turbo_json_loader = self.turbo_json_loader().clone();
+        let mut tags = HashMap::new();
+        for (package_name, _) in self.pkg_dep_graph().packages() {
+            if matches!(package_name, PackageName::Root) {
+                continue;
+            }
+            match turbo_json_loader.load(package_name) {
+                Ok(turbo_json) => {
+                    tags.insert(package_name.clone(), turbo_json.tags.clone());
+                }
+                Err(config::Error::NoTurboJSON) => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let rules = self
+            .root_turbo_json()
+            .boundaries
+            .as_ref()
+            .and_then(|boundaries| boundaries.tags.as_ref());
+
+        Ok(PackageTags::new(rules, tags))
+    }
+
+    /// Checks the tag rules for the workspace dependencies declared in a
+    /// package's package.json
+    async fn check_package_tags(
+        &self,
+        package_name: &PackageName,
+        package_json_path: &AbsoluteSystemPath,
+        internal_dependencies: &HashSet<&PackageNode>,
+        package_tags: &PackageTags<'_>,
+    ) -> Result<Vec<BoundariesDiagnostic>, Error> {
+        let violations = internal_dependencies
+            .iter()
+            .filter_map(|dependency| match dependency {
+                PackageNode::Workspace(dependency) => Some(dependency),
+                PackageNode::Root => None,
+            })
+            .sorted()
+            .flat_map(|dependency| {
+                package_tags
+                    .check(package_name, dependency)
+                    .into_iter()
+                    .map(move |violation| (dependency, violation))
+            })
+            .collect::<Vec<_>>();
+        if violations.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let Ok(package_json_content) = tokio::fs::read_to_string(package_json_path).await else {
+            return Err(Error::FileNotFound(package_json_path.to_owned()));
+        };
+        let span_for = |dependency: &PackageName| {
+            let key = format!("\"{dependency}\"");
+            package_json_content
+                .match_indices(&key)
+                .find(|(index, _)| {
+                    package_json_content[index + key.len()..]
+                        .trim_start()
+                        .starts_with(':')
+                })
+                .map_or(SourceSpan::new(0.into(), 0.into()), |(index, _)| {
+                    SourceSpan::new(index.into(), key.len().into())
+                })
+        };
+        let text = Arc::new(NamedSource::new(
+            package_json_path.as_str(),
+            package_json_content.clone(),
+        ));
+
+        Ok(violations
+            .into_iter()
+            .map(|(dependency, violation)| {
+                BoundariesDiagnostic::from_tag_violation(
+                    violation,
+                    package_name,
+                    dependency,
+                    span_for(dependency),
+                    text.clone(),
+                )
+            })
+            .collect())
+    }
+
+    /// Finds the workspace package that contains `path`
+    fn package_for_path(&self, path: &AbsoluteSystemPath) -> Option<&PackageName> {
+        self.pkg_dep_graph()
+            .packages()
+            .filter(|(package_name, _)| !matches!(package_name, PackageName::Root))
+            .filter(|(_, package_info)| {
+                path.as_path().starts_with(
+                    self.repo_root()
+                        .resolve(package_info.package_path())
+                        .as_path(),
+                )
+            })
+            .max_by_key(|(_, package_info)| package_info.package_path().as_str().len())
+            .map(|(package_name, _)| package_name)
+    }
+
+    /// Checks the tag rules for an import that resolves to a file in another
+    /// workspace package. Packages that are declared as dependencies are
+    /// skipped as they are checked against the package.json.
+    #[allow(clippy::too_many_arguments)]
+    fn check_import_tags(
+        &self,
+        package_name: &PackageName,
+        import: &str,
+        span: SourceSpan,
+        file_path: &AbsoluteSystemPath,
+        file_content: &str,
+        internal_dependencies: &HashSet<&PackageNode>,
+        package_tags: &PackageTags<'_>,
+        resolver: &Resolver,
+    ) -> Vec<BoundariesDiagnostic> {
+        let folder = file_path.parent().expect("file_path should have a parent");
+        let Ok(resolution) = resolver.resolve(folder, import) else {
+            return Vec::new();
+        };
+        let Ok(resolved_path) = AbsoluteSystemPathBuf::try_from(resolution.path()) else {
+            return Vec::new();
+        };
+        if resolved_path
+            .components()
+            .any(|component| component.as_str() == "node_modules")
+        {
+            return Vec::new();
+        }
+        let Some(dependency) = self.package_for_path(&resolved_path) else {
+            return Vec::new();
+        };
+        if dependency == package_name
+            || internal_dependencies.contains(&PackageNode::Workspace(dependency.clone()))
+        {
+            return Vec::new();
+        }
+
+        let text = Arc::new(NamedSource::new(
+            file_path.as_str(),
+            file_content.to_string(),
+        ));
+        package_tags
+            .check(package_name, dependency)
+            .into_iter()
+            .map(|violation| {
+                BoundariesDiagnostic::from_tag_violation(
+                    violation,
+                    package_name,
+                    dependency,
+                    span,
+                    text.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Either returns a list of errors along with the errors that were
+    /// suppressed by comments, or a single, fatal error
+    #[allow(clippy::too_many_arguments)]
+    async fn check_package(
+        &self,
+        repo: &Option<Mutex<Repository>>,
+        package_name: &PackageName,
+        package_root: &AbsoluteSystemPath,
+        package_json: &PackageJson,
+        internal_dependencies: HashSet<&PackageNode>,
+        unresolved_external_dependencies: Option<&BTreeMap<String, String>>,
+        package_tags: &PackageTags<'_>,
+        source_map: &SourceMap,
+    ) -> Result<(Vec<BoundariesDiagnostic>, Vec<SuppressedDiagnostic>), Error> {
+        let files = globwalk::globwalk(
+            package_root,
+            &[
+                "**/*.js".parse().unwrap(),
+                "**/*.jsx".parse().unwrap(),
+                "**/*.ts".parse().unwrap(),
+                "**/*.tsx".parse().unwrap(),
+                "**/*.vue".parse().unwrap(),
+                "**/*.svelte".parse().unwrap(),
+            ],
+            &["**/node_modules/**".parse().unwrap()],
+            globwalk::WalkType::Files,
+        )?;
+
+        let mut diagnostics: Vec<BoundariesDiagnostic> = Vec::new();
+        let mut suppressed = Vec::new();
+        // We assume the tsconfig.json is at the root of the package
+        let tsconfig_path = package_root.join_component("tsconfig.json");
+
+        let resolver =
+            Tracer::create_resolver(tsconfig_path.exists().then(|| tsconfig_path.as_ref()));
+
+        for file_path in files {
+            if let Some(repo) = repo {
+                let repo = repo.lock().expect("lock poisoned");
+                if matches!(repo.status_should_ignore(file_path.as_std_path()), Ok(true)) {
+                    continue;
+                }
+            };
+            // Read the file content
+            let Ok(file_content) = tokio::fs::read_to_string(&file_path).await else {
+                return Err(Error::FileNotFound(file_path.to_owned()));
+            };
+
+            let comments = SingleThreadedComments::default();
+
+            let source_file = source_map.new_source_file(
+                FileName::Custom(file_path.to_string()).into(),
+                file_content.clone(),
+            );
+
+            let syntax = if matches!(file_path.extension(), Some("ts") | Some("tsx")) {
+                Syntax::Typescript(TsSyntax {
+                    tsx: file_path.extension() == Some("tsx"),
+                    decorators: true,
+                    ..Default::default()
+                })
+            } else {
+                Syntax::Es(EsSyntax {
+                    jsx: true,
+                    ..Default::default()
+                })
+            };
+
+            let lexer = Lexer::new(
+                syntax,
+                EsVersion::EsNext,
+                StringInput::from(&*source_file),
+                Some(&comments),
+            );
+
+            let mut parser = Parser::new_from(Capturing::new(lexer));
+
+            // Parse the file as a module
+            let module = match parser.parse_module() {
+                Ok(module) => module,
+                Err(err) => {
+                    diagnostics.push(BoundariesDiagnostic::ParseError(file_path.to_owned(), err));
+                    continue;
+                }
+            };
+
+            let ignore_comments = Self::ignore_comments(&comments, source_map);
+
+            // Visit the AST and find imports
+            let mut finder = ImportFinder::default();
+            module.visit_with(&mut finder);
+            for (import, span, import_type) in finder.imports() {
+                let ignore_reason = Self::ignore_reason(&ignore_comments, source_map, *span);
+                let (start, end) = source_map.span_to_char_offset(&source_file, *span);
+                let start = start as usize;
+                let end = end as usize;
+                let span = SourceSpan::new(start.into(), (end - start).into());
+
+                // We have a file import
+                let check_result = if import.starts_with(".") {
+                    self.check_file_import(&file_path, package_root, import, span, &file_content)?
+                } else if Self::is_potential_package_name(import) {
+                    self.check_package_import(
+                        import,
+                        *import_type,
+                        span,
+                        &file_path,
+                        &file_content,
+                        package_json,
+                        &internal_dependencies,
+                        unresolved_external_dependencies,
+                        &resolver,
+                    )
+                } else {
+                    None
+                };
+
+                let import_diagnostics = if let Some(diagnostic) = check_result {
+                    vec![diagnostic]
+                } else if !import.starts_with(".") && package_tags.has_rules() {
+                    self.check_import_tags(
+                        package_name,
+                        import,
+                        span,
+                        &file_path,
+                        &file_content,
+                        &internal_dependencies,
+                        package_tags,
+                        &resolver,
+                    )
+                } else {
+                    vec![]
+                };
+
+                match ignore_reason {
+                    Some(reason) => {
+                        suppressed.extend(import_diagnostics.into_iter().map(|diagnostic| {
+                            SuppressedDiagnostic {
+                                diagnostic,
+                                reason: reason.clone(),
+                            }
+                        }))
+                    }
+                    None => diagnostics.extend(import_diagnostics),
+                }
+            }
+        }
+
+        Ok((diagnostics, suppressed))
+    }
+
+    fn ignore_reason(
+        ignore_comments: &HashMap<usize, String>,
+        source_map: &SourceMap,
+        span: Span,
+    ) -> Option<String> {
+        if ignore_comments.is_empty() {
+            return None;
+        }
+        let line = source_map.lookup_char_pos(span.lo).line;
+        ignore_comments.get(&line).cloned()
+    }
+
+    fn check_file_import(
+        &self,
+        file_path: &AbsoluteSystemPath,
+        package_path: &AbsoluteSystemPath,
+        import: &str,
+        source_span: SourceSpan,
+        file_content: &str,
+    ) -> Result<Option<BoundariesDiagnostic>, Error> {
+        let import_path = RelativeUnixPath::new(import)?;
+        let dir_path = file_path
+            .parent()
+            .ok_or_else(|| Error::NoParentDir(file_path.to_owned()))?;
+        let resolved_import_path = dir_path.join_unix_path(import_path).clean()?;
+        // We have to check for this case because `relation_to_path` returns `Parent` if
+        // the paths are equal and there's nothing wrong with importing the
+        // package you're in.
+        if resolved_import_path.as_str() == package_path.as_str() {
+            return Ok(None);
+        }
+        // We use `relation_to_path` and not `contains` because `contains`
+        // panics on invalid paths with too many `..` components
+        if !matches!(
+            package_path.relation_to_path(&resolved_import_path),
+            PathRelation::Parent
+        ) {
+            Ok(Some(BoundariesDiagnostic::ImportLeavesPackage {
+                import: import.to_string(),
+                span: source_span,
+                text: Arc::new(NamedSource::new(
+                    file_path.as_str(),
+                    file_content.to_string(),
+                )),
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Go through all the possible places a package could be declared to see if
+    /// it's a valid import. We don't use `oxc_resolver` because there are some
+    /// cases where you can resolve a package that isn't declared properly.
+    fn is_dependency(
+        internal_dependencies: &HashSet<&PackageNode>,
+        package_json: &PackageJson,
+        unresolved_external_dependencies: Option<&BTreeMap<String, String>>,
+        package_name: &PackageNode,
+    ) -> bool {
+        internal_dependencies.contains(&package_name)
+            || unresolved_external_dependencies.is_some_and(|external_dependencies| {
+                external_dependencies.contains_key(package_name.as_package_name().as_str())
+            })
+            || package_json
+                .dependencies
+                .as_ref()
+                .is_some_and(|dependencies| {
+                    dependencies.contains_key(package_name.as_package_name().as_str())
+                })
+            || package_json
+                .dev_dependencies
+                .as_ref()
+                .is_some_and(|dev_dependencies| {
+                    dev_dependencies.contains_key(package_name.as_package_name().as_str())
+                })
+            || package_json
+                .peer_dependencies
+                .as_ref()
+                .is_some_and(|peer_dependencies| {
+                    peer_dependencies.contains_key(package_name.as_package_name().as_str())
+                })
+            || package_json
+                .optional_dependencies
+                .as_ref()
+                .is_some_and(|optional_dependencies| {
+                    optional_dependencies.contains_key(package_name.as_package_name().as_str())
+                })
+    }
+
+    fn get_package_name(import: &str) -> String {
+        if import.starts_with("@") {
+            import.split('/').take(2).join("/")
+        } else {
+            import
+                .split_once("/")
+                .map(|(import, _)| import)
+                .unwrap_or(import)
+                .to_string()
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_package_import(
+        &self,
+        import: &str,
+        import_type: ImportType,
+        span: SourceSpan,
+        file_path: &AbsoluteSystemPath,
+        file_content: &str,
+        package_json: &PackageJson,
+        internal_dependencies: &HashSet<&PackageNode>,
+        unresolved_external_dependencies: Option<&BTreeMap<String, String>>,
+        resolver: &Resolver,
+    ) -> Option<BoundariesDiagnostic> {
+        let package_name = Self::get_package_name(import);
+
+        if package_name.starts_with("@types/") && matches!(import_type, ImportType::Value) {
+            return Some(BoundariesDiagnostic::NotTypeOnlyImport {
+                import: import.to_string(),
+                span,
+                text: Arc::new(NamedSource::new(
+                    file_path.as_str(),
+                    file_content.to_string(),
+                )),
+            });
+        }
+        let package_name = PackageNode::Workspace(PackageName::Other(package_name));
+        let folder = file_path.parent().expect("file_path should have a parent");
+        let is_valid_dependency = Self::is_dependency(
+            internal_dependencies,
+            package_json,
+            unresolved_external_dependencies,
+            &package_name,
+        );
+
+        if !is_valid_dependency
+            && !matches!(
+                resolver.resolve(folder, import),
+                Err(ResolveError::Builtin { .. })
+            )
+        {
+            // Check the @types package
+            let types_package_name = PackageNode::Workspace(PackageName::Other(format!(
+                "@types/{}",
+                package_name.as_package_name().as_str()
+            )));
+            let is_types_dependency = Self::is_dependency(
+                internal_dependencies,
+                package_json,
+                unresolved_external_dependencies,
+                &types_package_name,
+            );
+
+            if is_types_dependency {
+                return match import_type {
+                    ImportType::Type => None,
+                    ImportType::Value => Some(BoundariesDiagnostic::NotTypeOnlyImport {
+                        import: import.to_string(),
+                        span,
+                        text: Arc::new(NamedSource::new(
+                            file_path.as_str(),
+                            file_content.to_string(),
+                        )),
+                    }),
+                };
+            }
+
+            return Some(BoundariesDiagnostic::PackageNotFound {
+                name: package_name.to_string(),
+                span,
+                text: Arc::new(NamedSource::new(
+                    file_path.as_str(),
+                    file_content.to_string(),
+                )),
+            });
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("", ""; "empty")]
+    #[test_case("ship", "ship"; "basic")]
+    #[test_case("@types/ship", "@types/ship"; "types")]
+    #[test_case("@scope/ship", "@scope/ship"; "scoped")]
+    #[test_case("@scope/foo/bar", "@scope/foo"; "scoped with path")]
+    #[test_case("foo/bar", "foo"; "regular with path")]
+    #[test_case("foo/", "foo"; "trailing slash")]
+    #[test_case("foo/bar/baz", "foo"; "multiple slashes")]
+    fn test_get_package_name(import: &str, expected: &str) {
+        assert_eq!(Run::get_package_name(import), expected);
+    }
+
+    #[test_case(
+        TagViolation::Denied("packages tagged `web` cannot depend on packages tagged `internal`".to_string()),
+        "package `web` cannot depend on `ui` because a `deny` rule forbids it: packages tagged `web` cannot depend on packages tagged `internal`"
+        ; "denied"
+    )]
+    #[test_case(
+        TagViolation::NotAllowed("packages tagged `web` can only depend on packages tagged `shared`".to_string()),
+        "package `web` cannot depend on `ui` because no `allow` rule permits it: packages tagged `web` can only depend on packages tagged `shared`"
+        ; "not allowed"
+    )]
+    fn test_tag_violation_messages(violation: TagViolation, expected: &str) {
+        let diagnostic = BoundariesDiagnostic::from_tag_violation(
+            violation,
+            &PackageName::from("web"),
+            &PackageName::from("ui"),
+            SourceSpan::from(0..0),
+            Arc::new(NamedSource::new("index.ts", String::new())),
+        );
+        assert_eq!(diagnostic.to_string(), expected);
+    }
+
+    #[test]
+    fn test_ignore_comments() {
+        let source = r#"import { a } from "a";
+// @boundaries-ignore legacy code
+import { b } from "../../b";
+/* @boundaries-ignore */
+import { c } from "../../c";
+// @boundaries-ignored
+import { d } from "../../d";
+import { e } from "../../e"; // @boundaries-ignore same line
+import { f } from "../../f";
+"#;
+        let source_map = SourceMap::default();
+        let source_file = source_map.new_source_file(
+            FileName::Custom("index.ts".to_string()).into(),
+            source.to_string(),
+        );
+        let comments = SingleThreadedComments::default();
+        let lexer = Lexer::new(
+            Syntax::Typescript(TsSyntax::default()),
+            EsVersion::EsNext,
+            StringInput::from(&*source_file),
+            Some(&comments),
+        );
+        Parser::new_from(lexer).parse_module().unwrap();
+
+        let ignore_comments = Run::ignore_comments(&comments, &source_map);
+        assert_eq!(
+            ignore_comments,
+            [
+                (3, "legacy code".to_string()),
+                (5, "`@boundaries-ignore` comment".to_string()),
+                // Only the import on the same line is ignored, not the one after it
+                (8, "same line".to_string()),
+            ]
+            .into_iter()
+            .collect::<HashMap<_, _>>()
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Rules that restrict which packages can depend on each other based on the
+//! `tags` declared in each package's turbo.json.
+use std::collections::{BTreeMap, HashMap};
+
+use itertools::Itertools;
+use turborepo_repository::package_graph::PackageName;
+
+use crate::turbo_json::{Permissions, TagRules};
+
+/// A tag rule that is broken by one package depending on another. Each
+/// variant holds a description of the rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagViolation {
+    /// One of the packages has a tag that the rule denies
+    Denied(String),
+    /// The rule has an allow list and the package has none of those tags
+    NotAllowed(String),
+}
+
+enum PermissionError<'a> {
+    Denied(&'a str),
+    NotAllowed(&'a [String]),
+}
+
+pub struct PackageTags<'a> {
+    rules: Option<&'a BTreeMap<String, TagRules>>,
+    tags: HashMap<PackageName, Vec<String>>,
+}
+
+impl<'a> PackageTags<'a> {
+    pub fn new(
+        rules: Option<&'a BTreeMap<String, TagRules>>,
+        tags: HashMap<PackageName, Vec<String>>,
+    ) -> Self {
+        Self { rules, tags }
+    }
+
+    pub fn has_rules(&self) -> bool {
+        self.rules.is_some_and(|rules| !rules.is_empty())
+    }
+
+    /// Returns every tag rule broken by `package` depending on `dependency`.
+    /// Rules are checked both for the tags of `package` and for the tags of
+    /// `dependency`.
+    pub fn check(&self, package: &PackageName, dependency: &PackageName) -> Vec<TagViolation> {
+        let Some(rules) = self.rules else {
+            return Vec::new();
+        };
+        let package_tags = self.tags(package);
+        let dependency_tags = self.tags(dependency);
+
+        let mut violations = Vec::new();
+        for tag in package_tags {
+            let Some(permissions) = rules.get(tag).and_then(|rule| rule.dependencies.as_ref())
+            else {
+                continue;
+            };
+            violations.extend(
+                check_permissions(permissions, dependency_tags).map(|error| match error {
+                    PermissionError::Denied(denied) => TagViolation::Denied(format!(
+                        "packages tagged `{tag}` cannot depend on packages tagged `{denied}`"
+                    )),
+                    PermissionError::NotAllowed(allowed) => TagViolation::NotAllowed(format!(
+                        "packages tagged `{tag}` can only depend on packages tagged {}",
+                        format_tags(allowed)
+                    )),
+                }),
+            );
+        }
+
+        for tag in dependency_tags {
+            let Some(permissions) = rules.get(tag).and_then(|rule| rule.dependents.as_ref()) else {
+                continue;
+            };
+            violations.extend(check_permissions(permissions, package_tags).map(
+                |error| match error {
+                    PermissionError::Denied(denied) => TagViolation::Denied(format!(
+                        "packages tagged `{tag}` cannot be depended on by packages tagged \
+                         `{denied}`"
+                    )),
+                    PermissionError::NotAllowed(allowed) => TagViolation::NotAllowed(format!(
+                        "packages tagged `{tag}` can only be depended on by packages tagged {}",
+                        format_tags(allowed)
+                    )),
+                },
+            ));
+        }
+
+        violations
+    }
+
+    fn tags(&self, package: &PackageName) -> &[String] {
+        self.tags.get(package).map_or(&[], |tags| tags.as_slice())
+    }
+}
+
+fn check_permissions<'a>(
+    permissions: &'a Permissions,
+    tags: &'a [String],
+) -> impl Iterator<Item = PermissionError<'a>> {
+    let denied = permissions
+        .deny
+        .iter()
+        .flatten()
+        .filter(move |denied| tags.contains(*denied))
+        .map(|denied| PermissionError::Denied(denied));
+    let not_allowed = permissions
+        .allow
+        .as_ref()
+        .filter(move |allowed| !allowed.iter().any(|tag| tags.contains(tag)))
+        .map(|allowed| PermissionError::NotAllowed(allowed));
+
+    denied.chain(not_allowed)
+}
+
+fn format_tags(tags: &[String]) -> String {
+    tags.iter().map(|tag| format!("`{tag}`")).join(" or ")
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn permissions(allow: Option<&[&str]>, deny: &[&str]) -> Option<Permissions> {
+        let to_vec =
+            |tags: &[&str]| -> Vec<String> { tags.iter().map(|tag| tag.to_string()).collect() };
+        Some(Permissions {
+            allow: allow.map(to_vec),
+            deny: Some(to_vec(deny)),
+        })
+    }
+
+    fn package_tags(rules: &BTreeMap<String, TagRules>) -> PackageTags<'_> {
+        PackageTags::new(
+            Some(rules),
+            [
+                ("web", vec!["ui"]),
+                ("api", vec!["server"]),
+                ("design-system", vec!["ui", "shared"]),
+                ("utils", vec![]),
+            ]
+            .into_iter()
+            .map(|(package, tags)| {
+                (
+                    PackageName::from(package),
+                    tags.into_iter().map(|tag| tag.to_string()).collect(),
+                )
+            })
+            .collect(),
+        )
+    }
+
+    #[test_case("web", "design-system", &[] ; "allowed")]
+    #[test_case(
+        "web",
+        "api",
+        &[TagViolation::Denied(
+            "packages tagged `ui` cannot depend on packages tagged `server`".to_string()
+        )]
+        ; "denied dependency"
+    )]
+    #[test_case(
+        "api",
+        "utils",
+        &[TagViolation::NotAllowed(
+            "packages tagged `server` can only depend on packages tagged `shared` or `server`"
+                .to_string()
+        )]
+        ; "dependency not allowed"
+    )]
+    #[test_case(
+        "api",
+        "design-system",
+        &[TagViolation::Denied(
+            "packages tagged `shared` cannot be depended on by packages tagged `server`"
+                .to_string()
+        )]
+        ; "denied dependent"
+    )]
+    #[test_case("utils", "api", &[] ; "untagged package")]
+    fn test_check(package: &str, dependency: &str, expected: &[TagViolation]) {
+        let rules = [
+            (
+                "ui".to_string(),
+                TagRules {
+                    dependencies: permissions(None, &["server"]),
+                    dependents: None,
+                },
+            ),
+            (
+                "server".to_string(),
+                TagRules {
+                    dependencies: permissions(Some(&["shared", "server"][..]), &[]),
+                    dependents: None,
+                },
+            ),
+            (
+                "shared".to_string(),
+                TagRules {
+                    dependencies: None,
+                    dependents: permissions(None, &["server"]),
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let package_tags = package_tags(&rules);
+        assert_eq!(
+            package_tags.check(&PackageName::from(package), &PackageName::from(dependency)),
+            expected
+        );
+    }
+}
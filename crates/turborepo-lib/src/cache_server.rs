@@ -0,0 +1,621 @@
+//! A self-hosted remote cache. Implements the artifacts endpoints of the
+//! Vercel API so that `turbo` can use it by setting `apiUrl`, and stores
+//! artifacts in a local directory using the same layout as the local cache.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Read},
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Request, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use tokio::{io::AsyncReadExt, net::TcpListener, sync::mpsc};
+use tracing::{debug, error};
+use turborepo_cache::{fs::FSCache, CacheError, CacheLimits};
+use turborepo_vercel_api::{AnalyticsEvent, CachingStatus, CachingStatusResponse};
+
+/// How often artifacts are evicted when the cache is bounded
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Size of the chunks artifacts are streamed from disk in
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+/// Number of body chunks that can be buffered while an upload is written
+const UPLOAD_CHANNEL_SIZE: usize = 16;
+
+pub struct ServerOpts {
+    pub addr: SocketAddr,
+    /// Clients must send this as a bearer token if set
+    pub token: Option<String>,
+    /// Uploads larger than this many bytes are rejected
+    pub max_artifact_size: Option<u64>,
+    pub limits: CacheLimits,
+}
+
+struct ServerState {
+    cache: FSCache,
+    token: Option<String>,
+    max_artifact_size: Option<u64>,
+}
+
+#[derive(Debug, Error)]
+enum RequestError {
+    #[error("invalid artifact hash")]
+    InvalidHash,
+    #[error("artifact is larger than the maximum of {0} bytes")]
+    TooLarge(u64),
+    #[error("invalid x-artifact-duration header")]
+    InvalidDuration,
+    #[error("failed to read artifact: {0}")]
+    Body(#[source] axum::Error),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("cache operation panicked: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+impl IntoResponse for RequestError {
+    fn into_response(self) -> Response {
+        let (status, code) = match &self {
+            RequestError::InvalidHash => (StatusCode::BAD_REQUEST, "invalid_hash"),
+            RequestError::TooLarge(_) => (StatusCode::PAYLOAD_TOO_LARGE, "artifact_too_large"),
+            RequestError::InvalidDuration => (StatusCode::BAD_REQUEST, "invalid_duration"),
+            RequestError::Body(_) => (StatusCode::BAD_REQUEST, "invalid_body"),
+            RequestError::Cache(_) | RequestError::Join(_) => {
+                error!("cache server error: {self}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+            }
+        };
+
+        (
+            status,
+            Json(json!({ "error": { "code": code, "message": self.to_string() } })),
+        )
+            .into_response()
+    }
+}
+
+/// Builds the router for the cache server. Split out from `serve` so that
+/// tests can run it on an ephemeral port.
+fn router(
+    cache: FSCache,
+    token: Option<String>,
+    max_artifact_size: Option<u64>,
+) -> (Router, Arc<ServerState>) {
+    let state = Arc::new(ServerState {
+        cache,
+        token,
+        max_artifact_size,
+    });
+
+    let router = Router::new()
+        .route("/v8/artifacts/status", get(status))
+        .route("/v8/artifacts", post(query_artifacts))
+        .route("/v8/artifacts/events", post(record_events))
+        .route(
+            "/v8/artifacts/:hash",
+            get(fetch_artifact).head(artifact_exists).put(put_artifact),
+        )
+        .layer(middleware::from_fn_with_state(state.clone(), authenticate))
+        .with_state(state.clone());
+
+    (router, state)
+}
+
+/// Serves the cache until interrupted.
+pub async fn serve(cache: FSCache, opts: ServerOpts) -> std::io::Result<()> {
+    let (router, state) = router(cache, opts.token, opts.max_artifact_size);
+
+    if !opts.limits.is_unbounded() {
+        let limits = opts.limits;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let state = state.clone();
+                match tokio::task::spawn_blocking(move || state.cache.prune(&limits)).await {
+                    Ok(Ok(summary)) if summary.evicted > 0 => debug!(
+                        "evicted {} artifacts ({} bytes)",
+                        summary.evicted, summary.evicted_bytes
+                    ),
+                    Ok(Ok(_)) => {}
+                    Ok(Err(err)) => error!("failed to evict artifacts: {err}"),
+                    Err(err) => error!("failed to evict artifacts: {err}"),
+                }
+            }
+        });
+    }
+
+    let listener = TcpListener::bind(opts.addr).await?;
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
+
+async fn authenticate(
+    State(state): State<Arc<ServerState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(token) = &state.token {
+        let is_authorized = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()));
+
+        if !is_authorized {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({
+                    "error": { "code": "unauthorized", "message": "invalid or missing token" }
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Compares tokens without returning early on the first mismatched byte, so
+/// response times don't leak how much of the token was guessed correctly.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+async fn status() -> Json<CachingStatusResponse> {
+    Json(CachingStatusResponse {
+        status: CachingStatus::Enabled,
+    })
+}
+
+async fn record_events(Json(events): Json<Vec<AnalyticsEvent>>) -> StatusCode {
+    // There's nowhere to report cache usage to, but clients send it regardless
+    debug!("received {} cache events", events.len());
+    StatusCode::OK
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtifactsQuery {
+    hashes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ArtifactInfo {
+    #[serde(rename_all = "camelCase")]
+    Found {
+        size: u64,
+        task_duration_ms: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+    },
+    Missing {
+        error: ArtifactError,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct ArtifactError {
+    message: String,
+}
+
+/// Looks up several artifacts at once, returning their info keyed by hash.
+async fn query_artifacts(
+    State(state): State<Arc<ServerState>>,
+    Json(query): Json<ArtifactsQuery>,
+) -> Result<Json<BTreeMap<String, ArtifactInfo>>, RequestError> {
+    for hash in &query.hashes {
+        validate_hash(hash)?;
+    }
+
+    let artifacts = tokio::task::spawn_blocking(move || {
+        query
+            .hashes
+            .into_iter()
+            .map(|hash| {
+                let info = match state.cache.open_archive(&hash)? {
+                    Some((file, metadata)) => ArtifactInfo::Found {
+                        size: file.metadata()?.len(),
+                        task_duration_ms: metadata.duration,
+                        tag: metadata.tag,
+                    },
+                    None => ArtifactInfo::Missing {
+                        error: ArtifactError {
+                            message: "Artifact not found".to_string(),
+                        },
+                    },
+                };
+                Ok((hash, info))
+            })
+            .collect::<Result<BTreeMap<_, _>, CacheError>>()
+    })
+    .await??;
+
+    Ok(Json(artifacts))
+}
+
+async fn artifact_exists(
+    State(state): State<Arc<ServerState>>,
+    Path(hash): Path<String>,
+) -> Result<Response, RequestError> {
+    validate_hash(&hash)?;
+    let exists = tokio::task::spawn_blocking(move || state.cache.exists(&hash)).await??;
+
+    Ok(match exists {
+        Some(metadata) => {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "x-artifact-duration",
+                HeaderValue::from(metadata.time_saved),
+            );
+            (StatusCode::OK, headers).into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    })
+}
+
+async fn fetch_artifact(
+    State(state): State<Arc<ServerState>>,
+    Path(hash): Path<String>,
+) -> Result<Response, RequestError> {
+    validate_hash(&hash)?;
+    let artifact = tokio::task::spawn_blocking(move || state.cache.open_archive(&hash)).await??;
+
+    let Some((file, metadata)) = artifact else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+    headers.insert("x-artifact-duration", HeaderValue::from(metadata.duration));
+    if let Some(tag) = metadata
+        .tag
+        .and_then(|tag| HeaderValue::from_str(&tag).ok())
+    {
+        headers.insert("x-artifact-tag", tag);
+    }
+
+    // Stream the archive rather than reading it into memory, since artifacts
+    // can be large
+    let file = tokio::fs::File::from_std(file);
+    let body = stream::try_unfold(file, |mut file| async move {
+        let mut chunk = Vec::with_capacity(READ_CHUNK_SIZE);
+        let read = (&mut file)
+            .take(READ_CHUNK_SIZE as u64)
+            .read_to_end(&mut chunk)
+            .await?;
+        Ok::<_, io::Error>((read > 0).then(|| (Bytes::from(chunk), file)))
+    });
+
+    Ok((StatusCode::OK, headers, Body::from_stream(body)).into_response())
+}
+
+async fn put_artifact(
+    State(state): State<Arc<ServerState>>,
+    Path(hash): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<StatusCode, RequestError> {
+    validate_hash(&hash)?;
+
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let duration = header("x-artifact-duration")
+        .map(|duration| duration.parse::<u64>())
+        .transpose()
+        .map_err(|_| RequestError::InvalidDuration)?
+        .unwrap_or_default();
+    let tag = header("x-artifact-tag").map(|tag| tag.to_string());
+
+    // Reject oversized artifacts up front when the client tells us the size,
+    // and enforce the limit while reading otherwise
+    let limit = state.max_artifact_size;
+    if let (Some(limit), Some(length)) = (
+        limit,
+        header(CONTENT_LENGTH.as_str()).and_then(|length| length.parse::<u64>().ok()),
+    ) {
+        if length > limit {
+            return Err(RequestError::TooLarge(limit));
+        }
+    }
+
+    // The body is streamed to disk as it arrives rather than being buffered in
+    // memory. The cache only moves the artifact into place once it's fully
+    // written, so aborted uploads are never visible.
+    let (sender, receiver) = mpsc::channel(UPLOAD_CHANNEL_SIZE);
+    let writer = tokio::task::spawn_blocking(move || {
+        state.cache.put_archive(
+            &hash,
+            ChannelReader::new(receiver),
+            duration,
+            tag.as_deref(),
+        )
+    });
+
+    let mut result = Ok(());
+    let mut received = 0u64;
+    let mut body_stream = body.into_data_stream();
+    while let Some(chunk) = body_stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                result = Err(RequestError::Body(err));
+                break;
+            }
+        };
+        received += chunk.len() as u64;
+        if let Some(limit) = limit.filter(|limit| received > *limit) {
+            result = Err(RequestError::TooLarge(limit));
+            break;
+        }
+        if sender.send(Ok(chunk)).await.is_err() {
+            // The writer failed, its error is returned below
+            break;
+        }
+    }
+    if result.is_err() {
+        // Fail the write so the partially written artifact is cleaned up
+        let _ = sender
+            .send(Err(io::Error::other("upload was aborted")))
+            .await;
+    }
+    drop(sender);
+
+    let written = writer.await?;
+    result?;
+    written?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Adapts the chunks of a request body sent over a channel into a blocking
+/// reader, so they can be written to the cache as they arrive.
+struct ChannelReader {
+    receiver: mpsc::Receiver<io::Result<Bytes>>,
+    chunk: Bytes,
+}
+
+impl ChannelReader {
+    fn new(receiver: mpsc::Receiver<io::Result<Bytes>>) -> Self {
+        Self {
+            receiver,
+            chunk: Bytes::new(),
+        }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.chunk.is_empty() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.chunk = chunk?,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len());
+        buf[..len].copy_from_slice(&self.chunk.split_to(len));
+        Ok(len)
+    }
+}
+
+/// Hashes are used as file names, so they're checked with the same rules as
+/// the local cache uses before they're looked up.
+fn validate_hash(hash: &str) -> Result<(), RequestError> {
+    FSCache::validate_hash(hash).map_err(|_| RequestError::InvalidHash)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use anyhow::Result;
+    use camino::Utf8Path;
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf};
+    use turborepo_api_client::{APIAuth, APIClient};
+    use turborepo_cache::{http::HTTPCache, CacheOpts, CacheSource};
+
+    use super::*;
+
+    async fn start_server(
+        cache_root: &AbsoluteSystemPath,
+        token: Option<&str>,
+        max_artifact_size: Option<u64>,
+    ) -> Result<String> {
+        let cache = FSCache::new(Utf8Path::new("cache"), cache_root, None)?;
+        let (router, _) = router(cache, token.map(String::from), max_artifact_size);
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Ok(url)
+    }
+
+    fn http_cache(url: String, repo_root: &AbsoluteSystemPath, token: &str) -> Result<HTTPCache> {
+        let api_client = APIClient::new(url, Some(Duration::from_secs(10)), None, "2.0.0", false)?;
+        let opts = CacheOpts {
+            cache_dir: ".turbo/cache".into(),
+            cache: Default::default(),
+            workers: 0,
+            remote_cache_opts: None,
+            local_limits: Default::default(),
+            local_layout: Default::default(),
+        };
+        let api_auth = APIAuth {
+            team_id: Some("my-team".to_string()),
+            token: token.to_string(),
+            team_slug: None,
+        };
+        Ok(HTTPCache::new(
+            api_client,
+            &opts,
+            repo_root.to_owned(),
+            api_auth,
+            None,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_round_trip() -> Result<()> {
+        let server_root = tempdir()?;
+        let url = start_server(
+            AbsoluteSystemPath::from_std_path(server_root.path())?,
+            Some("secret"),
+            None,
+        )
+        .await?;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("out.txt")?;
+        repo_root_path
+            .resolve(&file)
+            .create_with_contents("output")?;
+
+        let cache = http_cache(url, repo_root_path, "secret")?;
+        assert!(cache.fetch("abc123").await?.is_none());
+
+        cache
+            .put(repo_root_path, "abc123", &[file.clone()], 300)
+            .await?;
+
+        let exists = cache.exists("abc123").await?.unwrap();
+        assert_eq!(exists.time_saved, 300);
+        assert_eq!(exists.source, CacheSource::Remote);
+
+        repo_root_path.resolve(&file).remove_file()?;
+        let (hit, files) = cache.fetch("abc123").await?.unwrap();
+        assert_eq!(hit.time_saved, 300);
+        assert_eq!(files, vec![file.clone()]);
+        assert_eq!(repo_root_path.resolve(&file).read_to_string()?, "output");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_token() -> Result<()> {
+        let server_root = tempdir()?;
+        let url = start_server(
+            AbsoluteSystemPath::from_std_path(server_root.path())?,
+            Some("secret"),
+            None,
+        )
+        .await?;
+
+        let repo_root = tempdir()?;
+        let cache = http_cache(
+            url,
+            AbsoluteSystemPath::from_std_path(repo_root.path())?,
+            "wrong",
+        )?;
+        assert!(cache.exists("abc123").await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rejects_large_artifacts() -> Result<()> {
+        let server_root = tempdir()?;
+        let url = start_server(
+            AbsoluteSystemPath::from_std_path(server_root.path())?,
+            None,
+            Some(16),
+        )
+        .await?;
+
+        let response = reqwest::Client::new()
+            .put(format!("{url}/v8/artifacts/abc123"))
+            .body(vec![0u8; 1024])
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_artifacts() -> Result<()> {
+        let server_root = tempdir()?;
+        let url = start_server(
+            AbsoluteSystemPath::from_std_path(server_root.path())?,
+            None,
+            None,
+        )
+        .await?;
+
+        let client = reqwest::Client::new();
+        let response = client
+            .put(format!("{url}/v8/artifacts/abc123"))
+            .header("x-artifact-duration", "300")
+            .header("x-artifact-tag", "signature")
+            .body(vec![0u8; 1024])
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+
+        let response = client
+            .get(format!("{url}/v8/artifacts/abc123"))
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.bytes().await?.as_ref(), &[0u8; 1024][..]);
+
+        let response: serde_json::Value = client
+            .post(format!("{url}/v8/artifacts"))
+            .json(&json!({ "hashes": ["abc123", "missing"] }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        assert_eq!(
+            response,
+            json!({
+                "abc123": { "size": 1024, "taskDurationMs": 300, "tag": "signature" },
+                "missing": { "error": { "message": "Artifact not found" } },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[test]
+    fn test_validate_hash() {
+        assert!(validate_hash("0123456789abcdef").is_ok());
+        assert!(validate_hash("").is_err());
+        assert!(validate_hash("../escape").is_err());
+        assert!(validate_hash("a/b").is_err());
+        assert!(validate_hash("custom-hash_1").is_ok());
+        assert!(validate_hash(&"a".repeat(128)).is_ok());
+        assert!(validate_hash(&"a".repeat(129)).is_err());
+    }
+}
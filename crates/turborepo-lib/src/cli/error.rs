@@ -8,7 +8,7 @@ use turborepo_telemetry::events::command::CommandEventBuilder;
 use turborepo_ui::{color, BOLD, GREY};
 
 use crate::{
-    commands::{bin, generate, link, ls, prune, run::get_signal, CommandBase},
+    commands::{bin, cache, generate, inputs, link, ls, prune, run::get_signal, runs, CommandBase},
     daemon::DaemonError,
     query,
     rewrite_json::RewriteError,
@@ -26,6 +26,8 @@ pub enum Error {
     #[error(transparent)]
     Boundaries(#[from] crate::boundaries::Error),
     #[error(transparent)]
+    Cache(#[from] cache::Error),
+    #[error(transparent)]
     Path(#[from] turbopath::PathError),
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -45,6 +47,9 @@ pub enum Error {
     Generate(#[from] generate::Error),
     #[error(transparent)]
     #[diagnostic(transparent)]
+    Inputs(#[from] inputs::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
     Ls(#[from] ls::Error),
     #[error(transparent)]
     Link(#[from] link::Error),
@@ -59,6 +64,8 @@ pub enum Error {
     #[diagnostic(transparent)]
     Run(#[from] run::Error),
     #[error(transparent)]
+    Runs(#[from] runs::Error),
+    #[error(transparent)]
     #[diagnostic(transparent)]
     Query(#[from] query::Error),
     #[error(transparent)]
@@ -3,7 +3,10 @@ use std::{
     env,
     ffi::OsString,
     fmt::{self, Display},
-    io, mem, process,
+    io, mem,
+    net::IpAddr,
+    process,
+    time::Duration,
 };
 
 use biome_deserialize_macros::Deserializable;
@@ -28,8 +31,8 @@ use turborepo_ui::{ColorConfig, GREY};
 use crate::{
     cli::error::print_potential_tasks,
     commands::{
-        bin, boundaries, config, daemon, generate, info, link, login, logout, ls, prune, query,
-        run, scan, telemetry, unlink, CommandBase,
+        bin, boundaries, cache, config, daemon, generate, info, inputs, link, login, logout, ls,
+        prune, query, run, runs, scan, telemetry, unlink, CommandBase,
     },
     get_version,
     run::watch::WatchClient,
@@ -331,6 +334,132 @@ pub enum TelemetryCommand {
     Status,
 }
 
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum RunsCommand {
+    /// Lists saved runs, most recent first
+    Ls {
+        /// Only list runs of commits whose SHA starts with this prefix
+        #[clap(long)]
+        sha: Option<String>,
+        /// Only list runs that include this task, e.g. `web#build`
+        #[clap(long)]
+        task: Option<String>,
+        /// Maximum number of runs to list
+        #[clap(long, default_value_t = 20)]
+        limit: usize,
+        /// Output format
+        #[clap(long, value_enum)]
+        output: Option<OutputFormat>,
+    },
+    /// Compares the tasks of two runs. Runs can be referred to by id or git
+    /// SHA, and default to the two most recent runs
+    Compare {
+        /// The run to compare against
+        base: Option<String>,
+        /// The run to compare. Defaults to the most recent run
+        head: Option<String>,
+        /// Output format
+        #[clap(long, value_enum)]
+        output: Option<OutputFormat>,
+    },
+    /// Shows how the duration of a task changed across runs
+    Trend {
+        /// The task to show, e.g. `web#build`
+        task: String,
+        /// Maximum number of runs to show
+        #[clap(long, default_value_t = 20)]
+        limit: usize,
+        /// Output format
+        #[clap(long, value_enum)]
+        output: Option<OutputFormat>,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum InputsCommand {
+    /// Suggests inputs for a task by tracing the imports of its package's
+    /// entry points, and lists the files matched by the current inputs that
+    /// are never imported
+    Suggest {
+        /// The task to suggest inputs for, e.g. `web#build`. Without a package,
+        /// suggests inputs for every package with the task
+        task: String,
+        /// Files to trace from, relative to the package. Defaults to the entry
+        /// points in package.json
+        #[clap(long)]
+        entry: Vec<String>,
+        /// Output format
+        #[clap(long, value_enum)]
+        output: Option<OutputFormat>,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum CacheCommand {
+    /// Lists the artifacts in the local cache
+    Ls {
+        /// Output format
+        #[clap(long, value_enum)]
+        output: Option<OutputFormat>,
+    },
+    /// Shows the files stored in a cache artifact
+    Show {
+        /// The hash of the artifact
+        hash: String,
+        /// Output format
+        #[clap(long, value_enum)]
+        output: Option<OutputFormat>,
+    },
+    /// Removes an artifact from the local cache
+    Rm {
+        /// The hash of the artifact
+        hash: String,
+    },
+    /// Removes every artifact from the local cache
+    Clean,
+    /// Evicts artifacts from the local cache that exceed the configured size
+    /// or age limits
+    #[clap(alias = "gc")]
+    Prune {
+        /// Maximum size of the local cache (e.g. "10GB"). Overrides
+        /// `cacheMaxSize`
+        #[clap(long, value_parser = turborepo_cache::config::parse_size)]
+        max_size: Option<u64>,
+        /// Maximum age of an unused artifact (e.g. "7d"). Overrides
+        /// `cacheMaxAge`
+        #[clap(long, value_parser = turborepo_cache::config::parse_age)]
+        max_age: Option<Duration>,
+    },
+    /// Runs a self-hosted remote cache server that stores artifacts in a
+    /// local directory
+    Serve {
+        /// Address to listen on
+        #[clap(long, default_value = "127.0.0.1")]
+        host: IpAddr,
+        /// Port to listen on
+        #[clap(long, default_value_t = 3000)]
+        port: u16,
+        /// Directory to store artifacts in. Defaults to the local cache
+        /// directory
+        #[clap(long)]
+        cache_dir: Option<Utf8PathBuf>,
+        /// Token that clients must send to use the cache
+        #[clap(long, env = "TURBO_CACHE_SERVER_TOKEN")]
+        auth_token: Option<String>,
+        /// Maximum size of a single uploaded artifact (e.g. "500MB")
+        #[clap(long, value_parser = turborepo_cache::config::parse_size)]
+        max_artifact_size: Option<u64>,
+        /// Maximum size of the cache directory (e.g. "100GB"). Overrides
+        /// `cacheMaxSize`
+        #[clap(long, value_parser = turborepo_cache::config::parse_size)]
+        max_size: Option<u64>,
+        /// Maximum age of an unused artifact (e.g. "30d"). Overrides
+        /// `cacheMaxAge`
+        #[clap(long, value_parser = turborepo_cache::config::parse_age)]
+        max_age: Option<Duration>,
+    },
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
 pub enum LinkTarget {
     RemoteCache,
@@ -568,6 +697,18 @@ pub enum Command {
     Boundaries {
         #[clap(short = 'F', long, group = "scope-filter-group")]
         filter: Vec<String>,
+        /// Add missing dependencies to package.json and make imports from
+        /// `@types` packages type-only
+        #[clap(long)]
+        fix: bool,
+        /// Output format. JSON output also lists the suppressed diagnostics
+        #[clap(long, value_enum)]
+        output: Option<OutputFormat>,
+    },
+    /// Manage the local filesystem cache
+    Cache {
+        #[clap(subcommand)]
+        command: CacheCommand,
     },
     /// Generate the autocompletion script for the specified shell
     Completion { shell: Shell },
@@ -664,6 +805,11 @@ pub enum Command {
     },
     /// Print debugging information
     Info,
+    /// Suggest task inputs based on the files your source code imports
+    Inputs {
+        #[clap(subcommand)]
+        command: InputsCommand,
+    },
     /// Prepare a subset of your monorepo.
     Prune {
         #[clap(hide = true, long)]
@@ -699,6 +845,11 @@ pub enum Command {
         #[clap(flatten)]
         execution_args: Box<ExecutionArgs>,
     },
+    /// Inspect the summaries of past runs saved with `--summarize`
+    Runs {
+        #[clap(subcommand)]
+        command: RunsCommand,
+    },
     /// Query your monorepo using GraphQL. If no query is provided, spins up a
     /// GraphQL server with GraphiQL.
     Query {
@@ -707,6 +858,10 @@ pub enum Command {
         variables: Option<Utf8PathBuf>,
         /// The query to run, either a file path or a query string
         query: Option<String>,
+        /// Watch for changes from this process instead of using the turbo
+        /// daemon when serving the GraphiQL playground
+        #[clap(long)]
+        no_daemon: bool,
     },
     Watch {
         #[clap(flatten)]
@@ -714,6 +869,10 @@ pub enum Command {
         /// EXPERIMENTAL: Write to cache in watch mode.
         #[clap(long)]
         experimental_write_cache: bool,
+        /// Watch for changes from this process instead of using the turbo
+        /// daemon
+        #[clap(long)]
+        no_daemon: bool,
     },
     /// Unlink the current directory from your Vercel organization and disable
     /// Remote Caching
@@ -807,6 +966,27 @@ fn path_non_empty(s: &str) -> Result<Utf8PathBuf, String> {
     }
 }
 
+fn parse_resource_limit(s: &str) -> Result<(String, u32), String> {
+    let (resource, limit) = s
+        .split_once('=')
+        .ok_or_else(|| "expected a resource limit such as \"memory=8\"".to_string())?;
+    if resource.is_empty() {
+        return Err("resource name must not be empty".to_string());
+    }
+    let limit = limit
+        .parse()
+        .map_err(|_| format!("invalid limit for {resource}: {limit}"))?;
+    Ok((resource.to_string(), limit))
+}
+
+fn parse_task_timeout(s: &str) -> Result<Duration, String> {
+    let timeout = humantime::parse_duration(s).map_err(|err| err.to_string())?;
+    if timeout.is_zero() {
+        return Err("timeout must be greater than zero".to_string());
+    }
+    Ok(timeout)
+}
+
 /// Arguments used in run and watch
 #[derive(Parser, Clone, Debug, Default, PartialEq)]
 #[command(groups = [
@@ -816,14 +996,36 @@ pub struct ExecutionArgs {
     /// Override the filesystem cache directory.
     #[clap(long, value_parser = path_non_empty)]
     pub cache_dir: Option<Utf8PathBuf>,
+    /// Maximum size of the filesystem cache (e.g. "10GB"). Least recently
+    /// used artifacts are evicted once the limit is exceeded.
+    #[clap(long, value_parser = turborepo_cache::config::parse_size)]
+    pub cache_max_size: Option<u64>,
+    /// Maximum age of an unused filesystem cache artifact (e.g. "7d").
+    /// Older artifacts are evicted after the run.
+    #[clap(long, value_parser = turborepo_cache::config::parse_age)]
+    pub cache_max_age: Option<Duration>,
     /// Limit the concurrency of task execution. Use 1 for serial (i.e.
     /// one-at-a-time) execution.
     #[clap(long)]
     pub concurrency: Option<String>,
+    /// Limit the total amount of a resource used by running tasks (e.g.
+    /// "memory=8"). Overrides the limit for that resource from
+    /// `resourceLimits` in turbo.json.
+    #[clap(long = "resource-limit", value_name = "NAME=AMOUNT", value_parser = parse_resource_limit, action = ArgAction::Append)]
+    pub resource_limits: Vec<(String, u32)>,
+    /// Specify whether or not to start tasks with the longest estimated
+    /// critical path first, based on the durations of previous runs
+    #[clap(long, value_name = "BOOL", action = ArgAction::Set, default_value = "true", default_missing_value = "true", num_args = 0..=1)]
+    pub critical_path: bool,
     /// Continue execution even if a task exits with an error or non-zero
     /// exit code. The default behavior is to bail
     #[clap(long = "continue")]
     pub continue_execution: bool,
+    /// Maximum amount of time a task may run before it is stopped and
+    /// marked as timed out (e.g. "10m"). Overrides `timeout` in turbo.json
+    /// for all non-persistent tasks.
+    #[clap(long, value_parser = parse_task_timeout)]
+    pub task_timeout: Option<Duration>,
     /// Run turbo in single-package mode
     #[clap(long)]
     pub single_package: bool,
@@ -851,6 +1053,11 @@ pub struct ExecutionArgs {
     #[clap(long, group = "scope-filter-group", conflicts_with = "filter")]
     pub affected: bool,
 
+    /// Run only tasks whose inputs, or the inputs of the tasks they depend
+    /// on, changed between the current branch and `main`
+    #[clap(long, group = "scope-filter-group", conflicts_with = "filter")]
+    pub affected_by_inputs: bool,
+
     /// Set type of process output logging. Use "full" to show
     /// all output. Use "hash-only" to show only turbo-computed
     /// task hashes. Use "new-only" to show only new output with
@@ -890,17 +1097,33 @@ impl ExecutionArgs {
     fn track(&self, telemetry: &CommandEventBuilder) {
         // default to false
         track_usage!(telemetry, self.framework_inference, |val: bool| !val);
+        track_usage!(telemetry, self.critical_path, |val: bool| !val);
 
         track_usage!(telemetry, self.continue_execution, |val| val);
         track_usage!(telemetry, self.single_package, |val| val);
         track_usage!(telemetry, self.only, |val| val);
         track_usage!(telemetry, &self.cache_dir, Option::is_some);
+        track_usage!(telemetry, &self.cache_max_size, Option::is_some);
+        track_usage!(telemetry, &self.cache_max_age, Option::is_some);
+        track_usage!(telemetry, &self.task_timeout, Option::is_some);
         track_usage!(telemetry, &self.pkg_inference_root, Option::is_some);
 
         if let Some(concurrency) = &self.concurrency {
             telemetry.track_arg_value("concurrency", concurrency, EventType::NonSensitive);
         }
 
+        if !self.resource_limits.is_empty() {
+            telemetry.track_arg_value(
+                "resource-limit",
+                self.resource_limits
+                    .iter()
+                    .map(|(resource, _)| resource.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                EventType::NonSensitive,
+            );
+        }
+
         if !self.global_deps.is_empty() {
             telemetry.track_arg_value(
                 "global-deps",
@@ -965,6 +1188,11 @@ pub struct RunArgs {
     pub cache_workers: u32,
     #[clap(alias = "dry", long = "dry-run", num_args = 0..=1, default_missing_value = "text")]
     pub dry_run: Option<DryRunMode>,
+    /// Explain why task hashes changed without running any tasks. Compares
+    /// the inputs of each task against its last run, or against the inputs
+    /// of a specific task hash when one is provided
+    #[clap(long, num_args = 0..=1, default_missing_value = "", require_equals = true, conflicts_with = "dry_run")]
+    pub explain: Option<String>,
     /// Generate a graph of the task execution and output to a file when a
     /// filename is specified (.svg, .png, .jpg, .pdf, .json,
     /// .html, .mermaid, .dot). Outputs dot graph to stdout when if no filename
@@ -1014,6 +1242,7 @@ impl Default for RunArgs {
             force: None,
             cache_workers: DEFAULT_NUM_WORKERS,
             dry_run: None,
+            explain: None,
             graph: None,
             no_cache: false,
             daemon: false,
@@ -1083,6 +1312,7 @@ impl RunArgs {
         track_usage!(telemetry, &self.profile, Option::is_some);
         track_usage!(telemetry, &self.anon_profile, Option::is_some);
         track_usage!(telemetry, &self.summarize, Option::is_some);
+        track_usage!(telemetry, &self.explain, Option::is_some);
         track_usage!(telemetry, &self.experimental_space_id, Option::is_some);
 
         // track values
@@ -1295,13 +1525,32 @@ pub async fn run(
 
             Ok(0)
         }
-        Command::Boundaries { .. } => {
+        Command::Boundaries { fix, output, .. } => {
             let event = CommandEventBuilder::new("boundaries").with_parent(&root_telemetry);
 
+            event.track_call();
+            let fix = *fix;
+            let output = *output;
+            let base = CommandBase::new(cli_args.clone(), repo_root, version, color_config)?;
+
+            Ok(boundaries::run(base, event, fix, output).await?)
+        }
+        Command::Cache { command } => {
+            let event = CommandEventBuilder::new("cache").with_parent(&root_telemetry);
+            event.track_call();
+            let base = CommandBase::new(cli_args.clone(), repo_root, version, color_config)?;
+            event.track_ui_mode(base.opts.run_opts.ui_mode);
+            cache::run(command, &base).await?;
+
+            Ok(0)
+        }
+        Command::Runs { command } => {
+            let event = CommandEventBuilder::new("runs").with_parent(&root_telemetry);
             event.track_call();
             let base = CommandBase::new(cli_args.clone(), repo_root, version, color_config)?;
+            runs::run(command, &base)?;
 
-            Ok(boundaries::run(base, event).await?)
+            Ok(0)
         }
         #[allow(unused_variables)]
         Command::Daemon { command, idle_time } => {
@@ -1348,6 +1597,15 @@ pub async fn run(
             info::run(base).await;
             Ok(0)
         }
+        Command::Inputs { command } => {
+            let event = CommandEventBuilder::new("inputs").with_parent(&root_telemetry);
+            event.track_call();
+            let base = CommandBase::new(cli_args.clone(), repo_root, version, color_config)?;
+            event.track_ui_mode(base.opts.run_opts.ui_mode);
+            inputs::run(command, base, event).await?;
+
+            Ok(0)
+        }
         Command::Telemetry { command } => {
             let event = CommandEventBuilder::new("telemetry").with_parent(&root_telemetry);
             event.track_call();
@@ -1499,7 +1757,9 @@ pub async fn run(
             })?;
             Ok(exit_code)
         }
-        Command::Query { query, variables } => {
+        Command::Query {
+            query, variables, ..
+        } => {
             warn!("query command is experimental and may change in the future");
             let query = query.clone();
             let variables = variables.clone();
@@ -1516,6 +1776,7 @@ pub async fn run(
         Command::Watch {
             execution_args,
             experimental_write_cache,
+            ..
         } => {
             let event = CommandEventBuilder::new("watch").with_parent(&root_telemetry);
             event.track_call();
@@ -1586,7 +1847,7 @@ pub async fn run(
 
 #[cfg(test)]
 mod test {
-    use std::{assert_matches::assert_matches, ffi::OsString};
+    use std::{assert_matches::assert_matches, ffi::OsString, net::IpAddr, time::Duration};
 
     use camino::Utf8PathBuf;
     use clap::Parser;
@@ -1614,6 +1875,7 @@ mod test {
         ExecutionArgs {
             output_logs: None,
             framework_inference: true,
+            critical_path: true,
             ..ExecutionArgs::default()
         }
     }
@@ -1650,7 +1912,10 @@ mod test {
         }
     }
 
-    use crate::cli::{Args, Command, DryRunMode, EnvMode, LogOrder, LogPrefix, OutputLogsMode};
+    use crate::cli::{
+        Args, CacheCommand, Command, DryRunMode, EnvMode, InputsCommand, LogOrder, LogPrefix,
+        OutputFormat, OutputLogsMode, RunsCommand,
+    };
 
     #[test_case::test_case(
         &["turbo", "run", "build"],
@@ -1727,6 +1992,36 @@ mod test {
 		} ;
         "framework_inference: flag set to false"
 	)]
+    #[test_case::test_case(
+        &["turbo", "run", "build", "--critical-path", "false"],
+        Args {
+            command: Some(Command::Run {
+                execution_args: Box::new(ExecutionArgs {
+                    tasks: vec!["build".to_string()],
+                    critical_path: false,
+                    ..get_default_execution_args()
+                }),
+                run_args: Box::new(get_default_run_args())
+            }),
+            ..Args::default()
+        } ;
+        "critical_path: flag set to false"
+    )]
+    #[test_case::test_case(
+        &["turbo", "run", "build", "--resource-limit", "memory=8", "--resource-limit=gpu=1"],
+        Args {
+            command: Some(Command::Run {
+                execution_args: Box::new(ExecutionArgs {
+                    tasks: vec!["build".to_string()],
+                    resource_limits: vec![("memory".to_string(), 8), ("gpu".to_string(), 1)],
+                    ..get_default_execution_args()
+                }),
+                run_args: Box::new(get_default_run_args())
+            }),
+            ..Args::default()
+        } ;
+        "resource limits"
+    )]
     #[test_case::test_case(
         &["turbo", "run", "build", "--env-mode"],
         Args {
@@ -1882,6 +2177,40 @@ mod test {
         } ;
         "dry run json"
 	)]
+    #[test_case::test_case(
+		&["turbo", "run", "--explain", "build"],
+        Args {
+            command: Some(Command::Run {
+                execution_args: Box::new(ExecutionArgs {
+                    tasks: vec!["build".to_string()],
+                    ..get_default_execution_args()
+                }),
+                run_args: Box::new(RunArgs {
+                    explain: Some("".to_string()),
+                    ..get_default_run_args()
+                })
+            }),
+            ..Args::default()
+        } ;
+        "explain"
+	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--explain=2a1b9c3d4e5f6a7b"],
+        Args {
+            command: Some(Command::Run {
+                execution_args: Box::new(ExecutionArgs {
+                    tasks: vec!["build".to_string()],
+                    ..get_default_execution_args()
+                }),
+                run_args: Box::new(RunArgs {
+                    explain: Some("2a1b9c3d4e5f6a7b".to_string()),
+                    ..get_default_run_args()
+                })
+            }),
+            ..Args::default()
+        } ;
+        "explain hash"
+	)]
     #[test_case::test_case(
 		&["turbo", "run", "build", "--filter", "water", "--filter", "earth", "--filter", "fire", "--filter", "air"],
         Args {
@@ -2345,7 +2674,8 @@ mod test {
                     tasks: vec!["build".to_string()],
                     ..get_default_execution_args()
                 }),
-                experimental_write_cache: false
+                experimental_write_cache: false,
+                no_daemon: false,
             }),
             ..Args::default()
         };
@@ -2360,7 +2690,8 @@ mod test {
                     cache_dir: Some(Utf8PathBuf::from("foobar")),
                     ..get_default_execution_args()
                 }),
-                experimental_write_cache: false
+                experimental_write_cache: false,
+                no_daemon: false,
             }),
             ..Args::default()
         };
@@ -2374,7 +2705,8 @@ mod test {
                   tasks: vec!["build".to_string(), "lint".to_string(), "check".to_string()],
                   ..get_default_execution_args()
                 }),
-                experimental_write_cache: false
+                experimental_write_cache: false,
+                no_daemon: false,
             }),
             ..Args::default()
         };
@@ -2388,16 +2720,60 @@ mod test {
                   tasks: vec!["build".to_string()],
                   ..get_default_execution_args()
                 }),
-                experimental_write_cache: true
+                experimental_write_cache: true,
+                no_daemon: false,
             }),
             ..Args::default()
         };
         "with experimental-write-cache"
     )]
+    #[test_case::test_case(
+        &["turbo", "watch", "build", "--no-daemon"],
+        Args {
+            command: Some(Command::Watch {
+                execution_args: Box::new(ExecutionArgs {
+                  tasks: vec!["build".to_string()],
+                  ..get_default_execution_args()
+                }),
+                experimental_write_cache: false,
+                no_daemon: true,
+            }),
+            ..Args::default()
+        };
+        "with no-daemon"
+    )]
     fn test_parse_watch(args: &[&str], expected: Args) {
         assert_eq!(Args::try_parse_from(args).unwrap(), expected);
     }
 
+    #[test_case::test_case(
+        &["turbo", "query"],
+        Args {
+            command: Some(Command::Query {
+                variables: None,
+                query: None,
+                no_daemon: false,
+            }),
+            ..Args::default()
+        };
+        "default query"
+    )]
+    #[test_case::test_case(
+        &["turbo", "query", "--no-daemon"],
+        Args {
+            command: Some(Command::Query {
+                variables: None,
+                query: None,
+                no_daemon: true,
+            }),
+            ..Args::default()
+        };
+        "with no-daemon"
+    )]
+    fn test_parse_query(args: &[&str], expected: Args) {
+        assert_eq!(Args::try_parse_from(args).unwrap(), expected);
+    }
+
     #[test_case::test_case(
         &["turbo", "run", "build", "--daemon", "--no-daemon"],
         "cannot be used with '--no-daemon'" ;
@@ -2418,6 +2794,21 @@ mod test {
         "unexpected argument '--no-deps' found" ;
         "no-deps without filter or scope"
     )]
+    #[test_case::test_case(
+        &["turbo", "run", "build", "--resource-limit", "memory"],
+        "expected a resource limit such as \"memory=8\"" ;
+        "resource limit without amount"
+    )]
+    #[test_case::test_case(
+        &["turbo", "run", "build", "--resource-limit", "memory=lots"],
+        "invalid limit for memory: lots" ;
+        "resource limit with invalid amount"
+    )]
+    #[test_case::test_case(
+        &["turbo", "run", "build", "--task-timeout", "0s"],
+        "timeout must be greater than zero" ;
+        "zero task timeout"
+    )]
     fn test_parse_run_failures(args: &[&str], expected: &str) {
         assert_matches!(
             Args::try_parse_from(args),
@@ -2448,6 +2839,237 @@ mod test {
         .test();
     }
 
+    #[test]
+    fn test_parse_cache_inspect() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "cache", "ls", "--output", "json"]).unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    command: CacheCommand::Ls {
+                        output: Some(OutputFormat::Json),
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+        assert_eq!(
+            Args::try_parse_from(["turbo", "cache", "show", "abc123"]).unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    command: CacheCommand::Show {
+                        hash: "abc123".to_string(),
+                        output: None,
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+        assert_eq!(
+            Args::try_parse_from(["turbo", "cache", "rm", "abc123"]).unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    command: CacheCommand::Rm {
+                        hash: "abc123".to_string(),
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+        assert!(Args::try_parse_from(["turbo", "cache", "rm"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_cache_prune() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "cache", "prune"]).unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    command: CacheCommand::Prune {
+                        max_size: None,
+                        max_age: None,
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert_eq!(
+            Args::try_parse_from([
+                "turbo",
+                "cache",
+                "gc",
+                "--max-size",
+                "1GB",
+                "--max-age",
+                "2d"
+            ])
+            .unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    command: CacheCommand::Prune {
+                        max_size: Some(1024 * 1024 * 1024),
+                        max_age: Some(Duration::from_secs(2 * 24 * 60 * 60)),
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert!(Args::try_parse_from(["turbo", "cache", "prune", "--max-size", "big"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_runs() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "runs", "ls", "--task", "web#build"]).unwrap(),
+            Args {
+                command: Some(Command::Runs {
+                    command: RunsCommand::Ls {
+                        sha: None,
+                        task: Some("web#build".to_string()),
+                        limit: 20,
+                        output: None,
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+        assert_eq!(
+            Args::try_parse_from(["turbo", "runs", "compare", "abc123"]).unwrap(),
+            Args {
+                command: Some(Command::Runs {
+                    command: RunsCommand::Compare {
+                        base: Some("abc123".to_string()),
+                        head: None,
+                        output: None,
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+        assert_eq!(
+            Args::try_parse_from([
+                "turbo",
+                "runs",
+                "trend",
+                "web#build",
+                "--limit",
+                "5",
+                "--output",
+                "json"
+            ])
+            .unwrap(),
+            Args {
+                command: Some(Command::Runs {
+                    command: RunsCommand::Trend {
+                        task: "web#build".to_string(),
+                        limit: 5,
+                        output: Some(OutputFormat::Json),
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+        assert!(Args::try_parse_from(["turbo", "runs", "trend"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_inputs_suggest() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "inputs", "suggest", "web#build"]).unwrap(),
+            Args {
+                command: Some(Command::Inputs {
+                    command: InputsCommand::Suggest {
+                        task: "web#build".to_string(),
+                        entry: vec![],
+                        output: None,
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+        assert_eq!(
+            Args::try_parse_from([
+                "turbo",
+                "inputs",
+                "suggest",
+                "build",
+                "--entry",
+                "src/index.ts",
+                "--entry",
+                "src/cli.ts",
+                "--output",
+                "json"
+            ])
+            .unwrap(),
+            Args {
+                command: Some(Command::Inputs {
+                    command: InputsCommand::Suggest {
+                        task: "build".to_string(),
+                        entry: vec!["src/index.ts".to_string(), "src/cli.ts".to_string()],
+                        output: Some(OutputFormat::Json),
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+        assert!(Args::try_parse_from(["turbo", "inputs", "suggest"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_cache_serve() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "cache", "serve"]).unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    command: CacheCommand::Serve {
+                        host: IpAddr::from([127, 0, 0, 1]),
+                        port: 3000,
+                        cache_dir: None,
+                        auth_token: None,
+                        max_artifact_size: None,
+                        max_size: None,
+                        max_age: None,
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert_eq!(
+            Args::try_parse_from([
+                "turbo",
+                "cache",
+                "serve",
+                "--host",
+                "0.0.0.0",
+                "--port",
+                "8080",
+                "--cache-dir",
+                "/var/cache/turbo",
+                "--auth-token",
+                "secret",
+                "--max-artifact-size",
+                "1KB",
+            ])
+            .unwrap(),
+            Args {
+                command: Some(Command::Cache {
+                    command: CacheCommand::Serve {
+                        host: IpAddr::from([0, 0, 0, 0]),
+                        port: 8080,
+                        cache_dir: Some(Utf8PathBuf::from("/var/cache/turbo")),
+                        auth_token: Some("secret".to_string()),
+                        max_artifact_size: Some(1024),
+                        max_size: None,
+                        max_age: None,
+                    }
+                }),
+                ..Args::default()
+            }
+        );
+    }
+
     #[test]
     fn test_parse_link() {
         assert_eq!(
@@ -1,13 +1,38 @@
+use serde::Serialize;
 use turborepo_telemetry::events::command::CommandEventBuilder;
 
 use crate::{
-    cli,
+    cli::{self, OutputFormat},
     commands::{run::get_signal, CommandBase},
+    query::Diagnostic,
     run::builder::RunBuilder,
     signal::SignalHandler,
 };
 
-pub async fn run(base: CommandBase, telemetry: CommandEventBuilder) -> Result<i32, cli::Error> {
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BoundariesOutput {
+    diagnostics: Vec<Diagnostic>,
+    suppressed_count: usize,
+    suppressed: Vec<SuppressedOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fixed: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SuppressedOutput {
+    #[serde(flatten)]
+    diagnostic: Diagnostic,
+    suppression_reason: String,
+}
+
+pub async fn run(
+    base: CommandBase,
+    telemetry: CommandEventBuilder,
+    fix: bool,
+    output: Option<OutputFormat>,
+) -> Result<i32, cli::Error> {
     let signal = get_signal()?;
     let handler = SignalHandler::new(signal);
 
@@ -16,11 +41,42 @@ pub async fn run(base: CommandBase, telemetry: CommandEventBuilder) -> Result<i3
         .build(&handler, telemetry)
         .await?;
 
-    let result = run.check_boundaries().await?;
+    let mut result = run.check_boundaries().await?;
 
-    result.emit();
+    let mut fixed = None;
+    if fix {
+        let found = result.diagnostics.len();
+        result = run.fix_boundaries(result).await?;
+        fixed = Some(found - result.diagnostics.len());
+    }
+    let is_ok = result.is_ok();
+
+    match output {
+        Some(OutputFormat::Json) => {
+            let output = BoundariesOutput {
+                suppressed_count: result.suppressed.len(),
+                diagnostics: result.diagnostics.into_iter().map(Into::into).collect(),
+                suppressed: result
+                    .suppressed
+                    .into_iter()
+                    .map(|suppressed| SuppressedOutput {
+                        diagnostic: suppressed.diagnostic.into(),
+                        suppression_reason: suppressed.reason,
+                    })
+                    .collect(),
+                fixed,
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        Some(OutputFormat::Pretty) | None => {
+            if let Some(fixed) = fixed {
+                println!("Fixed {fixed} issue(s)");
+            }
+            result.emit();
+        }
+    }
 
-    if result.is_ok() {
+    if is_ok {
         Ok(0)
     } else {
         Ok(1)
@@ -0,0 +1,370 @@
+//! Commands for inspecting and managing the local filesystem cache.
+
+use std::{
+    io::{self, Write},
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tabwriter::TabWriter;
+use thiserror::Error;
+use turborepo_cache::{
+    cache_archive::{ArchiveEntry, ArchiveEntryKind},
+    fs::{CacheEntry, FSCache},
+    CacheError, CacheLimits,
+};
+use turborepo_ui::{color, cprintln, cwriteln, BOLD, GREY, YELLOW};
+
+use crate::{
+    cache_server::{self, ServerOpts},
+    cli::{CacheCommand, OutputFormat},
+    commands::CommandBase,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("No artifact with hash `{hash}` found in the local cache.")]
+    ArtifactNotFound { hash: String },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("Failed to run cache server on {addr}: {source}")]
+    Server {
+        addr: SocketAddr,
+        #[source]
+        source: io::Error,
+    },
+}
+
+#[derive(Serialize)]
+struct ItemsWithCount<T> {
+    count: usize,
+    items: Vec<T>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactDetails {
+    hash: String,
+    size: u64,
+    last_used: DateTime<Utc>,
+    /// Time saved by restoring the artifact, in milliseconds
+    time_saved: Option<u64>,
+    task_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactContents {
+    hash: String,
+    task_id: Option<String>,
+    files: ItemsWithCount<ArchiveEntry>,
+}
+
+pub async fn run(command: &CacheCommand, base: &CommandBase) -> Result<(), Error> {
+    let local_cache = || FSCache::new(&base.opts.cache_opts.cache_dir, &base.repo_root, None);
+    let configured = base.opts.cache_opts.local_limits;
+
+    match command {
+        CacheCommand::Ls { output } => ls(base, &local_cache()?, *output),
+        CacheCommand::Show { hash, output } => show(base, &local_cache()?, hash, *output),
+        CacheCommand::Rm { hash } => {
+            if !local_cache()?.remove(hash)? {
+                return Err(Error::ArtifactNotFound { hash: hash.clone() });
+            }
+            println!("Removed {} from the local cache", hash);
+            Ok(())
+        }
+        CacheCommand::Clean => {
+            let summary = local_cache()?.clear()?;
+            println!(
+                "Removed {} ({}) from the local cache",
+                color!(
+                    base.color_config,
+                    BOLD,
+                    "{} artifact{}",
+                    summary.evicted,
+                    if summary.evicted == 1 { "" } else { "s" }
+                ),
+                format_bytes(summary.evicted_bytes)
+            );
+            Ok(())
+        }
+        CacheCommand::Prune { max_size, max_age } => {
+            let limits = CacheLimits {
+                max_size: max_size.or(configured.max_size),
+                max_age: max_age.or(configured.max_age),
+            };
+            prune(base, &local_cache()?, &limits)
+        }
+        CacheCommand::Serve {
+            host,
+            port,
+            cache_dir,
+            auth_token,
+            max_artifact_size,
+            max_size,
+            max_age,
+        } => {
+            // The server can store artifacts outside of the local cache
+            let cache_dir = cache_dir
+                .as_deref()
+                .unwrap_or(&base.opts.cache_opts.cache_dir);
+            let cache = FSCache::new(cache_dir, &base.repo_root, None)?;
+            let opts = ServerOpts {
+                addr: SocketAddr::new(*host, *port),
+                token: auth_token.clone(),
+                max_artifact_size: *max_artifact_size,
+                limits: CacheLimits {
+                    max_size: max_size.or(configured.max_size),
+                    max_age: max_age.or(configured.max_age),
+                },
+            };
+            serve(base, cache, opts).await
+        }
+    }
+}
+
+fn ls(base: &CommandBase, cache: &FSCache, output: Option<OutputFormat>) -> Result<(), Error> {
+    // Show the most recently used artifacts first
+    let artifacts = cache
+        .entries()?
+        .into_iter()
+        .rev()
+        .map(|entry| artifact_details(cache, entry))
+        .collect::<Vec<_>>();
+
+    match output {
+        Some(OutputFormat::Json) => {
+            let items = ItemsWithCount {
+                count: artifacts.len(),
+                items: artifacts,
+            };
+            println!("{}", serde_json::to_string_pretty(&items)?);
+        }
+        Some(OutputFormat::Pretty) | None => {
+            let total_size: u64 = artifacts.iter().map(|artifact| artifact.size).sum();
+            cprintln!(
+                base.color_config,
+                BOLD,
+                "{} artifacts ({})",
+                artifacts.len(),
+                format_bytes(total_size)
+            );
+            if artifacts.is_empty() {
+                return Ok(());
+            }
+            println!();
+
+            let now = SystemTime::now();
+            let mut tab_writer = TabWriter::new(io::stdout()).minwidth(0).padding(2);
+            cwriteln!(
+                tab_writer,
+                base.color_config,
+                GREY,
+                "Hash\tSize\tSaves\tLast used\tTask"
+            )?;
+            for artifact in &artifacts {
+                let age = now
+                    .duration_since(artifact.last_used.into())
+                    .unwrap_or_default();
+                writeln!(
+                    tab_writer,
+                    "{}\t{}\t{}\t{} ago\t{}",
+                    artifact.hash,
+                    format_bytes(artifact.size),
+                    artifact.time_saved.map_or_else(
+                        || "-".to_string(),
+                        |time_saved| format_duration(Duration::from_millis(time_saved))
+                    ),
+                    format_duration(age),
+                    artifact.task_id.as_deref().unwrap_or("-"),
+                )?;
+            }
+            tab_writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn show(
+    base: &CommandBase,
+    cache: &FSCache,
+    hash: &str,
+    output: Option<OutputFormat>,
+) -> Result<(), Error> {
+    let files = cache
+        .archive_entries(hash)?
+        .ok_or_else(|| Error::ArtifactNotFound {
+            hash: hash.to_string(),
+        })?;
+    let metadata = cache.metadata(hash)?;
+
+    match output {
+        Some(OutputFormat::Json) => {
+            let contents = ArtifactContents {
+                hash: hash.to_string(),
+                task_id: metadata.and_then(|metadata| metadata.task_id),
+                files: ItemsWithCount {
+                    count: files.len(),
+                    items: files,
+                },
+            };
+            println!("{}", serde_json::to_string_pretty(&contents)?);
+        }
+        Some(OutputFormat::Pretty) | None => {
+            cprintln!(base.color_config, BOLD, "{}", hash);
+            if let Some(task_id) = metadata.and_then(|metadata| metadata.task_id) {
+                cprintln!(base.color_config, GREY, "task: {}", task_id);
+            }
+            println!();
+            for file in &files {
+                match file.kind {
+                    ArchiveEntryKind::File => println!(
+                        "  {} {}",
+                        file.path,
+                        color!(base.color_config, GREY, "({})", format_bytes(file.size))
+                    ),
+                    ArchiveEntryKind::Directory | ArchiveEntryKind::Symlink => {
+                        println!("  {}", file.path)
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn prune(base: &CommandBase, cache: &FSCache, limits: &CacheLimits) -> Result<(), Error> {
+    if limits.is_unbounded() {
+        cprintln!(
+            base.color_config,
+            GREY,
+            "No cache limits configured. Set `cacheMaxSize` or `cacheMaxAge` in turbo.json, or \
+             pass --max-size or --max-age."
+        );
+        return Ok(());
+    }
+
+    let summary = cache.prune(limits)?;
+
+    println!(
+        "Evicted {} ({}) from the local cache",
+        color!(
+            base.color_config,
+            BOLD,
+            "{} artifact{}",
+            summary.evicted,
+            if summary.evicted == 1 { "" } else { "s" }
+        ),
+        format_bytes(summary.evicted_bytes)
+    );
+    cprintln!(
+        base.color_config,
+        GREY,
+        "{} artifacts ({}) remaining",
+        summary.remaining,
+        format_bytes(summary.remaining_bytes)
+    );
+
+    Ok(())
+}
+
+async fn serve(base: &CommandBase, cache: FSCache, opts: ServerOpts) -> Result<(), Error> {
+    let addr = opts.addr;
+    if opts.token.is_none() && !addr.ip().is_loopback() {
+        cprintln!(
+            base.color_config,
+            YELLOW,
+            "Warning: no --auth-token set, anyone who can reach {} can read and write to the cache",
+            addr
+        );
+    }
+    println!("Serving remote cache on http://{}", addr);
+    cprintln!(
+        base.color_config,
+        GREY,
+        "Point clients at it with `remoteCache.apiUrl`, and set TURBO_TEAM and TURBO_TOKEN"
+    );
+
+    cache_server::serve(cache, opts)
+        .await
+        .map_err(|source| Error::Server { addr, source })
+}
+
+fn artifact_details(cache: &FSCache, entry: CacheEntry) -> ArtifactDetails {
+    // A missing or unreadable metadata file shouldn't prevent listing the rest of
+    // the cache
+    let metadata = cache.metadata(&entry.hash).ok().flatten();
+
+    ArtifactDetails {
+        size: entry.size,
+        last_used: entry.last_used.into(),
+        time_saved: metadata.as_ref().map(|metadata| metadata.duration),
+        task_id: metadata.and_then(|metadata| metadata.task_id),
+        hash: entry.hash,
+    }
+}
+
+/// Formats a size in the same binary units that `cacheMaxSize` is written in,
+/// i.e. `1KB` is 1024 bytes.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes}B")
+    } else {
+        format!("{size:.2}{}", UNITS[unit])
+    }
+}
+
+/// Formats a duration at a granularity that's useful at a glance, i.e.
+/// seconds are dropped once the duration exceeds a minute.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let rounded = if secs >= 60 { secs - secs % 60 } else { secs };
+    humantime::format_duration(Duration::from_secs(rounded)).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+    use turborepo_cache::config::parse_size;
+
+    use super::format_bytes;
+
+    #[test_case(0, "0B")]
+    #[test_case(1023, "1023B")]
+    #[test_case(1024, "1.00KB")]
+    #[test_case(1536, "1.50KB")]
+    #[test_case(10 * 1024 * 1024 * 1024, "10.00GB")]
+    #[test_case(2048 * 1024 * 1024 * 1024 * 1024, "2048.00TB")]
+    fn test_format_bytes(bytes: u64, expected: &str) {
+        assert_eq!(format_bytes(bytes), expected);
+    }
+
+    #[test]
+    fn test_format_bytes_matches_parse_size() {
+        for size in ["512B", "1KB", "500MB", "10GB", "3TB"] {
+            let bytes = parse_size(size).unwrap();
+            assert_eq!(
+                parse_size(&format_bytes(bytes).replace(".00", "")),
+                Ok(bytes)
+            );
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! Suggests task inputs by tracing the imports of a package's entry points.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    io::{self, Write},
+};
+
+use camino::Utf8PathBuf;
+use miette::Diagnostic;
+use serde::Serialize;
+use tabwriter::TabWriter;
+use thiserror::Error;
+use turbo_trace::Tracer;
+use turbopath::{AnchoredSystemPathBuf, RelativeUnixPathBuf};
+use turborepo_repository::package_graph::{PackageInfo, PackageName};
+use turborepo_scm::package_deps::GitHashes;
+use turborepo_telemetry::events::command::CommandEventBuilder;
+use turborepo_ui::{color, cprintln, cwriteln, BOLD, GREY};
+
+use crate::{
+    cli::{InputsCommand, OutputFormat},
+    commands::{run::get_signal, CommandBase},
+    engine::TaskNode,
+    run,
+    run::{builder::RunBuilder, task_id::TaskId, Run},
+    signal::SignalHandler,
+};
+
+/// Fields of package.json that point at the files a package exposes
+const ENTRY_POINT_FIELDS: &[&str] = &["main", "module", "types", "typings", "bin", "exports"];
+
+/// Entry points to fall back to when package.json doesn't point at any source
+/// files, e.g. because `main` points at build outputs
+const DEFAULT_ENTRY_POINTS: &[&str] = &[
+    "src/index.ts",
+    "src/index.tsx",
+    "src/index.js",
+    "src/index.jsx",
+    "index.ts",
+    "index.tsx",
+    "index.js",
+    "index.jsx",
+];
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum Error {
+    #[error("Could not find task `{task}` in project.")]
+    TaskNotFound { task: String },
+    #[error("No entry points found for `{task_id}`. Pass them with `--entry`.")]
+    NoEntryPoints { task_id: String },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Run(#[from] run::Error),
+    #[error(transparent)]
+    Scm(#[from] turborepo_scm::Error),
+    #[error(transparent)]
+    Path(#[from] turbopath::PathError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+}
+
+#[derive(Serialize)]
+struct ItemsWithCount<T> {
+    count: usize,
+    items: Vec<T>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InputsSuggestion {
+    task_id: String,
+    entry_points: Vec<RelativeUnixPathBuf>,
+    /// Files in the package imported by the entry points, with their hashes
+    files: Vec<FileHash>,
+    /// Files matched by the task's current `inputs` that are never imported
+    unused: Vec<RelativeUnixPathBuf>,
+    errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileHash {
+    path: RelativeUnixPathBuf,
+    hash: String,
+}
+
+pub async fn run(
+    command: &InputsCommand,
+    base: CommandBase,
+    telemetry: CommandEventBuilder,
+) -> Result<(), Error> {
+    let InputsCommand::Suggest {
+        task,
+        entry,
+        output,
+    } = command;
+    let color_config = base.color_config;
+
+    let signal = get_signal()?;
+    let handler = SignalHandler::new(signal);
+    let run = RunBuilder::new(base)?
+        .add_all_tasks()
+        .do_not_validate_engine()
+        .build(&handler, telemetry)
+        .await?;
+
+    let mut suggestions = Vec::new();
+    for task_id in find_tasks(&run, task)? {
+        suggestions.push(suggest(&run, &task_id, entry).await?);
+    }
+
+    match output {
+        Some(OutputFormat::Json) => {
+            let items = ItemsWithCount {
+                count: suggestions.len(),
+                items: suggestions,
+            };
+            println!("{}", serde_json::to_string_pretty(&items)?);
+        }
+        Some(OutputFormat::Pretty) | None => {
+            for (index, suggestion) in suggestions.iter().enumerate() {
+                if index > 0 {
+                    println!();
+                }
+                cprintln!(color_config, BOLD, "{}", suggestion.task_id);
+                println!(
+                    "Entry points: {}",
+                    suggestion
+                        .entry_points
+                        .iter()
+                        .map(|entry_point| entry_point.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                for error in &suggestion.errors {
+                    println!(
+                        "{}",
+                        color!(color_config, GREY, "Could not trace: {}", error)
+                    );
+                }
+
+                println!();
+                println!("Imported files ({}):", suggestion.files.len());
+                let mut tab_writer = TabWriter::new(io::stdout()).minwidth(0).padding(2);
+                cwriteln!(tab_writer, color_config, GREY, "  File\tHash")?;
+                for file in &suggestion.files {
+                    writeln!(tab_writer, "  {}\t{}", file.path, file.hash)?;
+                }
+                tab_writer.flush()?;
+
+                println!();
+                println!(
+                    "Matched by inputs but never imported ({}):",
+                    suggestion.unused.len()
+                );
+                for path in &suggestion.unused {
+                    println!("  {}", path);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the tasks to suggest inputs for. A task without a package matches
+/// every package with a script for it.
+fn find_tasks(run: &Run, task: &str) -> Result<Vec<TaskId<'static>>, Error> {
+    let mut task_ids = match TaskId::try_from(task) {
+        Ok(task_id) => vec![task_id.into_owned()],
+        Err(_) => run
+            .engine()
+            .tasks()
+            .filter_map(|node| match node {
+                TaskNode::Root => None,
+                TaskNode::Task(task_id) => Some(task_id),
+            })
+            .filter(|task_id| task_id.task() == task)
+            .cloned()
+            .collect(),
+    };
+    task_ids.retain(|task_id| {
+        run.pkg_dep_graph()
+            .package_info(&PackageName::from(task_id.package()))
+            .is_some_and(|info| info.package_json.scripts.contains_key(task_id.task()))
+    });
+    task_ids.sort();
+
+    if task_ids.is_empty() {
+        return Err(Error::TaskNotFound {
+            task: task.to_string(),
+        });
+    }
+    Ok(task_ids)
+}
+
+async fn suggest(
+    run: &Run,
+    task_id: &TaskId<'static>,
+    entry: &[String],
+) -> Result<InputsSuggestion, Error> {
+    let repo_root = run.repo_root();
+    let info = run
+        .pkg_dep_graph()
+        .package_info(&PackageName::from(task_id.package()))
+        .ok_or_else(|| Error::TaskNotFound {
+            task: task_id.to_string(),
+        })?;
+    let package_path = info.package_path();
+    let package_dir = repo_root.resolve(package_path);
+
+    // The files git knows about, which leaves out build outputs and
+    // dependencies
+    let package_files =
+        run.scm()
+            .get_package_file_hashes::<&str>(repo_root, package_path, &[], None)?;
+
+    let entry_points = if entry.is_empty() {
+        package_entry_points(info, &package_files)
+    } else {
+        entry
+            .iter()
+            .map(|entry| RelativeUnixPathBuf::new(entry.trim_start_matches("./")))
+            .collect::<Result<_, _>>()?
+    };
+    if entry_points.is_empty() {
+        return Err(Error::NoEntryPoints {
+            task_id: task_id.to_string(),
+        });
+    }
+
+    let ts_config = package_dir.join_component("tsconfig.json");
+    let tracer = Tracer::new(
+        repo_root.to_owned(),
+        entry_points
+            .iter()
+            .map(|entry_point| package_dir.join_unix_path(entry_point))
+            .collect(),
+        ts_config
+            .exists()
+            .then(|| Utf8PathBuf::from(ts_config.as_str())),
+    );
+    let result = tracer.trace(None).await;
+
+    // Files in other packages and in node_modules are left out, since they
+    // aren't part of this package's inputs
+    let traced = result
+        .files
+        .keys()
+        .filter(|path| package_dir.contains(path))
+        .map(|path| package_dir.anchor(path))
+        .collect::<Result<Vec<AnchoredSystemPathBuf>, _>>()?
+        .into_iter()
+        .filter(|path| package_files.contains_key(&path.to_unix()))
+        .collect::<Vec<_>>();
+    let hashes = run
+        .scm()
+        .get_hashes_for_files(&package_dir, &traced, true)?;
+    let mut files = hashes
+        .into_iter()
+        .map(|(path, hash)| FileHash { path, hash })
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let inputs = run
+        .engine()
+        .task_definition(task_id)
+        .map(|definition| definition.inputs.as_slice())
+        .unwrap_or_default();
+    let imported = files.iter().map(|file| &file.path).collect::<HashSet<_>>();
+    let unused = run
+        .scm()
+        .get_package_file_hashes(repo_root, package_path, inputs, None)?
+        .into_keys()
+        .filter(|path| !imported.contains(path))
+        .collect::<BTreeSet<_>>();
+
+    Ok(InputsSuggestion {
+        task_id: task_id.to_string(),
+        entry_points,
+        files,
+        unused: unused.into_iter().collect(),
+        errors: result
+            .errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect(),
+    })
+}
+
+/// Gets the entry points of a package from package.json, keeping only the
+/// ones that are source files rather than build outputs.
+fn package_entry_points(info: &PackageInfo, package_files: &GitHashes) -> Vec<RelativeUnixPathBuf> {
+    let mut paths = Vec::new();
+    for field in ENTRY_POINT_FIELDS {
+        if let Some(value) = info.package_json.other.get(*field) {
+            collect_paths(value, &mut paths);
+        }
+    }
+
+    let entry_points = paths
+        .iter()
+        .filter_map(|path| RelativeUnixPathBuf::new(path.trim_start_matches("./")).ok())
+        .filter(|path| package_files.contains_key(path))
+        .collect::<BTreeSet<_>>();
+    if !entry_points.is_empty() {
+        return entry_points.into_iter().collect();
+    }
+
+    DEFAULT_ENTRY_POINTS
+        .iter()
+        .filter_map(|path| RelativeUnixPathBuf::new(*path).ok())
+        .filter(|path| package_files.contains_key(path))
+        .take(1)
+        .collect()
+}
+
+/// Collects the paths in a package.json field. `exports` and `bin` can nest
+/// paths in objects and arrays.
+fn collect_paths(value: &serde_json::Value, paths: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(path) if !path.contains('*') => paths.push(path.clone()),
+        serde_json::Value::Array(values) => {
+            for value in values {
+                collect_paths(value, paths);
+            }
+        }
+        serde_json::Value::Object(values) => {
+            for value in values.values() {
+                collect_paths(value, paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use test_case::test_case;
+    use turbopath::RelativeUnixPathBuf;
+    use turborepo_repository::{package_graph::PackageInfo, package_json::PackageJson};
+    use turborepo_scm::package_deps::GitHashes;
+
+    use super::{collect_paths, package_entry_points};
+
+    #[test_case(json!("./dist/index.js"), &["./dist/index.js"] ; "string")]
+    #[test_case(
+        json!({
+            ".": { "import": "./src/index.mjs", "require": "./src/index.cjs" },
+            "./utils": ["./src/utils.ts", "./src/utils.js"]
+        }),
+        &["./src/index.mjs", "./src/index.cjs", "./src/utils.ts", "./src/utils.js"] ;
+        "nested exports"
+    )]
+    #[test_case(json!({ "cli": "./bin/cli.js" }), &["./bin/cli.js"] ; "bin object")]
+    #[test_case(
+        json!({ ".": "./src/index.ts", "./features/*": "./src/features/*.ts" }),
+        &["./src/index.ts"] ;
+        "wildcards are skipped"
+    )]
+    #[test_case(json!({ "./package.json": null, "./flag": true }), &[] ; "non-paths are skipped")]
+    fn test_collect_paths(value: serde_json::Value, expected: &[&str]) {
+        let mut paths = Vec::new();
+        collect_paths(&value, &mut paths);
+        assert_eq!(paths, expected);
+    }
+
+    fn package_info(fields: serde_json::Value) -> PackageInfo {
+        let serde_json::Value::Object(fields) = fields else {
+            panic!("package.json fields should be an object");
+        };
+        PackageInfo {
+            package_json: PackageJson {
+                other: fields.into_iter().collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn package_files(files: &[&str]) -> GitHashes {
+        files
+            .iter()
+            .map(|file| (RelativeUnixPathBuf::new(*file).unwrap(), "hash".to_string()))
+            .collect()
+    }
+
+    #[test_case(
+        json!({ "main": "./dist/index.js", "types": "./src/index.ts" }),
+        &["src/index.ts", "src/index.js"],
+        &["src/index.ts"] ;
+        "build outputs are skipped"
+    )]
+    #[test_case(
+        json!({ "main": "./dist/index.js" }),
+        &["index.js", "src/index.tsx", "src/index.js"],
+        &["src/index.tsx"] ;
+        "falls back to first default entry point"
+    )]
+    #[test_case(
+        json!({ "main": "./dist/index.js", "exports": { ".": "./src/main.ts" } }),
+        &["src/main.ts", "src/index.ts"],
+        &["src/main.ts"] ;
+        "default entry points unused if package.json has source files"
+    )]
+    #[test_case(
+        json!({ "main": "./lib/index.js" }),
+        &["README.md"],
+        &[] ;
+        "no entry points"
+    )]
+    fn test_package_entry_points(fields: serde_json::Value, files: &[&str], expected: &[&str]) {
+        let entry_points = package_entry_points(&package_info(fields), &package_files(files));
+        assert_eq!(
+            entry_points
+                .iter()
+                .map(|entry_point| entry_point.as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
 use turborepo_api_client::{APIAuth, APIClient};
@@ -15,10 +15,12 @@ use crate::{
 
 pub(crate) mod bin;
 pub(crate) mod boundaries;
+pub(crate) mod cache;
 pub(crate) mod config;
 pub(crate) mod daemon;
 pub(crate) mod generate;
 pub(crate) mod info;
+pub(crate) mod inputs;
 pub(crate) mod link;
 pub(crate) mod login;
 pub(crate) mod logout;
@@ -26,6 +28,7 @@ pub(crate) mod ls;
 pub(crate) mod prune;
 pub(crate) mod query;
 pub(crate) mod run;
+pub(crate) mod runs;
 pub(crate) mod scan;
 pub(crate) mod telemetry;
 pub(crate) mod unlink;
@@ -87,7 +90,15 @@ impl CommandBase {
                 args.dangerously_disable_package_manager_check
                     .then_some(true),
             )
-            .with_daemon(args.run_args().and_then(|args| args.daemon()))
+            .with_daemon(match &args.command {
+                Some(cli::Command::Watch {
+                    no_daemon: true, ..
+                })
+                | Some(cli::Command::Query {
+                    no_daemon: true, ..
+                }) => Some(false),
+                _ => args.run_args().and_then(|args| args.daemon()),
+            })
             .with_env_mode(
                 args.execution_args()
                     .and_then(|execution_args| execution_args.env_mode),
@@ -96,6 +107,25 @@ impl CommandBase {
                 args.execution_args()
                     .and_then(|execution_args| execution_args.cache_dir.clone()),
             )
+            .with_cache_max_size(
+                args.execution_args()
+                    .and_then(|execution_args| execution_args.cache_max_size),
+            )
+            .with_cache_max_age(
+                args.execution_args()
+                    .and_then(|execution_args| execution_args.cache_max_age),
+            )
+            .with_resource_limits(
+                args.execution_args()
+                    .map(|execution_args| {
+                        execution_args
+                            .resource_limits
+                            .iter()
+                            .cloned()
+                            .collect::<BTreeMap<_, _>>()
+                    })
+                    .filter(|resource_limits| !resource_limits.is_empty()),
+            )
             .with_root_turbo_json_path(
                 args.root_turbo_json
                     .clone()
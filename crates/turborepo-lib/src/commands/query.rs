@@ -63,10 +63,10 @@ pub async fn run(
     let signal = get_signal()?;
     let handler = SignalHandler::new(signal);
 
-    let run_builder = RunBuilder::new(base)?
+    let run_builder = RunBuilder::new(base.clone())?
         .add_all_tasks()
         .do_not_validate_engine();
-    let run = run_builder.build(&handler, telemetry).await?;
+    let run = run_builder.build(&handler, telemetry.clone()).await?;
 
     if let Some(query) = query {
         let trimmed_query = query.trim();
@@ -109,7 +109,7 @@ pub async fn run(
             }
         }
     } else {
-        query::run_query_server(run, handler).await?;
+        query::run_query_server(base, telemetry, run, handler).await?;
     }
 
     Ok(0)
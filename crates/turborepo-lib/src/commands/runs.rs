@@ -0,0 +1,462 @@
+//! Commands for inspecting the run summaries saved by `--summarize`.
+
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, TimeZone, Utc};
+use serde::Serialize;
+use tabwriter::TabWriter;
+use thiserror::Error;
+use turborepo_ui::{cprintln, cwriteln, BOLD, GREY};
+
+use crate::{
+    cli::{OutputFormat, RunsCommand},
+    commands::CommandBase,
+    run::summary::{compare_runs, RunHistory, SavedRun, TaskComparison, TurboDuration},
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("No saved run matches `{query}`. Runs are matched by id or git SHA.")]
+    RunNotFound { query: String },
+    #[error("At least two saved runs are needed to compare. Run with `--summarize` to save one.")]
+    NotEnoughRuns,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+}
+
+#[derive(Serialize)]
+struct ItemsWithCount<T> {
+    count: usize,
+    items: Vec<T>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunDetails<'a> {
+    id: &'a str,
+    command: Option<&'a str>,
+    start_time: Option<DateTime<Utc>>,
+    /// Duration of the run in milliseconds
+    duration: Option<u128>,
+    exit_code: Option<i32>,
+    sha: Option<&'a str>,
+    branch: Option<&'a str>,
+    tasks: usize,
+    cache_hit_rate: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunComparison<'a> {
+    base: RunDetails<'a>,
+    head: RunDetails<'a>,
+    tasks: Vec<TaskComparisonDetails<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskComparisonDetails<'a> {
+    task_id: &'a str,
+    base_duration: Option<u128>,
+    head_duration: Option<u128>,
+    /// Change in duration from the base run to the head run in milliseconds
+    duration_change: Option<i64>,
+    base_hash: Option<&'a str>,
+    head_hash: Option<&'a str>,
+    hash_changed: bool,
+    base_cache_hit: Option<bool>,
+    head_cache_hit: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskRunDetails<'a> {
+    run_id: &'a str,
+    start_time: Option<DateTime<Utc>>,
+    /// Duration of the task in milliseconds
+    duration: Option<u128>,
+    hash: &'a str,
+    cache_hit: bool,
+    exit_code: Option<i32>,
+    sha: Option<&'a str>,
+}
+
+pub fn run(command: &RunsCommand, base: &CommandBase) -> Result<(), Error> {
+    let history = RunHistory::load(&base.repo_root);
+
+    match command {
+        RunsCommand::Ls {
+            sha,
+            task,
+            limit,
+            output,
+        } => {
+            let runs = history
+                .runs()
+                .filter(|run| {
+                    sha.as_deref()
+                        .map_or(true, |sha| run.sha().is_some_and(|s| s.starts_with(sha)))
+                })
+                .filter(|run| {
+                    task.as_deref()
+                        .map_or(true, |task| run.task(task).is_some())
+                })
+                .take(*limit)
+                .collect::<Vec<_>>();
+            ls(base, &runs, *output)
+        }
+        RunsCommand::Compare {
+            base: base_query,
+            head: head_query,
+            output,
+        } => {
+            let (base_run, head_run) = match (base_query, head_query) {
+                (None, None) => history.latest_two().ok_or(Error::NotEnoughRuns)?,
+                (Some(base_query), None) => {
+                    let head_run = history.runs().next().ok_or(Error::NotEnoughRuns)?;
+                    (find(&history, base_query)?, head_run)
+                }
+                (base_query, Some(head_query)) => {
+                    let head_run = find(&history, head_query)?;
+                    let base_run = match base_query {
+                        Some(base_query) => find(&history, base_query)?,
+                        // Compare against the run before `head`
+                        None => history
+                            .runs()
+                            .skip_while(|run| run.id != head_run.id)
+                            .nth(1)
+                            .ok_or(Error::NotEnoughRuns)?,
+                    };
+                    (base_run, head_run)
+                }
+            };
+            compare(base, base_run, head_run, *output)
+        }
+        RunsCommand::Trend {
+            task,
+            limit,
+            output,
+        } => {
+            let runs = history.runs_for_task(task).take(*limit).collect::<Vec<_>>();
+            trend(base, task, &runs, *output)
+        }
+    }
+}
+
+fn find<'a>(history: &'a RunHistory, query: &str) -> Result<&'a SavedRun, Error> {
+    history.find(query).ok_or_else(|| Error::RunNotFound {
+        query: query.to_string(),
+    })
+}
+
+fn ls(base: &CommandBase, runs: &[&SavedRun], output: Option<OutputFormat>) -> Result<(), Error> {
+    match output {
+        Some(OutputFormat::Json) => {
+            let items = ItemsWithCount {
+                count: runs.len(),
+                items: runs.iter().map(|run| run_details(run)).collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&items)?);
+        }
+        Some(OutputFormat::Pretty) | None => {
+            cprintln!(base.color_config, BOLD, "{} runs", runs.len());
+            if runs.is_empty() {
+                cprintln!(
+                    base.color_config,
+                    GREY,
+                    "Run summaries are saved in .turbo/runs when running with `--summarize`"
+                );
+                return Ok(());
+            }
+            println!();
+
+            let mut tab_writer = TabWriter::new(io::stdout()).minwidth(0).padding(2);
+            cwriteln!(
+                tab_writer,
+                base.color_config,
+                GREY,
+                "Id\tStarted\tDuration\tTasks\tCached\tSHA\tCommand"
+            )?;
+            for run in runs {
+                let execution = run.execution();
+                writeln!(
+                    tab_writer,
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    run.id,
+                    format_start_time(execution.map(|execution| execution.start_time)),
+                    format_duration(run.duration()),
+                    run.tasks().len(),
+                    format_rate(run.cache_hit_rate()),
+                    run.sha().map_or("-", short_sha),
+                    execution.map_or("-", |execution| execution.command.as_str()),
+                )?;
+            }
+            tab_writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn compare(
+    base: &CommandBase,
+    base_run: &SavedRun,
+    head_run: &SavedRun,
+    output: Option<OutputFormat>,
+) -> Result<(), Error> {
+    let comparisons = compare_runs(base_run, head_run);
+
+    match output {
+        Some(OutputFormat::Json) => {
+            let comparison = RunComparison {
+                base: run_details(base_run),
+                head: run_details(head_run),
+                tasks: comparisons.iter().map(task_comparison_details).collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&comparison)?);
+        }
+        Some(OutputFormat::Pretty) | None => {
+            cprintln!(
+                base.color_config,
+                BOLD,
+                "{} -> {}",
+                base_run.id,
+                head_run.id
+            );
+            println!(
+                "Duration:        {} -> {}",
+                format_duration(base_run.duration()),
+                format_duration(head_run.duration())
+            );
+            println!(
+                "Cache hit rate:  {} -> {}",
+                format_rate(base_run.cache_hit_rate()),
+                format_rate(head_run.cache_hit_rate())
+            );
+            if comparisons.is_empty() {
+                return Ok(());
+            }
+            println!();
+
+            let mut tab_writer = TabWriter::new(io::stdout()).minwidth(0).padding(2);
+            cwriteln!(
+                tab_writer,
+                base.color_config,
+                GREY,
+                "Task\tBase\tHead\tChange\tCache\tHash"
+            )?;
+            for comparison in &comparisons {
+                let base_task = comparison.base;
+                let head_task = comparison.head;
+                writeln!(
+                    tab_writer,
+                    "{}\t{}\t{}\t{}\t{} -> {}\t{}",
+                    comparison.task_id,
+                    format_duration(base_task.and_then(|task| task.execution_duration())),
+                    format_duration(head_task.and_then(|task| task.execution_duration())),
+                    comparison
+                        .duration_change()
+                        .map_or_else(|| "-".to_string(), format_change),
+                    format_cache(base_task.map(|task| task.cache_hit())),
+                    format_cache(head_task.map(|task| task.cache_hit())),
+                    match (base_task, head_task) {
+                        (Some(_), Some(_)) if comparison.hash_changed() => "changed",
+                        (Some(_), Some(_)) => "unchanged",
+                        (Some(_), None) => "removed",
+                        (None, _) => "added",
+                    },
+                )?;
+            }
+            tab_writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn trend(
+    base: &CommandBase,
+    task_id: &str,
+    runs: &[&SavedRun],
+    output: Option<OutputFormat>,
+) -> Result<(), Error> {
+    let task_runs = runs
+        .iter()
+        .filter_map(|run| {
+            let task = run.task(task_id)?;
+            Some(TaskRunDetails {
+                run_id: &run.id,
+                start_time: run
+                    .execution()
+                    .and_then(|execution| to_utc(execution.start_time)),
+                duration: task.execution_duration().map(|d| d.as_millis()),
+                hash: &task.hash,
+                cache_hit: task.cache_hit(),
+                exit_code: task.exit_code(),
+                sha: run.sha(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    match output {
+        Some(OutputFormat::Json) => {
+            let items = ItemsWithCount {
+                count: task_runs.len(),
+                items: task_runs,
+            };
+            println!("{}", serde_json::to_string_pretty(&items)?);
+        }
+        Some(OutputFormat::Pretty) | None => {
+            cprintln!(
+                base.color_config,
+                BOLD,
+                "{} ({} runs)",
+                task_id,
+                task_runs.len()
+            );
+            // Cache hits only replay logs, so they're left out of the average
+            let executed = task_runs
+                .iter()
+                .filter(|task_run| !task_run.cache_hit)
+                .filter_map(|task_run| task_run.duration)
+                .collect::<Vec<_>>();
+            if !executed.is_empty() {
+                let average = executed.iter().sum::<u128>() / executed.len() as u128;
+                cprintln!(
+                    base.color_config,
+                    GREY,
+                    "Average duration without cache hits: {}",
+                    format_duration(Some(Duration::from_millis(average as u64)))
+                );
+            }
+            if task_runs.is_empty() {
+                return Ok(());
+            }
+            println!();
+
+            let mut tab_writer = TabWriter::new(io::stdout()).minwidth(0).padding(2);
+            cwriteln!(
+                tab_writer,
+                base.color_config,
+                GREY,
+                "Run\tStarted\tDuration\tChange\tCache\tHash\tSHA"
+            )?;
+            // Runs are listed newest first, so each run is compared to the one after it
+            for (index, task_run) in task_runs.iter().enumerate() {
+                let change = task_runs
+                    .get(index + 1)
+                    .and_then(|previous| Some((task_run.duration?, previous.duration?)))
+                    .map(|(duration, previous)| duration as i64 - previous as i64);
+                writeln!(
+                    tab_writer,
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    task_run.run_id,
+                    format_start_time(task_run.start_time.map(|time| time.timestamp_millis())),
+                    format_duration(task_run.duration.map(|d| Duration::from_millis(d as u64))),
+                    change.map_or_else(|| "-".to_string(), format_change),
+                    format_cache(Some(task_run.cache_hit)),
+                    task_run.hash,
+                    task_run.sha.map_or("-", short_sha),
+                )?;
+            }
+            tab_writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_details(run: &SavedRun) -> RunDetails<'_> {
+    let execution = run.execution();
+    RunDetails {
+        id: &run.id,
+        command: execution.map(|execution| execution.command.as_str()),
+        start_time: execution.and_then(|execution| to_utc(execution.start_time)),
+        duration: run.duration().map(|duration| duration.as_millis()),
+        exit_code: execution.map(|execution| execution.exit_code),
+        sha: run.sha(),
+        branch: run.branch(),
+        tasks: run.tasks().len(),
+        cache_hit_rate: run.cache_hit_rate(),
+    }
+}
+
+fn task_comparison_details<'a>(comparison: &TaskComparison<'a>) -> TaskComparisonDetails<'a> {
+    TaskComparisonDetails {
+        task_id: comparison.task_id,
+        base_duration: comparison
+            .base
+            .and_then(|task| task.execution_duration())
+            .map(|duration| duration.as_millis()),
+        head_duration: comparison
+            .head
+            .and_then(|task| task.execution_duration())
+            .map(|duration| duration.as_millis()),
+        duration_change: comparison.duration_change(),
+        base_hash: comparison.base.map(|task| task.hash.as_str()),
+        head_hash: comparison.head.map(|task| task.hash.as_str()),
+        hash_changed: comparison.hash_changed(),
+        base_cache_hit: comparison.base.map(|task| task.cache_hit()),
+        head_cache_hit: comparison.head.map(|task| task.cache_hit()),
+    }
+}
+
+fn to_utc(millis: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt(millis).single()
+}
+
+fn format_start_time(millis: Option<i64>) -> String {
+    millis
+        .and_then(|millis| Local.timestamp_millis_opt(millis).single())
+        .map_or_else(
+            || "-".to_string(),
+            |time| time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        )
+}
+
+fn format_duration(duration: Option<Duration>) -> String {
+    duration
+        .and_then(|duration| chrono::Duration::from_std(duration).ok())
+        .map_or_else(|| "-".to_string(), |d| TurboDuration::from(d).to_string())
+}
+
+fn format_change(millis: i64) -> String {
+    let sign = if millis < 0 { "-" } else { "+" };
+    let duration = chrono::Duration::milliseconds(millis.abs());
+    format!("{sign}{}", TurboDuration::from(duration))
+}
+
+fn format_rate(rate: Option<f64>) -> String {
+    rate.map_or_else(|| "-".to_string(), |rate| format!("{:.0}%", rate * 100.0))
+}
+
+fn format_cache(cache_hit: Option<bool>) -> &'static str {
+    match cache_hit {
+        Some(true) => "HIT",
+        Some(false) => "MISS",
+        None => "-",
+    }
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..8).unwrap_or(sha)
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(2000, "+2s" ; "slower")]
+    #[test_case(-990, "-990ms" ; "faster")]
+    #[test_case(0, "+0ms" ; "unchanged")]
+    fn test_format_change(millis: i64, expected: &str) {
+        assert_eq!(format_change(millis), expected);
+    }
+}
@@ -7,7 +7,7 @@ use clap::ValueEnum;
 use itertools::Itertools;
 use tracing::warn;
 use turbopath::AbsoluteSystemPathBuf;
-use turborepo_cache::CacheConfig;
+use turborepo_cache::{CacheConfig, CacheLayout};
 
 use super::{ConfigurationOptions, Error, ResolvedConfigurationOptions};
 use crate::{
@@ -31,6 +31,9 @@ const TURBO_MAPPING: &[(&str, &str)] = [
     ("turbo_daemon", "daemon"),
     ("turbo_env_mode", "env_mode"),
     ("turbo_cache_dir", "cache_dir"),
+    ("turbo_cache_max_size", "cache_max_size"),
+    ("turbo_cache_max_age", "cache_max_age"),
+    ("turbo_cache_layout", "cache_layout"),
     ("turbo_preflight", "preflight"),
     ("turbo_scm_base", "scm_base"),
     ("turbo_scm_head", "scm_head"),
@@ -171,6 +174,27 @@ impl ResolvedConfigurationOptions for EnvVars {
 
         let cache_dir = self.output_map.get("cache_dir").map(|s| s.clone().into());
 
+        let cache_max_size = self
+            .output_map
+            .get("cache_max_size")
+            .filter(|s| !s.is_empty())
+            .map(|s| turborepo_cache::config::parse_size(s))
+            .transpose()?;
+
+        let cache_max_age = self
+            .output_map
+            .get("cache_max_age")
+            .filter(|s| !s.is_empty())
+            .map(|s| turborepo_cache::config::parse_age(s))
+            .transpose()?;
+
+        let cache_layout = self
+            .output_map
+            .get("cache_layout")
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<CacheLayout>())
+            .transpose()?;
+
         let root_turbo_json_path = self
             .output_map
             .get("root_turbo_json_path")
@@ -226,6 +250,11 @@ impl ResolvedConfigurationOptions for EnvVars {
             spaces_id,
             env_mode,
             cache_dir,
+            cache_max_size,
+            cache_max_age,
+            cache_layout,
+            // The remote cache backend can only be configured in turbo.json
+            remote_cache_backend: None,
             root_turbo_json_path,
             log_order,
         };
@@ -320,6 +349,9 @@ mod test {
         env.insert("turbo_run_summary".into(), "true".into());
         env.insert("turbo_allow_no_turbo_json".into(), "true".into());
         env.insert("turbo_remote_cache_upload_timeout".into(), "200".into());
+        env.insert("turbo_cache_max_size".into(), "10GB".into());
+        env.insert("turbo_cache_max_age".into(), "7d".into());
+        env.insert("turbo_cache_layout".into(), "deduplicated".into());
 
         let config = EnvVars::new(&env)
             .unwrap()
@@ -344,6 +376,12 @@ mod test {
         assert_eq!(Some(true), config.daemon);
         assert_eq!(Some(EnvMode::Strict), config.env_mode);
         assert_eq!(cache_dir, config.cache_dir.unwrap());
+        assert_eq!(config.cache_max_size(), Some(10 * 1024 * 1024 * 1024));
+        assert_eq!(config.cache_layout(), CacheLayout::Deduplicated);
+        assert_eq!(
+            config.cache_max_age(),
+            Some(std::time::Duration::from_secs(7 * 24 * 60 * 60))
+        );
         assert_eq!(
             config.root_turbo_json_path,
             Some(AbsoluteSystemPathBuf::new(root_turbo_json).unwrap())
@@ -371,6 +409,9 @@ mod test {
         env.insert("turbo_remote_cache_read_only".into(), "".into());
         env.insert("turbo_run_summary".into(), "".into());
         env.insert("turbo_allow_no_turbo_json".into(), "".into());
+        env.insert("turbo_cache_max_size".into(), "".into());
+        env.insert("turbo_cache_max_age".into(), "".into());
+        env.insert("turbo_cache_layout".into(), "".into());
 
         let config = EnvVars::new(&env)
             .unwrap()
@@ -394,5 +435,8 @@ mod test {
         assert!(!config.remote_cache_read_only());
         assert!(!config.run_summary());
         assert!(!config.allow_no_turbo_json());
+        assert_eq!(config.cache_max_size(), None);
+        assert_eq!(config.cache_max_age(), None);
+        assert_eq!(config.cache_layout(), CacheLayout::Archive);
     }
 }
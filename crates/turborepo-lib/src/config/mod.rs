@@ -3,7 +3,12 @@ mod file;
 mod override_env;
 mod turbo_json;
 
-use std::{collections::HashMap, ffi::OsString, io};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsString,
+    io,
+    time::Duration,
+};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use convert_case::{Case, Casing};
@@ -13,20 +18,20 @@ use file::{AuthFile, ConfigFile};
 use merge::Merge;
 use miette::{Diagnostic, NamedSource, SourceSpan};
 use override_env::OverrideEnvVars;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use struct_iterable::Iterable;
 use thiserror::Error;
 use tracing::debug;
 use turbo_json::TurboJsonReader;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
-use turborepo_cache::CacheConfig;
+use turborepo_cache::{CacheConfig, CacheLayout, RemoteCacheBackend};
 use turborepo_errors::TURBO_SITE;
 use turborepo_repository::package_graph::PackageName;
 
 pub use crate::turbo_json::{RawTurboJson, UIMode};
 use crate::{
     cli::{EnvMode, LogOrder},
-    turbo_json::CONFIG_FILE,
+    turbo_json::{RemoteCacheBackendKind, CONFIG_FILE},
 };
 
 #[derive(Debug, Error, Diagnostic)]
@@ -120,9 +125,18 @@ pub enum Error {
         #[source_code]
         text: NamedSource,
     },
-    #[error("You can only extend from the root of the workspace.")]
+    #[error("You must extend from the root of the workspace first.")]
+    #[diagnostic(help("Add \"//\" as the first entry of `extends`."))]
     ExtendFromNonRoot {
-        #[label("non-root workspace found here")]
+        #[label("expected \"//\" here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
+    #[error("\"{name}\" is extended more than once.")]
+    DuplicateExtends {
+        name: String,
+        #[label("duplicate found here")]
         span: Option<SourceSpan>,
         #[source_code]
         text: NamedSource,
@@ -192,6 +206,47 @@ pub enum Error {
         #[source_code]
         text: NamedSource,
     },
+    #[error("`{field}` must be a duration such as \"30s\" or \"10m\".")]
+    InvalidTaskDuration {
+        field: &'static str,
+        #[label("invalid duration found here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
+    #[error("`timeout` must be greater than zero.")]
+    ZeroTaskTimeout {
+        #[label("zero timeout found here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
+    #[error("`cacheMaxSize` must be a size such as \"500MB\" or \"10GB\".")]
+    InvalidCacheMaxSize {
+        #[label("invalid size found here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
+    #[error("`cacheMaxAge` must be a duration such as \"12h\" or \"7d\".")]
+    InvalidCacheMaxAge {
+        #[label("invalid duration found here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
+    #[error("The `{backend}` remote cache backend requires `remoteCache.{field}` to be set.")]
+    MissingRemoteCacheBackendField {
+        backend: RemoteCacheBackendKind,
+        field: &'static str,
+    },
+    #[error("`cacheLayout` must be either \"archive\" or \"deduplicated\".")]
+    InvalidCacheLayout {
+        #[label("invalid layout found here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
     #[error("Cannot load turbo.json for {0} in single package mode.")]
     InvalidTurboJsonLoad(PackageName),
 }
@@ -249,6 +304,20 @@ pub struct ConfigurationOptions {
     pub(crate) scm_head: Option<String>,
     #[serde(rename = "cacheDir")]
     pub(crate) cache_dir: Option<Utf8PathBuf>,
+    // Limits are written as human readable values, e.g. `10GB` or `7d`
+    #[serde(default, deserialize_with = "deserialize_cache_max_size")]
+    pub(crate) cache_max_size: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_cache_max_age")]
+    pub(crate) cache_max_age: Option<Duration>,
+    #[serde(skip)]
+    pub(crate) cache_layout: Option<CacheLayout>,
+    // The remote cache backend can only be configured in turbo.json
+    #[serde(skip)]
+    pub(crate) remote_cache_backend: Option<RemoteCacheBackend>,
+    // Resource limits can only be configured in turbo.json and via the CLI
+    #[serde(skip)]
+    #[merge(strategy = merge_resource_limits)]
+    pub(crate) resource_limits: Option<BTreeMap<String, u32>>,
     // This is skipped as we never want this to be stored in a file
     #[serde(skip)]
     pub(crate) root_turbo_json_path: Option<AbsoluteSystemPathBuf>,
@@ -262,6 +331,40 @@ pub struct ConfigurationOptions {
     pub(crate) allow_no_turbo_json: Option<bool>,
 }
 
+fn deserialize_cache_max_size<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|max_size| turborepo_cache::config::parse_size(&max_size))
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_cache_max_age<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|max_age| turborepo_cache::config::parse_age(&max_age))
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+// Limits are combined across sources with the more significant source taking
+// precedence for resources that are limited by both.
+fn merge_resource_limits(
+    limits: &mut Option<BTreeMap<String, u32>>,
+    other: Option<BTreeMap<String, u32>>,
+) {
+    if let Some(other) = other {
+        let limits = limits.get_or_insert_with(BTreeMap::new);
+        for (resource, limit) in other {
+            limits.entry(resource).or_insert(limit);
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct TurborepoConfigBuilder {
     repo_root: AbsoluteSystemPathBuf,
@@ -376,6 +479,31 @@ impl ConfigurationOptions {
         self.cache
     }
 
+    /// Maximum size of the local cache in bytes, if bounded
+    pub fn cache_max_size(&self) -> Option<u64> {
+        self.cache_max_size
+    }
+
+    /// Maximum time since a local artifact was last used, if bounded
+    pub fn cache_max_age(&self) -> Option<Duration> {
+        self.cache_max_age
+    }
+
+    /// How new artifacts are written to the local cache
+    pub fn cache_layout(&self) -> CacheLayout {
+        self.cache_layout.unwrap_or_default()
+    }
+
+    /// Where remote cache artifacts are stored, defaulting to the Vercel API
+    pub fn remote_cache_backend(&self) -> RemoteCacheBackend {
+        self.remote_cache_backend.clone().unwrap_or_default()
+    }
+
+    /// The total amount of each named resource that running tasks can use
+    pub fn resource_limits(&self) -> BTreeMap<String, u32> {
+        self.resource_limits.clone().unwrap_or_default()
+    }
+
     pub fn force(&self) -> bool {
         self.force.unwrap_or_default()
     }
@@ -512,7 +640,7 @@ impl TurborepoConfigBuilder {
 
 #[cfg(test)]
 mod test {
-    use std::{collections::HashMap, ffi::OsString};
+    use std::{collections::HashMap, ffi::OsString, time::Duration};
 
     use tempfile::TempDir;
     use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
@@ -592,6 +720,41 @@ mod test {
         assert_eq!(config.spaces_id().unwrap(), "my-spaces-id");
     }
 
+    #[test]
+    fn test_global_config_cache_limits() {
+        let tmp_dir = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp_dir.path()).unwrap();
+        let global_config_path = repo_root.join_component("global.json");
+        global_config_path
+            .create_with_contents(r#"{"cacheMaxSize": "10GB", "cacheMaxAge": "7d"}"#)
+            .unwrap();
+
+        let builder = TurborepoConfigBuilder {
+            repo_root: repo_root.clone(),
+            override_config: Default::default(),
+            global_config_path: Some(global_config_path.clone()),
+            environment: Some(HashMap::default()),
+        };
+
+        let config = builder.build().unwrap();
+        assert_eq!(config.cache_max_size(), Some(10 * 1024 * 1024 * 1024));
+        assert_eq!(
+            config.cache_max_age(),
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+
+        global_config_path
+            .create_with_contents(r#"{"cacheMaxSize": "10 gigs"}"#)
+            .unwrap();
+        let builder = TurborepoConfigBuilder {
+            repo_root,
+            override_config: Default::default(),
+            global_config_path: Some(global_config_path),
+            environment: Some(HashMap::default()),
+        };
+        assert!(builder.build().is_err());
+    }
+
     #[test]
     fn test_turbo_json_remote_cache() {
         let tmp_dir = TempDir::new().unwrap();
@@ -637,4 +800,33 @@ mod test {
         assert!(!config.preflight());
         assert_eq!(config.timeout(), 123);
     }
+
+    #[test]
+    fn test_resource_limits_are_combined() {
+        let tmp_dir = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp_dir.path()).unwrap();
+        repo_root
+            .join_component("turbo.json")
+            .create_with_contents(
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "resourceLimits": { "memory": 8, "gpu": 1 }
+                }))
+                .unwrap(),
+            )
+            .unwrap();
+
+        let builder = TurborepoConfigBuilder {
+            repo_root,
+            override_config: ConfigurationOptions::default()
+                .with_resource_limits(Some([("memory".to_string(), 16)].into())),
+            global_config_path: None,
+            environment: Some(HashMap::default()),
+        };
+
+        let config = builder.build().unwrap();
+        assert_eq!(
+            config.resource_limits(),
+            [("gpu".to_string(), 1), ("memory".to_string(), 16)].into()
+        );
+    }
 }
@@ -1,8 +1,11 @@
+use std::str::FromStr;
+
 use camino::Utf8PathBuf;
 use turbopath::{AbsoluteSystemPath, RelativeUnixPath};
+use turborepo_cache::{CacheLayout, RemoteCacheBackend};
 
 use super::{ConfigurationOptions, Error, ResolvedConfigurationOptions};
-use crate::turbo_json::RawTurboJson;
+use crate::turbo_json::{RawRemoteCacheOptions, RawTurboJson, RemoteCacheBackendKind};
 
 pub struct TurboJsonReader<'a> {
     repo_root: &'a AbsoluteSystemPath,
@@ -17,7 +20,9 @@ impl<'a> TurboJsonReader<'a> {
         turbo_json: RawTurboJson,
     ) -> Result<ConfigurationOptions, Error> {
         let mut opts = if let Some(remote_cache_options) = &turbo_json.remote_cache {
-            remote_cache_options.into()
+            let mut opts: ConfigurationOptions = remote_cache_options.into();
+            opts.remote_cache_backend = Self::remote_cache_backend(remote_cache_options)?;
+            opts
         } else {
             ConfigurationOptions::default()
         };
@@ -36,6 +41,36 @@ impl<'a> TurboJsonReader<'a> {
             None
         };
 
+        let cache_max_size = turbo_json
+            .cache_max_size
+            .map(|max_size| {
+                turborepo_cache::config::parse_size(&max_size).map_err(|_| {
+                    let (span, text) = max_size.span_and_text("turbo.json");
+                    Error::InvalidCacheMaxSize { span, text }
+                })
+            })
+            .transpose()?;
+
+        let cache_max_age = turbo_json
+            .cache_max_age
+            .map(|max_age| {
+                turborepo_cache::config::parse_age(&max_age).map_err(|_| {
+                    let (span, text) = max_age.span_and_text("turbo.json");
+                    Error::InvalidCacheMaxAge { span, text }
+                })
+            })
+            .transpose()?;
+
+        let cache_layout = turbo_json
+            .cache_layout
+            .map(|layout| {
+                CacheLayout::from_str(&layout).map_err(|_| {
+                    let (span, text) = layout.span_and_text("turbo.json");
+                    Error::InvalidCacheLayout { span, text }
+                })
+            })
+            .transpose()?;
+
         // Don't allow token to be set for shared config.
         opts.token = None;
         opts.spaces_id = turbo_json
@@ -47,8 +82,38 @@ impl<'a> TurboJsonReader<'a> {
         opts.daemon = turbo_json.daemon.map(|daemon| *daemon.as_inner());
         opts.env_mode = turbo_json.env_mode;
         opts.cache_dir = cache_dir;
+        opts.cache_max_size = cache_max_size;
+        opts.cache_max_age = cache_max_age;
+        opts.cache_layout = cache_layout;
+        opts.resource_limits = turbo_json.resource_limits;
         Ok(opts)
     }
+
+    fn remote_cache_backend(
+        remote_cache_options: &RawRemoteCacheOptions,
+    ) -> Result<Option<RemoteCacheBackend>, Error> {
+        let Some(backend) = remote_cache_options.backend else {
+            return Ok(None);
+        };
+        let require = |field: &'static str, value: &Option<String>| {
+            value
+                .clone()
+                .ok_or(Error::MissingRemoteCacheBackendField { backend, field })
+        };
+
+        Ok(Some(match backend {
+            RemoteCacheBackendKind::Vercel => RemoteCacheBackend::Vercel,
+            RemoteCacheBackendKind::S3 => RemoteCacheBackend::S3 {
+                bucket: require("bucket", &remote_cache_options.bucket)?,
+                region: require("region", &remote_cache_options.region)?,
+                endpoint: remote_cache_options.url.clone(),
+                prefix: remote_cache_options.prefix.clone(),
+            },
+            RemoteCacheBackendKind::Http => RemoteCacheBackend::Http {
+                url: require("url", &remote_cache_options.url)?,
+            },
+        }))
+    }
 }
 
 impl<'a> ResolvedConfigurationOptions for TurboJsonReader<'a> {
@@ -168,4 +233,109 @@ mod test {
         assert!(config.signature());
         assert!(config.preflight());
     }
+
+    #[test]
+    fn test_remote_cache_backend() {
+        let turbo_json = RawTurboJson::parse(
+            &serde_json::to_string_pretty(&json!({
+                "remoteCache": {
+                    "backend": "s3",
+                    "bucket": "turbo-cache",
+                    "region": "us-east-1",
+                    "url": "http://localhost:9000",
+                    "prefix": "artifacts/"
+                }
+            }))
+            .unwrap(),
+            "junk",
+        )
+        .unwrap();
+        let config = TurboJsonReader::turbo_json_to_config_options(turbo_json).unwrap();
+        assert_eq!(
+            config.remote_cache_backend(),
+            RemoteCacheBackend::S3 {
+                bucket: "turbo-cache".to_string(),
+                region: "us-east-1".to_string(),
+                endpoint: Some("http://localhost:9000".to_string()),
+                prefix: Some("artifacts/".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_remote_cache_backend_missing_field() {
+        let turbo_json = RawTurboJson::parse(
+            &serde_json::to_string_pretty(&json!({
+                "remoteCache": {
+                    "backend": "http"
+                }
+            }))
+            .unwrap(),
+            "junk",
+        )
+        .unwrap();
+        let err = TurboJsonReader::turbo_json_to_config_options(turbo_json).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::MissingRemoteCacheBackendField { field: "url", .. }
+        ));
+    }
+
+    #[test]
+    fn test_cache_limits() {
+        let turbo_json = RawTurboJson::parse(
+            &serde_json::to_string_pretty(&json!({
+                "cacheMaxSize": "5GB",
+                "cacheMaxAge": "14d"
+            }))
+            .unwrap(),
+            "junk",
+        )
+        .unwrap();
+        let config = TurboJsonReader::turbo_json_to_config_options(turbo_json).unwrap();
+        assert_eq!(config.cache_max_size(), Some(5 * 1024 * 1024 * 1024));
+        assert_eq!(
+            config.cache_max_age(),
+            Some(std::time::Duration::from_secs(14 * 24 * 60 * 60))
+        );
+    }
+
+    #[test]
+    fn test_cache_layout() {
+        let turbo_json = RawTurboJson::parse(
+            &serde_json::to_string_pretty(&json!({
+                "cacheLayout": "deduplicated"
+            }))
+            .unwrap(),
+            "junk",
+        )
+        .unwrap();
+        let config = TurboJsonReader::turbo_json_to_config_options(turbo_json).unwrap();
+        assert_eq!(config.cache_layout(), CacheLayout::Deduplicated);
+
+        let turbo_json = RawTurboJson::parse(
+            &serde_json::to_string_pretty(&json!({
+                "cacheLayout": "hardlinks"
+            }))
+            .unwrap(),
+            "junk",
+        )
+        .unwrap();
+        let err = TurboJsonReader::turbo_json_to_config_options(turbo_json).unwrap_err();
+        assert!(matches!(err, Error::InvalidCacheLayout { .. }));
+    }
+
+    #[test]
+    fn test_invalid_cache_limits() {
+        let turbo_json = RawTurboJson::parse(
+            &serde_json::to_string_pretty(&json!({
+                "cacheMaxSize": "lots"
+            }))
+            .unwrap(),
+            "junk",
+        )
+        .unwrap();
+        let err = TurboJsonReader::turbo_json_to_config_options(turbo_json).unwrap_err();
+        assert!(matches!(err, Error::InvalidCacheMaxSize { .. }));
+    }
 }
@@ -84,6 +84,42 @@ pub enum Error {
     },
     #[error(transparent)]
     Graph(#[from] graph::Error),
+    #[error("Could not find package \"{name}\" referenced in `extends`")]
+    MissingExtendedPackage {
+        #[label("package not found")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+        name: String,
+    },
+    #[error("Could not find a turbo.json in \"{name}\" to extend from")]
+    MissingExtendedTurboJson {
+        #[label("extended here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+        name: String,
+    },
+    #[error(
+        "Could not find \"{task_name}\" in root turbo.json, \"{package}\" or the packages it \
+         extends"
+    )]
+    MissingExtendedTask {
+        #[label("none of these packages define the task")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+        task_name: String,
+        package: String,
+    },
+    #[error("Cyclic `extends` found: {cycle}")]
+    CyclicExtends {
+        #[label("cycle closed here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+        cycle: String,
+    },
     #[error("Invalid task name: {reason}")]
     InvalidTaskName {
         #[label]
@@ -239,7 +275,7 @@ impl<'a> EngineBuilder<'a> {
                 .task_id()
                 .unwrap_or_else(|| TaskId::new(workspace.as_ref(), task.task()));
 
-            if Self::has_task_definition(&mut turbo_json_loader, workspace, task, &task_id)? {
+            if self.has_task_definition(&mut turbo_json_loader, workspace, task, &task_id)? {
                 missing_tasks.remove(task.as_inner());
 
                 // Even if a task definition was found, we _only_ want to add it as an entry
@@ -441,6 +477,7 @@ impl<'a> EngineBuilder<'a> {
     // Helper methods used when building the engine
 
     fn has_task_definition(
+        &self,
         loader: &mut TurboJsonLoader,
         workspace: &PackageName,
         task_name: &TaskName<'static>,
@@ -461,10 +498,12 @@ impl<'a> EngineBuilder<'a> {
 
         let Some(turbo_json) = turbo_json else {
             // If there was no turbo.json in the workspace, fallback to the root turbo.json
-            return Self::has_task_definition(loader, &PackageName::Root, task_name, task_id);
+            return self.has_task_definition(loader, &PackageName::Root, task_name, task_id);
         };
 
         let task_id_as_name = task_id.as_task_name();
+        let is_task_package = workspace == &PackageName::from(task_id.package());
+        let has_extended_packages = turbo_json.extends.len() > 1;
         if
         // See if pkg#task is defined e.g. `docs#build`. This can only happen in root turbo.json
         turbo_json.tasks.contains_key(&task_id_as_name)
@@ -474,14 +513,120 @@ impl<'a> EngineBuilder<'a> {
             // If user provided a task id, then we see if the task is defined
             // e.g. `docs#build` should resolve if there's a `build` in root turbo.json or docs workspace level turbo.json
             || (matches!(workspace, PackageName::Root) && turbo_json.tasks.contains_key(&TaskName::from(task_name.task())))
-            || (workspace == &PackageName::from(task_id.package()) && turbo_json.tasks.contains_key(&TaskName::from(task_name.task())))
+            || (is_task_package && turbo_json.tasks.contains_key(&TaskName::from(task_name.task())))
         {
-            Ok(true)
-        } else if !matches!(workspace, PackageName::Root) {
-            Self::has_task_definition(loader, &PackageName::Root, task_name, task_id)
-        } else {
-            Ok(false)
+            return Ok(true);
+        }
+
+        if matches!(workspace, PackageName::Root) {
+            return Ok(false);
+        }
+
+        // See if the task is defined by a package that this workspace extends
+        if is_task_package && has_extended_packages {
+            for extended in self.extended_packages(loader, workspace)? {
+                if loader
+                    .load(&extended)?
+                    .tasks
+                    .contains_key(&TaskName::from(task_name.task()))
+                {
+                    return Ok(true);
+                }
+            }
+        }
+
+        self.has_task_definition(loader, &PackageName::Root, task_name, task_id)
+    }
+
+    /// Returns the packages that a workspace's turbo.json extends, not
+    /// including the root, in the order that their task definitions should be
+    /// applied. Packages come after the packages they extend, in the order
+    /// they're listed. A package that's reached more than once is only applied
+    /// at the last place it's reached, so explicitly listing a package after
+    /// one that already extends it applies it on top.
+    fn extended_packages(
+        &self,
+        turbo_json_loader: &mut TurboJsonLoader,
+        workspace: &PackageName,
+    ) -> Result<Vec<PackageName>, Error> {
+        let mut extended = Vec::new();
+        if !matches!(workspace, PackageName::Root) {
+            self.collect_extended_packages(
+                turbo_json_loader,
+                workspace,
+                &mut vec![workspace.clone()],
+                &mut extended,
+            )?;
         }
+        Ok(extended)
+    }
+
+    fn collect_extended_packages(
+        &self,
+        turbo_json_loader: &mut TurboJsonLoader,
+        workspace: &PackageName,
+        stack: &mut Vec<PackageName>,
+        extended: &mut Vec<PackageName>,
+    ) -> Result<(), Error> {
+        let turbo_json = match turbo_json_loader.load(workspace) {
+            Ok(turbo_json) => turbo_json,
+            Err(config::Error::NoTurboJSON) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let validation_errors =
+            turbo_json.validate(&[validate_no_package_task_syntax, validate_extends]);
+        if !validation_errors.is_empty() {
+            return Err(Error::Validation {
+                errors: validation_errors,
+            });
+        }
+
+        // The first entry has been validated to be the root
+        let parents = turbo_json
+            .extends
+            .iter()
+            .skip(1)
+            .cloned()
+            .collect::<Vec<_>>();
+        for parent in parents {
+            let package = PackageName::from(parent.as_str());
+            if let Some(position) = stack.iter().position(|p| p == &package) {
+                let cycle = stack[position..]
+                    .iter()
+                    .chain(std::iter::once(&package))
+                    .join(" -> ");
+                let (span, text) = parent.span_and_text("turbo.json");
+                return Err(Error::CyclicExtends { span, text, cycle });
+            }
+            if self.package_graph.package_info(&package).is_none() {
+                let (span, text) = parent.span_and_text("turbo.json");
+                return Err(Error::MissingExtendedPackage {
+                    span,
+                    text,
+                    name: package.to_string(),
+                });
+            }
+            match turbo_json_loader.load(&package) {
+                Ok(_) => (),
+                Err(config::Error::NoTurboJSON) => {
+                    let (span, text) = parent.span_and_text("turbo.json");
+                    return Err(Error::MissingExtendedTurboJson {
+                        span,
+                        text,
+                        name: package.to_string(),
+                    });
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            stack.push(package.clone());
+            self.collect_extended_packages(turbo_json_loader, &package, stack, extended)?;
+            stack.pop();
+            extended.retain(|p| p != &package);
+            extended.push(package);
+        }
+
+        Ok(())
     }
 
     fn task_definition(
@@ -528,16 +673,18 @@ impl<'a> EngineBuilder<'a> {
         }
 
         if task_id.package() != ROOT_PKG_NAME {
-            match turbo_json_loader.load(&PackageName::from(task_id.package())) {
-                Ok(workspace_json) => {
-                    let validation_errors = workspace_json
-                        .validate(&[validate_no_package_task_syntax, validate_extends]);
-                    if !validation_errors.is_empty() {
-                        return Err(Error::Validation {
-                            errors: validation_errors,
-                        });
-                    }
+            let workspace = PackageName::from(task_id.package());
+            // This also validates the turbo.json of the workspace and every
+            // package it extends
+            for extended in self.extended_packages(turbo_json_loader, &workspace)? {
+                if let Some(extended_def) = turbo_json_loader.load(&extended)?.tasks.get(task_name)
+                {
+                    task_definitions.push(extended_def.value.clone());
+                }
+            }
 
+            match turbo_json_loader.load(&workspace) {
+                Ok(workspace_json) => {
                     if let Some(workspace_def) = workspace_json.tasks.get(task_name) {
                         task_definitions.push(workspace_def.value.clone());
                     }
@@ -550,6 +697,21 @@ impl<'a> EngineBuilder<'a> {
         }
 
         if task_definitions.is_empty() && self.should_validate_engine {
+            let workspace = PackageName::from(task_id.package());
+            if let Some(workspace_json) = turbo_json_loader
+                .load(&workspace)
+                .ok()
+                .filter(|workspace_json| workspace_json.extends.len() > 1)
+            {
+                let (span, text) = workspace_json.extends.span_and_text("turbo.json");
+                return Err(Error::MissingExtendedTask {
+                    span,
+                    text,
+                    task_name: task_name.to_string(),
+                    package: workspace.to_string(),
+                });
+            }
+
             let (span, text) = task_id.span_and_text("turbo.json");
             return Err(Error::MissingPackageTask {
                 span,
@@ -725,6 +887,9 @@ mod test {
     #[test_case(PackageName::from("b"), "c#curse", "c#curse", true ; "non-workspace root defined task")]
     #[test_case(PackageName::from("b"), "b#special", "b#special", true ; "workspace defined task")]
     #[test_case(PackageName::from("c"), "b#special", "b#special", false ; "non-workspace defined task")]
+    #[test_case(PackageName::from("d"), "lint", "d#lint", true ; "task in extended package")]
+    #[test_case(PackageName::from("d"), "d#lint", "d#lint", true ; "workspace task in extended package")]
+    #[test_case(PackageName::from("a"), "lint", "a#lint", false ; "task in unrelated package")]
     fn test_task_definition(
         workspace: PackageName,
         task_name: &'static str,
@@ -752,16 +917,45 @@ mod test {
                     }
                 })),
             ),
+            (
+                PackageName::from("config"),
+                turbo_json(json!({
+                    "extends": ["//"],
+                    "tasks": {
+                        "lint": {},
+                    }
+                })),
+            ),
+            (
+                PackageName::from("d"),
+                turbo_json(json!({
+                    "extends": ["//", "config"],
+                })),
+            ),
         ]
         .into_iter()
         .collect();
+        let repo_root_dir = TempDir::with_prefix("repo").unwrap();
+        let repo_root = AbsoluteSystemPathBuf::new(repo_root_dir.path().to_str().unwrap()).unwrap();
+        let package_graph = mock_package_graph(
+            &repo_root,
+            package_jsons! {
+                repo_root,
+                "a" => [],
+                "b" => [],
+                "c" => [],
+                "d" => [],
+                "config" => []
+            },
+        );
         let mut loader = TurboJsonLoader::noop(turbo_jsons);
+        let builder = EngineBuilder::new(&repo_root, &package_graph, loader.clone(), false);
         let task_name = TaskName::from(task_name);
         let task_id = TaskId::try_from(task_id).unwrap();
 
-        let has_def =
-            EngineBuilder::has_task_definition(&mut loader, &workspace, &task_name, &task_id)
-                .unwrap();
+        let has_def = builder
+            .has_task_definition(&mut loader, &workspace, &task_name, &task_id)
+            .unwrap();
         assert_eq!(has_def, expected);
     }
 
@@ -1478,4 +1672,200 @@ mod test {
             .unwrap();
         assert_snapshot!(msg);
     }
+
+    #[test]
+    fn test_extends_shared_config() {
+        let repo_root_dir = TempDir::with_prefix("repo").unwrap();
+        let repo_root = AbsoluteSystemPathBuf::new(repo_root_dir.path().to_str().unwrap()).unwrap();
+        let package_graph = mock_package_graph(
+            &repo_root,
+            package_jsons! {
+                repo_root,
+                "web" => [],
+                "base-config" => [],
+                "next-config" => []
+            },
+        );
+        let turbo_jsons = vec![
+            (
+                PackageName::Root,
+                turbo_json(json!({
+                    "tasks": {
+                        "build": { "outputs": ["dist/**"], "inputs": ["root"] },
+                    }
+                })),
+            ),
+            (
+                PackageName::from("base-config"),
+                turbo_json(json!({
+                    "extends": ["//"],
+                    "tasks": {
+                        "build": { "inputs": ["base"], "env": ["BASE"], "cache": false },
+                    }
+                })),
+            ),
+            (
+                PackageName::from("next-config"),
+                turbo_json(json!({
+                    "extends": ["//", "base-config"],
+                    "tasks": {
+                        "build": { "outputs": [".next/**"], "cache": true },
+                        "typecheck": {},
+                    }
+                })),
+            ),
+            (
+                PackageName::from("web"),
+                turbo_json(json!({
+                    "extends": ["//", "next-config", "base-config"],
+                    "tasks": {
+                        "build": { "env": ["WEB"] },
+                    }
+                })),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let loader = TurboJsonLoader::noop(turbo_jsons);
+        let engine = EngineBuilder::new(&repo_root, &package_graph, loader, false)
+            .with_tasks(vec![
+                Spanned::new(TaskName::from("build")),
+                Spanned::new(TaskName::from("typecheck")),
+            ])
+            .with_workspaces(vec![PackageName::from("web")])
+            .build()
+            .unwrap();
+
+        let build = engine
+            .task_definition(&TaskId::new("web", "build"))
+            .unwrap();
+        assert_eq!(build.outputs.inclusions, [".next/**"]);
+        assert_eq!(build.inputs, ["base"]);
+        assert_eq!(build.env, ["WEB"]);
+        // base-config is listed after next-config, so it's applied last even though
+        // next-config extends it as well
+        assert!(!build.cache);
+        assert!(engine
+            .task_definition(&TaskId::new("web", "typecheck"))
+            .is_some());
+    }
+
+    #[test_case(
+        json!({ "extends": ["//", "web"] }),
+        "Cyclic `extends` found: web -> web"
+        ; "self"
+    )]
+    #[test_case(
+        json!({ "extends": ["//", "shared"] }),
+        "Cyclic `extends` found: web -> shared -> web"
+        ; "cycle"
+    )]
+    #[test_case(
+        json!({ "extends": ["//", "missing"] }),
+        "Could not find package \"missing\" referenced in `extends`"
+        ; "missing package"
+    )]
+    #[test_case(
+        json!({ "extends": ["//", "no-config"] }),
+        "Could not find a turbo.json in \"no-config\" to extend from"
+        ; "missing turbo json"
+    )]
+    #[test_case(
+        json!({ "extends": ["//", "config"], "tasks": { "build": { "dependsOn": ["lint"] } } }),
+        "Could not find \"lint\" in root turbo.json, \"web\" or the packages it extends"
+        ; "missing task"
+    )]
+    fn test_extends_errors(extends: serde_json::Value, expected: &str) {
+        let repo_root_dir = TempDir::with_prefix("repo").unwrap();
+        let repo_root = AbsoluteSystemPathBuf::new(repo_root_dir.path().to_str().unwrap()).unwrap();
+        let package_graph = mock_package_graph(
+            &repo_root,
+            package_jsons! {
+                repo_root,
+                "web" => [],
+                "shared" => [],
+                "config" => [],
+                "no-config" => []
+            },
+        );
+        let turbo_jsons = vec![
+            (
+                PackageName::Root,
+                turbo_json(json!({
+                    "tasks": {
+                        "build": {},
+                    }
+                })),
+            ),
+            (
+                PackageName::from("shared"),
+                turbo_json(json!({
+                    "extends": ["//", "web"],
+                })),
+            ),
+            (
+                PackageName::from("config"),
+                turbo_json(json!({
+                    "extends": ["//"],
+                })),
+            ),
+            (PackageName::from("web"), turbo_json(extends)),
+        ]
+        .into_iter()
+        .collect();
+        let loader = TurboJsonLoader::noop(turbo_jsons);
+        let engine = EngineBuilder::new(&repo_root, &package_graph, loader, false)
+            .with_tasks(vec![Spanned::new(TaskName::from("build"))])
+            .with_workspaces(vec![PackageName::from("web")])
+            .build();
+        assert_eq!(engine.unwrap_err().to_string(), expected);
+    }
+
+    #[test]
+    fn test_split_persistent_tasks() {
+        let repo_root_dir = TempDir::with_prefix("repo").unwrap();
+        let repo_root = AbsoluteSystemPathBuf::new(repo_root_dir.path().to_str().unwrap()).unwrap();
+        let package_graph = mock_package_graph(
+            &repo_root,
+            package_jsons! {
+                repo_root,
+                "ui" => [],
+                "web" => ["ui"]
+            },
+        );
+        let turbo_jsons = vec![(
+            PackageName::Root,
+            turbo_json(json!({
+                "tasks": {
+                    "build": { "dependsOn": ["^build"] },
+                    "dev": { "dependsOn": ["^build"], "persistent": true, "interruptible": true },
+                }
+            })),
+        )]
+        .into_iter()
+        .collect();
+        let loader = TurboJsonLoader::noop(turbo_jsons);
+        let engine = EngineBuilder::new(&repo_root, &package_graph, loader, false)
+            .with_tasks(vec![
+                Spanned::new(TaskName::from("build")),
+                Spanned::new(TaskName::from("dev")),
+            ])
+            .with_workspaces(vec![PackageName::from("ui"), PackageName::from("web")])
+            .build()
+            .unwrap();
+
+        let non_persistent = engine.create_engine_for_non_persistent_tasks();
+        let expected = deps! {
+            "ui#build" => ["___ROOT___"],
+            "web#build" => ["ui#build"]
+        };
+        assert_eq!(all_dependencies(&non_persistent), expected);
+
+        let persistent =
+            engine.create_engine_for_persistent_task(&TaskId::try_from("web#dev").unwrap());
+        let expected = deps! {
+            "web#dev" => ["___ROOT___"]
+        };
+        assert_eq!(all_dependencies(&persistent), expected);
+    }
 }
@@ -0,0 +1,252 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    time::Duration,
+};
+
+use petgraph::{algo::toposort, Direction};
+
+use super::{Engine, TaskNode};
+use crate::run::task_id::TaskId;
+
+// Estimate used for every task if we have no history for any task in the graph
+const DEFAULT_ESTIMATE: Duration = Duration::from_secs(1);
+
+/// Estimated durations for each task in the graph along with the length of
+/// the longest chain of work that depends on each task.
+///
+/// Tasks with a longer remaining critical path should be started first as
+/// delaying them delays the entire run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CriticalPaths {
+    estimates: HashMap<TaskId<'static>, Duration>,
+    remaining: HashMap<TaskId<'static>, Duration>,
+}
+
+/// A task along with the estimates that determined its place in the schedule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledTask {
+    pub task_id: TaskId<'static>,
+    pub estimated_duration: Duration,
+    pub critical_path: Duration,
+}
+
+impl CriticalPaths {
+    /// The estimated duration of the task itself
+    pub fn estimate(&self, task_id: &TaskId) -> Option<Duration> {
+        self.estimates.get(task_id).copied()
+    }
+
+    /// The estimated duration of the task plus the longest chain of tasks
+    /// that depend on it
+    pub fn remaining(&self, task_id: &TaskId) -> Option<Duration> {
+        self.remaining.get(task_id).copied()
+    }
+}
+
+impl Engine {
+    /// Computes the remaining critical path for every task in the engine.
+    /// Tasks without a known duration are estimated using the mean of the
+    /// known durations.
+    pub fn critical_paths(&self, durations: &HashMap<TaskId<'static>, Duration>) -> CriticalPaths {
+        let known = self
+            .task_lookup
+            .keys()
+            .filter_map(|task_id| durations.get(task_id))
+            .copied()
+            .collect::<Vec<_>>();
+        let fallback = match known.len() {
+            0 => DEFAULT_ESTIMATE,
+            n => known.iter().sum::<Duration>() / n as u32,
+        };
+
+        let estimates = self
+            .task_lookup
+            .keys()
+            .map(|task_id| {
+                let estimate = durations.get(task_id).copied().unwrap_or(fallback);
+                (task_id.clone(), estimate)
+            })
+            .collect::<HashMap<_, _>>();
+
+        // Edges point from a task to its dependencies so a topological sort
+        // visits every dependent before the tasks it depends on.
+        let Ok(order) = toposort(&self.task_graph, None) else {
+            // Cycles are reported when the engine is validated, there isn't
+            // a meaningful critical path to compute.
+            return CriticalPaths::default();
+        };
+
+        let mut remaining_by_index = HashMap::new();
+        let mut remaining = HashMap::new();
+        for index in order {
+            let TaskNode::Task(task_id) = &self.task_graph[index] else {
+                continue;
+            };
+            let longest_dependent = self
+                .task_graph
+                .neighbors_directed(index, Direction::Incoming)
+                .filter_map(|dependent| remaining_by_index.get(&dependent))
+                .max()
+                .copied()
+                .unwrap_or_default();
+            let task_remaining = estimates[task_id] + longest_dependent;
+            remaining_by_index.insert(index, task_remaining);
+            remaining.insert(task_id.clone(), task_remaining);
+        }
+
+        CriticalPaths {
+            estimates,
+            remaining,
+        }
+    }
+
+    /// Produces the order tasks would be started in if only one task could
+    /// run at a time. Ready tasks are ordered by their remaining critical
+    /// path with ties broken by task id.
+    pub fn schedule(&self, critical_paths: &CriticalPaths) -> Vec<ScheduledTask> {
+        let mut pending_dependencies = HashMap::new();
+        let mut ready = BinaryHeap::new();
+        for (task_id, index) in &self.task_lookup {
+            let dependencies = self
+                .task_graph
+                .neighbors_directed(*index, Direction::Outgoing)
+                .filter(|dependency| *dependency != self.root_index)
+                .count();
+            if dependencies == 0 {
+                let priority = critical_paths.remaining(task_id).unwrap_or_default();
+                ready.push((priority, Reverse(task_id.clone()), *index));
+            } else {
+                pending_dependencies.insert(*index, dependencies);
+            }
+        }
+
+        let mut schedule = Vec::with_capacity(self.task_lookup.len());
+        while let Some((priority, Reverse(task_id), index)) = ready.pop() {
+            for dependent in self
+                .task_graph
+                .neighbors_directed(index, Direction::Incoming)
+            {
+                let Some(count) = pending_dependencies.get_mut(&dependent) else {
+                    continue;
+                };
+                *count -= 1;
+                if *count == 0 {
+                    if let TaskNode::Task(dependent_id) = &self.task_graph[dependent] {
+                        let priority = critical_paths.remaining(dependent_id).unwrap_or_default();
+                        ready.push((priority, Reverse(dependent_id.clone()), dependent));
+                    }
+                }
+            }
+            schedule.push(ScheduledTask {
+                estimated_duration: critical_paths.estimate(&task_id).unwrap_or_default(),
+                critical_path: priority,
+                task_id,
+            });
+        }
+
+        schedule
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::task_graph::TaskDefinition;
+
+    // test depends on build which depends on codegen, lint has no dependencies
+    fn engine() -> Engine {
+        let mut engine = Engine::new();
+        let codegen = engine.get_index(&TaskId::new("a", "codegen"));
+        let build = engine.get_index(&TaskId::new("a", "build"));
+        let test = engine.get_index(&TaskId::new("a", "test"));
+        engine.get_index(&TaskId::new("b", "lint"));
+        engine.task_graph.add_edge(build, codegen, ());
+        engine.task_graph.add_edge(test, build, ());
+        engine.connect_to_root(&TaskId::new("a", "codegen"));
+        engine.connect_to_root(&TaskId::new("b", "lint"));
+        for task in ["a#codegen", "a#build", "a#test", "b#lint"] {
+            engine.add_definition(TaskId::try_from(task).unwrap(), TaskDefinition::default());
+        }
+        engine.seal()
+    }
+
+    fn durations(durations: &[(&'static str, u64)]) -> HashMap<TaskId<'static>, Duration> {
+        durations
+            .iter()
+            .map(|(task, secs)| (TaskId::try_from(*task).unwrap(), Duration::from_secs(*secs)))
+            .collect()
+    }
+
+    #[test]
+    fn test_remaining_includes_dependents() {
+        let engine = engine();
+        let critical_paths = engine.critical_paths(&durations(&[
+            ("a#codegen", 1),
+            ("a#build", 10),
+            ("a#test", 5),
+            ("b#lint", 12),
+        ]));
+        let remaining = |task| {
+            critical_paths
+                .remaining(&TaskId::try_from(task).unwrap())
+                .unwrap()
+                .as_secs()
+        };
+        assert_eq!(remaining("a#test"), 5);
+        assert_eq!(remaining("a#build"), 15);
+        assert_eq!(remaining("a#codegen"), 16);
+        assert_eq!(remaining("b#lint"), 12);
+    }
+
+    #[test]
+    fn test_unknown_tasks_use_mean_estimate() {
+        let engine = engine();
+        let critical_paths = engine.critical_paths(&durations(&[("a#build", 10), ("a#test", 2)]));
+        let estimate = |task| {
+            critical_paths
+                .estimate(&TaskId::try_from(task).unwrap())
+                .unwrap()
+                .as_secs()
+        };
+        assert_eq!(estimate("a#codegen"), 6);
+        assert_eq!(estimate("b#lint"), 6);
+    }
+
+    #[test]
+    fn test_schedule_prefers_longest_path() {
+        let engine = engine();
+        let critical_paths = engine.critical_paths(&durations(&[
+            ("a#codegen", 1),
+            ("a#build", 10),
+            ("a#test", 5),
+            ("b#lint", 12),
+        ]));
+        let schedule = engine
+            .schedule(&critical_paths)
+            .into_iter()
+            .map(|task| task.task_id.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(schedule, ["a#codegen", "a#build", "b#lint", "a#test"]);
+    }
+
+    #[test]
+    fn test_schedule_without_history_is_stable() {
+        let engine = engine();
+        let critical_paths = engine.critical_paths(&HashMap::new());
+        let schedule = engine
+            .schedule(&critical_paths)
+            .into_iter()
+            .map(|task| (task.task_id.to_string(), task.critical_path.as_secs()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            schedule,
+            [
+                ("a#codegen".to_string(), 3),
+                ("a#build".to_string(), 2),
+                ("a#test".to_string(), 1),
+                ("b#lint".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -1,12 +1,17 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use futures::{stream::FuturesUnordered, StreamExt};
-use tokio::sync::{mpsc, oneshot, Semaphore};
+use tokio::sync::{mpsc, oneshot};
 use tracing::log::debug;
 use turborepo_graph_utils::Walker;
 
-use super::{Engine, TaskNode};
-use crate::run::task_id::TaskId;
+use super::{CriticalPaths, Engine, TaskNode};
+use crate::{run::task_id::TaskId, task_graph::TaskDefinition};
 
 pub struct Message<T, U> {
     pub info: T,
@@ -18,10 +23,12 @@ pub struct Message<T, U> {
 type VisitorData = TaskId<'static>;
 type VisitorResult = Result<(), StopExecution>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExecutionOptions {
     parallel: bool,
     concurrency: usize,
+    critical_paths: Option<Arc<CriticalPaths>>,
+    resource_limits: BTreeMap<String, u32>,
 }
 
 impl ExecutionOptions {
@@ -29,8 +36,25 @@ impl ExecutionOptions {
         Self {
             parallel,
             concurrency,
+            critical_paths: None,
+            resource_limits: BTreeMap::new(),
         }
     }
+
+    /// When tasks are waiting for a concurrency slot, start the task with the
+    /// longest remaining critical path first instead of the first task that
+    /// became ready.
+    pub fn with_critical_paths(mut self, critical_paths: Arc<CriticalPaths>) -> Self {
+        self.critical_paths = Some(critical_paths);
+        self
+    }
+
+    /// Limit the total amount of each named resource that the `resources` of
+    /// running tasks can add up to. Resources without a limit are ignored.
+    pub fn with_resource_limits(mut self, resource_limits: BTreeMap<String, u32>) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -54,7 +78,7 @@ pub struct StopExecution;
 
 impl Engine {
     /// Execute a task graph by sending task ids to the visitor
-    /// while respecting concurrency limits.
+    /// while respecting concurrency and resource limits.
     /// The visitor is expected to handle any error handling on its end.
     /// We enforce this by only allowing the returning of a sentinel error
     /// type which will stop any further execution of tasks.
@@ -72,8 +96,18 @@ impl Engine {
         let ExecutionOptions {
             parallel,
             concurrency,
+            critical_paths,
+            resource_limits,
         } = options;
-        let sema = Arc::new(Semaphore::new(concurrency));
+        if !parallel {
+            self.warn_about_ignored_resources(&resource_limits);
+        }
+        let resource_limits = Arc::new(resource_limits);
+        let sema = Arc::new(PrioritySemaphore::new(
+            std::iter::once(u32::try_from(concurrency).unwrap_or(u32::MAX))
+                .chain(resource_limits.values().copied())
+                .collect(),
+        ));
         let mut tasks: FuturesUnordered<tokio::task::JoinHandle<Result<(), ExecuteError>>> =
             FuturesUnordered::new();
 
@@ -85,6 +119,8 @@ impl Engine {
             let sema = sema.clone();
             let walker = walker.clone();
             let this = self.clone();
+            let critical_paths = critical_paths.clone();
+            let resource_limits = resource_limits.clone();
 
             tasks.push(tokio::spawn(async move {
                 let TaskNode::Task(task_id) = this
@@ -104,10 +140,17 @@ impl Engine {
 
                 // Acquire the semaphore unless parallel
                 let _permit = match parallel {
-                    false => Some(sema.acquire().await.expect(
-                        "Graph concurrency semaphore closed while tasks are still attempting to \
-                         acquire permits",
-                    )),
+                    false => {
+                        // Without critical paths every task has the same priority so permits
+                        // are handed out in the order tasks became ready.
+                        let priority = critical_paths
+                            .as_ref()
+                            .and_then(|critical_paths| critical_paths.remaining(task_id))
+                            .unwrap_or_default();
+                        let request =
+                            resource_request(this.task_definition(task_id), &resource_limits);
+                        Some(sema.acquire(priority, request).await)
+                    }
                     true => None,
                 };
 
@@ -143,6 +186,250 @@ impl Engine {
 
         Ok(())
     }
+
+    /// Resources without a limit don't restrict which tasks can run together,
+    /// which is likely a typo in the task's `resources` or a missing limit.
+    /// Resources of persistent tasks are ignored too, see `resource_request`.
+    fn warn_about_ignored_resources(&self, resource_limits: &BTreeMap<String, u32>) {
+        let unlimited = self
+            .task_definitions
+            .values()
+            .flat_map(|definition| definition.resources.keys())
+            .filter(|resource| !resource_limits.contains_key(*resource))
+            .collect::<BTreeSet<_>>();
+        for resource in unlimited {
+            tracing::warn!(
+                "Tasks use the resource `{resource}`, but no limit is set for it so it will be \
+                 ignored. Set one with `resourceLimits` in turbo.json or `--resource-limit`."
+            );
+        }
+
+        let persistent = self
+            .task_definitions
+            .iter()
+            .filter(|(_, definition)| definition.persistent && !definition.resources.is_empty())
+            .map(|(task_id, _)| task_id)
+            .collect::<BTreeSet<_>>();
+        for task_id in persistent {
+            tracing::warn!(
+                "`{task_id}` is persistent, so the `resources` it uses will be ignored. \
+                 Persistent tasks never finish, so they would never give them back."
+            );
+        }
+    }
+}
+
+/// The amount of each resource a task needs before it can start, starting with
+/// its slot in the task concurrency.
+///
+/// A task that needs more of a resource than the limit allows gets all of it
+/// and runs once nothing else is using it. Persistent tasks don't use any
+/// resources, since they would hold them forever and block every task waiting
+/// for them.
+fn resource_request(
+    definition: Option<&TaskDefinition>,
+    resource_limits: &BTreeMap<String, u32>,
+) -> Vec<u32> {
+    let resources = definition
+        .filter(|definition| !definition.persistent)
+        .map(|definition| &definition.resources);
+    std::iter::once(1)
+        .chain(resource_limits.iter().map(|(resource, limit)| {
+            resources
+                .and_then(|resources| resources.get(resource))
+                .map_or(0, |amount| (*amount).min(*limit))
+        }))
+        .collect()
+}
+
+/// A semaphore over several counted resources, the first of which is the
+/// number of tasks that can run concurrently. A permit is granted once every
+/// resource it requests is available. Waiters are served strictly in order of
+/// priority, with equal priorities served in the order they started waiting. A
+/// waiter that doesn't fit blocks the ones behind it, so that tasks requesting
+/// lots of a resource aren't starved by smaller ones.
+struct PrioritySemaphore {
+    state: Mutex<SemaphoreState>,
+}
+
+struct SemaphoreState {
+    available: Vec<u32>,
+    next_seq: u64,
+    waiters: BinaryHeap<Waiter>,
+}
+
+struct Waiter {
+    priority: Duration,
+    seq: u64,
+    request: Vec<u32>,
+    notify: oneshot::Sender<()>,
+}
+
+struct PriorityPermit {
+    semaphore: Arc<PrioritySemaphore>,
+    request: Vec<u32>,
+}
+
+impl SemaphoreState {
+    fn try_take(&mut self, request: &[u32]) -> bool {
+        let fits = self
+            .available
+            .iter()
+            .zip(request)
+            .all(|(available, requested)| available >= requested);
+        if fits {
+            for (available, requested) in self.available.iter_mut().zip(request) {
+                *available -= requested;
+            }
+        }
+        fits
+    }
+
+    fn give_back(&mut self, request: &[u32]) {
+        for (available, requested) in self.available.iter_mut().zip(request) {
+            *available += requested;
+        }
+    }
+
+    /// Hands out permits to waiters in order until the next one doesn't fit.
+    fn wake_waiters(&mut self) {
+        while let Some(waiter) = self.waiters.pop() {
+            if !self.try_take(&waiter.request) {
+                self.waiters.push(waiter);
+                break;
+            }
+            if waiter.notify.send(()).is_err() {
+                // The waiter is no longer listening so its resources are free
+                self.give_back(&waiter.request);
+            }
+        }
+    }
+}
+
+impl PrioritySemaphore {
+    fn new(limits: Vec<u32>) -> Self {
+        Self {
+            state: Mutex::new(SemaphoreState {
+                available: limits,
+                next_seq: 0,
+                waiters: BinaryHeap::new(),
+            }),
+        }
+    }
+
+    /// Waits until every requested resource is available. Requests must not
+    /// exceed the limits the semaphore was created with.
+    ///
+    /// This is cancellation safe: if the returned future is dropped, it stops
+    /// waiting, and any permit that was already handed to it is released.
+    async fn acquire(self: &Arc<Self>, priority: Duration, request: Vec<u32>) -> PriorityPermit {
+        let pending = {
+            let mut state = self.state.lock().expect("semaphore mutex poisoned");
+            if state.waiters.is_empty() && state.try_take(&request) {
+                None
+            } else {
+                let (notify, notified) = oneshot::channel();
+                let seq = state.next_seq;
+                state.next_seq += 1;
+                state.waiters.push(Waiter {
+                    priority,
+                    seq,
+                    request: request.clone(),
+                    notify,
+                });
+                // The new waiter may have a higher priority than the ones already waiting
+                state.wake_waiters();
+                Some(PendingPermit {
+                    semaphore: self,
+                    seq,
+                    request: &request,
+                    notified: Some(notified),
+                })
+            }
+        };
+        if let Some(mut pending) = pending {
+            let notified = pending.notified.as_mut().expect("permit is still pending");
+            // The sender is only dropped after a permit has been handed to us
+            notified
+                .await
+                .expect("semaphore dropped while tasks are still waiting for permits");
+            pending.notified = None;
+        }
+        PriorityPermit {
+            semaphore: self.clone(),
+            request,
+        }
+    }
+
+    fn release(&self, request: &[u32]) {
+        let mut state = self.state.lock().expect("semaphore mutex poisoned");
+        state.give_back(request);
+        state.wake_waiters();
+    }
+}
+
+/// A permit that's being waited for. If it's dropped before the permit is
+/// received, the waiter is removed, or the permit is released if it was handed
+/// out in the meantime.
+struct PendingPermit<'a> {
+    semaphore: &'a PrioritySemaphore,
+    seq: u64,
+    request: &'a [u32],
+    notified: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for PendingPermit<'_> {
+    fn drop(&mut self) {
+        let Some(mut notified) = self.notified.take() else {
+            return;
+        };
+        let mut state = self
+            .semaphore
+            .state
+            .lock()
+            .expect("semaphore mutex poisoned");
+        // Permits are handed out while holding the lock, so this can't race with
+        // `release`
+        if notified.try_recv().is_ok() {
+            state.give_back(self.request);
+        } else {
+            state.waiters.retain(|waiter| waiter.seq != self.seq);
+        }
+        // Either way, the waiters behind this one may now be able to run
+        state.wake_waiters();
+    }
+}
+
+impl Drop for PriorityPermit {
+    fn drop(&mut self) {
+        self.semaphore.release(&self.request);
+    }
+}
+
+impl Waiter {
+    fn key(&self) -> (Duration, Reverse<u64>) {
+        (self.priority, Reverse(self.seq))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
 }
 
 impl<T, U> Message<T, U> {
@@ -151,3 +438,134 @@ impl<T, U> Message<T, U> {
         (Self { info, callback }, receiver)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_permits_go_to_highest_priority() {
+        let sema = Arc::new(PrioritySemaphore::new(vec![1]));
+        let permit = sema.acquire(Duration::ZERO, vec![1]).await;
+
+        let (order_tx, mut order_rx) = mpsc::unbounded_channel();
+        let mut waiters = Vec::new();
+        for (name, priority) in [("low", 1), ("high", 10), ("medium", 5), ("also low", 1)] {
+            let sema = sema.clone();
+            let order_tx = order_tx.clone();
+            waiters.push(tokio::spawn(async move {
+                let _permit = sema.acquire(Duration::from_secs(priority), vec![1]).await;
+                order_tx.send(name).unwrap();
+            }));
+            // Make sure each waiter is queued before the next one
+            while sema.state.lock().unwrap().waiters.len() < waiters.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+        drop(order_tx);
+        drop(permit);
+
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+        let mut order = Vec::new();
+        while let Some(name) = order_rx.recv().await {
+            order.push(name);
+        }
+        assert_eq!(order, ["high", "medium", "low", "also low"]);
+    }
+
+    #[tokio::test]
+    async fn test_permits_are_returned() {
+        let sema = Arc::new(PrioritySemaphore::new(vec![2, 8]));
+        let first = sema.acquire(Duration::ZERO, vec![1, 3]).await;
+        let second = sema.acquire(Duration::ZERO, vec![1, 5]).await;
+        assert_eq!(sema.state.lock().unwrap().available, [0, 0]);
+        drop(first);
+        drop(second);
+        assert_eq!(sema.state.lock().unwrap().available, [2, 8]);
+    }
+
+    #[tokio::test]
+    async fn test_waiters_are_not_served_past_a_blocked_waiter() {
+        // 4 concurrent tasks and 8 units of memory
+        let sema = Arc::new(PrioritySemaphore::new(vec![4, 8]));
+        let heavy = sema.acquire(Duration::ZERO, vec![1, 6]).await;
+
+        let mut waiters = Vec::new();
+        for (priority, request) in [(10, vec![1, 6]), (0, vec![1, 1])] {
+            let sema = sema.clone();
+            waiters.push(tokio::spawn(async move {
+                let _permit = sema.acquire(Duration::from_secs(priority), request).await;
+            }));
+            while sema.state.lock().unwrap().waiters.len() < waiters.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        // The light task would fit, but it waits behind the heavy one so the heavy
+        // task can't be starved
+        assert_eq!(sema.state.lock().unwrap().available, [3, 2]);
+        drop(heavy);
+
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+        assert_eq!(sema.state.lock().unwrap().available, [4, 8]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiters_release_permits() {
+        let sema = Arc::new(PrioritySemaphore::new(vec![1]));
+        let permit = sema.acquire(Duration::ZERO, vec![1]).await;
+
+        // Cancelled while still waiting
+        let waiter = {
+            let sema = sema.clone();
+            tokio::spawn(async move {
+                let _permit = sema.acquire(Duration::ZERO, vec![1]).await;
+            })
+        };
+        while sema.state.lock().unwrap().waiters.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        waiter.abort();
+        assert!(waiter.await.unwrap_err().is_cancelled());
+        assert!(sema.state.lock().unwrap().waiters.is_empty());
+
+        // Cancelled after the permit was handed out, but before it was received
+        let waiter = {
+            let sema = sema.clone();
+            tokio::spawn(async move {
+                let _permit = sema.acquire(Duration::ZERO, vec![1]).await;
+            })
+        };
+        while sema.state.lock().unwrap().waiters.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        drop(permit);
+        assert_eq!(sema.state.lock().unwrap().available, [0]);
+        waiter.abort();
+        assert!(waiter.await.unwrap_err().is_cancelled());
+        assert_eq!(sema.state.lock().unwrap().available, [1]);
+    }
+
+    #[test]
+    fn test_resource_request() {
+        let limits = BTreeMap::from([("gpu".to_string(), 1), ("memory".to_string(), 8)]);
+        let definition = TaskDefinition {
+            resources: BTreeMap::from([("memory".to_string(), 16), ("disk".to_string(), 2)]),
+            ..Default::default()
+        };
+        // Unlimited resources are ignored and amounts are capped at the limit
+        assert_eq!(resource_request(Some(&definition), &limits), [1, 0, 8]);
+        assert_eq!(resource_request(None, &limits), [1, 0, 0]);
+
+        // Persistent tasks only take up a concurrency slot
+        let persistent = TaskDefinition {
+            persistent: true,
+            ..definition
+        };
+        assert_eq!(resource_request(Some(&persistent), &limits), [1, 0, 0]);
+    }
+}
@@ -1,4 +1,5 @@
 mod builder;
+mod critical_path;
 mod execute;
 
 mod dot;
@@ -10,6 +11,7 @@ use std::{
 };
 
 pub use builder::{EngineBuilder, Error as BuilderError};
+pub use critical_path::{CriticalPaths, ScheduledTask};
 pub use execute::{ExecuteError, ExecutionOptions, Message, StopExecution};
 use miette::{Diagnostic, NamedSource, SourceSpan};
 use petgraph::Graph;
@@ -62,6 +64,9 @@ pub struct Engine<S = Built> {
     task_definitions: HashMap<TaskId<'static>, TaskDefinition>,
     task_locations: HashMap<TaskId<'static>, Spanned<()>>,
     package_tasks: HashMap<PackageName, Vec<petgraph::graph::NodeIndex>>,
+    // Dependencies that were filtered out of the graph because they run
+    // separately. They still count towards the task's hash.
+    removed_dependencies: HashMap<TaskId<'static>, HashSet<TaskNode>>,
     pub(crate) has_non_interruptible_tasks: bool,
 }
 
@@ -77,6 +82,7 @@ impl Engine<Building> {
             task_definitions: HashMap::default(),
             task_locations: HashMap::default(),
             package_tasks: HashMap::default(),
+            removed_dependencies: HashMap::default(),
             has_non_interruptible_tasks: false,
         }
     }
@@ -99,6 +105,13 @@ impl Engine<Building> {
         self.task_graph.add_edge(source, self.root_index, ());
     }
 
+    #[cfg(test)]
+    pub fn add_dependency(&mut self, task_id: &TaskId<'static>, dependency: &TaskId<'static>) {
+        let source = self.get_index(task_id);
+        let target = self.get_index(dependency);
+        self.task_graph.add_edge(source, target, ());
+    }
+
     pub fn add_definition(
         &mut self,
         task_id: TaskId<'static>,
@@ -132,6 +145,7 @@ impl Engine<Building> {
             task_definitions,
             task_locations,
             package_tasks,
+            removed_dependencies,
             has_non_interruptible_tasks,
             ..
         } = self;
@@ -143,6 +157,7 @@ impl Engine<Building> {
             task_definitions,
             task_locations,
             package_tasks,
+            removed_dependencies,
             has_non_interruptible_tasks,
         }
     }
@@ -224,11 +239,68 @@ impl Engine<Built> {
             task_definitions: self.task_definitions.clone(),
             task_locations: self.task_locations.clone(),
             package_tasks: self.package_tasks.clone(),
+            removed_dependencies: self.removed_dependencies.clone(),
             // We've filtered out persistent tasks
             has_non_interruptible_tasks: false,
         }
     }
 
+    /// Creates an `Engine` that only contains the given tasks and the tasks
+    /// that they depend on
+    pub fn create_engine_for_tasks<'a>(
+        &self,
+        tasks: impl IntoIterator<Item = &'a TaskId<'static>>,
+    ) -> Engine<Built> {
+        let mut included_tasks = HashSet::new();
+        for task in tasks {
+            included_tasks.extend(self.transitive_dependencies(task));
+        }
+
+        let new_graph = self.task_graph.filter_map(
+            |_, node| match node {
+                TaskNode::Root => Some(node.clone()),
+                TaskNode::Task(_) => included_tasks.contains(node).then(|| node.clone()),
+            },
+            |_, _| Some(()),
+        );
+
+        let root_index = new_graph
+            .node_indices()
+            .find(|index| new_graph[*index] == TaskNode::Root)
+            .expect("root node should be present");
+
+        let task_lookup: HashMap<_, _> = new_graph
+            .node_indices()
+            .filter_map(|index| {
+                let task = new_graph
+                    .node_weight(index)
+                    .expect("node index should be present");
+                match task {
+                    TaskNode::Root => None,
+                    TaskNode::Task(task) => Some((task.clone(), index)),
+                }
+            })
+            .collect();
+
+        let has_non_interruptible_tasks = task_lookup.keys().any(|task| {
+            self.task_definitions
+                .get(task)
+                .is_some_and(|def| def.persistent && !def.interruptible)
+        });
+
+        Engine {
+            marker: std::marker::PhantomData,
+            root_index,
+            task_graph: new_graph,
+            task_lookup,
+            task_definitions: self.task_definitions.clone(),
+            task_locations: self.task_locations.clone(),
+            package_tasks: self.package_tasks.clone(),
+            removed_dependencies: self.removed_dependencies.clone(),
+            has_non_interruptible_tasks,
+        }
+    }
+
     /// Creates an `Engine` with only interruptible tasks, i.e. non-persistent
     /// tasks and persistent tasks that are allowed to be interrupted
     pub fn create_engine_for_interruptible_tasks(&self) -> Engine<Built> {
@@ -277,6 +349,7 @@ impl Engine<Built> {
             task_definitions: self.task_definitions.clone(),
             task_locations: self.task_locations.clone(),
             package_tasks: self.package_tasks.clone(),
+            removed_dependencies: self.removed_dependencies.clone(),
             has_non_interruptible_tasks: false,
         }
     }
@@ -338,14 +411,128 @@ impl Engine<Built> {
             task_definitions: self.task_definitions.clone(),
             task_locations: self.task_locations.clone(),
             package_tasks: self.package_tasks.clone(),
+            removed_dependencies: self.removed_dependencies.clone(),
             has_non_interruptible_tasks: true,
         }
     }
 
+    /// Creates an `Engine` with only the tasks that are not persistent
+    pub fn create_engine_for_non_persistent_tasks(&self) -> Engine<Built> {
+        self.filter_tasks(|_, def| !def.persistent, false)
+    }
+
+    /// Creates an `Engine` with only the given task. The task is connected
+    /// directly to the root, so the tasks that it depends on need to be run
+    /// beforehand and their hashes passed in to hash the task.
+    pub fn create_engine_for_persistent_task(&self, task_id: &TaskId) -> Engine<Built> {
+        self.filter_tasks(|task, _| task == task_id, true)
+    }
+
+    /// Creates an `Engine` with only the tasks that `include` returns true for,
+    /// optionally connecting each of them directly to the root. Dependencies
+    /// that are left out are kept track of for hashing.
+    fn filter_tasks(
+        &self,
+        include: impl Fn(&TaskId<'static>, &TaskDefinition) -> bool,
+        connect_to_root: bool,
+    ) -> Engine<Built> {
+        let mut has_non_interruptible_tasks = false;
+        let mut new_graph = self.task_graph.filter_map(
+            |node_idx, node| match &self.task_graph[node_idx] {
+                TaskNode::Task(task) => {
+                    let def = self
+                        .task_definitions
+                        .get(task)
+                        .expect("task should have definition");
+
+                    if include(task, def) {
+                        has_non_interruptible_tasks |= def.persistent && !def.interruptible;
+                        Some(node.clone())
+                    } else {
+                        None
+                    }
+                }
+                TaskNode::Root => Some(node.clone()),
+            },
+            |_, _| Some(()),
+        );
+
+        let root_index = new_graph
+            .node_indices()
+            .find(|index| new_graph[*index] == TaskNode::Root)
+            .expect("root node should be present");
+
+        if connect_to_root {
+            for index in new_graph.node_indices() {
+                if new_graph[index] == TaskNode::Root {
+                    continue;
+                }
+
+                new_graph.add_edge(index, root_index, ());
+            }
+        }
+
+        let task_lookup: HashMap<_, _> = new_graph
+            .node_indices()
+            .filter_map(|index| {
+                let task = new_graph
+                    .node_weight(index)
+                    .expect("node index should be present");
+                match task {
+                    TaskNode::Root => None,
+                    TaskNode::Task(task) => Some((task.clone(), index)),
+                }
+            })
+            .collect();
+
+        let mut removed_dependencies = self.removed_dependencies.clone();
+        for (task, index) in &task_lookup {
+            let dependencies = new_graph
+                .neighbors_directed(*index, petgraph::Direction::Outgoing)
+                .map(|index| &new_graph[index])
+                .collect::<HashSet<_>>();
+            let removed = self
+                .dependencies(task)
+                .into_iter()
+                .flatten()
+                .filter(|dependency| !dependencies.contains(dependency))
+                .cloned()
+                .collect::<HashSet<_>>();
+            if !removed.is_empty() {
+                removed_dependencies
+                    .entry(task.clone())
+                    .or_default()
+                    .extend(removed);
+            }
+        }
+
+        Engine {
+            marker: std::marker::PhantomData,
+            root_index,
+            task_graph: new_graph,
+            task_lookup,
+            task_definitions: self.task_definitions.clone(),
+            task_locations: self.task_locations.clone(),
+            package_tasks: self.package_tasks.clone(),
+            removed_dependencies,
+            has_non_interruptible_tasks,
+        }
+    }
+
     pub fn dependencies(&self, task_id: &TaskId) -> Option<HashSet<&TaskNode>> {
         self.neighbors(task_id, petgraph::Direction::Outgoing)
     }
 
+    /// The dependencies whose hashes go into the task's hash. Unlike
+    /// `dependencies`, this includes the ones filtered out of this engine.
+    pub fn hash_dependencies(&self, task_id: &TaskId) -> Option<HashSet<&TaskNode>> {
+        let mut dependencies = self.dependencies(task_id)?;
+        if let Some(removed) = self.removed_dependencies.get(task_id) {
+            dependencies.extend(removed);
+        }
+        Some(dependencies)
+    }
+
     pub fn dependents(&self, task_id: &TaskId) -> Option<HashSet<&TaskNode>> {
         self.neighbors(task_id, petgraph::Direction::Incoming)
     }
@@ -816,4 +1003,108 @@ mod test {
         assert!(tasks.contains(&&TaskNode::Task(a_dev_task_id)));
         assert!(tasks.contains(&&TaskNode::Task(b_build_task_id)));
     }
+
+    #[tokio::test]
+    async fn test_create_engine_for_tasks() {
+        let mut engine = Engine::new();
+
+        // `b#test` depends on `b#build`, which depends on `a#build`
+        let a_build_task_id = TaskId::new("a", "build");
+        let b_build_task_id = TaskId::new("b", "build");
+        let b_test_task_id = TaskId::new("b", "test");
+        let a_test_task_id = TaskId::new("a", "test");
+
+        let a_build_idx = engine.get_index(&a_build_task_id);
+        let b_build_idx = engine.get_index(&b_build_task_id);
+        let b_test_idx = engine.get_index(&b_test_task_id);
+        engine.get_index(&a_test_task_id);
+        for task_id in [
+            &a_build_task_id,
+            &b_build_task_id,
+            &b_test_task_id,
+            &a_test_task_id,
+        ] {
+            engine.add_definition(task_id.clone(), TaskDefinition::default());
+        }
+        engine.task_graph.add_edge(b_build_idx, a_build_idx, ());
+        engine.task_graph.add_edge(b_test_idx, b_build_idx, ());
+        engine.connect_to_root(&a_build_task_id);
+
+        let engine = engine.seal();
+        let pruned = engine.create_engine_for_tasks([&b_test_task_id]);
+
+        let tasks: HashSet<_> = pruned.tasks().collect();
+        assert_eq!(
+            tasks,
+            [
+                TaskNode::Root,
+                TaskNode::Task(a_build_task_id.clone()),
+                TaskNode::Task(b_build_task_id.clone()),
+                TaskNode::Task(b_test_task_id.clone()),
+            ]
+            .iter()
+            .collect()
+        );
+        assert_eq!(
+            pruned.dependencies(&b_test_task_id),
+            Some([&TaskNode::Task(b_build_task_id)].into_iter().collect())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_engine_for_persistent_task() {
+        let mut engine = Engine::new();
+
+        // `a#dev` depends on `a#build` and `b#build`
+        let a_build_task_id = TaskId::new("a", "build");
+        let b_build_task_id = TaskId::new("b", "build");
+        let a_dev_task_id = TaskId::new("a", "dev");
+
+        let a_build_idx = engine.get_index(&a_build_task_id);
+        let b_build_idx = engine.get_index(&b_build_task_id);
+        let a_dev_idx = engine.get_index(&a_dev_task_id);
+        for task_id in [&a_build_task_id, &b_build_task_id] {
+            engine.add_definition(task_id.clone(), TaskDefinition::default());
+        }
+        engine.add_definition(
+            a_dev_task_id.clone(),
+            TaskDefinition {
+                persistent: true,
+                interruptible: true,
+                ..Default::default()
+            },
+        );
+        engine.task_graph.add_edge(a_dev_idx, a_build_idx, ());
+        engine.task_graph.add_edge(a_dev_idx, b_build_idx, ());
+        engine.connect_to_root(&a_build_task_id);
+        engine.connect_to_root(&b_build_task_id);
+
+        let engine = engine.seal();
+        let persistent = engine.create_engine_for_persistent_task(&a_dev_task_id);
+
+        let tasks: HashSet<_> = persistent.tasks().collect();
+        assert_eq!(
+            tasks,
+            [TaskNode::Root, TaskNode::Task(a_dev_task_id.clone())]
+                .iter()
+                .collect()
+        );
+        assert_eq!(
+            persistent.dependencies(&a_dev_task_id),
+            Some([&TaskNode::Root].into_iter().collect())
+        );
+        // The dependencies run separately, but still go into the task's hash
+        assert_eq!(
+            persistent.hash_dependencies(&a_dev_task_id),
+            Some(
+                [
+                    &TaskNode::Root,
+                    &TaskNode::Task(a_build_task_id),
+                    &TaskNode::Task(b_build_task_id)
+                ]
+                .into_iter()
+                .collect()
+            )
+        );
+    }
 }
@@ -21,6 +21,7 @@ mod diagnostics;
 mod engine;
 
 mod boundaries;
+mod cache_server;
 mod framework;
 mod gitignore;
 pub(crate) mod globwatcher;
@@ -1,11 +1,11 @@
-use std::backtrace;
+use std::{backtrace, collections::BTreeMap, time::Duration};
 
 use camino::Utf8PathBuf;
 use serde::Serialize;
 use thiserror::Error;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 use turborepo_api_client::APIAuth;
-use turborepo_cache::{CacheOpts, RemoteCacheOpts};
+use turborepo_cache::{CacheLimits, CacheOpts, RemoteCacheOpts};
 
 use crate::{
     cli::{
@@ -84,7 +84,9 @@ impl Opts {
             cmd.push_str(pattern);
         }
 
-        if self.scope_opts.affected_range.is_some() {
+        if self.scope_opts.affected_by_inputs {
+            cmd.push_str(" --affected-by-inputs");
+        } else if self.scope_opts.affected_range.is_some() {
             cmd.push_str(" --affected");
         }
 
@@ -96,6 +98,11 @@ impl Opts {
             cmd.push_str(" --continue");
         }
 
+        if let Some(task_timeout) = self.run_opts.task_timeout {
+            cmd.push_str(" --task-timeout=");
+            cmd.push_str(&humantime::format_duration(task_timeout).to_string());
+        }
+
         if let Some(dry) = self.run_opts.dry_run {
             match dry {
                 DryRunMode::Json => cmd.push_str(" --dry=json"),
@@ -148,7 +155,7 @@ impl Opts {
 
                 (&Box::new(execution_args), &Box::default())
             }
-            Some(Command::Boundaries { filter }) => {
+            Some(Command::Boundaries { filter, .. }) => {
                 let execution_args = ExecutionArgs {
                     filter: filter.clone(),
                     ..Default::default()
@@ -215,11 +222,16 @@ pub struct RunOpts {
     pub(crate) cache_dir: Utf8PathBuf,
     // Whether or not to infer the framework for each workspace.
     pub(crate) framework_inference: bool,
+    // Whether or not to prioritise tasks by their estimated critical path.
+    pub(crate) critical_path: bool,
+    pub(crate) resource_limits: BTreeMap<String, u32>,
     pub profile: Option<String>,
     pub(crate) continue_on_error: bool,
+    pub(crate) task_timeout: Option<Duration>,
     pub(crate) pass_through_args: Vec<String>,
     pub(crate) only: bool,
     pub(crate) dry_run: Option<DryRunMode>,
+    pub(crate) explain: Option<ExplainOpts>,
     pub graph: Option<GraphOpts>,
     pub(crate) daemon: Option<bool>,
     pub(crate) single_package: bool,
@@ -269,6 +281,14 @@ pub enum GraphOpts {
     File(String),
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum ExplainOpts {
+    /// Compare against the inputs of each task's last run
+    LastRun,
+    /// Compare against the inputs saved for a task hash
+    Hash(String),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum ResolvedLogOrder {
     Stream,
@@ -314,6 +334,11 @@ impl<'a> TryFrom<OptsInputs<'a>> for RunOpts {
             f => GraphOpts::File(f.to_string()),
         });
 
+        let explain = inputs.run_args.explain.as_deref().map(|hash| match hash {
+            "" => ExplainOpts::LastRun,
+            hash => ExplainOpts::Hash(hash.to_string()),
+        });
+
         let (is_github_actions, log_order, log_prefix) = match inputs.config.log_order() {
             LogOrder::Auto if turborepo_ci::Vendor::get_constant() == Some("GITHUB_ACTIONS") => (
                 true,
@@ -348,16 +373,20 @@ impl<'a> TryFrom<OptsInputs<'a>> for RunOpts {
                 .clone()
                 .or(inputs.config.spaces_id().map(|s| s.to_owned())),
             framework_inference: inputs.execution_args.framework_inference,
+            critical_path: inputs.execution_args.critical_path,
+            resource_limits: inputs.config.resource_limits(),
             concurrency,
             parallel: inputs.run_args.parallel,
             profile: inputs.run_args.profile.clone(),
             continue_on_error: inputs.execution_args.continue_execution,
+            task_timeout: inputs.execution_args.task_timeout,
             pass_through_args: inputs.execution_args.pass_through_args.clone(),
             only: inputs.execution_args.only,
             daemon: inputs.config.daemon(),
             single_package: inputs.execution_args.single_package,
             graph,
             dry_run: inputs.run_args.dry_run,
+            explain,
             env_mode: inputs.config.env_mode(),
             cache_dir: inputs.config.cache_dir().into(),
             is_github_actions,
@@ -403,6 +432,8 @@ pub struct ScopeOpts {
     pub global_deps: Vec<String>,
     pub filter_patterns: Vec<String>,
     pub affected_range: Option<(Option<String>, Option<String>)>,
+    // Only run tasks whose inputs changed within `affected_range`
+    pub affected_by_inputs: bool,
 }
 
 impl<'a> TryFrom<OptsInputs<'a>> for ScopeOpts {
@@ -416,7 +447,8 @@ impl<'a> TryFrom<OptsInputs<'a>> for ScopeOpts {
             .map(AnchoredSystemPathBuf::from_raw)
             .transpose()?;
 
-        let affected_range = inputs.execution_args.affected.then(|| {
+        let affected_by_inputs = inputs.execution_args.affected_by_inputs;
+        let affected_range = (inputs.execution_args.affected || affected_by_inputs).then(|| {
             let scm_base = inputs.config.scm_base();
             let scm_head = inputs.config.scm_head();
             (
@@ -429,6 +461,7 @@ impl<'a> TryFrom<OptsInputs<'a>> for ScopeOpts {
             global_deps: inputs.execution_args.global_deps.clone(),
             pkg_inference_root,
             affected_range,
+            affected_by_inputs,
             filter_patterns: inputs.execution_args.filter.clone(),
         })
     }
@@ -490,11 +523,15 @@ impl<'a> TryFrom<OptsInputs<'a>> for CacheOpts {
             cache.remote.write = false;
         }
 
-        if !is_linked {
+        let remote_cache_backend = inputs.config.remote_cache_backend();
+
+        // Self-hosted backends don't go through Vercel, so they don't require linking
+        if !is_linked && remote_cache_backend.is_vercel() {
             cache.remote.read = false;
             cache.remote.write = false;
         } else if let Some(false) = inputs.config.enabled {
-            // We're linked, but if the user has explicitly disabled remote cache
+            // We're linked (or self-hosted), but the user has explicitly disabled remote
+            // cache
             cache.remote.read = false;
             cache.remote.write = false;
         };
@@ -503,15 +540,16 @@ impl<'a> TryFrom<OptsInputs<'a>> for CacheOpts {
             cache.remote.write = false;
         }
 
-        // Note that we don't currently use the team_id value here. In the future, we
-        // should probably verify that we only use the signature value when the
-        // configured team_id matches the final resolved team_id.
-        let unused_remote_cache_opts_team_id =
-            inputs.config.team_id().map(|team_id| team_id.to_string());
+        // Note that the team_id value is only used to sign artifacts for self-hosted
+        // backends. In the future, we should probably verify that we only use the
+        // signature value when the configured team_id matches the final resolved
+        // team_id.
+        let remote_cache_opts_team_id = inputs.config.team_id().map(|team_id| team_id.to_string());
         let signature = inputs.config.signature();
         let remote_cache_opts = Some(RemoteCacheOpts::new(
-            unused_remote_cache_opts_team_id,
+            remote_cache_opts_team_id,
             signature,
+            remote_cache_backend,
         ));
 
         Ok(CacheOpts {
@@ -519,6 +557,11 @@ impl<'a> TryFrom<OptsInputs<'a>> for CacheOpts {
             cache,
             workers: inputs.run_args.cache_workers,
             remote_cache_opts,
+            local_limits: CacheLimits {
+                max_size: inputs.config.cache_max_size(),
+                max_age: inputs.config.cache_max_age(),
+            },
+            local_layout: inputs.config.cache_layout(),
         })
     }
 }
@@ -539,6 +582,8 @@ impl ScopeOpts {
 
 #[cfg(test)]
 mod test {
+    use std::{collections::BTreeMap, time::Duration};
+
     use clap::Parser;
     use itertools::Itertools;
     use serde_json::json;
@@ -566,6 +611,7 @@ mod test {
         pass_through_args: Vec<String>,
         parallel: bool,
         continue_on_error: bool,
+        task_timeout: Option<Duration>,
         dry_run: Option<DryRunMode>,
         affected: Option<(String, String)>,
     }
@@ -612,6 +658,15 @@ mod test {
             },
         "turbo run build --filter=my-app --parallel --continue"
     )]
+    #[test_case(
+        TestCaseOpts{
+            tasks: vec!["test".to_string()],
+            continue_on_error: true,
+            task_timeout: Some(Duration::from_secs(600)),
+            ..Default::default()
+            },
+        "turbo run test --continue --task-timeout=10m"
+    )]
     #[test_case(
         TestCaseOpts{
             filter_patterns: vec!["my-app".to_string()],
@@ -655,11 +710,15 @@ mod test {
             env_mode: crate::cli::EnvMode::Loose,
             cache_dir: camino::Utf8PathBuf::new(),
             framework_inference: true,
+            critical_path: true,
+            resource_limits: BTreeMap::new(),
             profile: None,
             continue_on_error: opts_input.continue_on_error,
+            task_timeout: opts_input.task_timeout,
             pass_through_args: opts_input.pass_through_args,
             only: opts_input.only,
             dry_run: opts_input.dry_run,
+            explain: None,
             graph: None,
             ui_mode: UIMode::Stream,
             single_package: false,
@@ -675,6 +734,8 @@ mod test {
             cache: Default::default(),
             workers: 0,
             remote_cache_opts: None,
+            local_limits: Default::default(),
+            local_layout: Default::default(),
         };
         let runcache_opts = RunCacheOpts::default();
         let scope_opts = ScopeOpts {
@@ -684,6 +745,7 @@ mod test {
             affected_range: opts_input
                 .affected
                 .map(|(base, head)| (Some(base), Some(head))),
+            affected_by_inputs: false,
         };
         let config = ConfigurationOptions::default();
         let root_turbo_json_path = config.root_turbo_json_path(&AbsoluteSystemPathBuf::default());
@@ -214,7 +214,7 @@ impl Subscriber {
             return false;
         }
 
-        tracing::warn!("hashes are the same, no need to rerun");
+        tracing::debug!("hashes are the same, no need to rerun");
 
         true
     }
@@ -344,8 +344,8 @@ impl Subscriber {
 
                 let changed_packages = change_mapper.changed_packages(changed_files.clone(), None);
 
-                tracing::warn!("changed_files: {:?}", changed_files);
-                tracing::warn!("changed_packages: {:?}", changed_packages);
+                tracing::debug!("changed_files: {:?}", changed_files);
+                tracing::debug!("changed_packages: {:?}", changed_packages);
 
                 match changed_packages {
                     Ok(PackageChanges::All(_)) => {
@@ -20,7 +20,7 @@ use std::{
 
 pub use command::Command;
 use futures::Future;
-use tokio::task::JoinSet;
+use tokio::{sync::watch, task::JoinSet};
 use tracing::{debug, trace};
 
 pub use self::child::{Child, ChildExit};
@@ -32,6 +32,9 @@ pub use self::child::{Child, ChildExit};
 #[derive(Debug, Clone)]
 pub struct ProcessManager {
     state: Arc<Mutex<ProcessManagerInner>>,
+    // Set once the manager starts closing, for anything that needs to stop
+    // waiting when it does
+    closing: Arc<watch::Sender<bool>>,
     use_pty: bool,
 }
 
@@ -57,6 +60,7 @@ impl ProcessManager {
                 children: Vec::new(),
                 size: None,
             })),
+            closing: Arc::new(watch::Sender::new(false)),
             use_pty,
         }
     }
@@ -120,6 +124,14 @@ impl ProcessManager {
         Some(child)
     }
 
+    /// Waits until the process manager starts closing, returning immediately
+    /// if it already has.
+    pub async fn closing(&self) {
+        let mut closing = self.closing.subscribe();
+        // The sender is owned by the manager, so it can't be dropped while we wait
+        let _ = closing.wait_for(|closing| *closing).await;
+    }
+
     /// Stop the process manager, closing all child processes. On posix
     /// systems this will send a SIGINT, and on windows it will just kill
     /// the process immediately.
@@ -151,6 +163,7 @@ impl ProcessManager {
         {
             let mut lock = self.state.lock().expect("not poisoned");
             lock.is_closing = true;
+            self.closing.send_replace(true);
             for child in lock.children.iter() {
                 let child = child.clone();
                 set.spawn(async move { callback(child).await });
@@ -235,6 +248,25 @@ mod test {
         assert_eq!(out, b"hello world\n");
     }
 
+    #[tokio::test]
+    async fn test_closing() {
+        let manager = ProcessManager::new(false);
+        let closing = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.closing().await }
+        });
+        manager.stop().await;
+        tokio::time::timeout(Duration::from_secs(1), closing)
+            .await
+            .expect("closing should resolve once the manager stops")
+            .unwrap();
+
+        // A manager that is already closed doesn't wait
+        tokio::time::timeout(Duration::from_secs(1), manager.closing())
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_multiple() {
         let manager = ProcessManager::new(false);
@@ -29,6 +29,27 @@ impl From<BoundariesDiagnostic> for Diagnostic {
                 import: Some(import),
                 reason: None,
             },
+            BoundariesDiagnostic::DeniedTag {
+                dependency,
+                reason,
+                span,
+                text,
+                ..
+            }
+            | BoundariesDiagnostic::TagNotAllowed {
+                dependency,
+                reason,
+                span,
+                text,
+                ..
+            } => Diagnostic {
+                message,
+                path: Some(text.name().to_string()),
+                start: Some(span.offset()),
+                end: Some(span.offset() + span.len()),
+                import: Some(dependency),
+                reason: Some(reason),
+            },
             BoundariesDiagnostic::ParseError(_, _) => Diagnostic {
                 message,
                 start: None,
@@ -1,8 +1,16 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
-use async_graphql::Object;
+use async_graphql::{Object, SimpleObject};
+use itertools::Itertools;
+use turborepo_repository::package_graph::PackageName;
 
-use crate::run::Run;
+use crate::{
+    query::{package::Package, Array, Error},
+    run::Run,
+};
 
 #[derive(Clone)]
 pub struct ExternalPackage {
@@ -30,4 +38,154 @@ impl ExternalPackage {
     async fn name(&self) -> String {
         self.human_name().to_string()
     }
+
+    /// The key of the package in the lockfile
+    async fn key(&self) -> String {
+        self.package.key.clone()
+    }
+
+    /// The version of the package that the lockfile resolves to
+    async fn version(&self) -> String {
+        self.package.version.clone()
+    }
+
+    /// The dependencies of the package as they are declared in the lockfile,
+    /// i.e. dependency names along with their version ranges
+    async fn dependencies(&self) -> Result<Array<DeclaredDependency>, Error> {
+        let Some(lockfile) = self.run.pkg_dep_graph().lockfile() else {
+            return Ok(Array::from(Vec::new()));
+        };
+
+        Ok(lockfile
+            .all_dependencies(&self.package.key)?
+            .unwrap_or_default()
+            .into_iter()
+            .sorted()
+            .map(|(name, specifier)| DeclaredDependency { name, specifier })
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct DeclaredDependency {
+    name: String,
+    /// The version range that the dependency was declared with
+    specifier: String,
+}
+
+/// An external dependency declared in a workspace's package.json
+#[derive(SimpleObject)]
+pub struct ExternalDependency {
+    name: String,
+    /// The version range declared in package.json
+    specifier: String,
+    /// The package that the lockfile resolves the dependency to. `null` if
+    /// there is no lockfile or the dependency isn't in it.
+    resolved: Option<ExternalPackage>,
+}
+
+/// A version of an external package along with the workspaces that depend on
+/// it directly
+#[derive(SimpleObject)]
+pub struct ExternalPackageVersion {
+    version: String,
+    package: ExternalPackage,
+    dependents: Array<Package>,
+}
+
+/// An external package and every version of it that workspaces in the
+/// repository depend on
+#[derive(SimpleObject)]
+pub struct ExternalPackageUsage {
+    name: String,
+    versions: Array<ExternalPackageVersion>,
+}
+
+/// The workspaces that depend on each version of an external package
+pub type PackageVersions = BTreeMap<turborepo_lockfiles::Package, BTreeSet<PackageName>>;
+
+/// Resolves the external dependencies declared in the package.json of
+/// `package` using the lockfile
+pub fn external_dependencies(
+    run: &Arc<Run>,
+    package: &PackageName,
+) -> Result<Array<ExternalDependency>, Error> {
+    let package_graph = run.pkg_dep_graph();
+    let package_info = package_graph
+        .package_info(package)
+        .ok_or_else(|| Error::PackageNotFound(package.clone()))?;
+    let workspace_path = package_info.package_path().to_unix();
+
+    package_info
+        .unresolved_external_dependencies
+        .iter()
+        .flatten()
+        .map(|(name, specifier)| {
+            let resolved = package_graph
+                .lockfile()
+                .map(|lockfile| lockfile.resolve_package(workspace_path.as_str(), name, specifier))
+                .transpose()?
+                .flatten()
+                .map(|package| ExternalPackage::new(run.clone(), package));
+
+            Ok::<_, Error>(ExternalDependency {
+                name: name.clone(),
+                specifier: specifier.clone(),
+                resolved,
+            })
+        })
+        .collect()
+}
+
+/// Finds the versions of every external package that workspaces depend on
+/// directly, along with the workspaces that depend on each version
+pub fn external_package_versions(
+    run: &Arc<Run>,
+) -> Result<BTreeMap<String, PackageVersions>, Error> {
+    let package_graph = run.pkg_dep_graph();
+    let lockfile = package_graph.lockfile().ok_or(Error::LockfileNotFound)?;
+
+    let mut versions: BTreeMap<String, PackageVersions> = BTreeMap::new();
+    for (package_name, package_info) in package_graph.packages() {
+        let workspace_path = package_info.package_path().to_unix();
+        for (name, specifier) in package_info
+            .unresolved_external_dependencies
+            .iter()
+            .flatten()
+        {
+            let Some(package) =
+                lockfile.resolve_package(workspace_path.as_str(), name, specifier)?
+            else {
+                continue;
+            };
+            versions
+                .entry(name.clone())
+                .or_default()
+                .entry(package)
+                .or_default()
+                .insert(package_name.clone());
+        }
+    }
+
+    Ok(versions)
+}
+
+impl ExternalPackageUsage {
+    pub fn new(run: &Arc<Run>, name: String, versions: PackageVersions) -> Result<Self, Error> {
+        let versions = versions
+            .into_iter()
+            .map(|(package, dependents)| {
+                Ok(ExternalPackageVersion {
+                    version: package.version.clone(),
+                    package: ExternalPackage::new(run.clone(), package),
+                    dependents: dependents
+                        .into_iter()
+                        .map(|dependent| Package::new(run.clone(), dependent))
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self { name, versions })
+    }
 }
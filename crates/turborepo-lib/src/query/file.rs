@@ -105,6 +105,13 @@ impl From<turbo_trace::TraceError> for Diagnostic {
                 file_path,
                 reason,
                 ..
+            }
+            | turbo_trace::TraceError::TemplateImport {
+                span,
+                text,
+                file_path,
+                reason,
+                ..
             } => {
                 let import = text
                     .read_span(&span, 1, 1)
@@ -166,6 +173,52 @@ impl From<ImportType> for turbo_trace::ImportTraceType {
     }
 }
 
+/// How to handle dynamic imports and requires of template strings with
+/// expressions, which can't be resolved to a single file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Enum)]
+pub enum TemplateImports {
+    /// Report the import as an error
+    Report,
+    /// Skip the import
+    Ignore,
+}
+
+impl From<TemplateImports> for turbo_trace::TemplateImports {
+    fn from(template_imports: TemplateImports) -> Self {
+        match template_imports {
+            TemplateImports::Report => turbo_trace::TemplateImports::Report,
+            TemplateImports::Ignore => turbo_trace::TemplateImports::Ignore,
+        }
+    }
+}
+
+/// The kind of a file, which determines how its imports are found.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Enum)]
+pub enum FileKind {
+    /// JavaScript or TypeScript
+    Script,
+    /// Vue, Svelte or MDX, where imports are found in the embedded scripts
+    Component,
+    /// CSS, Sass or Less, where `@import` and `url()` refer to other files
+    Style,
+    /// JSON, which doesn't import anything
+    Json,
+    /// Any other file, e.g. an image or a font, which doesn't import anything
+    Asset,
+}
+
+impl From<turbo_trace::FileKind> for FileKind {
+    fn from(kind: turbo_trace::FileKind) -> Self {
+        match kind {
+            turbo_trace::FileKind::Script => FileKind::Script,
+            turbo_trace::FileKind::Component => FileKind::Component,
+            turbo_trace::FileKind::Style => FileKind::Style,
+            turbo_trace::FileKind::Json => FileKind::Json,
+            turbo_trace::FileKind::Asset => FileKind::Asset,
+        }
+    }
+}
+
 #[Object]
 impl File {
     async fn contents(&self) -> Result<String, Error> {
@@ -185,11 +238,16 @@ impl File {
         Ok(self.path.to_string())
     }
 
+    async fn kind(&self) -> FileKind {
+        turbo_trace::FileKind::from_path(&self.path).into()
+    }
+
     async fn dependencies(
         &self,
         depth: Option<usize>,
         ts_config: Option<String>,
         import_type: Option<ImportType>,
+        template_imports: Option<TemplateImports>,
         emit_errors: Option<bool>,
     ) -> Result<TraceResult, Error> {
         let mut tracer = Tracer::new(
@@ -201,6 +259,9 @@ impl File {
         if let Some(import_type) = import_type {
             tracer.set_import_type(import_type.into());
         }
+        if let Some(template_imports) = template_imports {
+            tracer.set_template_imports(template_imports.into());
+        }
 
         let mut result = tracer.trace(depth).await;
         if emit_errors.unwrap_or(true) {
@@ -215,6 +276,7 @@ impl File {
         &self,
         ts_config: Option<String>,
         import_type: Option<ImportType>,
+        template_imports: Option<TemplateImports>,
     ) -> Result<TraceResult, Error> {
         let mut tracer = Tracer::new(
             self.run.repo_root().to_owned(),
@@ -225,6 +287,9 @@ impl File {
         if let Some(import_type) = import_type {
             tracer.set_import_type(import_type.into());
         }
+        if let Some(template_imports) = template_imports {
+            tracer.set_template_imports(template_imports.into());
+        }
 
         let mut result = tracer.reverse_trace().await;
         result.emit_errors();
@@ -0,0 +1,204 @@
+//! Keeps the run behind the query server up to date by rebuilding it whenever
+//! packages in the repository change. Changes come from the daemon, or from a
+//! file watcher in this process when the daemon is disabled.
+use std::{fmt::Display, future::Future, sync::Arc};
+
+use futures::{Stream, StreamExt};
+use tokio::{
+    select,
+    sync::{watch, Notify},
+};
+use tracing::{debug, warn};
+use turborepo_telemetry::events::command::CommandEventBuilder;
+
+use crate::{
+    commands::CommandBase,
+    daemon::{proto, DaemonError},
+    query::Error,
+    run::{self, builder::RunBuilder, watch::watch_in_process, Run},
+    signal::SignalHandler,
+    DaemonConnector, DaemonPaths,
+};
+
+/// Sends a new run on `run` each time packages change. Only returns if
+/// watching for changes fails.
+pub async fn watch_run(
+    base: CommandBase,
+    telemetry: CommandEventBuilder,
+    signal: SignalHandler,
+    run: &watch::Sender<Arc<Run>>,
+) -> Result<(), Error> {
+    let events = if matches!(base.opts.run_opts.daemon, Some(false)) {
+        debug!("daemon is disabled, watching files in process");
+        watch_in_process(&base.repo_root)
+            .await?
+            .map(|event| event.map_err(Error::from))
+            .boxed()
+    } else {
+        let connector = DaemonConnector {
+            can_start_server: true,
+            can_kill_server: false,
+            paths: DaemonPaths::from_repo_root(&base.repo_root),
+        };
+        let mut client = connector.connect().await?;
+        client
+            .package_changes()
+            .await?
+            .map(|event| event.map_err(Error::from))
+            .boxed()
+    };
+
+    rebuild_on_changes(events, || rebuild(&base, &telemetry, &signal), run).await
+}
+
+/// Sends the result of `rebuild` on `value` after each package change event.
+/// Only returns if `events` reports an error or ends.
+async fn rebuild_on_changes<T, E, F, Fut>(
+    mut events: impl Stream<Item = Result<proto::PackageChangeEvent, Error>> + Unpin,
+    rebuild: F,
+    value: &watch::Sender<Arc<T>>,
+) -> Result<(), Error>
+where
+    E: Display,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    // Changes usually come in bursts, so we only rebuild once for all the
+    // events that arrive while a rebuild is in progress
+    let notify = Notify::new();
+
+    let event_fut = async {
+        while let Some(event) = events.next().await {
+            if let Some(proto::package_change_event::Event::Error(proto::PackageChangeError {
+                message,
+            })) = event?.event
+            {
+                return Err(DaemonError::Unavailable(message).into());
+            }
+            notify.notify_one();
+        }
+
+        Err(Error::DaemonConnectionClosed)
+    };
+
+    let rebuild_fut = async {
+        loop {
+            notify.notified().await;
+            match rebuild().await {
+                Ok(new_value) => {
+                    debug!("rebuilt run for query server");
+                    value.send_replace(Arc::new(new_value));
+                }
+                // Files are often in an invalid state while they're being
+                // edited, so we keep serving the last run that was built
+                Err(err) => warn!("failed to update query server: {err}"),
+            }
+        }
+    };
+
+    select! {
+        result = event_fut => result,
+        _ = rebuild_fut => Ok(()),
+    }
+}
+
+async fn rebuild(
+    base: &CommandBase,
+    telemetry: &CommandEventBuilder,
+    signal: &SignalHandler,
+) -> Result<Run, run::Error> {
+    RunBuilder::new(base.clone())?
+        .add_all_tasks()
+        .do_not_validate_engine()
+        .hide_prelude()
+        .build(signal, telemetry.clone())
+        .await
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use futures::StreamExt;
+    use tokio::sync::{mpsc, watch};
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    use super::rebuild_on_changes;
+    use crate::{daemon::proto, query::Error};
+
+    fn package_changed(name: &str) -> Result<proto::PackageChangeEvent, Error> {
+        Ok(proto::PackageChangeEvent {
+            event: Some(proto::package_change_event::Event::PackageChanged(
+                proto::PackageChanged {
+                    package_name: name.to_string(),
+                },
+            )),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_rebuilds_on_package_changes() {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (value_tx, mut value_rx) = watch::channel(Arc::new(0));
+        let builds = &AtomicUsize::new(0);
+
+        let rebuild = move || async move {
+            let build = builds.fetch_add(1, Ordering::SeqCst) + 1;
+            // The second build fails, which should keep the first build around
+            if build == 2 {
+                Err("invalid turbo.json")
+            } else {
+                Ok(build)
+            }
+        };
+
+        let watch_fut = rebuild_on_changes(
+            UnboundedReceiverStream::new(event_rx).boxed(),
+            rebuild,
+            &value_tx,
+        );
+        let events_fut = async {
+            event_tx.send(package_changed("web")).unwrap();
+            value_rx.changed().await.unwrap();
+            assert_eq!(**value_rx.borrow_and_update(), 1);
+
+            event_tx.send(package_changed("docs")).unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert!(!value_rx.has_changed().unwrap());
+            assert_eq!(**value_rx.borrow(), 1);
+
+            event_tx.send(package_changed("web")).unwrap();
+            value_rx.changed().await.unwrap();
+            assert_eq!(**value_rx.borrow_and_update(), 3);
+
+            drop(event_tx);
+        };
+
+        let (result, ()) = tokio::join!(watch_fut, events_fut);
+        assert!(matches!(result, Err(Error::DaemonConnectionClosed)));
+    }
+
+    #[tokio::test]
+    async fn test_stops_on_package_change_error() {
+        let events = futures::stream::iter([
+            package_changed("web"),
+            Ok(proto::PackageChangeEvent {
+                event: Some(proto::package_change_event::Event::Error(
+                    proto::PackageChangeError {
+                        message: "file watching stopped".to_string(),
+                    },
+                )),
+            }),
+        ]);
+        let (value_tx, _value_rx) = watch::channel(Arc::new(()));
+
+        let result = rebuild_on_changes(events, || async { Ok::<_, Error>(()) }, &value_tx).await;
+        assert!(matches!(result, Err(Error::Daemon(_))));
+    }
+}
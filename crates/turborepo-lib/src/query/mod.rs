@@ -1,8 +1,10 @@
 mod boundaries;
 mod external_package;
 mod file;
+mod live;
 mod package;
 mod server;
+mod subscription;
 mod task;
 
 use std::{
@@ -13,19 +15,30 @@ use std::{
 
 use async_graphql::{http::GraphiQLSource, *};
 use axum::{response, response::IntoResponse};
-use external_package::ExternalPackage;
+use external_package::{
+    external_package_versions, ExternalDependency, ExternalPackage, ExternalPackageUsage,
+    ExternalPackageVersion,
+};
 use package::Package;
+use serde::Serialize;
 pub use server::run_server;
 use thiserror::Error;
-use tokio::select;
+use tokio::{select, sync::watch};
+use tracing::warn;
 use turbo_trace::TraceError;
 use turbopath::AbsoluteSystemPathBuf;
 use turborepo_repository::{change_mapper::AllPackageChangeReason, package_graph::PackageName};
+use turborepo_telemetry::events::command::CommandEventBuilder;
 
 use crate::{
+    commands::CommandBase,
+    engine::TaskNode,
     get_version,
-    query::{file::File, task::RepositoryTask},
-    run::{builder::RunBuilder, Run},
+    query::{
+        file::File,
+        task::{RepositoryTask, TaskFilter},
+    },
+    run::{affected_tasks::TaskAffectedReason, builder::RunBuilder, task_id::TaskId, Run},
     signal::SignalHandler,
 };
 
@@ -37,12 +50,31 @@ pub enum Error {
     Trace(#[related] Vec<TraceError>),
     #[error("No signal handler.")]
     NoSignalHandler,
+    #[error("Failed to connect to daemon.")]
+    #[diagnostic(transparent)]
+    Daemon(#[from] crate::daemon::DaemonError),
+    #[error("Failed to connect to daemon.")]
+    DaemonConnector(#[from] crate::daemon::DaemonConnectorError),
+    #[error("Daemon connection closed.")]
+    DaemonConnectionClosed,
+    #[error("Package change error.")]
+    PackageChange(#[from] tonic::Status),
     #[error("File `{0}` not found.")]
     FileNotFound(String),
     #[error("Failed to start GraphQL server.")]
     Server(#[from] io::Error),
     #[error("Package not found: {0}")]
     PackageNotFound(PackageName),
+    #[error("No lockfile found.")]
+    LockfileNotFound,
+    #[error("Failed to read lockfile: {0}")]
+    Lockfile(#[from] turborepo_lockfiles::Error),
+    #[error("Invalid task id `{0}`. Task ids must be of the form `package#task`.")]
+    InvalidTaskId(String),
+    #[error("Task not found: {0}")]
+    TaskNotFound(String),
+    #[error("Background work panicked: {0}")]
+    Join(#[from] tokio::task::JoinError),
     #[error("Failed to serialize result: {0}")]
     Serde(#[from] serde_json::Error),
     #[error(transparent)]
@@ -50,6 +82,9 @@ pub enum Error {
     Run(#[from] crate::run::Error),
     #[error(transparent)]
     #[diagnostic(transparent)]
+    Watch(#[from] crate::run::watch::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
     Path(#[from] turbopath::PathError),
     #[error(transparent)]
     UI(#[from] turborepo_ui::Error),
@@ -61,16 +96,61 @@ pub enum Error {
 }
 
 pub struct RepositoryQuery {
-    run: Arc<Run>,
+    run: watch::Receiver<Arc<Run>>,
 }
 
 impl RepositoryQuery {
     pub fn new(run: Arc<Run>) -> Self {
+        let (_, run) = watch::channel(run);
+        Self { run }
+    }
+
+    /// Creates a query that always uses the latest run sent on `run`
+    pub fn live(run: watch::Receiver<Arc<Run>>) -> Self {
         Self { run }
     }
 
+    fn run(&self) -> Arc<Run> {
+        self.run.borrow().clone()
+    }
+
+    fn changed_packages(
+        run: &Arc<Run>,
+        base: Option<String>,
+        head: Option<String>,
+        filter: Option<&PackagePredicate>,
+    ) -> Result<Array<ChangedPackage>, Error> {
+        let mut opts = run.opts().clone();
+        opts.scope_opts.affected_range = Some((base, head));
+
+        let mut packages = RunBuilder::calculate_filtered_packages(
+            run.repo_root(),
+            &opts,
+            run.pkg_dep_graph(),
+            run.scm(),
+            run.root_turbo_json(),
+        )?
+        .into_iter()
+        .map(|(package, reason)| {
+            Ok(ChangedPackage {
+                package: Package::new(run.clone(), package)?,
+                reason: Self::convert_change_reason(run, reason),
+            })
+        })
+        .filter(|package: &Result<ChangedPackage, Error>| {
+            let Ok(package) = package.as_ref() else {
+                return true;
+            };
+            filter.map_or(true, |f| f.check(&package.package))
+        })
+        .collect::<Result<Array<_>, _>>()?;
+
+        packages.sort_by(|a, b| a.package.get_name().cmp(b.package.get_name()));
+        Ok(packages)
+    }
+
     fn convert_change_reason(
-        &self,
+        run: &Arc<Run>,
         reason: turborepo_repository::change_mapper::PackageInclusionReason,
     ) -> PackageChangeReason {
         match reason {
@@ -115,8 +195,8 @@ impl RepositoryQuery {
                 PackageChangeReason::ConservativeRootLockfileChanged(ConservativeRootLockfileChanged { empty: false })
             }
             turborepo_repository::change_mapper::PackageInclusionReason::LockfileChanged { removed, added } => {
-                let removed = removed.into_iter().map(|package| ExternalPackage::new(self.run.clone(), package)).collect::<Array<_>>();
-                let added = added.into_iter().map(|package| ExternalPackage::new(self.run.clone(), package)).collect::<Array<_>>();
+                let removed = removed.into_iter().map(|package| ExternalPackage::new(run.clone(), package)).collect::<Array<_>>();
+                let added = added.into_iter().map(|package| ExternalPackage::new(run.clone(), package)).collect::<Array<_>>();
                 PackageChangeReason::LockfileChanged(LockfileChanged { empty: false, removed, added })
             }
             turborepo_repository::change_mapper::PackageInclusionReason::DependencyChanged {
@@ -150,8 +230,17 @@ impl RepositoryQuery {
 #[graphql(concrete(name = "RepositoryTasks", params(RepositoryTask)))]
 #[graphql(concrete(name = "Packages", params(Package)))]
 #[graphql(concrete(name = "ChangedPackages", params(ChangedPackage)))]
+#[graphql(concrete(name = "ChangedTasks", params(ChangedTask)))]
+#[graphql(concrete(name = "TaskInputs", params(task::TaskInput)))]
 #[graphql(concrete(name = "Files", params(File)))]
 #[graphql(concrete(name = "ExternalPackages", params(ExternalPackage)))]
+#[graphql(concrete(name = "ExternalDependencies", params(ExternalDependency)))]
+#[graphql(concrete(
+    name = "DeclaredDependencies",
+    params(external_package::DeclaredDependency)
+))]
+#[graphql(concrete(name = "ExternalPackageVersions", params(ExternalPackageVersion)))]
+#[graphql(concrete(name = "ExternalPackageUsages", params(ExternalPackageUsage)))]
 #[graphql(concrete(name = "Diagnostics", params(Diagnostic)))]
 pub struct Array<T: OutputType> {
     items: Vec<T>,
@@ -515,6 +604,63 @@ struct ChangedPackage {
     package: Package,
 }
 
+#[derive(SimpleObject)]
+struct InputsChanged {
+    files: Vec<String>,
+}
+
+#[derive(SimpleObject)]
+struct DependencyAffected {
+    task_name: String,
+}
+
+#[derive(SimpleObject)]
+struct ExternalDependenciesChanged {
+    /// This is a nothing field
+    empty: bool,
+}
+
+#[derive(SimpleObject)]
+struct AllTasksAffected {
+    reason: String,
+}
+
+#[derive(Union)]
+enum TaskChangeReason {
+    InputsChanged(InputsChanged),
+    DependencyAffected(DependencyAffected),
+    ExternalDependenciesChanged(ExternalDependenciesChanged),
+    AllTasksAffected(AllTasksAffected),
+}
+
+impl From<TaskAffectedReason> for TaskChangeReason {
+    fn from(reason: TaskAffectedReason) -> Self {
+        match reason {
+            TaskAffectedReason::InputsChanged { files } => {
+                TaskChangeReason::InputsChanged(InputsChanged { files })
+            }
+            TaskAffectedReason::DependencyAffected { task } => {
+                TaskChangeReason::DependencyAffected(DependencyAffected { task_name: task })
+            }
+            TaskAffectedReason::ExternalDependenciesChanged => {
+                TaskChangeReason::ExternalDependenciesChanged(ExternalDependenciesChanged {
+                    empty: false,
+                })
+            }
+            TaskAffectedReason::AllTasksAffected { reason } => {
+                TaskChangeReason::AllTasksAffected(AllTasksAffected { reason })
+            }
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+struct ChangedTask {
+    reason: TaskChangeReason,
+    #[graphql(flatten)]
+    task: RepositoryTask,
+}
+
 #[Object]
 impl RepositoryQuery {
     async fn affected_packages(
@@ -523,39 +669,112 @@ impl RepositoryQuery {
         head: Option<String>,
         filter: Option<PackagePredicate>,
     ) -> Result<Array<ChangedPackage>, Error> {
-        let mut opts = self.run.opts().clone();
-        opts.scope_opts.affected_range = Some((base, head));
+        Self::changed_packages(&self.run(), base, head, filter.as_ref())
+    }
 
-        let mut packages = RunBuilder::calculate_filtered_packages(
-            self.run.repo_root(),
-            &opts,
-            self.run.pkg_dep_graph(),
-            self.run.scm(),
-            self.run.root_turbo_json(),
-        )?
-        .into_iter()
-        .map(|(package, reason)| {
-            Ok(ChangedPackage {
-                package: Package::new(self.run.clone(), package)?,
-                reason: self.convert_change_reason(reason),
+    /// Gets the tasks whose inputs, or the inputs of the tasks they depend on,
+    /// changed between `base` and `head`. Only tasks named in `tasks` are
+    /// returned, if it is provided.
+    async fn affected_tasks(
+        &self,
+        base: Option<String>,
+        head: Option<String>,
+        tasks: Option<Vec<String>>,
+    ) -> Result<Array<ChangedTask>, Error> {
+        let run = self.run();
+        let mut scope_opts = run.opts().scope_opts.clone();
+        scope_opts.affected_range = Some((base, head));
+
+        let package_inputs_hashes = run.package_inputs_hashes()?;
+        let mut affected_tasks = run
+            .affected_tasks(&scope_opts, &package_inputs_hashes)?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(task_id, _)| {
+                tasks.as_ref().map_or(true, |tasks| {
+                    tasks.iter().any(|task| task == task_id.task())
+                })
             })
-        })
-        .filter(|package: &Result<ChangedPackage, Error>| {
-            let Ok(package) = package.as_ref() else {
-                return true;
-            };
-            filter.as_ref().map_or(true, |f| f.check(&package.package))
-        })
-        .collect::<Result<Array<_>, _>>()?;
-
-        packages.sort_by(|a, b| a.package.get_name().cmp(b.package.get_name()));
-        Ok(packages)
+            .map(|(task_id, reason)| {
+                Ok(ChangedTask {
+                    task: RepositoryTask::new(&task_id, &run)?,
+                    reason: reason.into(),
+                })
+            })
+            .collect::<Result<Array<_>, Error>>()?;
+
+        affected_tasks.sort_by(|a, b| {
+            a.task
+                .package
+                .get_name()
+                .cmp(b.task.package.get_name())
+                .then_with(|| a.task.name.cmp(&b.task.name))
+        });
+        Ok(affected_tasks)
     }
 
     /// Gets a single package by name
     async fn package(&self, name: String) -> Result<Package, Error> {
         let name = PackageName::from(name);
-        Package::new(self.run.clone(), name)
+        Package::new(self.run(), name)
+    }
+
+    /// Gets a single task by id, e.g. `web#build`
+    async fn task(&self, id: String) -> Result<RepositoryTask, Error> {
+        let run = self.run();
+        let task_id = TaskId::try_from(id.as_str())
+            .map_err(|_| Error::InvalidTaskId(id.clone()))?
+            .into_owned();
+        if run.engine().task_definition(&task_id).is_none() {
+            return Err(Error::TaskNotFound(id));
+        }
+
+        RepositoryTask::new(&task_id, &run)
+    }
+
+    /// Gets the tasks in the task graph that match the given filter
+    async fn tasks(&self, filter: Option<TaskFilter>) -> Result<Array<RepositoryTask>, Error> {
+        let run = self.run();
+        let filter = filter.unwrap_or_default();
+        let engine = run.engine();
+        let mut tasks = engine
+            .tasks()
+            .filter_map(|task| match task {
+                TaskNode::Root => None,
+                TaskNode::Task(task_id) => Some(task_id),
+            })
+            .filter(|task_id| filter.check(task_id, engine.task_definition(task_id)))
+            .map(|task_id| RepositoryTask::new(task_id, &run))
+            .collect::<Result<Array<_>, _>>()?;
+
+        tasks.sort_by(|a, b| {
+            a.package
+                .get_name()
+                .cmp(b.package.get_name())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(tasks)
+    }
+
+    /// Gets the versions of an external package that workspaces depend on,
+    /// along with the workspaces that depend on each version
+    async fn external_package(&self, name: String) -> Result<ExternalPackageUsage, Error> {
+        let run = self.run();
+        let versions = external_package_versions(&run)?
+            .remove(&name)
+            .unwrap_or_default();
+        ExternalPackageUsage::new(&run, name, versions)
+    }
+
+    /// Gets the external packages that workspaces depend on more than one
+    /// version of
+    async fn duplicate_external_packages(&self) -> Result<Array<ExternalPackageUsage>, Error> {
+        let run = self.run();
+        external_package_versions(&run)?
+            .into_iter()
+            .filter(|(_, versions)| versions.len() > 1)
+            .map(|(name, versions)| ExternalPackageUsage::new(&run, name, versions))
+            .collect()
     }
 
     async fn version(&self) -> &'static str {
@@ -564,7 +783,8 @@ impl RepositoryQuery {
 
     /// Check boundaries for all packages.
     async fn boundaries(&self) -> Result<Array<Diagnostic>, Error> {
-        match self.run.check_boundaries().await {
+        let run = self.run();
+        match run.check_boundaries().await {
             Ok(result) => {
                 result.emit();
 
@@ -575,33 +795,33 @@ impl RepositoryQuery {
     }
 
     async fn file(&self, path: String) -> Result<File, Error> {
-        let abs_path = AbsoluteSystemPathBuf::from_unknown(self.run.repo_root(), path);
+        let run = self.run();
+        let abs_path = AbsoluteSystemPathBuf::from_unknown(run.repo_root(), path);
 
         if !abs_path.exists() {
             return Err(Error::FileNotFound(abs_path.to_string()));
         }
 
-        File::new(self.run.clone(), abs_path)
+        File::new(run, abs_path)
     }
 
     /// Gets a list of packages that match the given filter
     async fn packages(&self, filter: Option<PackagePredicate>) -> Result<Array<Package>, Error> {
+        let run = self.run();
         let Some(filter) = filter else {
-            let mut packages = self
-                .run
+            let mut packages = run
                 .pkg_dep_graph()
                 .packages()
-                .map(|(name, _)| Package::new(self.run.clone(), name.clone()))
+                .map(|(name, _)| Package::new(run.clone(), name.clone()))
                 .collect::<Result<Array<_>, _>>()?;
             packages.sort_by(|a, b| a.get_name().cmp(b.get_name()));
             return Ok(packages);
         };
 
-        let mut packages = self
-            .run
+        let mut packages = run
             .pkg_dep_graph()
             .packages()
-            .map(|(name, _)| Package::new(self.run.clone(), name.clone()))
+            .map(|(name, _)| Package::new(run.clone(), name.clone()))
             .filter(|pkg| pkg.as_ref().is_ok_and(|pkg| filter.check(pkg)))
             .collect::<Result<Array<_>, _>>()?;
         packages.sort_by(|a, b| a.get_name().cmp(b.get_name()));
@@ -611,28 +831,51 @@ impl RepositoryQuery {
 }
 
 pub async fn graphiql() -> impl IntoResponse {
-    response::Html(GraphiQLSource::build().endpoint("/").finish())
-}
-
-pub async fn run_query_server(run: Run, signal: SignalHandler) -> Result<(), Error> {
+    response::Html(
+        GraphiQLSource::build()
+            .endpoint("/")
+            .subscription_endpoint("/subscriptions")
+            .finish(),
+    )
+}
+
+/// Serves queries against the repository until a signal is received. The
+/// server rebuilds its run whenever the daemon reports that packages changed,
+/// so query results and subscriptions stay up to date.
+pub async fn run_query_server(
+    base: CommandBase,
+    telemetry: CommandEventBuilder,
+    run: Run,
+    signal: SignalHandler,
+) -> Result<(), Error> {
     let subscriber = signal.subscribe().ok_or(Error::NoSignalHandler)?;
+    let (run_sender, run_receiver) = watch::channel(Arc::new(run));
     println!("GraphiQL IDE: http://localhost:8000");
     webbrowser::open("http://localhost:8000")?;
+
+    let watch_fut = async {
+        if let Err(err) = live::watch_run(base, telemetry, signal.clone(), &run_sender).await {
+            warn!("unable to watch for changes, query results will not be updated: {err}");
+        }
+        std::future::pending::<()>().await
+    };
+
     select! {
         biased;
         _ = subscriber.listen() => {
             println!("Shutting down GraphQL server");
             return Ok(());
         }
-        result = server::run_server(None, Arc::new(run)) => {
+        result = server::run_server(None, run_receiver) => {
             result?;
         }
+        _ = watch_fut => {}
     }
 
     Ok(())
 }
 
-#[derive(SimpleObject, Debug, Default)]
+#[derive(SimpleObject, Debug, Default, Serialize)]
 pub struct Diagnostic {
     pub message: String,
     pub reason: Option<String>,
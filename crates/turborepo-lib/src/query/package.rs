@@ -6,7 +6,11 @@ use turborepo_errors::Spanned;
 use turborepo_repository::package_graph::{PackageName, PackageNode};
 
 use crate::{
-    query::{task::RepositoryTask, Array, Error},
+    query::{
+        external_package::{external_dependencies, ExternalDependency},
+        task::RepositoryTask,
+        Array, Error,
+    },
     run::Run,
 };
 
@@ -107,6 +111,12 @@ impl Package {
             .to_string())
     }
 
+    /// The external dependencies declared in the package.json of this package,
+    /// along with the versions the lockfile resolves them to
+    async fn external_dependencies(&self) -> Result<Array<ExternalDependency>, Error> {
+        external_dependencies(&self.run, &self.name)
+    }
+
     /// The upstream packages that have this package as a direct dependency
     async fn direct_dependents(&self) -> Result<Array<Package>, Error> {
         let node: PackageNode = PackageNode::Workspace(self.name.clone());
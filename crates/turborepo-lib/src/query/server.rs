@@ -1,20 +1,26 @@
 use std::sync::Arc;
 
-use async_graphql::{EmptyMutation, EmptySubscription, MergedObject, Schema};
-use async_graphql_axum::GraphQL;
+use async_graphql::{EmptyMutation, MergedObject, Schema};
+use async_graphql_axum::{GraphQL, GraphQLSubscription};
 use axum::{http::Method, routing::get, Router};
-use tokio::net::TcpListener;
+use tokio::{net::TcpListener, sync::watch};
 use tower_http::cors::{Any, CorsLayer};
 use turborepo_ui::wui::query::SharedState;
 
-use crate::{query, query::graphiql, run::Run};
+use crate::{
+    query,
+    query::{graphiql, subscription::RepositorySubscription},
+    run::Run,
+};
 
 #[derive(MergedObject)]
 struct Query(turborepo_ui::wui::RunQuery, query::RepositoryQuery);
 
+/// Serves queries against `run`. Each request uses the latest run sent on the
+/// channel.
 pub async fn run_server(
     state: Option<SharedState>,
-    run: Arc<Run>,
+    run: watch::Receiver<Arc<Run>>,
 ) -> Result<(), turborepo_ui::Error> {
     let cors = CorsLayer::new()
         // allow `GET` and `POST` when accessing the resource
@@ -24,12 +30,20 @@ pub async fn run_server(
         .allow_origin(Any);
 
     let web_ui_query = turborepo_ui::wui::RunQuery::new(state.clone());
-    let turbo_query = query::RepositoryQuery::new(run);
+    let turbo_query = query::RepositoryQuery::live(run.clone());
     let combined_query = Query(web_ui_query, turbo_query);
 
-    let schema = Schema::new(combined_query, EmptyMutation, EmptySubscription);
+    let schema = Schema::new(
+        combined_query,
+        EmptyMutation,
+        RepositorySubscription::new(run),
+    );
     let app = Router::new()
-        .route("/", get(graphiql).post_service(GraphQL::new(schema)))
+        .route(
+            "/",
+            get(graphiql).post_service(GraphQL::new(schema.clone())),
+        )
+        .route_service("/subscriptions", GraphQLSubscription::new(schema))
         .layer(cors);
 
     axum::serve(
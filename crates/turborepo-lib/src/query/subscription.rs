@@ -0,0 +1,221 @@
+//! Subscriptions emit a result for the current state of the repository and
+//! then emit again whenever the query server picks up a change that alters
+//! the result.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future::{self, Future},
+    sync::Arc,
+};
+
+use async_graphql::Subscription;
+use futures::{stream, Stream};
+use tokio::sync::watch;
+use turbopath::AnchoredSystemPathBuf;
+use turborepo_repository::package_graph::{PackageName, PackageNode};
+
+use crate::{
+    query::{package::Package, Array, ChangedPackage, Error, RepositoryQuery},
+    run::Run,
+};
+
+pub struct RepositorySubscription {
+    run: watch::Receiver<Arc<Run>>,
+}
+
+impl RepositorySubscription {
+    pub fn new(run: watch::Receiver<Arc<Run>>) -> Self {
+        Self { run }
+    }
+}
+
+#[Subscription]
+impl RepositorySubscription {
+    /// Emits the packages in the repository, and again whenever a package is
+    /// added, removed or moved, or the dependencies between packages change
+    async fn packages(&self) -> impl Stream<Item = Result<Array<Package>, Error>> {
+        watch_run(
+            self.run.clone(),
+            |run| {
+                future::ready(
+                    run.pkg_dep_graph()
+                        .packages()
+                        .map(|(name, _)| Package::new(run.clone(), name.clone()))
+                        .collect::<Result<Array<_>, _>>()
+                        .map(|mut packages| {
+                            packages.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+                            packages
+                        }),
+                )
+            },
+            |run, _| package_graph_key(run),
+        )
+    }
+
+    /// Emits the packages that changed between `base` and `head`, and again
+    /// whenever the set of changed packages changes
+    async fn affected_packages(
+        &self,
+        base: Option<String>,
+        head: Option<String>,
+    ) -> impl Stream<Item = Result<Array<ChangedPackage>, Error>> {
+        watch_run(
+            self.run.clone(),
+            move |run| {
+                let (run, base, head) = (run.clone(), base.clone(), head.clone());
+                async move {
+                    // Finding the changed packages runs `git diff`, so it's kept off of
+                    // the async runtime
+                    tokio::task::spawn_blocking(move || {
+                        RepositoryQuery::changed_packages(&run, base, head, None)
+                    })
+                    .await?
+                }
+            },
+            |_, packages| changed_packages_key(packages),
+        )
+    }
+}
+
+type PackageGraphKey = BTreeMap<PackageName, (AnchoredSystemPathBuf, BTreeSet<String>)>;
+
+/// The parts of the package graph that a `packages` subscription reports
+/// changes to
+fn package_graph_key(run: &Run) -> PackageGraphKey {
+    let package_graph = run.pkg_dep_graph();
+    package_graph
+        .packages()
+        .map(|(name, info)| {
+            let dependencies = package_graph
+                .immediate_dependencies(&PackageNode::Workspace(name.clone()))
+                .into_iter()
+                .flatten()
+                .map(|dependency| dependency.to_string())
+                .collect();
+            (name.clone(), (info.package_path().to_owned(), dependencies))
+        })
+        .collect()
+}
+
+/// The changed packages that an `affectedPackages` subscription reports
+/// changes to. Errors are compared by message, so a different error is still
+/// emitted.
+fn changed_packages_key(
+    packages: &Result<Array<ChangedPackage>, Error>,
+) -> Result<Vec<PackageName>, String> {
+    match packages {
+        Ok(packages) => Ok(packages
+            .iter()
+            .map(|package| package.package.get_name().clone())
+            .collect()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Emits `resolve` for the current run and then for each new run, skipping
+/// results where `key` is unchanged from the last result that was emitted
+fn watch_run<R, T, K, F>(
+    run: watch::Receiver<R>,
+    resolve: impl Fn(&R) -> F + Send + 'static,
+    key: impl Fn(&R, &T) -> K + Send + 'static,
+) -> impl Stream<Item = T> + Send + 'static
+where
+    R: Clone + Send + Sync + 'static,
+    F: Future<Output = T> + Send,
+    T: Send + 'static,
+    K: PartialEq + Send + 'static,
+{
+    let state = (run, resolve, key, None, false);
+    stream::unfold(
+        state,
+        |(mut run, resolve, key, mut last_key, mut started)| async move {
+            loop {
+                // The sender is dropped when the server stops watching for changes
+                if started && run.changed().await.is_err() {
+                    return None;
+                }
+                started = true;
+
+                let current = run.borrow_and_update().clone();
+                let result = resolve(&current).await;
+                let current_key = key(&current, &result);
+                if last_key.as_ref() != Some(&current_key) {
+                    last_key = Some(current_key);
+                    return Some((result, (run, resolve, key, last_key, started)));
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::{future, time::Duration};
+
+    use futures::{Stream, StreamExt};
+    use tokio::sync::watch;
+
+    use super::{changed_packages_key, watch_run};
+    use crate::query::{Array, ChangedPackage, Error};
+
+    /// Asserts that `stream` doesn't emit anything after a short wait
+    async fn assert_pending<S: Stream + Unpin>(stream: &mut S) {
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), stream.next())
+                .await
+                .is_err(),
+            "expected the stream to skip the result"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_run_skips_unchanged_keys() {
+        let (run_tx, run_rx) = watch::channel(1);
+        // Only whether the value is odd is reported on
+        let mut results = Box::pin(watch_run(
+            run_rx,
+            |value| future::ready(*value),
+            |_, value| value % 2,
+        ));
+
+        assert_eq!(results.next().await, Some(1));
+
+        run_tx.send(3).unwrap();
+        assert_pending(&mut results).await;
+
+        run_tx.send(4).unwrap();
+        assert_eq!(results.next().await, Some(4));
+
+        // The stream ends once the server stops watching for changes
+        drop(run_tx);
+        assert_eq!(results.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_watch_run_emits_different_errors() {
+        let (run_tx, run_rx) = watch::channel("turbo.json");
+        let mut results = Box::pin(watch_run(
+            run_rx,
+            |file| {
+                future::ready(Err::<Array<ChangedPackage>, _>(Error::FileNotFound(
+                    file.to_string(),
+                )))
+            },
+            |_, result| changed_packages_key(result),
+        ));
+
+        assert!(matches!(
+            results.next().await,
+            Some(Err(Error::FileNotFound(file))) if file == "turbo.json"
+        ));
+
+        // The same error isn't emitted twice
+        run_tx.send("turbo.json").unwrap();
+        assert_pending(&mut results).await;
+
+        run_tx.send("package.json").unwrap();
+        assert!(matches!(
+            results.next().await,
+            Some(Err(Error::FileNotFound(file))) if file == "package.json"
+        ));
+    }
+}
@@ -1,12 +1,15 @@
 use std::sync::Arc;
 
-use async_graphql::Object;
+use async_graphql::{Enum, InputObject, Object, SimpleObject};
+use itertools::Itertools;
+use turborepo_cache::CacheHitMetadata;
 use turborepo_errors::Spanned;
 
 use crate::{
     engine::TaskNode,
     query::{package::Package, Array, Error},
     run::{task_id::TaskId, Run},
+    task_graph::TaskDefinition,
 };
 
 pub struct RepositoryTask {
@@ -27,6 +30,10 @@ impl RepositoryTask {
         })
     }
 
+    fn task_id(&self) -> TaskId<'static> {
+        TaskId::from_static(self.package.get_name().to_string(), self.name.clone())
+    }
+
     fn collect_and_sort<'a>(
         &self,
         task_id: &TaskId<'a>,
@@ -68,6 +75,52 @@ impl RepositoryTask {
         self.script.as_ref().map(|script| script.value.to_string())
     }
 
+    /// The task definition after merging the root and package turbo.json.
+    /// `null` if the task isn't in the task graph.
+    async fn definition(&self) -> Option<ResolvedTaskDefinition> {
+        self.package
+            .run()
+            .engine()
+            .task_definition(&self.task_id())
+            .map(ResolvedTaskDefinition::from)
+    }
+
+    /// The hash that would be used for this task if it were run now
+    async fn hash(&self) -> Result<Option<String>, Error> {
+        let task_hashes = self.package.run().task_hashes().await?;
+        Ok(task_hashes.hash(&self.task_id()))
+    }
+
+    /// The files that make up the inputs of this task along with their hashes
+    async fn expanded_inputs(&self) -> Result<Option<Array<TaskInput>>, Error> {
+        let task_hashes = self.package.run().task_hashes().await?;
+        Ok(task_hashes
+            .get_expanded_inputs(&self.task_id())
+            .map(|inputs| {
+                inputs
+                    .0
+                    .into_iter()
+                    .sorted_by(|(a, _), (b, _)| a.cmp(b))
+                    .map(|(path, hash)| TaskInput {
+                        path: path.to_string(),
+                        hash,
+                    })
+                    .collect()
+            }))
+    }
+
+    /// Whether the outputs of this task are in the local or remote cache.
+    /// `null` if the task isn't in the task graph.
+    async fn cache_status(&self) -> Result<Option<TaskCacheStatus>, Error> {
+        let task_id = self.task_id();
+        let task_hashes = self.package.run().task_hashes().await?;
+        if task_hashes.hash(&task_id).is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(task_hashes.cache_status(&task_id).into()))
+    }
+
     async fn direct_dependents(&self) -> Result<Array<RepositoryTask>, Error> {
         let task_id = TaskId::from_static(self.package.get_name().to_string(), self.name.clone());
 
@@ -167,3 +220,110 @@ impl RepositoryTask {
         )
     }
 }
+
+#[derive(SimpleObject)]
+pub struct ResolvedTaskDefinition {
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    excluded_outputs: Vec<String>,
+    env: Vec<String>,
+    pass_through_env: Option<Vec<String>>,
+    persistent: bool,
+    interruptible: bool,
+    interactive: bool,
+    cache: bool,
+}
+
+impl From<&TaskDefinition> for ResolvedTaskDefinition {
+    fn from(definition: &TaskDefinition) -> Self {
+        Self {
+            inputs: definition.inputs.clone(),
+            outputs: definition.outputs.inclusions.clone(),
+            excluded_outputs: definition.outputs.exclusions.clone(),
+            env: definition.env.clone(),
+            pass_through_env: definition.pass_through_env.clone(),
+            persistent: definition.persistent,
+            interruptible: definition.interruptible,
+            interactive: definition.interactive,
+            cache: definition.cache,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct TaskInput {
+    path: String,
+    hash: String,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+enum CacheStatus {
+    Hit,
+    Miss,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+enum CacheSource {
+    Local,
+    Remote,
+}
+
+#[derive(SimpleObject)]
+pub struct TaskCacheStatus {
+    status: CacheStatus,
+    /// Where the cache hit came from. `null` on a cache miss.
+    source: Option<CacheSource>,
+    /// The time in milliseconds that the original run of the task took.
+    /// 0 on a cache miss.
+    time_saved: u64,
+}
+
+impl From<Option<CacheHitMetadata>> for TaskCacheStatus {
+    fn from(cache_hit: Option<CacheHitMetadata>) -> Self {
+        match cache_hit {
+            Some(CacheHitMetadata { source, time_saved }) => Self {
+                status: CacheStatus::Hit,
+                source: Some(match source {
+                    turborepo_cache::CacheSource::Local => CacheSource::Local,
+                    turborepo_cache::CacheSource::Remote => CacheSource::Remote,
+                }),
+                time_saved,
+            },
+            None => Self {
+                status: CacheStatus::Miss,
+                source: None,
+                time_saved: 0,
+            },
+        }
+    }
+}
+
+/// Filters tasks in the task graph. A task matches if it matches every
+/// field that is provided.
+#[derive(InputObject, Default)]
+pub struct TaskFilter {
+    /// The name of the task, e.g. `build`
+    name: Option<String>,
+    /// The name of the package the task belongs to
+    package: Option<String>,
+    persistent: Option<bool>,
+    cache: Option<bool>,
+}
+
+impl TaskFilter {
+    pub fn check(&self, task_id: &TaskId, definition: Option<&TaskDefinition>) -> bool {
+        self.name
+            .as_ref()
+            .map_or(true, |name| name == task_id.task())
+            && self
+                .package
+                .as_ref()
+                .map_or(true, |package| package == task_id.package())
+            && self.persistent.map_or(true, |persistent| {
+                definition.is_some_and(|definition| definition.persistent == persistent)
+            })
+            && self.cache.map_or(true, |cache| {
+                definition.is_some_and(|definition| definition.cache == cache)
+            })
+    }
+}
@@ -0,0 +1,484 @@
+//! Task level change detection for `--affected-by-inputs`. Rather than
+//! running every task in a changed package, a task is only affected if one of
+//! the files that make up its inputs changed, or if a task that it depends on
+//! is affected.
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use turbopath::AbsoluteSystemPath;
+use turborepo_repository::{
+    change_mapper::{AllPackageChangeReason, PackageInclusionReason},
+    package_graph::{PackageGraph, PackageName},
+};
+
+use crate::{
+    engine::{Engine, TaskNode},
+    run::{scope::Changes, task_id::TaskId},
+    task_hash::PackageInputsHashes,
+};
+
+/// Why a task is affected by the changes in the affected range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TaskAffectedReason {
+    /// Files that are inputs of the task changed
+    InputsChanged { files: Vec<String> },
+    /// A task that this task depends on is affected
+    DependencyAffected { task: String },
+    /// The external dependencies of the task's package changed
+    ExternalDependenciesChanged,
+    /// A change that affects every task, such as a global dependency changing
+    AllTasksAffected { reason: String },
+}
+
+/// Returns every affected task in `engine` along with the reason that it's
+/// affected. Tasks that aren't affected are omitted.
+pub fn affected_tasks(
+    engine: &Engine,
+    pkg_dep_graph: &PackageGraph,
+    repo_root: &AbsoluteSystemPath,
+    changes: &Changes,
+    package_inputs_hashes: &PackageInputsHashes,
+) -> HashMap<TaskId<'static>, TaskAffectedReason> {
+    let finder = AffectedTaskFinder {
+        engine,
+        pkg_dep_graph,
+        repo_root,
+        changes,
+        package_inputs_hashes,
+    };
+    let mut reasons = HashMap::new();
+    for task in engine.tasks() {
+        if let TaskNode::Task(task_id) = task {
+            finder.affected_reason(task_id, &mut reasons);
+        }
+    }
+
+    reasons
+        .into_iter()
+        .filter_map(|(task_id, reason)| Some((task_id, reason?)))
+        .collect()
+}
+
+struct AffectedTaskFinder<'a> {
+    engine: &'a Engine,
+    pkg_dep_graph: &'a PackageGraph,
+    repo_root: &'a AbsoluteSystemPath,
+    changes: &'a Changes,
+    package_inputs_hashes: &'a PackageInputsHashes,
+}
+
+impl AffectedTaskFinder<'_> {
+    fn affected_reason(
+        &self,
+        task_id: &TaskId<'static>,
+        reasons: &mut HashMap<TaskId<'static>, Option<TaskAffectedReason>>,
+    ) -> Option<TaskAffectedReason> {
+        if let Some(reason) = reasons.get(task_id) {
+            return reason.clone();
+        }
+
+        let mut reason = self.changed_inputs_reason(task_id);
+        if reason.is_none() {
+            let mut dependencies = self
+                .engine
+                .dependencies(task_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|node| match node {
+                    TaskNode::Task(dependency) => Some(dependency),
+                    TaskNode::Root => None,
+                })
+                .collect::<Vec<_>>();
+            dependencies.sort();
+            for dependency in dependencies {
+                if self.affected_reason(dependency, reasons).is_some() {
+                    reason = Some(TaskAffectedReason::DependencyAffected {
+                        task: dependency.to_string(),
+                    });
+                    break;
+                }
+            }
+        }
+
+        reasons.insert(task_id.clone(), reason.clone());
+        reason
+    }
+
+    /// Checks whether the changes affect the task directly, ignoring the
+    /// tasks that it depends on
+    fn changed_inputs_reason(&self, task_id: &TaskId<'static>) -> Option<TaskAffectedReason> {
+        let package_name = PackageName::from(task_id.package());
+        match self.changes.packages.get(&package_name)? {
+            PackageInclusionReason::All(reason) => Some(TaskAffectedReason::AllTasksAffected {
+                reason: describe_all_packages_reason(reason),
+            }),
+            PackageInclusionReason::LockfileChanged { .. }
+            | PackageInclusionReason::ConservativeRootLockfileChanged => {
+                Some(TaskAffectedReason::ExternalDependenciesChanged)
+            }
+            PackageInclusionReason::FileChanged { .. } => {
+                let files = self.changed_inputs(&package_name, task_id);
+                (!files.is_empty()).then_some(TaskAffectedReason::InputsChanged { files })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the changed files that are inputs of the task, relative to the
+    /// repository root
+    fn changed_inputs(&self, package_name: &PackageName, task_id: &TaskId<'static>) -> Vec<String> {
+        let (Some(changed_files), Some(package_info), Some(inputs)) = (
+            self.changes.files.as_ref(),
+            self.pkg_dep_graph.package_info(package_name),
+            self.package_inputs_hashes.expanded_inputs(task_id),
+        ) else {
+            return Vec::new();
+        };
+        let package_root = self.repo_root.resolve(package_info.package_path());
+        let inputs = inputs
+            .0
+            .keys()
+            .filter_map(|input| package_root.join_unix_path(input).clean().ok())
+            .collect::<HashSet<_>>();
+
+        let mut files = changed_files
+            .iter()
+            .filter(|file| {
+                let path = self.repo_root.resolve(file);
+                // Deleted files can't be matched against the inputs, so we
+                // assume that they were inputs of every task in their package
+                inputs.contains(&path)
+                    || (!path.exists() && path.as_path().starts_with(package_root.as_path()))
+            })
+            .map(|file| file.to_unix().to_string())
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+}
+
+fn describe_all_packages_reason(reason: &AllPackageChangeReason) -> String {
+    match reason {
+        AllPackageChangeReason::GlobalDepsChanged { file } => {
+            format!("global dependency `{}` changed", file.to_unix())
+        }
+        AllPackageChangeReason::DefaultGlobalFileChanged { file } => {
+            format!("`{}` changed", file.to_unix())
+        }
+        AllPackageChangeReason::LockfileChangeDetectionFailed => {
+            "unable to detect which packages the lockfile changes affect".to_string()
+        }
+        AllPackageChangeReason::LockfileChangedWithoutDetails => "the lockfile changed".to_string(),
+        AllPackageChangeReason::RootInternalDepChanged { root_internal_dep } => {
+            format!("`{root_internal_dep}`, a dependency of the root package, changed")
+        }
+        AllPackageChangeReason::GitRefNotFound { from_ref, to_ref } => format!(
+            "unable to find the git range {}...{}",
+            from_ref.as_deref().unwrap_or_default(),
+            to_ref.as_deref().unwrap_or_default()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use tempfile::TempDir;
+    use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPathBuf, RelativeUnixPathBuf};
+    use turborepo_repository::{
+        change_mapper::{AllPackageChangeReason, PackageInclusionReason},
+        discovery::{DiscoveryResponse, PackageDiscovery},
+        package_graph::{PackageGraph, PackageName},
+        package_json::PackageJson,
+        package_manager::PackageManager,
+    };
+
+    use super::{affected_tasks, TaskAffectedReason};
+    use crate::{
+        engine::Engine,
+        hash::FileHashes,
+        run::{scope::Changes, task_id::TaskId},
+        task_graph::TaskDefinition,
+        task_hash::PackageInputsHashes,
+    };
+
+    struct MockDiscovery;
+
+    impl PackageDiscovery for MockDiscovery {
+        async fn discover_packages(
+            &self,
+        ) -> Result<DiscoveryResponse, turborepo_repository::discovery::Error> {
+            Ok(DiscoveryResponse {
+                package_manager: PackageManager::Pnpm,
+                workspaces: vec![],
+            })
+        }
+
+        async fn discover_packages_blocking(
+            &self,
+        ) -> Result<DiscoveryResponse, turborepo_repository::discovery::Error> {
+            self.discover_packages().await
+        }
+    }
+
+    struct Repo {
+        _tempdir: TempDir,
+        repo_root: AbsoluteSystemPathBuf,
+        engine: Engine,
+        pkg_dep_graph: PackageGraph,
+        package_inputs_hashes: PackageInputsHashes,
+    }
+
+    impl Repo {
+        /// Sets up a repository with a `ui` and a `web` package, where
+        /// `web#build` depends on `ui#build` and `web#test` depends on
+        /// `web#build`. `web#lint` is the only task that has the README as an
+        /// input.
+        async fn new() -> Self {
+            let tempdir = tempfile::tempdir().unwrap();
+            let repo_root = AbsoluteSystemPathBuf::try_from(tempdir.path()).unwrap();
+
+            let tasks = [
+                ("ui#build", "packages/ui", vec!["src/button.ts"]),
+                ("web#build", "packages/web", vec!["src/index.ts"]),
+                (
+                    "web#lint",
+                    "packages/web",
+                    vec!["src/index.ts", "README.md"],
+                ),
+                ("web#test", "packages/web", vec!["src/index.ts"]),
+            ];
+
+            let mut package_jsons = HashMap::new();
+            let mut expanded_inputs = HashMap::new();
+            let mut engine = Engine::new();
+            for (task, package_path, inputs) in tasks {
+                let task_id = TaskId::try_from(task).unwrap().into_owned();
+                let package_root =
+                    repo_root.join_unix_path(RelativeUnixPathBuf::new(package_path).unwrap());
+                for input in &inputs {
+                    let file =
+                        package_root.join_unix_path(RelativeUnixPathBuf::new(*input).unwrap());
+                    file.ensure_dir().unwrap();
+                    file.create_with_contents("").unwrap();
+                }
+                package_jsons.insert(
+                    package_root.join_component("package.json"),
+                    PackageJson {
+                        name: Some(task_id.package().to_string()),
+                        ..Default::default()
+                    },
+                );
+                expanded_inputs.insert(
+                    task_id.clone(),
+                    FileHashes(
+                        inputs
+                            .into_iter()
+                            .map(|input| (RelativeUnixPathBuf::new(input).unwrap(), String::new()))
+                            .collect(),
+                    ),
+                );
+                engine.get_index(&task_id);
+                engine.add_definition(task_id, TaskDefinition::default());
+            }
+
+            let ui_build = TaskId::new("ui", "build");
+            let web_build = TaskId::new("web", "build");
+            engine.add_dependency(&web_build, &ui_build);
+            engine.add_dependency(&TaskId::new("web", "test"), &web_build);
+            engine.connect_to_root(&ui_build);
+            engine.connect_to_root(&TaskId::new("web", "lint"));
+
+            let pkg_dep_graph = PackageGraph::builder(&repo_root, PackageJson::default())
+                .with_package_discovery(MockDiscovery)
+                .with_package_jsons(Some(package_jsons))
+                .build()
+                .await
+                .unwrap();
+
+            Self {
+                _tempdir: tempdir,
+                repo_root,
+                engine: engine.seal(),
+                pkg_dep_graph,
+                package_inputs_hashes: PackageInputsHashes::from_expanded_inputs(expanded_inputs),
+            }
+        }
+
+        fn affected(
+            &self,
+            files: &[&str],
+            packages: impl IntoIterator<Item = (&'static str, PackageInclusionReason)>,
+        ) -> HashMap<String, TaskAffectedReason> {
+            let changes = Changes {
+                files: Some(
+                    files
+                        .iter()
+                        .map(|file| AnchoredSystemPathBuf::from_raw(file).unwrap())
+                        .collect(),
+                ),
+                packages: packages
+                    .into_iter()
+                    .map(|(package, reason)| (PackageName::from(package), reason))
+                    .collect(),
+            };
+
+            affected_tasks(
+                &self.engine,
+                &self.pkg_dep_graph,
+                &self.repo_root,
+                &changes,
+                &self.package_inputs_hashes,
+            )
+            .into_iter()
+            .map(|(task_id, reason)| (task_id.to_string(), reason))
+            .collect()
+        }
+    }
+
+    fn file_changed(file: &str) -> PackageInclusionReason {
+        PackageInclusionReason::FileChanged {
+            file: AnchoredSystemPathBuf::from_raw(file).unwrap(),
+        }
+    }
+
+    fn inputs_changed(files: &[&str]) -> TaskAffectedReason {
+        TaskAffectedReason::InputsChanged {
+            files: files.iter().map(|file| file.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_excluded_by_inputs() {
+        let repo = Repo::new().await;
+        let readme = "packages/web/README.md";
+
+        // Only `web#lint` has the README as an input, so the other tasks in
+        // `web` aren't affected even though the package changed
+        assert_eq!(
+            repo.affected(&[readme], [("web", file_changed(readme))]),
+            HashMap::from([("web#lint".to_string(), inputs_changed(&[readme]))])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dependency_affected() {
+        let repo = Repo::new().await;
+        let button = "packages/ui/src/button.ts";
+
+        assert_eq!(
+            repo.affected(&[button], [("ui", file_changed(button))]),
+            HashMap::from([
+                ("ui#build".to_string(), inputs_changed(&[button])),
+                (
+                    "web#build".to_string(),
+                    TaskAffectedReason::DependencyAffected {
+                        task: "ui#build".to_string()
+                    }
+                ),
+                (
+                    "web#test".to_string(),
+                    TaskAffectedReason::DependencyAffected {
+                        task: "web#build".to_string()
+                    }
+                ),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_own_inputs_take_precedence() {
+        let repo = Repo::new().await;
+        let button = "packages/ui/src/button.ts";
+        let index = "packages/web/src/index.ts";
+
+        let affected = repo.affected(
+            &[button, index],
+            [("ui", file_changed(button)), ("web", file_changed(index))],
+        );
+        assert_eq!(affected.len(), 4);
+        assert_eq!(affected["web#build"], inputs_changed(&[index]));
+        assert_eq!(affected["web#test"], inputs_changed(&[index]));
+    }
+
+    #[tokio::test]
+    async fn test_lockfile_and_global_changes() {
+        let repo = Repo::new().await;
+
+        let affected = repo.affected(
+            &["pnpm-lock.yaml", ".env"],
+            [
+                (
+                    "ui",
+                    PackageInclusionReason::LockfileChanged {
+                        removed: vec![],
+                        added: vec![],
+                    },
+                ),
+                (
+                    "web",
+                    PackageInclusionReason::All(AllPackageChangeReason::GlobalDepsChanged {
+                        file: AnchoredSystemPathBuf::from_raw(".env").unwrap(),
+                    }),
+                ),
+            ],
+        );
+
+        let all_tasks_affected = TaskAffectedReason::AllTasksAffected {
+            reason: "global dependency `.env` changed".to_string(),
+        };
+        assert_eq!(
+            affected,
+            HashMap::from([
+                (
+                    "ui#build".to_string(),
+                    TaskAffectedReason::ExternalDependenciesChanged
+                ),
+                ("web#build".to_string(), all_tasks_affected.clone()),
+                ("web#lint".to_string(), all_tasks_affected.clone()),
+                ("web#test".to_string(), all_tasks_affected),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deleted_file() {
+        let repo = Repo::new().await;
+        // A deleted file isn't in any task's inputs anymore, so every task in
+        // its package is assumed to be affected
+        let deleted = "packages/web/src/deleted.ts";
+
+        let affected = repo.affected(&[deleted], [("web", file_changed(deleted))]);
+        assert_eq!(
+            affected,
+            HashMap::from([
+                ("web#build".to_string(), inputs_changed(&[deleted])),
+                ("web#lint".to_string(), inputs_changed(&[deleted])),
+                ("web#test".to_string(), inputs_changed(&[deleted])),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_package() {
+        let repo = Repo::new().await;
+        let button = "packages/ui/src/button.ts";
+
+        // Packages that are only included because they depend on a changed
+        // package are left to the task graph
+        assert_eq!(
+            repo.affected(
+                &[button],
+                [(
+                    "web",
+                    PackageInclusionReason::DependencyChanged {
+                        dependency: PackageName::from("ui")
+                    }
+                )]
+            ),
+            HashMap::new()
+        );
+    }
+}
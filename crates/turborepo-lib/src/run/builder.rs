@@ -440,7 +440,7 @@ impl RunBuilder {
                 &pkg_dep_graph,
                 &root_turbo_json,
                 filtered_pkgs.keys(),
-                turbo_json_loader,
+                turbo_json_loader.clone(),
             )?;
         }
 
@@ -454,11 +454,13 @@ impl RunBuilder {
             color_selector,
             daemon.clone(),
             self.color_config,
-            self.opts.run_opts.dry_run.is_some(),
+            self.opts.run_opts.dry_run.is_some() || self.opts.run_opts.explain.is_some(),
         ));
 
         let should_print_prelude = self.should_print_prelude_override.unwrap_or_else(|| {
-            self.opts.run_opts.dry_run.is_none() && self.opts.run_opts.graph.is_none()
+            self.opts.run_opts.dry_run.is_none()
+                && self.opts.run_opts.explain.is_none()
+                && self.opts.run_opts.graph.is_none()
         });
 
         Ok(Run {
@@ -476,6 +478,7 @@ impl RunBuilder {
             filtered_pkgs: filtered_pkgs.keys().cloned().collect(),
             pkg_dep_graph: Arc::new(pkg_dep_graph),
             root_turbo_json,
+            turbo_json_loader,
             scm,
             engine: Arc::new(engine),
             run_cache,
@@ -483,6 +486,8 @@ impl RunBuilder {
             daemon,
             should_print_prelude,
             micro_frontend_configs,
+            task_hash_tracker: Default::default(),
+            dependency_hashes: HashMap::new(),
         })
     }
 
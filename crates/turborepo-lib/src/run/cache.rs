@@ -401,6 +401,7 @@ impl TaskCache {
                 self.hash.clone(),
                 relative_paths.clone(),
                 duration.as_millis() as u64,
+                Some(self.task_id.to_string()),
             )
             .await?;
 
@@ -482,6 +483,7 @@ impl ConfigCache {
                         self.hash.clone(),
                         vec![self.anchored_path.clone()],
                         0,
+                        None,
                     )
                     .await
             }
@@ -8,7 +8,7 @@ use crate::{
     config, daemon, engine,
     engine::ValidateError,
     opts,
-    run::{global_hash, scope},
+    run::{explain, global_hash, scope},
     task_graph, task_hash,
 };
 
@@ -48,6 +48,8 @@ pub enum Error {
     #[error(transparent)]
     GlobalHash(#[from] global_hash::Error),
     #[error(transparent)]
+    Explain(#[from] explain::Error),
+    #[error(transparent)]
     TaskHash(#[from] task_hash::Error),
     #[error(transparent)]
     #[diagnostic(transparent)]
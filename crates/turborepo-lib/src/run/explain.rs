@@ -0,0 +1,742 @@
+//! Saves the inputs that went into each task's hash so that `--explain` can
+//! report exactly which of them changed when a task misses the cache.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    io::{self, Write},
+    sync::Arc,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tabwriter::TabWriter;
+use thiserror::Error;
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+use turborepo_ui::{cprintln, cwriteln, ColorConfig, BOLD, GREY};
+
+use crate::{
+    cli::EnvMode, opts::ExplainOpts, run::global_hash::GlobalHashableInputs,
+    task_hash::TaskHashTracker,
+};
+
+// Saved inputs are small, but a new file is written every time a task's hash
+// changes, so we only keep the most recent ones.
+const MAX_SAVED_INPUTS: usize = 1000;
+// The global inputs are saved once per global hash, which changes much less
+// often than task hashes
+const MAX_SAVED_GLOBAL_INPUTS: usize = 100;
+const LATEST_FILE: &str = "latest.json";
+const GLOBAL_DIR: &str = "global";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("No saved hash inputs found for hash `{0}`.")]
+    HashNotFound(String),
+    #[error("Task `{task_id}` (hash `{hash}`) is not part of this run.")]
+    TaskNotInRun { task_id: String, hash: String },
+    #[error("Failed to save hash inputs: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to read hash inputs: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The inputs that make up a task's hash, except for the global hash and the
+/// hashes of the task's files which are kept separately
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskHashInputs {
+    pub task_id: String,
+    pub hash: String,
+    pub package_dir: Option<String>,
+    pub external_dependencies_hash: Option<String>,
+    /// Task id and hash of each dependency
+    pub dependencies: BTreeMap<String, String>,
+    pub outputs: Vec<String>,
+    pub pass_through_args: Vec<String>,
+    pub env: Vec<String>,
+    /// Hashed values of the environment variables that were included
+    pub env_vars: BTreeMap<String, String>,
+    pub pass_through_env: Vec<String>,
+    pub env_mode: EnvMode,
+    pub framework: Option<String>,
+}
+
+/// The inputs that make up the global hash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalHashInputs {
+    pub hash: String,
+    pub files: BTreeMap<String, String>,
+    pub root_external_dependencies_hash: Option<String>,
+    pub root_internal_dependencies_hash: Option<String>,
+    pub engines: BTreeMap<String, String>,
+    pub env: Vec<String>,
+    /// Hashed values of the environment variables that were included
+    pub env_vars: BTreeMap<String, String>,
+    pub pass_through_env: Vec<String>,
+    pub env_mode: EnvMode,
+    pub framework_inference: bool,
+}
+
+impl GlobalHashInputs {
+    pub fn new(inputs: &GlobalHashableInputs<'_>, global_hash: &str) -> Self {
+        Self {
+            hash: global_hash.to_string(),
+            files: inputs
+                .global_file_hash_map
+                .iter()
+                .map(|(path, hash)| (path.to_string(), hash.clone()))
+                .collect(),
+            root_external_dependencies_hash: inputs
+                .root_external_dependencies_hash
+                .map(str::to_string),
+            root_internal_dependencies_hash: inputs
+                .root_internal_dependencies_hash
+                .map(str::to_string),
+            engines: inputs
+                .engines
+                .iter()
+                .flatten()
+                .map(|(name, version)| (name.to_string(), version.to_string()))
+                .collect(),
+            env: inputs.env.to_vec(),
+            env_vars: inputs
+                .resolved_env_vars
+                .as_ref()
+                .map(|env_vars| hashed_env_vars(env_vars.all.to_secret_hashable()))
+                .unwrap_or_default(),
+            pass_through_env: inputs.pass_through_env.unwrap_or_default().to_vec(),
+            env_mode: inputs.env_mode,
+            framework_inference: inputs.framework_inference,
+        }
+    }
+}
+
+/// Everything that went into a task's hash
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedHashInputs {
+    pub task: TaskHashInputs,
+    /// Hashes of the task's input files, relative to its package
+    pub files: BTreeMap<String, String>,
+    /// Shared by every task in a run
+    pub global: Arc<GlobalHashInputs>,
+}
+
+/// A task's hash inputs as they're saved to disk. The global inputs are saved
+/// separately, once per global hash.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskInputsFile<'a> {
+    #[serde(flatten)]
+    task: &'a TaskHashInputs,
+    files: &'a BTreeMap<String, String>,
+    global_hash: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnedTaskInputsFile {
+    #[serde(flatten)]
+    task: TaskHashInputs,
+    files: BTreeMap<String, String>,
+    global_hash: String,
+}
+
+/// Converts `NAME=hash` pairs into a map
+pub fn hashed_env_vars(pairs: Vec<String>) -> BTreeMap<String, String> {
+    pairs
+        .into_iter()
+        .filter_map(|pair| {
+            let (name, hash) = pair.split_once('=')?;
+            Some((name.to_string(), hash.to_string()))
+        })
+        .collect()
+}
+
+/// Collects the hash inputs of every task hashed by `tracker`, sorted by task
+pub fn collect(tracker: &TaskHashTracker, global: &Arc<GlobalHashInputs>) -> Vec<SavedHashInputs> {
+    let mut inputs = tracker
+        .hash_inputs()
+        .into_iter()
+        .map(|(task, files)| SavedHashInputs {
+            task,
+            files: files
+                .map(|files| {
+                    files
+                        .0
+                        .into_iter()
+                        .map(|(path, hash)| (path.to_string(), hash))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            global: global.clone(),
+        })
+        .collect::<Vec<_>>();
+    inputs.sort_by(|a, b| a.task.task_id.cmp(&b.task.task_id));
+    inputs
+}
+
+/// The hash inputs saved in `.turbo/hash-inputs`, one file per task hash,
+/// along with the most recent hash of each task. The global inputs are saved
+/// in `.turbo/hash-inputs/global`, one file per global hash.
+pub struct HashInputsStore {
+    dir: AbsoluteSystemPathBuf,
+}
+
+impl HashInputsStore {
+    pub fn new(repo_root: &AbsoluteSystemPath) -> Self {
+        Self {
+            dir: repo_root.join_components(&[".turbo", "hash-inputs"]),
+        }
+    }
+
+    pub fn save(&self, global: &GlobalHashInputs, inputs: &[SavedHashInputs]) -> Result<(), Error> {
+        let global_dir = self.dir.join_component(GLOBAL_DIR);
+        global_dir.create_dir_all()?;
+        let global_path = self.global_path(&global.hash);
+        if !global_path.exists() {
+            write_atomic(&global_path, serde_json::to_string(global)?)?;
+        }
+
+        let mut latest = self.latest()?;
+        for inputs in inputs {
+            let path = self
+                .dir
+                .join_component(&format!("{}.json", inputs.task.hash));
+            // The inputs for a hash never change, so they only need to be
+            // written the first time a task runs with that hash
+            if !path.exists() {
+                let file = TaskInputsFile {
+                    task: &inputs.task,
+                    files: &inputs.files,
+                    global_hash: &inputs.global.hash,
+                };
+                write_atomic(&path, serde_json::to_string(&file)?)?;
+            }
+            latest.insert(inputs.task.task_id.clone(), inputs.task.hash.clone());
+        }
+        write_atomic(
+            &self.dir.join_component(LATEST_FILE),
+            serde_json::to_string_pretty(&latest)?,
+        )?;
+
+        prune(
+            &self.dir,
+            MAX_SAVED_INPUTS,
+            &latest.values().map(String::as_str).collect(),
+        );
+        prune(
+            &global_dir,
+            MAX_SAVED_GLOBAL_INPUTS,
+            &HashSet::from([global.hash.as_str()]),
+        );
+        Ok(())
+    }
+
+    pub fn load(&self, hash: &str) -> Result<Option<SavedHashInputs>, Error> {
+        let Some(file) =
+            read_json::<OwnedTaskInputsFile>(&self.dir.join_component(&format!("{hash}.json")))?
+        else {
+            return Ok(None);
+        };
+        // The global inputs may have been pruned, in which case there's
+        // nothing complete to compare against
+        let Some(global) = read_json(&self.global_path(&file.global_hash))? else {
+            return Ok(None);
+        };
+
+        Ok(Some(SavedHashInputs {
+            task: file.task,
+            files: file.files,
+            global: Arc::new(global),
+        }))
+    }
+
+    /// The hash of each task the last time it ran
+    pub fn latest(&self) -> Result<HashMap<String, String>, Error> {
+        Ok(read_json(&self.dir.join_component(LATEST_FILE))?.unwrap_or_default())
+    }
+
+    fn global_path(&self, hash: &str) -> AbsoluteSystemPathBuf {
+        self.dir
+            .join_components(&[GLOBAL_DIR, &format!("{hash}.json")])
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &AbsoluteSystemPath) -> Result<Option<T>, Error> {
+    match path.read_to_string() {
+        Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Writes to a temporary file first so that a run that's interrupted partway
+// through never leaves a truncated file behind
+fn write_atomic(path: &AbsoluteSystemPath, contents: String) -> Result<(), Error> {
+    let temp_path = format!("{path}.{}.tmp", std::process::id());
+    std::fs::write(&temp_path, contents)?;
+    if let Err(e) = std::fs::rename(&temp_path, path) {
+        std::fs::remove_file(&temp_path).ok();
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+// Removes the oldest saved inputs in `dir` so that at most `max` are left,
+// never removing the ones with a hash in `keep`
+fn prune(dir: &AbsoluteSystemPath, max: usize, keep: &HashSet<&str>) {
+    let Ok(entries) = dir.as_path().read_dir_utf8() else {
+        return;
+    };
+    let mut saved = entries
+        .flatten()
+        .filter(|entry| {
+            let file_name = entry.file_name();
+            file_name != LATEST_FILE
+                && file_name
+                    .strip_suffix(".json")
+                    .is_some_and(|hash| !keep.contains(hash))
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry)))
+        .collect::<Vec<_>>();
+    let Some(excess) = (saved.len() + keep.len()).checked_sub(max) else {
+        return;
+    };
+    saved.sort_by_key(|(modified, _)| *modified);
+    for (_, entry) in saved.into_iter().take(excess) {
+        if let Err(e) = std::fs::remove_file(entry.path()) {
+            debug!("unable to remove saved hash inputs {}: {e}", entry.path());
+        }
+    }
+}
+
+/// A hash input that differs between two runs of a task
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputChange {
+    pub input: Input,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    File(String),
+    EnvVar(String),
+    Dependency(String),
+    /// A setting of the task, such as its outputs
+    Setting(&'static str),
+    GlobalFile(String),
+    GlobalEnvVar(String),
+    GlobalSetting(&'static str),
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::File(path) => write!(f, "file\t{path}"),
+            Input::EnvVar(name) => write!(f, "env var\t{name}"),
+            Input::Dependency(task_id) => write!(f, "dependency\t{task_id}"),
+            Input::Setting(name) => write!(f, "setting\t{name}"),
+            Input::GlobalFile(path) => write!(f, "global file\t{path}"),
+            Input::GlobalEnvVar(name) => write!(f, "global env var\t{name}"),
+            Input::GlobalSetting(name) => write!(f, "global setting\t{name}"),
+        }
+    }
+}
+
+impl InputChange {
+    fn describe(&self) -> String {
+        let show_values = matches!(
+            self.input,
+            Input::Dependency(_) | Input::Setting(_) | Input::GlobalSetting(_)
+        );
+        match (&self.before, &self.after) {
+            (None, Some(after)) if show_values => format!("added ({after})"),
+            (Some(before), None) if show_values => format!("removed ({before})"),
+            (Some(before), Some(after)) if show_values => format!("{before} -> {after}"),
+            (None, _) => "added".to_string(),
+            (_, None) => "removed".to_string(),
+            (Some(_), Some(_)) => "changed".to_string(),
+        }
+    }
+}
+
+/// Finds the inputs that differ between two sets of hash inputs for a task
+pub fn diff(previous: &SavedHashInputs, current: &SavedHashInputs) -> Vec<InputChange> {
+    let mut changes = Vec::new();
+    let (before, after) = (&previous.task, &current.task);
+
+    diff_maps(&mut changes, &previous.files, &current.files, Input::File);
+    diff_maps(
+        &mut changes,
+        &before.env_vars,
+        &after.env_vars,
+        Input::EnvVar,
+    );
+    diff_maps(
+        &mut changes,
+        &before.dependencies,
+        &after.dependencies,
+        Input::Dependency,
+    );
+    let mut setting = |name, before: String, after: String| {
+        if before != after {
+            changes.push(InputChange {
+                input: Input::Setting(name),
+                before: Some(before).filter(|before| !before.is_empty()),
+                after: Some(after).filter(|after| !after.is_empty()),
+            });
+        }
+    };
+    setting(
+        "package directory",
+        before.package_dir.clone().unwrap_or_default(),
+        after.package_dir.clone().unwrap_or_default(),
+    );
+    setting(
+        "external dependencies",
+        before
+            .external_dependencies_hash
+            .clone()
+            .unwrap_or_default(),
+        after.external_dependencies_hash.clone().unwrap_or_default(),
+    );
+    setting(
+        "outputs",
+        before.outputs.join(", "),
+        after.outputs.join(", "),
+    );
+    setting(
+        "pass through args",
+        before.pass_through_args.join(" "),
+        after.pass_through_args.join(" "),
+    );
+    setting("env", before.env.join(", "), after.env.join(", "));
+    setting(
+        "passThroughEnv",
+        before.pass_through_env.join(", "),
+        after.pass_through_env.join(", "),
+    );
+    setting(
+        "envMode",
+        before.env_mode.to_string(),
+        after.env_mode.to_string(),
+    );
+
+    let (before, after) = (&previous.global, &current.global);
+    if before.hash != after.hash {
+        diff_maps(&mut changes, &before.files, &after.files, Input::GlobalFile);
+        diff_maps(
+            &mut changes,
+            &before.env_vars,
+            &after.env_vars,
+            Input::GlobalEnvVar,
+        );
+        let mut setting = |name, before: String, after: String| {
+            if before != after {
+                changes.push(InputChange {
+                    input: Input::GlobalSetting(name),
+                    before: Some(before).filter(|before| !before.is_empty()),
+                    after: Some(after).filter(|after| !after.is_empty()),
+                });
+            }
+        };
+        setting(
+            "root external dependencies",
+            before
+                .root_external_dependencies_hash
+                .clone()
+                .unwrap_or_default(),
+            after
+                .root_external_dependencies_hash
+                .clone()
+                .unwrap_or_default(),
+        );
+        setting(
+            "root internal dependencies",
+            before
+                .root_internal_dependencies_hash
+                .clone()
+                .unwrap_or_default(),
+            after
+                .root_internal_dependencies_hash
+                .clone()
+                .unwrap_or_default(),
+        );
+        setting(
+            "engines",
+            format_map(&before.engines),
+            format_map(&after.engines),
+        );
+        setting("globalEnv", before.env.join(", "), after.env.join(", "));
+        setting(
+            "globalPassThroughEnv",
+            before.pass_through_env.join(", "),
+            after.pass_through_env.join(", "),
+        );
+        setting(
+            "envMode",
+            before.env_mode.to_string(),
+            after.env_mode.to_string(),
+        );
+        setting(
+            "framework inference",
+            before.framework_inference.to_string(),
+            after.framework_inference.to_string(),
+        );
+    }
+
+    changes
+}
+
+fn diff_maps(
+    changes: &mut Vec<InputChange>,
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+    input: impl Fn(String) -> Input,
+) {
+    let removed = before
+        .iter()
+        .filter(|(key, _)| !after.contains_key(*key))
+        .map(|(key, value)| (key, Some(value), None));
+    let added_or_changed = after.iter().filter_map(|(key, value)| {
+        let previous = before.get(key);
+        (previous != Some(value)).then_some((key, previous, Some(value)))
+    });
+    let mut map_changes = removed.chain(added_or_changed).collect::<Vec<_>>();
+    map_changes.sort_by_key(|(key, _, _)| *key);
+    changes.extend(
+        map_changes
+            .into_iter()
+            .map(|(key, before, after)| InputChange {
+                input: input(key.clone()),
+                before: before.cloned(),
+                after: after.cloned(),
+            }),
+    );
+}
+
+fn format_map(map: &BTreeMap<String, String>) -> String {
+    map.iter()
+        .map(|(key, value)| format!("{key}@{value}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Prints which inputs changed for the tasks in this run, compared to either
+/// the last run of each task or the inputs saved for a specific hash
+pub fn explain(
+    repo_root: &AbsoluteSystemPath,
+    opts: &ExplainOpts,
+    current: &[SavedHashInputs],
+    color_config: ColorConfig,
+) -> Result<(), Error> {
+    let store = HashInputsStore::new(repo_root);
+
+    match opts {
+        ExplainOpts::Hash(hash) => {
+            let previous = store
+                .load(hash)?
+                .ok_or_else(|| Error::HashNotFound(hash.clone()))?;
+            let current = current
+                .iter()
+                .find(|inputs| inputs.task.task_id == previous.task.task_id)
+                .ok_or_else(|| Error::TaskNotInRun {
+                    task_id: previous.task.task_id.clone(),
+                    hash: hash.clone(),
+                })?;
+            print_explanation(current, Some(&previous), color_config)?;
+        }
+        ExplainOpts::LastRun => {
+            let latest = store.latest()?;
+            for inputs in current {
+                let previous = match latest.get(&inputs.task.task_id) {
+                    Some(hash) => store.load(hash)?,
+                    None => None,
+                };
+                print_explanation(inputs, previous.as_ref(), color_config)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_explanation(
+    current: &SavedHashInputs,
+    previous: Option<&SavedHashInputs>,
+    color_config: ColorConfig,
+) -> Result<(), Error> {
+    let task = &current.task;
+    let Some(previous) = previous else {
+        cprintln!(color_config, BOLD, "{} {}", task.task_id, task.hash);
+        cprintln!(color_config, GREY, "  no saved inputs to compare against");
+        return Ok(());
+    };
+    if previous.task.hash == task.hash {
+        cprintln!(color_config, BOLD, "{} {}", task.task_id, task.hash);
+        cprintln!(color_config, GREY, "  unchanged");
+        return Ok(());
+    }
+
+    cprintln!(
+        color_config,
+        BOLD,
+        "{} {} (was {})",
+        task.task_id,
+        task.hash,
+        previous.task.hash
+    );
+    let changes = diff(previous, current);
+    if changes.is_empty() {
+        // Inputs that aren't saved, e.g. the turbo version, changed
+        cprintln!(color_config, GREY, "  no saved inputs changed");
+        return Ok(());
+    }
+
+    let mut tab_writer = TabWriter::new(io::stdout()).minwidth(0).padding(2);
+    for change in &changes {
+        write!(tab_writer, "  {}\t", change.input)?;
+        cwriteln!(tab_writer, color_config, GREY, "{}", change.describe())?;
+    }
+    tab_writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn inputs(hash: &str) -> SavedHashInputs {
+        SavedHashInputs {
+            task: TaskHashInputs {
+                task_id: "web#build".to_string(),
+                hash: hash.to_string(),
+                package_dir: Some("apps/web".to_string()),
+                external_dependencies_hash: Some("abc".to_string()),
+                dependencies: BTreeMap::from([("ui#build".to_string(), "1111".to_string())]),
+                outputs: vec!["dist/**".to_string()],
+                pass_through_args: vec![],
+                env: vec!["API_*".to_string()],
+                env_vars: BTreeMap::from([("API_URL".to_string(), "aaaa".to_string())]),
+                pass_through_env: vec![],
+                env_mode: EnvMode::Strict,
+                framework: None,
+            },
+            files: BTreeMap::from([
+                ("package.json".to_string(), "1".to_string()),
+                ("src/index.ts".to_string(), "2".to_string()),
+            ]),
+            global: Arc::new(GlobalHashInputs {
+                hash: "global".to_string(),
+                files: BTreeMap::new(),
+                root_external_dependencies_hash: None,
+                root_internal_dependencies_hash: None,
+                engines: BTreeMap::new(),
+                env: vec![],
+                env_vars: BTreeMap::new(),
+                pass_through_env: vec![],
+                env_mode: EnvMode::Strict,
+                framework_inference: true,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = inputs("1234");
+        let mut current = inputs("5678");
+        current.files.remove("package.json");
+        current
+            .files
+            .insert("src/index.ts".to_string(), "3".to_string());
+        current
+            .files
+            .insert("src/new.ts".to_string(), "4".to_string());
+        current
+            .task
+            .env_vars
+            .insert("API_URL".to_string(), "bbbb".to_string());
+        current
+            .task
+            .dependencies
+            .insert("ui#build".to_string(), "2222".to_string());
+        current.task.outputs.push(".next/**".to_string());
+
+        assert_eq!(
+            diff(&previous, &current)
+                .iter()
+                .map(|change| (change.input.to_string(), change.describe()))
+                .collect::<Vec<_>>(),
+            [
+                ("file\tpackage.json", "removed"),
+                ("file\tsrc/index.ts", "changed"),
+                ("file\tsrc/new.ts", "added"),
+                ("env var\tAPI_URL", "changed"),
+                ("dependency\tui#build", "1111 -> 2222"),
+                ("setting\toutputs", "dist/** -> dist/**, .next/**"),
+            ]
+            .map(|(input, change)| (input.to_string(), change.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_diff_global() {
+        let previous = inputs("1234");
+        let mut current = inputs("5678");
+        let global = Arc::make_mut(&mut current.global);
+        global.hash = "new global".to_string();
+        global.files.insert(".env".to_string(), "1".to_string());
+        global.env.push("CI".to_string());
+
+        assert_eq!(
+            diff(&previous, &current),
+            [
+                InputChange {
+                    input: Input::GlobalFile(".env".to_string()),
+                    before: None,
+                    after: Some("1".to_string()),
+                },
+                InputChange {
+                    input: Input::GlobalSetting("globalEnv"),
+                    before: None,
+                    after: Some("CI".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let store = HashInputsStore::new(repo_root);
+        assert!(store.latest().unwrap().is_empty());
+
+        let global = inputs("1234").global;
+        store.save(&global, &[inputs("1234")]).unwrap();
+        store.save(&global, &[inputs("5678")]).unwrap();
+
+        assert_eq!(
+            store.latest().unwrap(),
+            HashMap::from([("web#build".to_string(), "5678".to_string())])
+        );
+        assert_eq!(store.load("1234").unwrap(), Some(inputs("1234")));
+        assert_eq!(store.load("9999").unwrap(), None);
+
+        // The global inputs are only saved once for both runs
+        let global_dir = repo_root.join_components(&[".turbo", "hash-inputs", "global"]);
+        assert_eq!(global_dir.as_path().read_dir_utf8().unwrap().count(), 1);
+
+        // Without its global inputs, a task's inputs can't be compared
+        global_dir
+            .join_component("global.json")
+            .remove_file()
+            .unwrap();
+        assert_eq!(store.load("1234").unwrap(), None);
+    }
+}
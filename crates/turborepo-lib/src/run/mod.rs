@@ -1,8 +1,10 @@
 #![allow(dead_code)]
 
+pub(crate) mod affected_tasks;
 pub mod builder;
 mod cache;
 mod error;
+pub(crate) mod explain;
 pub(crate) mod global_hash;
 mod graph_visualizer;
 pub(crate) mod package_discovery;
@@ -14,7 +16,7 @@ mod ui;
 pub mod watch;
 
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::Write,
     sync::Arc,
     time::Duration,
@@ -23,8 +25,8 @@ use std::{
 pub use cache::{CacheOutput, ConfigCache, Error as CacheError, RunCache, TaskCache};
 use chrono::{DateTime, Local};
 use rayon::iter::ParallelBridge;
-use tokio::{select, task::JoinHandle};
-use tracing::{debug, instrument};
+use tokio::{select, sync::OnceCell, task::JoinHandle};
+use tracing::{debug, instrument, warn};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
 use turborepo_api_client::{APIAuth, APIClient};
 use turborepo_ci::Vendor;
@@ -40,15 +42,24 @@ use turborepo_ui::{
 pub use crate::run::error::Error;
 use crate::{
     cli::EnvMode,
-    engine::Engine,
+    engine::{Engine, TaskNode},
     microfrontends::MicrofrontendsConfigs,
-    opts::Opts,
+    opts::{Opts, ScopeOpts},
     process::ProcessManager,
-    run::{global_hash::get_global_hash_inputs, summary::RunTracker, task_access::TaskAccess},
+    run::{
+        affected_tasks::TaskAffectedReason,
+        explain::{GlobalHashInputs, HashInputsStore},
+        global_hash::{get_global_hash_inputs, GlobalHashableInputs},
+        summary::{task_durations, RunTracker},
+        task_access::TaskAccess,
+        task_id::TaskId,
+    },
     signal::SignalHandler,
     task_graph::Visitor,
-    task_hash::{get_external_deps_hash, get_internal_deps_hash, PackageInputsHashes},
-    turbo_json::{TurboJson, UIMode},
+    task_hash::{
+        get_external_deps_hash, get_internal_deps_hash, PackageInputsHashes, TaskHashTracker,
+    },
+    turbo_json::{TurboJson, TurboJsonLoader, UIMode},
     DaemonClient, DaemonConnector,
 };
 
@@ -67,6 +78,7 @@ pub struct Run {
     filtered_pkgs: HashSet<PackageName>,
     pkg_dep_graph: Arc<PackageGraph>,
     root_turbo_json: TurboJson,
+    turbo_json_loader: TurboJsonLoader,
     scm: SCM,
     run_cache: Arc<RunCache>,
     signal_handler: SignalHandler,
@@ -75,6 +87,10 @@ pub struct Run {
     daemon: Option<DaemonClient<DaemonConnector>>,
     should_print_prelude: bool,
     micro_frontend_configs: Option<MicrofrontendsConfigs>,
+    task_hash_tracker: Arc<OnceCell<TaskHashTracker>>,
+    // The hashes of tasks that this run's tasks depend on, but which were run
+    // separately
+    dependency_hashes: HashMap<TaskId<'static>, String>,
 }
 
 type UIResult<T> = Result<Option<(T, JoinHandle<Result<(), turborepo_ui::Error>>)>, Error>;
@@ -138,11 +154,16 @@ impl Run {
         &self.root_turbo_json
     }
 
+    pub fn turbo_json_loader(&self) -> &TurboJsonLoader {
+        &self.turbo_json_loader
+    }
+
     pub fn create_run_for_non_interruptible_tasks(&self) -> Self {
         let mut new_run = Self {
             // ProcessManager is shared via an `Arc`,
             // so we want to explicitly recreate it instead of cloning
             processes: ProcessManager::new(self.processes.use_pty()),
+            task_hash_tracker: Default::default(),
             ..self.clone()
         };
 
@@ -153,13 +174,51 @@ impl Run {
     }
 
     pub fn create_run_for_interruptible_tasks(&self) -> Self {
-        let mut new_run = self.clone();
+        let mut new_run = Self {
+            task_hash_tracker: Default::default(),
+            ..self.clone()
+        };
         let new_engine = new_run.engine.create_engine_for_interruptible_tasks();
         new_run.engine = Arc::new(new_engine);
 
         new_run
     }
 
+    pub fn create_run_for_non_persistent_tasks(&self) -> Self {
+        let mut new_run = Self {
+            task_hash_tracker: Default::default(),
+            ..self.clone()
+        };
+        let new_engine = new_run.engine.create_engine_for_non_persistent_tasks();
+        new_run.engine = Arc::new(new_engine);
+
+        new_run
+    }
+
+    /// Creates a run for a single persistent task so that it can be stopped
+    /// without stopping any other tasks
+    pub fn create_run_for_persistent_task(&self, task_id: &TaskId) -> Self {
+        let mut new_run = Self {
+            // ProcessManager is shared via an `Arc`,
+            // so we want to explicitly recreate it instead of cloning
+            processes: ProcessManager::new(self.processes.use_pty()),
+            task_hash_tracker: Default::default(),
+            ..self.clone()
+        };
+
+        let new_engine = new_run.engine.create_engine_for_persistent_task(task_id);
+        new_run.engine = Arc::new(new_engine);
+
+        new_run
+    }
+
+    /// Passes in the hashes of tasks that were run separately, e.g. the
+    /// dependencies of a run created by `create_run_for_persistent_task`
+    pub fn with_dependency_hashes(mut self, hashes: HashMap<TaskId<'static>, String>) -> Self {
+        self.dependency_hashes = hashes;
+        self
+    }
+
     // Produces the transitive closure of the filtered packages,
     // i.e. the packages relevant for this run.
     #[instrument(skip(self), ret)]
@@ -221,6 +280,7 @@ impl Run {
     pub fn should_start_ui(&self) -> Result<bool, Error> {
         Ok(self.opts.run_opts.ui_mode.use_tui()
             && self.opts.run_opts.dry_run.is_none()
+            && self.opts.run_opts.explain.is_none()
             && tui::terminal_big_enough()?)
     }
 
@@ -277,6 +337,190 @@ impl Run {
         }
     }
 
+    /// Calculates the files that make up the inputs of every task in the
+    /// engine along with their hashes
+    pub fn package_inputs_hashes(&self) -> Result<PackageInputsHashes, Error> {
+        let workspaces = self.pkg_dep_graph.packages().collect();
+        Ok(PackageInputsHashes::calculate_file_hashes(
+            &self.scm,
+            self.engine.tasks().par_bridge(),
+            workspaces,
+            self.engine.task_definitions(),
+            &self.repo_root,
+            &self.run_telemetry,
+            &self.daemon,
+        )?)
+    }
+
+    /// Finds the tasks in the engine that are affected by the changes in the
+    /// affected range of `scope_opts`. Returns `None` if there is no affected
+    /// range.
+    pub fn affected_tasks(
+        &self,
+        scope_opts: &ScopeOpts,
+        package_inputs_hashes: &PackageInputsHashes,
+    ) -> Result<Option<HashMap<TaskId<'static>, TaskAffectedReason>>, Error> {
+        let Some(changes) = scope::resolve_changes(
+            scope_opts,
+            &self.repo_root,
+            &self.pkg_dep_graph,
+            &self.scm,
+            &self.root_turbo_json,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(affected_tasks::affected_tasks(
+            &self.engine,
+            &self.pkg_dep_graph,
+            &self.repo_root,
+            &changes,
+            package_inputs_hashes,
+        )))
+    }
+
+    /// Hashes the external and internal dependencies of the root package.
+    /// Both are `None` in single package mode.
+    fn root_dependencies_hashes(&self) -> Result<(Option<String>, Option<String>), Error> {
+        let root_workspace = self
+            .pkg_dep_graph
+            .package_info(&PackageName::Root)
+            .expect("must have root workspace");
+
+        let is_monorepo = !self.opts.run_opts.single_package;
+
+        let root_external_dependencies_hash =
+            is_monorepo.then(|| get_external_deps_hash(&root_workspace.transitive_dependencies));
+
+        let root_internal_dependencies_hash = is_monorepo
+            .then(|| {
+                get_internal_deps_hash(
+                    &self.scm,
+                    &self.repo_root,
+                    self.pkg_dep_graph
+                        .root_internal_package_dependencies_paths(),
+                )
+            })
+            .transpose()?;
+
+        Ok((
+            root_external_dependencies_hash,
+            root_internal_dependencies_hash,
+        ))
+    }
+
+    fn global_hash_inputs<'a>(
+        &'a self,
+        root_external_dependencies_hash: Option<&'a str>,
+        root_internal_dependencies_hash: Option<&'a str>,
+    ) -> Result<GlobalHashableInputs<'a>, Error> {
+        let root_workspace = self
+            .pkg_dep_graph
+            .package_info(&PackageName::Root)
+            .expect("must have root workspace");
+
+        let env_mode = self.opts.run_opts.env_mode;
+        let pass_through_env = match env_mode {
+            EnvMode::Loose => {
+                // Remove the passthroughs from hash consideration if we're explicitly loose.
+                None
+            }
+            EnvMode::Strict => self.root_turbo_json.global_pass_through_env.as_deref(),
+        };
+
+        Ok(get_global_hash_inputs(
+            root_external_dependencies_hash,
+            root_internal_dependencies_hash,
+            root_workspace,
+            &self.repo_root,
+            self.pkg_dep_graph.package_manager(),
+            self.pkg_dep_graph.lockfile(),
+            &self.root_turbo_json.global_deps,
+            &self.env_at_execution_start,
+            &self.root_turbo_json.global_env,
+            pass_through_env,
+            env_mode,
+            self.opts.run_opts.framework_inference,
+            &self.scm,
+        )?)
+    }
+
+    fn global_env(
+        &self,
+        global_hash_inputs: &GlobalHashableInputs<'_>,
+    ) -> Result<EnvironmentVariableMap, Error> {
+        let mut env = self
+            .env_at_execution_start
+            .from_wildcards(global_hash_inputs.pass_through_env.unwrap_or_default())
+            .map_err(Error::Env)?;
+        if let Some(resolved_global) = &global_hash_inputs.resolved_env_vars {
+            env.union(&resolved_global.all);
+        }
+        Ok(env)
+    }
+
+    fn run_tracker(&self) -> RunTracker {
+        RunTracker::new(
+            self.start_at,
+            self.opts.synthesize_command(),
+            self.opts.scope_opts.pkg_inference_root.as_deref(),
+            &self.env_at_execution_start,
+            &self.repo_root,
+            self.version,
+            self.opts.run_opts.experimental_space_id.clone(),
+            self.api_client.clone(),
+            self.api_auth.clone(),
+            Vendor::get_user(),
+            &self.scm,
+        )
+    }
+
+    /// Calculates the hash, expanded inputs and cache status of every task in
+    /// the engine without running any of them. The result is calculated once
+    /// per run. Once the run has started, the tracker it uses is returned
+    /// instead, which also records which tasks succeeded.
+    pub async fn task_hashes(&self) -> Result<&TaskHashTracker, Error> {
+        self.task_hash_tracker
+            .get_or_try_init(|| async {
+                let package_inputs_hashes = self.package_inputs_hashes()?;
+                let (root_external_dependencies_hash, root_internal_dependencies_hash) =
+                    self.root_dependencies_hashes()?;
+                let global_hash_inputs = self.global_hash_inputs(
+                    root_external_dependencies_hash.as_deref(),
+                    root_internal_dependencies_hash.as_deref(),
+                )?;
+                let global_hash = global_hash_inputs.calculate_global_hash();
+                let global_env = self.global_env(&global_hash_inputs)?;
+
+                let mut visitor = Visitor::new(
+                    self.pkg_dep_graph.clone(),
+                    self.run_cache.clone(),
+                    self.run_tracker(),
+                    &self.task_access,
+                    &self.opts.run_opts,
+                    package_inputs_hashes,
+                    &self.env_at_execution_start,
+                    &global_hash,
+                    self.color_config,
+                    self.processes.clone(),
+                    &self.repo_root,
+                    global_env,
+                    None,
+                    false,
+                    self.micro_frontend_configs.as_ref(),
+                )
+                .await;
+                visitor.dry_run();
+                visitor
+                    .visit(self.engine.clone(), &self.run_telemetry)
+                    .await?;
+
+                Ok::<_, Error>(visitor.task_hash_tracker())
+            })
+            .await
+    }
+
     pub async fn run(&self, ui_sender: Option<UISender>, is_watch: bool) -> Result<i32, Error> {
         let skip_cache_writes = self.opts.cache_opts.cache.skip_writes();
         if let Some(subscriber) = self.signal_handler.subscribe() {
@@ -365,90 +609,44 @@ impl Run {
             return Ok(0);
         }
 
-        let workspaces = self.pkg_dep_graph.packages().collect();
-        let package_inputs_hashes = PackageInputsHashes::calculate_file_hashes(
-            &self.scm,
-            self.engine.tasks().par_bridge(),
-            workspaces,
-            self.engine.task_definitions(),
-            &self.repo_root,
-            &self.run_telemetry,
-            &self.daemon,
-        )?;
+        let package_inputs_hashes = self.package_inputs_hashes()?;
 
-        let root_workspace = self
-            .pkg_dep_graph
-            .package_info(&PackageName::Root)
-            .expect("must have root workspace");
-
-        let is_monorepo = !self.opts.run_opts.single_package;
-
-        let root_external_dependencies_hash =
-            is_monorepo.then(|| get_external_deps_hash(&root_workspace.transitive_dependencies));
-
-        let root_internal_dependencies_hash = is_monorepo
-            .then(|| {
-                get_internal_deps_hash(
-                    &self.scm,
-                    &self.repo_root,
-                    self.pkg_dep_graph
-                        .root_internal_package_dependencies_paths(),
-                )
-            })
-            .transpose()?;
-
-        let global_hash_inputs = {
-            let env_mode = self.opts.run_opts.env_mode;
-            let pass_through_env = match env_mode {
-                EnvMode::Loose => {
-                    // Remove the passthroughs from hash consideration if we're explicitly loose.
-                    None
-                }
-                EnvMode::Strict => self.root_turbo_json.global_pass_through_env.as_deref(),
-            };
-
-            get_global_hash_inputs(
-                root_external_dependencies_hash.as_deref(),
-                root_internal_dependencies_hash.as_deref(),
-                root_workspace,
-                &self.repo_root,
-                self.pkg_dep_graph.package_manager(),
-                self.pkg_dep_graph.lockfile(),
-                &self.root_turbo_json.global_deps,
-                &self.env_at_execution_start,
-                &self.root_turbo_json.global_env,
-                pass_through_env,
-                env_mode,
-                self.opts.run_opts.framework_inference,
-                &self.scm,
-            )?
+        let affected_tasks = match self.opts.scope_opts.affected_by_inputs {
+            true => self.affected_tasks(&self.opts.scope_opts, &package_inputs_hashes)?,
+            false => None,
         };
-        let global_hash = global_hash_inputs.calculate_global_hash();
-
-        let global_env = {
-            let mut env = self
-                .env_at_execution_start
-                .from_wildcards(global_hash_inputs.pass_through_env.unwrap_or_default())
-                .map_err(Error::Env)?;
-            if let Some(resolved_global) = &global_hash_inputs.resolved_env_vars {
-                env.union(&resolved_global.all);
+        let engine = match &affected_tasks {
+            Some(affected_tasks) => {
+                Arc::new(self.engine.create_engine_for_tasks(affected_tasks.keys()))
             }
-            env
+            None => self.engine.clone(),
         };
 
-        let run_tracker = RunTracker::new(
-            self.start_at,
-            self.opts.synthesize_command(),
-            self.opts.scope_opts.pkg_inference_root.as_deref(),
-            &self.env_at_execution_start,
-            &self.repo_root,
-            self.version,
-            self.opts.run_opts.experimental_space_id.clone(),
-            self.api_client.clone(),
-            self.api_auth.clone(),
-            Vendor::get_user(),
-            &self.scm,
-        );
+        let (root_external_dependencies_hash, root_internal_dependencies_hash) =
+            self.root_dependencies_hashes()?;
+        let global_hash_inputs = self.global_hash_inputs(
+            root_external_dependencies_hash.as_deref(),
+            root_internal_dependencies_hash.as_deref(),
+        )?;
+        let global_hash = global_hash_inputs.calculate_global_hash();
+        let global_env = self.global_env(&global_hash_inputs)?;
+
+        let mut run_tracker = self.run_tracker();
+        if self.opts.run_opts.critical_path {
+            let durations = task_durations(
+                &self.repo_root,
+                &self.opts.cache_opts.cache_dir,
+                engine.tasks().filter_map(|task| match task {
+                    TaskNode::Task(task_id) => Some(task_id),
+                    TaskNode::Root => None,
+                }),
+            );
+            run_tracker =
+                run_tracker.with_critical_paths(Arc::new(engine.critical_paths(&durations)));
+        }
+        if let Some(affected_tasks) = affected_tasks {
+            run_tracker = run_tracker.with_affected_tasks(Arc::new(affected_tasks));
+        }
 
         let mut visitor = Visitor::new(
             self.pkg_dep_graph.clone(),
@@ -469,17 +667,20 @@ impl Run {
         )
         .await;
 
-        if self.opts.run_opts.dry_run.is_some() {
+        if self.opts.run_opts.dry_run.is_some() || self.opts.run_opts.explain.is_some() {
             visitor.dry_run();
         }
+        let task_hash_tracker = visitor.task_hash_tracker();
+        task_hash_tracker.insert_dependency_hashes(self.dependency_hashes.clone());
+        // Keeps the tracker around for after the run, unless the hashes were
+        // already calculated without running
+        self.task_hash_tracker.set(task_hash_tracker.clone()).ok();
 
         // we look for this log line to mark the start of the run
         // in benchmarks, so please don't remove it
         debug!("running visitor");
 
-        let errors = visitor
-            .visit(self.engine.clone(), &self.run_telemetry)
-            .await?;
+        let errors = visitor.visit(engine.clone(), &self.run_telemetry).await?;
 
         let exit_code = errors
             .iter()
@@ -497,17 +698,44 @@ impl Run {
             writeln!(std::io::stderr(), "{error_prefix}{err}").ok();
         }
 
+        let global_inputs = Arc::new(GlobalHashInputs::new(&global_hash_inputs, &global_hash));
+        let hash_inputs = explain::collect(&task_hash_tracker, &global_inputs);
+        if let Some(explain_opts) = &self.opts.run_opts.explain {
+            explain::explain(
+                &self.repo_root,
+                explain_opts,
+                &hash_inputs,
+                self.color_config,
+            )?;
+            return Ok(exit_code);
+        }
+        // The inputs are saved while the run summary is written so that they
+        // don't hold up the run
+        let save_hash_inputs = self.opts.run_opts.dry_run.is_none().then(|| {
+            let store = HashInputsStore::new(&self.repo_root);
+            tokio::task::spawn_blocking(move || store.save(&global_inputs, &hash_inputs))
+        });
+
         visitor
             .finish(
                 exit_code,
                 &self.filtered_pkgs,
                 global_hash_inputs,
-                &self.engine,
+                &engine,
                 &self.env_at_execution_start,
                 self.opts.scope_opts.pkg_inference_root.as_deref(),
             )
             .await?;
 
+        // Failing to save the inputs shouldn't fail the run
+        if let Some(save_hash_inputs) = save_hash_inputs {
+            match save_hash_inputs.await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => warn!("{err}"),
+                Err(err) => warn!("failed to save hash inputs: {err}"),
+            }
+        }
+
         Ok(exit_code)
     }
 }
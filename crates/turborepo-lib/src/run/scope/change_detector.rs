@@ -25,6 +25,15 @@ pub trait GitChangeDetector {
     ) -> Result<HashMap<PackageName, PackageInclusionReason>, ResolutionError>;
 }
 
+/// The files that changed between two git refs along with the packages that
+/// they changed
+pub struct Changes {
+    /// `None` if the changed files couldn't be determined, in which case every
+    /// package is changed
+    pub files: Option<HashSet<AnchoredSystemPathBuf>>,
+    pub packages: HashMap<PackageName, PackageInclusionReason>,
+}
+
 pub struct ScopeChangeDetector<'a> {
     turbo_root: &'a AbsoluteSystemPath,
     change_mapper: ChangeMapper<'a, GlobalDepsPackageChangeMapper<'a>>,
@@ -84,17 +93,15 @@ impl<'a> ScopeChangeDetector<'a> {
 
         Some(Some(content))
     }
-}
 
-impl<'a> GitChangeDetector for ScopeChangeDetector<'a> {
-    fn changed_packages(
+    pub fn changes(
         &self,
         from_ref: Option<&str>,
         to_ref: Option<&str>,
         include_uncommitted: bool,
         allow_unknown_objects: bool,
         merge_base: bool,
-    ) -> Result<HashMap<PackageName, PackageInclusionReason>, ResolutionError> {
+    ) -> Result<Changes, ResolutionError> {
         let changed_files = match self.scm.changed_files(
             self.turbo_root,
             from_ref,
@@ -105,7 +112,7 @@ impl<'a> GitChangeDetector for ScopeChangeDetector<'a> {
         )? {
             Err(InvalidRange { from_ref, to_ref }) => {
                 debug!("all packages changed");
-                return Ok(self
+                let packages = self
                     .pkg_graph
                     .packages()
                     .map(|(name, _)| {
@@ -117,7 +124,11 @@ impl<'a> GitChangeDetector for ScopeChangeDetector<'a> {
                             }),
                         )
                     })
-                    .collect());
+                    .collect();
+                return Ok(Changes {
+                    files: None,
+                    packages,
+                });
             }
             Ok(changed_files) => changed_files,
         };
@@ -132,17 +143,16 @@ impl<'a> GitChangeDetector for ScopeChangeDetector<'a> {
                 .collect::<Vec<String>>()
         );
 
-        match self
+        let packages = match self
             .change_mapper
-            .changed_packages(changed_files, lockfile_contents)?
+            .changed_packages(changed_files.clone(), lockfile_contents)?
         {
             PackageChanges::All(reason) => {
                 debug!("all packages changed: {:?}", reason);
-                Ok(self
-                    .pkg_graph
+                self.pkg_graph
                     .packages()
                     .map(|(name, _)| (name.to_owned(), PackageInclusionReason::All(reason.clone())))
-                    .collect())
+                    .collect()
             }
             PackageChanges::Some(packages) => {
                 debug!(
@@ -154,11 +164,36 @@ impl<'a> GitChangeDetector for ScopeChangeDetector<'a> {
                         .collect::<Vec<String>>()
                 );
 
-                Ok(packages
+                packages
                     .iter()
                     .map(|(package, reason)| (package.name.clone(), reason.clone()))
-                    .collect())
+                    .collect()
             }
-        }
+        };
+
+        Ok(Changes {
+            files: Some(changed_files),
+            packages,
+        })
+    }
+}
+
+impl<'a> GitChangeDetector for ScopeChangeDetector<'a> {
+    fn changed_packages(
+        &self,
+        from_ref: Option<&str>,
+        to_ref: Option<&str>,
+        include_uncommitted: bool,
+        allow_unknown_objects: bool,
+        merge_base: bool,
+    ) -> Result<HashMap<PackageName, PackageInclusionReason>, ResolutionError> {
+        self.changes(
+            from_ref,
+            to_ref,
+            include_uncommitted,
+            allow_unknown_objects,
+            merge_base,
+        )
+        .map(|changes| changes.packages)
     }
 }
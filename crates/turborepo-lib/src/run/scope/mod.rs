@@ -5,6 +5,8 @@ pub mod target_selector;
 
 use std::collections::HashMap;
 
+pub use change_detector::Changes;
+use change_detector::ScopeChangeDetector;
 use filter::{FilterResolver, PackageInference};
 use turbopath::AbsoluteSystemPath;
 use turborepo_repository::{
@@ -38,3 +40,29 @@ pub fn resolve_packages(
     )?
     .resolve(&opts.affected_range, &opts.get_filters())
 }
+
+/// Returns the changed files and packages within the affected range, or
+/// `None` if there isn't an affected range
+#[tracing::instrument(skip(opts, pkg_graph, scm, root_turbo_json))]
+pub fn resolve_changes(
+    opts: &ScopeOpts,
+    turbo_root: &AbsoluteSystemPath,
+    pkg_graph: &PackageGraph,
+    scm: &SCM,
+    root_turbo_json: &TurboJson,
+) -> Result<Option<Changes>, ResolutionError> {
+    let Some((from_ref, to_ref)) = &opts.affected_range else {
+        return Ok(None);
+    };
+    let global_deps = opts
+        .global_deps
+        .iter()
+        .map(|s| s.as_str())
+        .chain(root_turbo_json.global_deps.iter().map(|s| s.as_str()));
+    let change_detector =
+        ScopeChangeDetector::new(turbo_root, scm, pkg_graph, global_deps, vec![])?;
+
+    change_detector
+        .changes(from_ref.as_deref(), to_ref.as_deref(), true, true, true)
+        .map(Some)
+}
@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, time::Duration};
 
 use chrono::{DateTime, Local};
 use serde::Serialize;
@@ -34,6 +34,8 @@ pub struct ExecutionSummary<'a> {
     success: usize,
     // number of tasks that exited with failure
     failed: usize,
+    // number of tasks that were killed for exceeding their timeout (included in failed)
+    timed_out: usize,
     // number of tasks that had a cache hit
     cached: usize,
     // number of tasks that started
@@ -59,6 +61,7 @@ impl<'a> ExecutionSummary<'a> {
             command,
             success: state.success,
             failed: state.failed,
+            timed_out: state.timed_out,
             cached: state.cached,
             attempted: state.attempted,
             // We're either at some path in the repo, or at the root, which is an empty path
@@ -128,6 +131,21 @@ impl<'a> ExecutionSummary<'a> {
             line_data.push(("Failed", formatted.join(", ")));
         }
 
+        let mut timed_out: Vec<_> = failed_tasks
+            .iter()
+            .filter(|task| {
+                task.shared
+                    .execution
+                    .as_ref()
+                    .is_some_and(|execution| execution.timed_out)
+            })
+            .map(|task| color!(ui, BOLD_RED, "{}", task.task_id).to_string())
+            .collect();
+        if !timed_out.is_empty() {
+            timed_out.sort();
+            line_data.push(("Timed out", timed_out.join(", ")));
+        }
+
         let max_length = line_data
             .iter()
             .map(|(header, _)| header.len())
@@ -171,6 +189,7 @@ impl<'a> ExecutionSummary<'a> {
 pub struct SummaryState {
     pub attempted: usize,
     pub failed: usize,
+    pub timed_out: usize,
     pub cached: usize,
     pub success: usize,
     pub tasks: Vec<TaskState>,
@@ -187,6 +206,10 @@ impl SummaryState {
         match event {
             Event::Building => self.attempted += 1,
             Event::BuildFailed => self.failed += 1,
+            Event::TimedOut => {
+                self.failed += 1;
+                self.timed_out += 1;
+            }
             Event::Cached => self.cached += 1,
             Event::Built => self.success += 1,
             Event::Canceled => (),
@@ -213,6 +236,8 @@ struct TrackerMessage {
 enum Event {
     Building,
     BuildFailed,
+    // Killed after exceeding the task timeout
+    TimedOut,
     Cached,
     Built,
     // Canceled due to external signal or internal failure
@@ -227,6 +252,16 @@ pub struct TaskExecutionSummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    // number of times the task was run, including retries (0 for cache hits).
+    // Only included when the task was retried.
+    #[serde(skip_serializing_if = "is_single_attempt")]
+    pub attempts: u32,
+}
+
+fn is_single_attempt(attempts: &u32) -> bool {
+    *attempts <= 1
 }
 
 impl TaskExecutionSummary {
@@ -348,6 +383,8 @@ impl TaskTracker<chrono::DateTime<Local>> {
             // Go synthesizes a zero exit code on cache hits
             exit_code: Some(0),
             error: None,
+            timed_out: false,
+            attempts: 0,
         };
 
         let state = TaskState {
@@ -364,7 +401,7 @@ impl TaskTracker<chrono::DateTime<Local>> {
         execution
     }
 
-    pub async fn build_succeeded(self, exit_code: i32) -> TaskExecutionSummary {
+    pub async fn build_succeeded(self, exit_code: i32, attempts: u32) -> TaskExecutionSummary {
         let Self {
             sender,
             started_at,
@@ -377,6 +414,8 @@ impl TaskTracker<chrono::DateTime<Local>> {
             end_time: ended_at.timestamp_millis(),
             exit_code: Some(exit_code),
             error: None,
+            timed_out: false,
+            attempts,
         };
 
         let state = TaskState {
@@ -397,6 +436,7 @@ impl TaskTracker<chrono::DateTime<Local>> {
         self,
         exit_code: Option<i32>,
         error: impl fmt::Display,
+        attempts: u32,
     ) -> TaskExecutionSummary {
         let Self {
             sender,
@@ -410,6 +450,8 @@ impl TaskTracker<chrono::DateTime<Local>> {
             end_time: ended_at.timestamp_millis(),
             exit_code,
             error: Some(error.to_string()),
+            timed_out: false,
+            attempts,
         };
 
         let state = TaskState {
@@ -425,6 +467,41 @@ impl TaskTracker<chrono::DateTime<Local>> {
             .expect("summary state thread finished");
         execution
     }
+
+    pub async fn timed_out(self, timeout: Duration, attempts: u32) -> TaskExecutionSummary {
+        let Self {
+            sender,
+            started_at,
+            task_id,
+        } = self;
+
+        let ended_at = Local::now();
+        let execution = TaskExecutionSummary {
+            start_time: started_at.timestamp_millis(),
+            end_time: ended_at.timestamp_millis(),
+            // The task was killed so there's no meaningful exit code
+            exit_code: None,
+            error: Some(format!(
+                "timed out after {}",
+                humantime::format_duration(timeout)
+            )),
+            timed_out: true,
+            attempts,
+        };
+
+        let state = TaskState {
+            task_id,
+            execution: Some(execution.clone()),
+        };
+        sender
+            .send(TrackerMessage {
+                event: Event::TimedOut,
+                state: Some(state),
+            })
+            .await
+            .expect("summary state thread finished");
+        execution
+    }
 }
 
 #[cfg(test)]
@@ -442,12 +519,13 @@ mod test {
         let bar = TaskId::new("bar", "build");
         let baz = TaskId::new("baz", "build");
         let boo = TaskId::new("boo", "build");
+        let qux = TaskId::new("qux", "build");
         let mut tasks = Vec::new();
         {
             let tracker = summary.task_tracker(foo.clone());
             tasks.push(tokio::spawn(async move {
                 let tracker = tracker.start().await;
-                tracker.build_succeeded(0).await;
+                tracker.build_succeeded(0, 1).await;
             }));
         }
         {
@@ -461,7 +539,7 @@ mod test {
             let tracker = summary.task_tracker(baz.clone());
             tasks.push(tokio::spawn(async move {
                 let tracker = tracker.start().await;
-                tracker.build_failed(Some(1), "big bad error").await;
+                tracker.build_failed(Some(1), "big bad error", 3).await;
             }));
         }
         {
@@ -471,26 +549,44 @@ mod test {
                 tracker.cancel();
             }));
         }
+        {
+            let tracker = summary.task_tracker(qux.clone());
+            tasks.push(tokio::spawn(async move {
+                let tracker = tracker.start().await;
+                tracker
+                    .timed_out(Duration::seconds(30).to_std().unwrap(), 1)
+                    .await;
+            }));
+        }
         for task in tasks {
             task.await.unwrap();
         }
 
         let state = summary.finish().await.unwrap();
-        assert_eq!(state.attempted, 4);
+        assert_eq!(state.attempted, 5);
         assert_eq!(state.cached, 1);
-        assert_eq!(state.failed, 1);
+        assert_eq!(state.failed, 2);
+        assert_eq!(state.timed_out, 1);
         assert_eq!(state.success, 1);
         let foo_state = state.tasks.iter().find(|task| task.task_id == foo).unwrap();
         assert_eq!(foo_state.execution.as_ref().unwrap().exit_code, Some(0));
         let bar_state = state.tasks.iter().find(|task| task.task_id == bar).unwrap();
         assert_eq!(bar_state.execution.as_ref().unwrap().exit_code, Some(0));
+        assert_eq!(bar_state.execution.as_ref().unwrap().attempts, 0);
         let baz_state = state.tasks.iter().find(|task| task.task_id == baz).unwrap();
         assert_eq!(baz_state.execution.as_ref().unwrap().exit_code, Some(1));
+        assert_eq!(baz_state.execution.as_ref().unwrap().attempts, 3);
         let boo_state = state.tasks.iter().find(|task| task.task_id == boo);
         assert!(
             boo_state.is_none(),
             "canceling doesn't produce execution data"
         );
+        let qux_state = state.tasks.iter().find(|task| task.task_id == qux).unwrap();
+        let qux_execution = qux_state.execution.as_ref().unwrap();
+        assert!(qux_execution.timed_out);
+        assert!(qux_execution.is_failure());
+        assert_eq!(qux_execution.exit_code, None);
+        assert_eq!(qux_execution.error.as_deref(), Some("timed out after 30s"));
     }
 
     #[tokio::test]
@@ -504,7 +600,7 @@ mod test {
         let tracker = tracker.start().await;
 
         tokio::time::sleep(sleep_duration.to_std().unwrap()).await;
-        tracker.build_succeeded(0).await;
+        tracker.build_succeeded(0, 1).await;
         let mut state = summary.finish().await.unwrap();
         assert_eq!(state.tasks.len(), 1);
         let summary = state.tasks.pop().unwrap().execution.unwrap();
@@ -523,7 +619,9 @@ mod test {
             start_time: 123,
             end_time: 234,
             exit_code: Some(0),
-            error: None
+            error: None,
+            timed_out: false,
+            attempts: 1,
         },
         json!({ "startTime": 123, "endTime": 234, "exitCode": 0 })
         ; "success"
@@ -534,9 +632,23 @@ mod test {
             end_time: 234,
             exit_code: Some(1),
             error: Some("cannot find anything".into()),
+            timed_out: false,
+            attempts: 2,
+        },
+        json!({ "startTime": 123, "endTime": 234, "exitCode": 1, "error": "cannot find anything", "attempts": 2 })
+        ; "failure after retrying"
+    )]
+    #[test_case(
+        TaskExecutionSummary {
+            start_time: 123,
+            end_time: 234,
+            exit_code: None,
+            error: Some("timed out after 5m".into()),
+            timed_out: true,
+            attempts: 1,
         },
-        json!({ "startTime": 123, "endTime": 234, "exitCode": 1, "error": "cannot find anything" })
-        ; "failure"
+        json!({ "startTime": 123, "endTime": 234, "exitCode": null, "error": "timed out after 5m", "timedOut": true })
+        ; "timed out"
     )]
     fn test_serialization(value: impl serde::Serialize, expected: serde_json::Value) {
         assert_eq!(serde_json::to_value(value).unwrap(), expected);
@@ -0,0 +1,689 @@
+//! Reads the run summaries saved by previous `--summarize` runs. These are
+//! used to estimate how long each task takes and to compare runs with
+//! `turbo runs`.
+use std::{collections::HashMap, time::Duration};
+
+use camino::Utf8Path;
+use serde::Deserialize;
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+use turborepo_cache::fs::FSCache;
+use turborepo_repository::package_graph::ROOT_PKG_NAME;
+
+use crate::run::task_id::TaskId;
+
+// Only the most recent summaries are read to keep startup fast and to prefer
+// recent timings over ones from before the repository changed.
+const MAX_SUMMARIES: usize = 10;
+
+#[derive(Debug, Deserialize)]
+struct SavedRunSummary {
+    #[serde(default)]
+    monorepo: bool,
+    tasks: Vec<SavedTaskSummary>,
+    execution: Option<SavedRunExecution>,
+    scm: Option<SavedScm>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedRunExecution {
+    pub command: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub exit_code: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct SavedScm {
+    sha: Option<String>,
+    branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedTaskSummary {
+    pub task_id: String,
+    #[serde(default)]
+    pub hash: String,
+    cache: SavedCacheSummary,
+    execution: Option<SavedExecution>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedCacheSummary {
+    status: String,
+    time_saved: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedExecution {
+    start_time: i64,
+    end_time: i64,
+    exit_code: Option<i32>,
+}
+
+impl SavedTaskSummary {
+    // A cache hit replays quickly, so we use the duration of the run that
+    // populated the cache instead.
+    fn duration(&self) -> Option<Duration> {
+        if self.cache_hit() && self.cache.time_saved > 0 {
+            return Some(Duration::from_millis(self.cache.time_saved));
+        }
+        let execution = self.execution.as_ref()?;
+        if execution.exit_code != Some(0) {
+            return None;
+        }
+        self.execution_duration()
+    }
+
+    /// How long the task took in the run, including replaying logs for cache
+    /// hits
+    pub fn execution_duration(&self) -> Option<Duration> {
+        let execution = self.execution.as_ref()?;
+        let elapsed = u64::try_from(execution.end_time - execution.start_time).ok()?;
+        Some(Duration::from_millis(elapsed))
+    }
+
+    pub fn cache_hit(&self) -> bool {
+        self.cache.status == "HIT"
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.execution.as_ref()?.exit_code
+    }
+}
+
+/// A run summary saved in `.turbo/runs`
+#[derive(Debug)]
+pub struct SavedRun {
+    pub id: String,
+    summary: SavedRunSummary,
+}
+
+impl SavedRun {
+    pub fn execution(&self) -> Option<&SavedRunExecution> {
+        self.summary.execution.as_ref()
+    }
+
+    pub fn sha(&self) -> Option<&str> {
+        self.summary.scm.as_ref()?.sha.as_deref()
+    }
+
+    pub fn branch(&self) -> Option<&str> {
+        self.summary.scm.as_ref()?.branch.as_deref()
+    }
+
+    pub fn tasks(&self) -> &[SavedTaskSummary] {
+        &self.summary.tasks
+    }
+
+    pub fn task(&self, task_id: &str) -> Option<&SavedTaskSummary> {
+        self.summary
+            .tasks
+            .iter()
+            .find(|task| task.task_id == task_id)
+    }
+
+    /// The duration of the whole run
+    pub fn duration(&self) -> Option<Duration> {
+        let execution = self.execution()?;
+        let elapsed = u64::try_from(execution.end_time - execution.start_time).ok()?;
+        Some(Duration::from_millis(elapsed))
+    }
+
+    /// The fraction of tasks that were cache hits, or `None` if the run has
+    /// no tasks
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let tasks = self.tasks();
+        if tasks.is_empty() {
+            return None;
+        }
+        let hits = tasks.iter().filter(|task| task.cache_hit()).count();
+        Some(hits as f64 / tasks.len() as f64)
+    }
+
+    fn task_id(&self, task: &SavedTaskSummary) -> Option<TaskId<'static>> {
+        if self.summary.monorepo {
+            TaskId::try_from(task.task_id.as_str())
+                .ok()
+                .map(|task_id| task_id.into_owned())
+        } else {
+            Some(TaskId::from_static(
+                ROOT_PKG_NAME.to_string(),
+                task.task_id.clone(),
+            ))
+        }
+    }
+}
+
+/// The run summaries saved in `.turbo/runs`, indexed by git SHA and by task
+#[derive(Debug, Default)]
+pub struct RunHistory {
+    // Sorted from oldest to newest
+    runs: Vec<SavedRun>,
+    by_sha: HashMap<String, Vec<usize>>,
+    by_task: HashMap<String, Vec<usize>>,
+}
+
+impl RunHistory {
+    /// Loads every saved run summary. Summaries that can't be read are
+    /// skipped.
+    pub fn load(repo_root: &AbsoluteSystemPath) -> Self {
+        Self::load_recent(repo_root, usize::MAX)
+    }
+
+    /// Loads the `limit` most recent run summaries
+    pub fn load_recent(repo_root: &AbsoluteSystemPath, limit: usize) -> Self {
+        let runs_dir = repo_root.join_components(&[".turbo", "runs"]);
+        let Ok(entries) = runs_dir.as_path().read_dir_utf8() else {
+            return Self::default();
+        };
+
+        // Run ids are KSUIDs which sort in the order they were created
+        let mut file_names = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string())
+            .filter(|file_name| file_name.ends_with(".json"))
+            .collect::<Vec<_>>();
+        file_names.sort();
+
+        let skip = file_names.len().saturating_sub(limit);
+        let runs = file_names
+            .into_iter()
+            .skip(skip)
+            .filter_map(|file_name| read_run(&runs_dir, &file_name))
+            .collect();
+
+        Self::new(runs)
+    }
+
+    fn new(runs: Vec<SavedRun>) -> Self {
+        let mut by_sha: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut by_task: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, run) in runs.iter().enumerate() {
+            if let Some(sha) = run.sha() {
+                by_sha.entry(sha.to_string()).or_default().push(index);
+            }
+            for task in run.tasks() {
+                by_task.entry(task.task_id.clone()).or_default().push(index);
+            }
+        }
+
+        Self {
+            runs,
+            by_sha,
+            by_task,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// All runs, from newest to oldest
+    pub fn runs(&self) -> impl Iterator<Item = &SavedRun> {
+        self.runs.iter().rev()
+    }
+
+    /// Runs of commits whose SHA starts with `sha`, from newest to oldest
+    pub fn runs_for_sha<'a>(&'a self, sha: &'a str) -> impl Iterator<Item = &'a SavedRun> {
+        self.runs()
+            .filter(move |run| run.sha().is_some_and(|run_sha| run_sha.starts_with(sha)))
+    }
+
+    /// Runs that include `task_id`, from newest to oldest
+    pub fn runs_for_task(&self, task_id: &str) -> impl Iterator<Item = &SavedRun> {
+        self.by_task
+            .get(task_id)
+            .into_iter()
+            .flat_map(|indices| indices.iter().rev())
+            .map(|index| &self.runs[*index])
+    }
+
+    /// Finds a run by its id, a prefix of its id, or the SHA of the commit it
+    /// ran on. If several runs match a SHA, the most recent is returned.
+    pub fn find(&self, query: &str) -> Option<&SavedRun> {
+        if let Some(run) = self.runs().find(|run| run.id.starts_with(query)) {
+            return Some(run);
+        }
+        if let Some(index) = self.by_sha.get(query).and_then(|indices| indices.last()) {
+            return Some(&self.runs[*index]);
+        }
+        self.runs_for_sha(query).next()
+    }
+
+    /// The most recent run, and the one before it
+    pub fn latest_two(&self) -> Option<(&SavedRun, &SavedRun)> {
+        let mut runs = self.runs();
+        let head = runs.next()?;
+        let base = runs.next()?;
+        Some((base, head))
+    }
+}
+
+fn read_run(runs_dir: &AbsoluteSystemPathBuf, file_name: &str) -> Option<SavedRun> {
+    let path = runs_dir.join_component(file_name);
+    let contents = match path.read_to_string() {
+        Ok(contents) => contents,
+        Err(e) => {
+            debug!("unable to read run summary {path}: {e}");
+            return None;
+        }
+    };
+    let summary = match serde_json::from_str(&contents) {
+        Ok(summary) => summary,
+        Err(e) => {
+            debug!("unable to parse run summary {path}: {e}");
+            return None;
+        }
+    };
+
+    Some(SavedRun {
+        id: file_name.trim_end_matches(".json").to_string(),
+        summary,
+    })
+}
+
+/// How a task changed between two runs. A task that is only in one of the
+/// runs has no summary for the other.
+#[derive(Debug)]
+pub struct TaskComparison<'a> {
+    pub task_id: &'a str,
+    pub base: Option<&'a SavedTaskSummary>,
+    pub head: Option<&'a SavedTaskSummary>,
+}
+
+impl TaskComparison<'_> {
+    /// The change in duration from the base run to the head run in
+    /// milliseconds
+    pub fn duration_change(&self) -> Option<i64> {
+        let base = self.base?.execution_duration()?;
+        let head = self.head?.execution_duration()?;
+        Some(head.as_millis() as i64 - base.as_millis() as i64)
+    }
+
+    pub fn hash_changed(&self) -> bool {
+        match (self.base, self.head) {
+            (Some(base), Some(head)) => base.hash != head.hash,
+            _ => true,
+        }
+    }
+}
+
+/// Compares the tasks of two runs. Tasks that slowed down the most come
+/// first, followed by tasks that are missing from one of the runs.
+pub fn compare_runs<'a>(base: &'a SavedRun, head: &'a SavedRun) -> Vec<TaskComparison<'a>> {
+    let mut task_ids = base
+        .tasks()
+        .iter()
+        .chain(head.tasks())
+        .map(|task| task.task_id.as_str())
+        .collect::<Vec<_>>();
+    task_ids.sort();
+    task_ids.dedup();
+
+    let mut comparisons = task_ids
+        .into_iter()
+        .map(|task_id| TaskComparison {
+            task_id,
+            base: base.task(task_id),
+            head: head.task(task_id),
+        })
+        .collect::<Vec<_>>();
+    // `sort_by_key` is stable, so tasks with the same change stay sorted by id
+    comparisons.sort_by_key(|comparison| {
+        std::cmp::Reverse(comparison.duration_change().unwrap_or(i64::MIN))
+    });
+
+    comparisons
+}
+
+/// Collects the most recently observed duration of each of `task_ids` from the
+/// run summaries saved in `.turbo/runs`. Tasks that aren't in a saved summary,
+/// e.g. because `--summarize` isn't used, fall back to the duration recorded
+/// when their most recent artifact was written to the local cache.
+pub fn task_durations<'a>(
+    repo_root: &AbsoluteSystemPath,
+    cache_dir: &Utf8Path,
+    task_ids: impl IntoIterator<Item = &'a TaskId<'static>>,
+) -> HashMap<TaskId<'static>, Duration> {
+    let history = RunHistory::load_recent(repo_root, MAX_SUMMARIES);
+
+    let mut durations = cached_task_durations(repo_root, cache_dir, task_ids);
+    for run in history.runs.iter() {
+        for task in run.tasks() {
+            let Some(duration) = task.duration() else {
+                continue;
+            };
+            let Some(task_id) = run.task_id(task) else {
+                continue;
+            };
+            durations.insert(task_id, duration);
+        }
+    }
+
+    durations
+}
+
+/// Looks up each task's duration in the local cache. This runs before every
+/// run, so only the given tasks are looked up rather than reading the metadata
+/// of every artifact.
+fn cached_task_durations<'a>(
+    repo_root: &AbsoluteSystemPath,
+    cache_dir: &Utf8Path,
+    task_ids: impl IntoIterator<Item = &'a TaskId<'static>>,
+) -> HashMap<TaskId<'static>, Duration> {
+    // Don't create the cache directory just to find out that it's empty
+    if !AbsoluteSystemPathBuf::from_unknown(repo_root, cache_dir).exists() {
+        return HashMap::new();
+    }
+    let Ok(cache) = FSCache::new(cache_dir, repo_root, None) else {
+        return HashMap::new();
+    };
+
+    task_ids
+        .into_iter()
+        .filter_map(|task_id| {
+            let duration = cache.task_duration(&task_id.to_string())?;
+            Some((task_id.clone(), Duration::from_millis(duration)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_task_durations() {
+        let tmp = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let runs_dir = repo_root.join_components(&[".turbo", "runs"]);
+        runs_dir.create_dir_all().unwrap();
+
+        let older = json!({
+            "monorepo": true,
+            "tasks": [
+                {
+                    "taskId": "web#build",
+                    "cache": { "status": "MISS", "timeSaved": 0 },
+                    "execution": { "startTime": 1000, "endTime": 4000, "exitCode": 0 }
+                },
+                {
+                    "taskId": "web#lint",
+                    "cache": { "status": "MISS", "timeSaved": 0 },
+                    "execution": { "startTime": 1000, "endTime": 2000, "exitCode": 0 }
+                }
+            ]
+        });
+        let newer = json!({
+            "monorepo": true,
+            "tasks": [
+                {
+                    "taskId": "web#build",
+                    "cache": { "status": "HIT", "timeSaved": 5000 },
+                    "execution": { "startTime": 1000, "endTime": 1010, "exitCode": 0 }
+                },
+                {
+                    "taskId": "web#lint",
+                    "cache": { "status": "MISS", "timeSaved": 0 },
+                    "execution": { "startTime": 1000, "endTime": 9000, "exitCode": 1 }
+                }
+            ]
+        });
+        runs_dir
+            .join_component("2a1OVzyNkzGKQYJMv5fjPnc1fk2.json")
+            .create_with_contents(older.to_string())
+            .unwrap();
+        runs_dir
+            .join_component("2a1OW1sQtbAdbJmrQeQAmr9GEnM.json")
+            .create_with_contents(newer.to_string())
+            .unwrap();
+        runs_dir
+            .join_component("2a1OW3dWfzKrdTHoPeWp3jc3GXb.json")
+            .create_with_contents("not json")
+            .unwrap();
+
+        let durations = task_durations(repo_root, Utf8Path::new(".turbo/cache"), []);
+        assert_eq!(
+            durations.get(&TaskId::new("web", "build")),
+            Some(&Duration::from_secs(5))
+        );
+        // Failed executions are ignored
+        assert_eq!(
+            durations.get(&TaskId::new("web", "lint")),
+            Some(&Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn test_task_durations_fall_back_to_cache() {
+        let tmp = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let cache_dir = Utf8Path::new(".turbo/cache");
+        let cache = FSCache::new(cache_dir, repo_root, None).unwrap();
+        cache
+            .put(repo_root, "web-build", &[], 3000, Some("web#build"))
+            .unwrap();
+        cache
+            .put(repo_root, "web-lint", &[], 1000, Some("web#lint"))
+            .unwrap();
+        cache.put(repo_root, "unknown", &[], 9000, None).unwrap();
+
+        let runs_dir = repo_root.join_components(&[".turbo", "runs"]);
+        runs_dir.create_dir_all().unwrap();
+        let summary = json!({
+            "monorepo": true,
+            "tasks": [
+                {
+                    "taskId": "web#build",
+                    "cache": { "status": "MISS", "timeSaved": 0 },
+                    "execution": { "startTime": 0, "endTime": 4000, "exitCode": 0 }
+                }
+            ]
+        });
+        runs_dir
+            .join_component("2a1OVzyNkzGKQYJMv5fjPnc1fk2.json")
+            .create_with_contents(summary.to_string())
+            .unwrap();
+
+        let task_ids = [TaskId::new("web", "build"), TaskId::new("web", "lint")];
+        let durations = task_durations(repo_root, cache_dir, &task_ids);
+        // Saved summaries take precedence over the cache
+        assert_eq!(
+            durations.get(&TaskId::new("web", "build")),
+            Some(&Duration::from_secs(4))
+        );
+        assert_eq!(
+            durations.get(&TaskId::new("web", "lint")),
+            Some(&Duration::from_secs(1))
+        );
+        assert_eq!(durations.len(), 2);
+    }
+
+    #[test]
+    fn test_task_durations_do_not_scan_cache() {
+        let tmp = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let cache_dir = Utf8Path::new(".turbo/cache");
+        let cache = FSCache::new(cache_dir, repo_root, None).unwrap();
+        for i in 0..100 {
+            let task_id = format!("pkg-{i}#build");
+            cache
+                .put(repo_root, &format!("pkg-{i}"), &[], 1000, Some(&task_id))
+                .unwrap();
+        }
+        // Only the metadata of this artifact knows its task, so finding it would
+        // mean reading the metadata of every artifact
+        repo_root
+            .join_components(&[".turbo", "cache", "legacy-meta.json"])
+            .create_with_contents(
+                json!({ "hash": "legacy", "duration": 5000, "taskId": "web#build" }).to_string(),
+            )
+            .unwrap();
+
+        let task_ids = [TaskId::new("web", "build"), TaskId::new("pkg-1", "build")];
+        let durations = task_durations(repo_root, cache_dir, &task_ids);
+        assert_eq!(
+            durations,
+            HashMap::from([(TaskId::new("pkg-1", "build"), Duration::from_secs(1))])
+        );
+    }
+
+    #[test]
+    fn test_single_package_task_durations() {
+        let tmp = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let runs_dir = repo_root.join_components(&[".turbo", "runs"]);
+        runs_dir.create_dir_all().unwrap();
+        let summary = json!({
+            "monorepo": false,
+            "tasks": [
+                {
+                    "taskId": "build",
+                    "cache": { "status": "MISS", "timeSaved": 0 },
+                    "execution": { "startTime": 0, "endTime": 2500, "exitCode": 0 }
+                }
+            ]
+        });
+        runs_dir
+            .join_component("2a1OVzyNkzGKQYJMv5fjPnc1fk2.json")
+            .create_with_contents(summary.to_string())
+            .unwrap();
+
+        let durations = task_durations(repo_root, Utf8Path::new(".turbo/cache"), []);
+        assert_eq!(
+            durations.get(&TaskId::new("//", "build")),
+            Some(&Duration::from_millis(2500))
+        );
+    }
+
+    #[test]
+    fn test_missing_runs_dir() {
+        let tmp = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        assert!(task_durations(repo_root, Utf8Path::new(".turbo/cache"), []).is_empty());
+    }
+
+    fn summary(sha: &str, tasks: &[(&str, &str, &str, i64)]) -> serde_json::Value {
+        json!({
+            "monorepo": true,
+            "scm": { "type": "git", "sha": sha, "branch": "main" },
+            "execution": {
+                "command": "turbo run build",
+                "startTime": 0,
+                "endTime": 10000,
+                "exitCode": 0
+            },
+            "tasks": tasks
+                .iter()
+                .map(|(task_id, hash, status, duration)| json!({
+                    "taskId": task_id,
+                    "hash": hash,
+                    "cache": { "status": status, "timeSaved": 0 },
+                    "execution": { "startTime": 0, "endTime": duration, "exitCode": 0 }
+                }))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn test_run_history() {
+        let tmp = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let runs_dir = repo_root.join_components(&[".turbo", "runs"]);
+        runs_dir.create_dir_all().unwrap();
+        for (id, sha) in [
+            ("2a1OVzyNkzGKQYJMv5fjPnc1fk2", "abc123"),
+            ("2a1OW1sQtbAdbJmrQeQAmr9GEnM", "abc123"),
+            ("2a1OW3dWfzKrdTHoPeWp3jc3GXb", "def456"),
+        ] {
+            runs_dir
+                .join_component(&format!("{id}.json"))
+                .create_with_contents(summary(sha, &[("web#build", "1", "MISS", 1000)]).to_string())
+                .unwrap();
+        }
+
+        let history = RunHistory::load(repo_root);
+        assert_eq!(
+            history
+                .runs()
+                .map(|run| run.id.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "2a1OW3dWfzKrdTHoPeWp3jc3GXb",
+                "2a1OW1sQtbAdbJmrQeQAmr9GEnM",
+                "2a1OVzyNkzGKQYJMv5fjPnc1fk2"
+            ]
+        );
+        // The most recent run of a SHA is used
+        assert_eq!(
+            history.find("abc123").map(|run| run.id.as_str()),
+            Some("2a1OW1sQtbAdbJmrQeQAmr9GEnM")
+        );
+        assert_eq!(
+            history.find("2a1OVz").map(|run| run.id.as_str()),
+            Some("2a1OVzyNkzGKQYJMv5fjPnc1fk2")
+        );
+        assert_eq!(history.runs_for_sha("def").count(), 1);
+        assert_eq!(history.runs_for_task("web#build").count(), 3);
+        assert!(history.find("unknown").is_none());
+
+        let recent = RunHistory::load_recent(repo_root, 1);
+        assert_eq!(recent.runs().count(), 1);
+    }
+
+    #[test]
+    fn test_compare_runs() {
+        let base = SavedRun {
+            id: "base".to_string(),
+            summary: serde_json::from_value(summary(
+                "abc123",
+                &[
+                    ("web#build", "1", "MISS", 1000),
+                    ("web#lint", "2", "MISS", 500),
+                    ("docs#build", "3", "MISS", 300),
+                ],
+            ))
+            .unwrap(),
+        };
+        let head = SavedRun {
+            id: "head".to_string(),
+            summary: serde_json::from_value(summary(
+                "def456",
+                &[
+                    ("web#build", "1", "HIT", 10),
+                    ("web#lint", "4", "MISS", 2500),
+                    ("web#test", "5", "MISS", 700),
+                ],
+            ))
+            .unwrap(),
+        };
+
+        let comparisons = compare_runs(&base, &head);
+        assert_eq!(
+            comparisons
+                .iter()
+                .map(|comparison| (
+                    comparison.task_id,
+                    comparison.duration_change(),
+                    comparison.hash_changed()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("web#lint", Some(2000), true),
+                ("web#build", Some(-990), false),
+                ("docs#build", None, true),
+                ("web#test", None, true),
+            ]
+        );
+        assert_eq!(base.cache_hit_rate(), Some(0.0));
+        assert_eq!(head.cache_hit_rate(), Some(1.0 / 3.0));
+    }
+}
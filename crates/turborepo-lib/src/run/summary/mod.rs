@@ -7,16 +7,25 @@
 mod duration;
 mod execution;
 mod global_hash;
+mod history;
 mod scm;
 mod spaces;
 mod task;
 mod task_factory;
-use std::{collections::HashSet, io, io::Write};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    io::Write,
+    sync::Arc,
+};
 
 use chrono::{DateTime, Local};
 pub use duration::TurboDuration;
 pub use execution::{TaskExecutionSummary, TaskTracker};
 pub use global_hash::GlobalHashSummary;
+pub use history::{
+    compare_runs, task_durations, RunHistory, SavedRun, SavedTaskSummary, TaskComparison,
+};
 use itertools::Itertools;
 use serde::Serialize;
 pub use spaces::{SpacesTaskClient, SpacesTaskInformation};
@@ -38,13 +47,16 @@ use super::task_id::TaskId;
 use crate::{
     cli,
     cli::{DryRunMode, EnvMode},
-    engine::Engine,
+    engine::{CriticalPaths, Engine},
     opts::RunOpts,
-    run::summary::{
-        execution::{ExecutionSummary, ExecutionTracker},
-        scm::SCMState,
-        spaces::{SpaceRequest, SpacesClient, SpacesClientHandle},
-        task::TaskSummary,
+    run::{
+        affected_tasks::TaskAffectedReason,
+        summary::{
+            execution::{ExecutionSummary, ExecutionTracker},
+            scm::SCMState,
+            spaces::{SpaceRequest, SpacesClient, SpacesClientHandle},
+            task::TaskSummary,
+        },
     },
     task_hash::TaskHashTracker,
 };
@@ -98,6 +110,9 @@ pub struct RunSummary<'a> {
     env_mode: EnvMode,
     framework_inference: bool,
     tasks: Vec<TaskSummary>,
+    // Only present for `--dry=json` when tasks are prioritised by critical path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule: Option<Vec<ScheduleEntry>>,
     user: String,
     scm: SCMState,
     #[serde(skip)]
@@ -110,6 +125,19 @@ pub struct RunSummary<'a> {
     spaces_client_handle: Option<SpacesClientHandle>,
 }
 
+/// The position of a task in the order it would be started in, along with the
+/// estimates used to pick that order.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScheduleEntry {
+    task_id: String,
+    // Estimated duration of the task in milliseconds
+    estimated_duration: u64,
+    // Estimated duration of the task and its longest chain of dependents in
+    // milliseconds
+    critical_path: u64,
+}
+
 /// We use this to track the run, so it's constructed before the run.
 #[derive(Debug)]
 pub struct RunTracker {
@@ -120,6 +148,8 @@ pub struct RunTracker {
     spaces_client_handle: Option<SpacesClientHandle>,
     user: String,
     synthesized_command: String,
+    critical_paths: Option<Arc<CriticalPaths>>,
+    affected_tasks: Option<Arc<HashMap<TaskId<'static>, TaskAffectedReason>>>,
 }
 
 impl RunTracker {
@@ -163,9 +193,28 @@ impl RunTracker {
             user,
             synthesized_command,
             spaces_client_handle,
+            critical_paths: None,
+            affected_tasks: None,
         }
     }
 
+    pub fn with_critical_paths(mut self, critical_paths: Arc<CriticalPaths>) -> Self {
+        self.critical_paths = Some(critical_paths);
+        self
+    }
+
+    pub fn critical_paths(&self) -> Option<&Arc<CriticalPaths>> {
+        self.critical_paths.as_ref()
+    }
+
+    pub fn with_affected_tasks(
+        mut self,
+        affected_tasks: Arc<HashMap<TaskId<'static>, TaskAffectedReason>>,
+    ) -> Self {
+        self.affected_tasks = Some(affected_tasks);
+        self
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[tracing::instrument(skip(
         repo_root,
@@ -222,6 +271,7 @@ impl RunTracker {
             env_mode: global_env_mode,
             framework_inference: run_opts.framework_inference,
             tasks,
+            schedule: None,
             global_hash_summary,
             scm: self.scm,
             user: self.user,
@@ -269,9 +319,11 @@ impl RunTracker {
             env_at_execution_start,
             run_opts,
             global_env_mode,
-        );
+        )
+        .with_affected_tasks(self.affected_tasks.clone());
 
-        let run_summary: RunSummary = self
+        let critical_paths = self.critical_paths.clone();
+        let mut run_summary: RunSummary = self
             .to_summary(
                 repo_root,
                 package_inference_root,
@@ -285,6 +337,22 @@ impl RunTracker {
             )
             .await?;
 
+        if let (RunType::DryJson, Some(critical_paths)) = (&run_summary.run_type, critical_paths) {
+            let schedule = engine
+                .schedule(&critical_paths)
+                .into_iter()
+                .map(|task| ScheduleEntry {
+                    task_id: match run_opts.single_package {
+                        true => task.task_id.task().to_string(),
+                        false => task.task_id.to_string(),
+                    },
+                    estimated_duration: task.estimated_duration.as_millis() as u64,
+                    critical_path: task.critical_path.as_millis() as u64,
+                })
+                .collect();
+            run_summary.schedule = Some(schedule);
+        }
+
         run_summary
             .finish(end_time, exit_code, pkg_dep_graph, ui, is_watch)
             .await
@@ -324,6 +392,8 @@ struct SinglePackageRunSummary<'a> {
     env_mode: EnvMode,
     framework_inference: bool,
     tasks: Vec<SinglePackageTaskSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule: Option<&'a Vec<ScheduleEntry>>,
     user: &'a str,
     pub scm: &'a SCMState,
 }
@@ -347,6 +417,7 @@ impl<'a> From<&'a RunSummary<'a>> for SinglePackageRunSummary<'a> {
             env_mode: run_summary.env_mode,
             framework_inference: run_summary.framework_inference,
             tasks,
+            schedule: run_summary.schedule.as_ref(),
             user: &run_summary.user,
             scm: &run_summary.scm,
         }
@@ -8,7 +8,7 @@ use turborepo_env::{DetailedMap, EnvironmentVariableMap};
 use super::{execution::TaskExecutionSummary, EnvMode};
 use crate::{
     cli::OutputLogsMode,
-    run::task_id::TaskId,
+    run::{affected_tasks::TaskAffectedReason, task_id::TaskId},
     task_graph::{TaskDefinition, TaskOutputs},
 };
 
@@ -83,6 +83,10 @@ pub(crate) struct SharedTaskSummary<T> {
     pub environment_variables: TaskEnvVarSummary,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub execution: Option<TaskExecutionSummary>,
+    // Why the task is affected when running with `--affected-by-inputs`. Tasks
+    // that only run because an affected task depends on them don't have one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub affected_reason: Option<TaskAffectedReason>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -106,6 +110,16 @@ pub struct TaskSummaryTaskDefinition {
     pass_through_env: Option<Vec<String>>,
     interactive: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout_grace_period: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_delay: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    resources: BTreeMap<String, u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     env_mode: Option<EnvMode>,
 }
 
@@ -234,6 +248,7 @@ impl From<SharedTaskSummary<TaskId<'static>>> for SharedTaskSummary<String> {
             execution,
             env_mode,
             environment_variables,
+            affected_reason,
             ..
         } = value;
         Self {
@@ -261,6 +276,7 @@ impl From<SharedTaskSummary<TaskId<'static>>> for SharedTaskSummary<String> {
             execution,
             env_mode,
             environment_variables,
+            affected_reason,
         }
     }
 }
@@ -283,6 +299,11 @@ impl From<TaskDefinition> for TaskSummaryTaskDefinition {
             persistent,
             interruptible,
             interactive,
+            timeout,
+            timeout_grace_period,
+            retries,
+            retry_delay,
+            resources,
             env_mode,
             siblings: _,
         } = value;
@@ -320,6 +341,13 @@ impl From<TaskDefinition> for TaskSummaryTaskDefinition {
             interactive,
             env,
             pass_through_env,
+            timeout: timeout.map(|timeout| humantime::format_duration(timeout).to_string()),
+            timeout_grace_period: timeout_grace_period
+                .map(|grace_period| humantime::format_duration(grace_period).to_string()),
+            retries: (retries > 0).then_some(retries),
+            retry_delay: retry_delay
+                .map(|retry_delay| humantime::format_duration(retry_delay).to_string()),
+            resources,
             env_mode,
         }
     }
@@ -383,6 +411,48 @@ mod test {
         })
         ; "resolved task definition"
     )]
+    #[test_case(
+        TaskSummaryTaskDefinition::from(TaskDefinition {
+            timeout: Some(std::time::Duration::from_secs(90)),
+            retries: 2,
+            ..Default::default()
+        }),
+        json!({
+            "outputs": [],
+            "cache": true,
+            "dependsOn": [],
+            "inputs": [],
+            "outputLogs": "full",
+            "persistent": false,
+            "interruptible": false,
+            "interactive": false,
+            "env": [],
+            "passThroughEnv": null,
+            "timeout": "1m 30s",
+            "retries": 2,
+        })
+        ; "resolved task definition with timeout and retries"
+    )]
+    #[test_case(
+        TaskSummaryTaskDefinition::from(TaskDefinition {
+            resources: BTreeMap::from([("memory".to_string(), 4)]),
+            ..Default::default()
+        }),
+        json!({
+            "outputs": [],
+            "cache": true,
+            "dependsOn": [],
+            "inputs": [],
+            "outputLogs": "full",
+            "persistent": false,
+            "interruptible": false,
+            "interactive": false,
+            "env": [],
+            "passThroughEnv": null,
+            "resources": { "memory": 4 },
+        })
+        ; "resolved task definition with resources"
+    )]
     fn test_serialization(value: impl serde::Serialize, expected: serde_json::Value) {
         assert_eq!(serde_json::to_value(value).unwrap(), expected);
     }
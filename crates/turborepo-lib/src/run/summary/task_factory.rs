@@ -1,4 +1,7 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use turborepo_env::EnvironmentVariableMap;
 use turborepo_repository::package_graph::{PackageGraph, PackageInfo, PackageName};
@@ -12,7 +15,7 @@ use crate::{
     cli,
     engine::{Engine, TaskNode},
     opts::RunOpts,
-    run::task_id::TaskId,
+    run::{affected_tasks::TaskAffectedReason, task_id::TaskId},
     task_graph::TaskDefinition,
     task_hash::{get_external_deps_hash, TaskHashTracker},
 };
@@ -24,6 +27,7 @@ pub struct TaskSummaryFactory<'a> {
     env_at_start: &'a EnvironmentVariableMap,
     run_opts: &'a RunOpts,
     global_env_mode: cli::EnvMode,
+    affected_tasks: Option<Arc<HashMap<TaskId<'static>, TaskAffectedReason>>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -50,9 +54,18 @@ impl<'a> TaskSummaryFactory<'a> {
             env_at_start,
             run_opts,
             global_env_mode,
+            affected_tasks: None,
         }
     }
 
+    pub fn with_affected_tasks(
+        mut self,
+        affected_tasks: Option<Arc<HashMap<TaskId<'static>, TaskAffectedReason>>>,
+    ) -> Self {
+        self.affected_tasks = affected_tasks;
+        self
+    }
+
     pub fn task_summary(
         &self,
         task_id: TaskId<'static>,
@@ -184,6 +197,10 @@ impl<'a> TaskSummaryFactory<'a> {
             )
             .expect("invalid glob in task definition should have been caught earlier"),
             execution,
+            affected_reason: self
+                .affected_tasks
+                .as_ref()
+                .and_then(|affected_tasks| affected_tasks.get(task_id).cloned()),
         })
     }
 
@@ -12,6 +12,8 @@ pub async fn start_web_ui_server(
     let subscriber = turborepo_ui::wui::subscriber::Subscriber::new(rx);
     tokio::spawn(subscriber.watch(state.clone()));
 
+    // The web UI serves a single run, so the run never changes
+    let (_, run) = tokio::sync::watch::channel(run);
     query::run_server(Some(state.clone()), run).await?;
 
     Ok(())
@@ -1,24 +1,36 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     ops::DerefMut as _,
     sync::{Arc, Mutex},
 };
 
-use futures::StreamExt;
+use futures::{stream, Stream, StreamExt};
+use itertools::Itertools;
 use miette::{Diagnostic, SourceSpan};
 use thiserror::Error;
-use tokio::{select, sync::Notify, task::JoinHandle};
-use tracing::{instrument, trace, warn};
+use tokio::{
+    select,
+    sync::{broadcast::error::RecvError, watch, Notify},
+    task::JoinHandle,
+};
+use tracing::{debug, instrument, trace, warn};
+use turbopath::AbsoluteSystemPath;
 use turborepo_repository::package_graph::PackageName;
 use turborepo_telemetry::events::command::CommandEventBuilder;
-use turborepo_ui::sender::UISender;
+use turborepo_ui::{color, cprintln, sender::UISender, GREY};
 
 use crate::{
     commands::{self, CommandBase},
-    daemon::{proto, DaemonConnectorError, DaemonError},
+    daemon::{proto, DaemonConnectorError, DaemonError, FileWatching},
+    engine::{Engine, TaskNode},
     get_version, opts,
-    run::{self, builder::RunBuilder, scope::target_selector::InvalidSelectorError, Run},
+    package_changes_watcher::PackageChangeEvent,
+    run::{
+        self, builder::RunBuilder, scope::target_selector::InvalidSelectorError, task_id::TaskId,
+        Run,
+    },
     signal::SignalHandler,
+    task_hash::TaskHashTracker,
     turbo_json::CONFIG_FILE,
     DaemonConnector, DaemonPaths,
 };
@@ -48,7 +60,8 @@ pub struct WatchClient {
     run: Arc<Run>,
     watched_packages: HashSet<PackageName>,
     persistent_tasks_handle: Option<RunHandle>,
-    connector: DaemonConnector,
+    active_runs: Vec<ActiveRun>,
+    change_source: ChangeSource,
     base: CommandBase,
     telemetry: CommandEventBuilder,
     handler: SignalHandler,
@@ -57,9 +70,85 @@ pub struct WatchClient {
     experimental_write_cache: bool,
 }
 
+/// Where watch mode gets package changes from
+#[derive(Clone)]
+enum ChangeSource {
+    Daemon(DaemonConnector),
+    /// Watch files in this process, for when the daemon is disabled
+    InProcess,
+}
+
 struct RunHandle {
     stopper: run::RunStopper,
     run_task: JoinHandle<Result<i32, run::Error>>,
+    // Set for persistent tasks, which wait for the rest of their batch to
+    // finish before they start
+    dependencies: Option<watch::Receiver<Option<FinishedTasks>>>,
+}
+
+/// The non-persistent tasks of a batch, once they've finished
+#[derive(Clone)]
+struct FinishedTasks {
+    // `None` if the run failed before any tasks were hashed
+    tasks: Option<TaskHashTracker>,
+    continue_on_error: bool,
+}
+
+impl FinishedTasks {
+    /// Gets the hashes of `dependencies` so that a persistent task can be
+    /// started, or the first dependency that didn't succeed
+    fn dependency_hashes<'a>(
+        &self,
+        dependencies: &'a [TaskId<'static>],
+    ) -> Result<HashMap<TaskId<'static>, String>, &'a TaskId<'static>> {
+        dependencies
+            .iter()
+            .map(|dependency| {
+                let hash = self
+                    .tasks
+                    .as_ref()
+                    .filter(|tasks| self.continue_on_error || tasks.succeeded(dependency))
+                    .and_then(|tasks| tasks.hash(dependency))
+                    .ok_or(dependency)?;
+                Ok((dependency.clone(), hash))
+            })
+            .collect()
+    }
+}
+
+impl RunHandle {
+    fn spawn(run: Run, ui_sender: Option<UISender>) -> Self {
+        Self {
+            stopper: run.stopper(),
+            run_task: tokio::spawn(async move { run.run(ui_sender, true).await }),
+            dependencies: None,
+        }
+    }
+
+    async fn stop(self) {
+        // Shut down the tasks for the run
+        self.stopper.stop().await;
+        // A run that hasn't started yet has nothing to flush
+        if self
+            .dependencies
+            .as_ref()
+            .is_some_and(|dependencies| dependencies.borrow().is_none())
+        {
+            self.run_task.abort();
+        }
+        // Run should exit shortly after we stop all child tasks, wait for it to
+        // finish to ensure all messages are flushed.
+        let _ = self.run_task.await;
+    }
+}
+
+/// A run of some of the interruptible tasks, which is stopped when a change
+/// affects any of its tasks
+struct ActiveRun {
+    handle: RunHandle,
+    tasks: HashSet<TaskId<'static>>,
+    // The packages whose changes the run was started for
+    packages: HashSet<PackageName>,
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -93,6 +182,10 @@ pub enum Error {
     },
     #[error("Daemon connection closed.")]
     ConnectionClosed,
+    #[error("Failed to start watching files.")]
+    FileWatching(#[from] turborepo_filewatch::WatchError),
+    #[error("Stopped watching files.")]
+    FileWatchingStopped,
     #[error("Failed to subscribe to signal handler. Shutting down.")]
     NoSignalHandler,
     #[error("Watch interrupted due to signal.")]
@@ -124,10 +217,6 @@ impl WatchClient {
             ));
         }
 
-        if matches!(base.opts.run_opts.daemon, Some(false)) {
-            warn!("daemon is required for watch, ignoring request to disable daemon");
-        }
-
         let new_base = base.clone();
         let run = Arc::new(
             RunBuilder::new(new_base)?
@@ -139,31 +228,44 @@ impl WatchClient {
 
         let (ui_sender, ui_handle) = run.start_ui()?.unzip();
 
-        let connector = DaemonConnector {
-            can_start_server: true,
-            can_kill_server: true,
-            paths: DaemonPaths::from_repo_root(&base.repo_root),
+        let change_source = if matches!(base.opts.run_opts.daemon, Some(false)) {
+            debug!("daemon is disabled, watching files in process");
+            ChangeSource::InProcess
+        } else {
+            ChangeSource::Daemon(DaemonConnector {
+                can_start_server: true,
+                can_kill_server: true,
+                paths: DaemonPaths::from_repo_root(&base.repo_root),
+            })
         };
 
         Ok(Self {
             base,
             run,
             watched_packages,
-            connector,
+            change_source,
             handler,
             telemetry,
             experimental_write_cache,
             persistent_tasks_handle: None,
+            active_runs: Vec::new(),
             ui_sender,
             ui_handle,
         })
     }
 
     pub async fn start(&mut self) -> Result<(), Error> {
-        let connector = self.connector.clone();
-        let mut client = connector.connect().await?;
-
-        let mut events = client.package_changes().await?;
+        let mut events = match self.change_source.clone() {
+            ChangeSource::Daemon(connector) => {
+                let mut client = connector.connect().await?;
+                client
+                    .package_changes()
+                    .await?
+                    .map(|event| event.map_err(Error::from))
+                    .boxed()
+            }
+            ChangeSource::InProcess => watch_in_process(&self.base.repo_root).await?.boxed(),
+        };
 
         let signal_subscriber = self.handler.subscribe().ok_or(Error::NoSignalHandler)?;
 
@@ -185,7 +287,6 @@ impl WatchClient {
         };
 
         let run_fut = async {
-            let mut run_handle: Option<RunHandle> = None;
             loop {
                 notify_run.notified().await;
                 let some_changed_packages = {
@@ -196,15 +297,7 @@ impl WatchClient {
                 };
 
                 if let Some(changed_packages) = some_changed_packages {
-                    // Clean up currently running tasks
-                    if let Some(RunHandle { stopper, run_task }) = run_handle.take() {
-                        // Shut down the tasks for the run
-                        stopper.stop().await;
-                        // Run should exit shortly after we stop all child tasks, wait for it to
-                        // finish to ensure all messages are flushed.
-                        let _ = run_task.await;
-                    }
-                    run_handle = Some(self.execute_run(changed_packages).await?);
+                    self.execute_run(changed_packages).await?;
                 }
             }
         };
@@ -261,34 +354,47 @@ impl WatchClient {
         if let Some(sender) = &self.ui_sender {
             sender.stop().await;
         }
-        if let Some(RunHandle { stopper, run_task }) = self.persistent_tasks_handle.take() {
-            // Shut down the tasks for the run
-            stopper.stop().await;
-            // Run should exit shortly after we stop all child tasks, wait for it to finish
-            // to ensure all messages are flushed.
-            let _ = run_task.await;
+        self.stop_runs().await;
+    }
+
+    async fn stop_runs(&mut self) {
+        for active_run in self.active_runs.drain(..) {
+            active_run.handle.stop().await;
+        }
+        if let Some(handle) = self.persistent_tasks_handle.take() {
+            handle.stop().await;
         }
     }
 
-    /// Executes a run with the given changed packages. Splits the run into two
-    /// parts:
+    /// Executes a run with the given changed packages. Splits the run into:
     /// 1. The persistent tasks that are not allowed to be interrupted
-    /// 2. The non-persistent tasks and the persistent tasks that are allowed to
-    ///    be interrupted
+    /// 2. The non-persistent tasks
+    /// 3. A run for each persistent task that is allowed to be interrupted
     ///
-    /// Returns a handle to the task running (2)
-    async fn execute_run(&mut self, changed_packages: ChangedPackages) -> Result<RunHandle, Error> {
+    /// When only some packages changed, (1) is left running along with any
+    /// runs from (2) and (3) that don't contain an affected task.
+    async fn execute_run(&mut self, changed_packages: ChangedPackages) -> Result<(), Error> {
         // Should we recover here?
         trace!("handling run with changed packages: {changed_packages:?}");
         match changed_packages {
             ChangedPackages::Some(packages) => {
-                let packages = packages
+                let changed_packages: HashSet<_> = packages
                     .into_iter()
                     .filter(|pkg| {
                         // If not in the watched packages set, ignore
                         self.watched_packages.contains(pkg)
                     })
                     .collect();
+                if changed_packages.is_empty() {
+                    return Ok(());
+                }
+
+                let packages = stop_affected_runs(
+                    &self.run.engine,
+                    &mut self.active_runs,
+                    changed_packages.clone(),
+                )
+                .await;
 
                 let mut opts = self.base.opts().clone();
                 if !self.experimental_write_cache {
@@ -307,23 +413,15 @@ impl WatchClient {
                 let telemetry = self.telemetry.clone();
 
                 let run = RunBuilder::new(new_base)?
-                    .with_entrypoint_packages(packages)
+                    .with_entrypoint_packages(packages.clone())
                     .hide_prelude()
                     .build(&signal_handler, telemetry)
                     .await?;
 
-                if let Some(sender) = &self.ui_sender {
-                    let task_names = run.engine.tasks_with_command(&run.pkg_dep_graph);
-                    sender
-                        .restart_tasks(task_names)
-                        .map_err(|err| Error::UISend(format!("some packages changed: {err}")))?;
-                }
+                self.report_restart(&run, &changed_packages)?;
+                self.start_interruptible_tasks(&run, packages);
 
-                let ui_sender = self.ui_sender.clone();
-                Ok(RunHandle {
-                    stopper: run.stopper(),
-                    run_task: tokio::spawn(async move { run.run(ui_sender, true).await }),
-                })
+                Ok(())
             }
             ChangedPackages::All => {
                 let mut opts = self.base.opts().clone();
@@ -348,14 +446,9 @@ impl WatchClient {
 
                 self.watched_packages = self.run.get_relevant_packages();
 
-                // Clean up currently running persistent tasks
-                if let Some(RunHandle { stopper, run_task }) = self.persistent_tasks_handle.take() {
-                    // Shut down the tasks for the run
-                    stopper.stop().await;
-                    // Run should exit shortly after we stop all child tasks, wait for it to finish
-                    // to ensure all messages are flushed.
-                    let _ = run_task.await;
-                }
+                // Clean up all currently running tasks
+                self.stop_runs().await;
+
                 if let Some(sender) = &self.ui_sender {
                     let task_names = self.run.engine.tasks_with_command(&self.run.pkg_dep_graph);
                     sender
@@ -364,38 +457,360 @@ impl WatchClient {
                 }
 
                 if self.run.has_non_interruptible_tasks() {
-                    debug_assert!(
-                        self.persistent_tasks_handle.is_none(),
-                        "persistent handle should be empty before creating a new one"
-                    );
                     let persistent_run = self.run.create_run_for_non_interruptible_tasks();
-                    let ui_sender = self.ui_sender.clone();
                     // If we have persistent tasks, we run them on a separate thread
                     // since persistent tasks don't finish
-                    self.persistent_tasks_handle = Some(RunHandle {
-                        stopper: persistent_run.stopper(),
-                        run_task: tokio::spawn(
-                            async move { persistent_run.run(ui_sender, true).await },
-                        ),
-                    });
-
-                    let non_persistent_run = self.run.create_run_for_interruptible_tasks();
-                    let ui_sender = self.ui_sender.clone();
-                    Ok(RunHandle {
-                        stopper: non_persistent_run.stopper(),
-                        run_task: tokio::spawn(async move {
-                            non_persistent_run.run(ui_sender, true).await
-                        }),
-                    })
-                } else {
-                    let ui_sender = self.ui_sender.clone();
-                    let run = self.run.clone();
-                    Ok(RunHandle {
-                        stopper: run.stopper(),
-                        run_task: tokio::spawn(async move { run.run(ui_sender, true).await }),
-                    })
+                    self.persistent_tasks_handle =
+                        Some(RunHandle::spawn(persistent_run, self.ui_sender.clone()));
                 }
+
+                let run = self.run.clone();
+                self.start_interruptible_tasks(&run, self.watched_packages.clone());
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Starts the interruptible tasks of `run`. Each persistent task gets a
+    /// run of its own so that it can be restarted without restarting any other
+    /// task. Persistent tasks can't be depended on, so they start once the
+    /// non-persistent tasks have finished, as long as their own dependencies
+    /// succeeded.
+    fn start_interruptible_tasks(&mut self, run: &Run, packages: HashSet<PackageName>) {
+        let (finished_tx, finished_rx) = watch::channel(None);
+        let continue_on_error = run.opts.run_opts.continue_on_error;
+
+        let non_persistent_run = run.create_run_for_non_persistent_tasks();
+        let tasks = task_ids(&non_persistent_run.engine);
+        if tasks.is_empty() {
+            finished_tx.send_replace(Some(FinishedTasks {
+                tasks: None,
+                continue_on_error,
+            }));
+        } else {
+            let ui_sender = self.ui_sender.clone();
+            let handle = RunHandle {
+                stopper: non_persistent_run.stopper(),
+                run_task: tokio::spawn(async move {
+                    let result = non_persistent_run.run(ui_sender, true).await;
+                    finished_tx.send_replace(Some(FinishedTasks {
+                        tasks: non_persistent_run.task_hash_tracker.get().cloned(),
+                        continue_on_error,
+                    }));
+                    result
+                }),
+                dependencies: None,
+            };
+            self.active_runs.push(ActiveRun {
+                handle,
+                tasks,
+                packages: packages.clone(),
+            });
+        }
+
+        for task in run.engine.tasks() {
+            let TaskNode::Task(task) = task else {
+                continue;
+            };
+            if !run
+                .engine
+                .task_definition(task)
+                .is_some_and(|def| def.persistent && def.interruptible)
+            {
+                continue;
             }
+
+            let dependencies = run
+                .engine
+                .dependencies(task)
+                .into_iter()
+                .flatten()
+                .filter_map(|dependency| match dependency {
+                    TaskNode::Root => None,
+                    TaskNode::Task(dependency) => Some(dependency.clone()),
+                })
+                .collect::<Vec<_>>();
+            let persistent_run = run.create_run_for_persistent_task(task);
+            let mut finished = finished_rx.clone();
+            let ui_sender = self.ui_sender.clone();
+            let task_id = task.clone();
+            let handle = RunHandle {
+                stopper: persistent_run.stopper(),
+                run_task: tokio::spawn(async move {
+                    let finished = finished
+                        .wait_for(Option::is_some)
+                        .await
+                        .ok()
+                        .and_then(|finished| (*finished).clone());
+                    // The batch was stopped before it finished
+                    let Some(finished) = finished else {
+                        return Ok(0);
+                    };
+                    let hashes = match finished.dependency_hashes(&dependencies) {
+                        Ok(hashes) => hashes,
+                        Err(dependency) => {
+                            warn!(
+                                "not starting {task_id} because its dependency {dependency} failed"
+                            );
+                            return Ok(0);
+                        }
+                    };
+                    persistent_run
+                        .with_dependency_hashes(hashes)
+                        .run(ui_sender, true)
+                        .await
+                }),
+                dependencies: Some(finished_rx.clone()),
+            };
+            self.active_runs.push(ActiveRun {
+                handle,
+                tasks: HashSet::from([task.clone()]),
+                packages: packages.clone(),
+            });
+        }
+    }
+
+    /// Marks the tasks in `run` as restarted and notes which of the changed
+    /// packages caused the restart
+    fn report_restart(
+        &self,
+        run: &Run,
+        changed_packages: &HashSet<PackageName>,
+    ) -> Result<(), Error> {
+        let mut task_names = run.engine.tasks_with_command(&run.pkg_dep_graph);
+        if task_names.is_empty() {
+            return Ok(());
         }
+        task_names.sort();
+        let reason = format!(
+            "{} changed",
+            changed_packages
+                .iter()
+                .map(|pkg| pkg.to_string())
+                .sorted()
+                .join(", ")
+        );
+
+        let Some(sender) = &self.ui_sender else {
+            cprintln!(
+                self.base.color_config,
+                GREY,
+                "• Restarting {}: {reason}",
+                task_names.join(", ")
+            );
+            return Ok(());
+        };
+
+        sender
+            .restart_tasks(task_names.clone())
+            .map_err(|err| Error::UISend(format!("some packages changed: {err}")))?;
+        let message = format!(
+            "\r\n{}\r\n",
+            color!(self.base.color_config, GREY, "• Restarting: {reason}")
+        );
+        for task in task_names {
+            sender
+                .output(task, message.clone().into_bytes())
+                .map_err(|err| Error::UISend(format!("some packages changed: {err}")))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Watches for package changes in this process instead of the daemon. Events
+/// are sent in the same form as the daemon sends them.
+pub(crate) async fn watch_in_process(
+    repo_root: &AbsoluteSystemPath,
+) -> Result<impl Stream<Item = Result<proto::PackageChangeEvent, Error>>, Error> {
+    let file_watching = FileWatching::new(repo_root.to_owned())?;
+    let package_changes = file_watching
+        .get_or_init_package_changes_watcher()
+        .package_changes()
+        .await;
+
+    // Like the daemon, we start off by rediscovering all packages
+    let initial_event = proto::PackageChangeEvent {
+        event: Some(proto::package_change_event::Event::RediscoverPackages(
+            proto::RediscoverPackages {},
+        )),
+    };
+
+    // The file watching state is held by the stream so that it stops when the
+    // stream is dropped
+    let events = stream::unfold(Some((file_watching, package_changes)), |state| async move {
+        let (file_watching, mut package_changes) = state?;
+        let event = match package_changes.recv().await {
+            Ok(PackageChangeEvent::Package { name }) => {
+                proto::package_change_event::Event::PackageChanged(proto::PackageChanged {
+                    package_name: name.to_string(),
+                })
+            }
+            Ok(PackageChangeEvent::Rediscover) => {
+                proto::package_change_event::Event::RediscoverPackages(proto::RediscoverPackages {})
+            }
+            Err(RecvError::Lagged(_)) => {
+                warn!("package changes stream lagged");
+                proto::package_change_event::Event::RediscoverPackages(proto::RediscoverPackages {})
+            }
+            Err(RecvError::Closed) => return Some((Err(Error::FileWatchingStopped), None)),
+        };
+
+        Some((
+            Ok(proto::PackageChangeEvent { event: Some(event) }),
+            Some((file_watching, package_changes)),
+        ))
+    });
+
+    Ok(stream::once(async move { Ok(initial_event) }).chain(events))
+}
+
+/// Stops the runs that contain tasks affected by changes to `packages`.
+/// A stopped run may have been running tasks that aren't affected, so the
+/// packages it was started for are added to the returned packages to make
+/// sure those tasks are run again.
+async fn stop_affected_runs(
+    engine: &Engine,
+    active_runs: &mut Vec<ActiveRun>,
+    mut packages: HashSet<PackageName>,
+) -> HashSet<PackageName> {
+    active_runs.retain(|active_run| !active_run.handle.run_task.is_finished());
+
+    loop {
+        let affected_tasks = task_ids(&engine.create_engine_for_subgraph(&packages));
+        let (affected_runs, unaffected_runs): (Vec<_>, Vec<_>) = active_runs
+            .drain(..)
+            .partition(|active_run| !active_run.tasks.is_disjoint(&affected_tasks));
+        *active_runs = unaffected_runs;
+
+        if affected_runs.is_empty() {
+            return packages;
+        }
+
+        for active_run in affected_runs {
+            packages.extend(active_run.packages);
+            active_run.handle.stop().await;
+        }
+    }
+}
+
+fn task_ids(engine: &Engine) -> HashSet<TaskId<'static>> {
+    engine
+        .tasks()
+        .filter_map(|task| match task {
+            TaskNode::Root => None,
+            TaskNode::Task(task) => Some(task.clone()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use tokio::sync::watch;
+    use turborepo_repository::package_graph::PackageName;
+
+    use super::{stop_affected_runs, ActiveRun, RunHandle};
+    use crate::{
+        engine::Engine, process::ProcessManager, run::task_id::TaskId, task_graph::TaskDefinition,
+    };
+
+    fn engine(tasks: &[&'static str]) -> Engine {
+        let mut engine = Engine::new();
+        for task in tasks {
+            let task_id = TaskId::try_from(*task).unwrap();
+            engine.connect_to_root(&task_id);
+            engine.add_definition(task_id, TaskDefinition::default());
+        }
+        engine.seal()
+    }
+
+    fn active_run(tasks: &[&'static str], packages: &[&str]) -> ActiveRun {
+        // A run that is waiting on its dependencies, so that stopping it
+        // aborts it
+        let (_, dependencies) = watch::channel(None);
+        ActiveRun {
+            handle: RunHandle {
+                stopper: crate::run::RunStopper {
+                    manager: ProcessManager::new(false),
+                },
+                run_task: tokio::spawn(std::future::pending()),
+                dependencies: Some(dependencies),
+            },
+            tasks: tasks
+                .iter()
+                .map(|task| TaskId::try_from(*task).unwrap())
+                .collect(),
+            packages: package_names(packages),
+        }
+    }
+
+    fn package_names(packages: &[&str]) -> HashSet<PackageName> {
+        packages.iter().map(|pkg| PackageName::from(*pkg)).collect()
+    }
+
+    fn run_tasks(active_runs: &[ActiveRun]) -> Vec<Vec<String>> {
+        active_runs
+            .iter()
+            .map(|active_run| {
+                let mut tasks = active_run
+                    .tasks
+                    .iter()
+                    .map(|task| task.to_string())
+                    .collect::<Vec<_>>();
+                tasks.sort();
+                tasks
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stops_affected_runs() {
+        let engine = engine(&["a#build", "b#build", "b#dev"]);
+        let mut active_runs = vec![
+            active_run(&["a#build", "b#build"], &["a", "b"]),
+            active_run(&["b#dev"], &["b"]),
+        ];
+
+        let packages = stop_affected_runs(&engine, &mut active_runs, package_names(&["a"])).await;
+
+        // The run for `a` and `b` is stopped, so `b` has to be run again
+        assert_eq!(packages, package_names(&["a", "b"]));
+        // `b#dev` is affected by `b`, which is only known once the first run
+        // has been stopped
+        assert!(active_runs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_keeps_unaffected_runs() {
+        let engine = engine(&["a#build", "b#build", "c#dev"]);
+        let mut active_runs = vec![
+            active_run(&["a#build", "b#build"], &["a", "b"]),
+            active_run(&["c#dev"], &["c"]),
+        ];
+
+        let packages = stop_affected_runs(&engine, &mut active_runs, package_names(&["b"])).await;
+
+        assert_eq!(packages, package_names(&["a", "b"]));
+        assert_eq!(run_tasks(&active_runs), vec![vec!["c#dev".to_string()]]);
+    }
+
+    #[tokio::test]
+    async fn test_drops_finished_runs() {
+        let engine = engine(&["a#build", "b#dev"]);
+        let mut finished_run = active_run(&["a#build"], &["a"]);
+        finished_run.handle.run_task = tokio::spawn(async { Ok(0) });
+        while !finished_run.handle.run_task.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        let mut active_runs = vec![finished_run, active_run(&["b#dev"], &["b"])];
+
+        let packages = stop_affected_runs(&engine, &mut active_runs, package_names(&["c"])).await;
+
+        // Nothing was stopped, so only the changed package needs to be run
+        assert_eq!(packages, package_names(&["c"]));
+        assert_eq!(run_tasks(&active_runs), vec![vec!["b#dev".to_string()]]);
     }
 }
@@ -1,6 +1,6 @@
 mod visitor;
 
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
 
 use globwalk::{GlobError, ValidatedGlob};
 use serde::{Deserialize, Serialize};
@@ -88,6 +88,27 @@ pub struct TaskDefinition {
     // input.
     pub interactive: bool,
 
+    // Timeout is the maximum amount of time the task is allowed to run before
+    // it gets terminated and marked as timed out.
+    pub timeout: Option<Duration>,
+
+    // TimeoutGracePeriod is how long a timed out task is given to exit after
+    // being interrupted before it gets killed. A zero grace period kills the
+    // task immediately.
+    pub timeout_grace_period: Option<Duration>,
+
+    // Retries is the number of times a failed task is re-run before it gets
+    // reported as a failure.
+    pub retries: u32,
+
+    // RetryDelay is how long to wait before starting the next attempt of a
+    // failed task.
+    pub retry_delay: Option<Duration>,
+
+    // Resources is the amount of each named resource the task uses while it
+    // runs. Running tasks are limited to the resource limits of the run.
+    pub resources: BTreeMap<String, u32>,
+
     // Override for global env mode setting
     pub env_mode: Option<EnvMode>,
 
@@ -112,6 +133,11 @@ impl Default for TaskDefinition {
             persistent: Default::default(),
             interruptible: Default::default(),
             interactive: Default::default(),
+            timeout: Default::default(),
+            timeout_grace_period: Default::default(),
+            retries: Default::default(),
+            retry_delay: Default::default(),
+            resources: Default::default(),
             env_mode: Default::default(),
             siblings: Default::default(),
         }
@@ -20,6 +20,11 @@ pub enum TaskErrorCause {
     Spawn { msg: String },
     #[error("command {command} exited ({exit_code})")]
     Exit { command: String, exit_code: i32 },
+    #[error("command {command} timed out after {}", humantime::format_duration(*.timeout))]
+    Timeout {
+        command: String,
+        timeout: std::time::Duration,
+    },
     #[error("turbo has internal error processing task")]
     Internal,
 }
@@ -87,6 +92,10 @@ impl TaskErrorCause {
     pub fn from_execution(command: String, exit_code: i32) -> Self {
         TaskErrorCause::Exit { command, exit_code }
     }
+
+    pub fn from_timeout(command: String, timeout: std::time::Duration) -> Self {
+        TaskErrorCause::Timeout { command, timeout }
+    }
 }
 
 #[cfg(test)]
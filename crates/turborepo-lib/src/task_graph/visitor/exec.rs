@@ -28,9 +28,13 @@ use crate::{
         task_id::TaskId,
         CacheOutput, TaskCache,
     },
+    task_graph::TaskDefinition,
     task_hash::TaskHashTracker,
 };
 
+// How long a task is given to exit after being interrupted before it is killed
+const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_millis(500);
+
 pub struct ExecContextFactory<'a> {
     visitor: &'a Visitor<'a>,
     errors: Arc<Mutex<Vec<TaskError>>>,
@@ -81,6 +85,7 @@ impl<'a> ExecContextFactory<'a> {
         mut execution_env: EnvironmentVariableMap,
         takes_input: bool,
         task_access: TaskAccess,
+        task_definition: &TaskDefinition,
     ) -> Result<Option<ExecContext>, super::Error> {
         let task_id_for_display = self.visitor.display_task_id(&task_id);
         let task_id_string = &task_id.to_string();
@@ -91,6 +96,12 @@ impl<'a> ExecContextFactory<'a> {
         else {
             return Ok(None);
         };
+        // The `--task-timeout` override isn't applied to persistent tasks as they
+        // aren't expected to exit.
+        let timeout = match self.visitor.run_opts.task_timeout {
+            Some(timeout) if !task_definition.persistent => Some(timeout),
+            _ => task_definition.timeout,
+        };
         Ok(Some(ExecContext {
             engine: self.engine.clone(),
             ui_mode: self.visitor.run_opts.ui_mode,
@@ -109,6 +120,12 @@ impl<'a> ExecContextFactory<'a> {
             task_hash,
             execution_env,
             continue_on_error: self.visitor.run_opts.continue_on_error,
+            timeout,
+            stop_timeout: task_definition
+                .timeout_grace_period
+                .unwrap_or(DEFAULT_STOP_TIMEOUT),
+            retries: task_definition.retries,
+            retry_delay: task_definition.retry_delay,
             errors: self.errors.clone(),
             warnings: self.visitor.warnings.clone(),
             takes_input,
@@ -170,6 +187,10 @@ pub struct ExecContext {
     task_hash: String,
     execution_env: EnvironmentVariableMap,
     continue_on_error: bool,
+    timeout: Option<Duration>,
+    stop_timeout: Duration,
+    retries: u32,
+    retry_delay: Option<Duration>,
     errors: Arc<Mutex<Vec<TaskError>>>,
     warnings: Arc<Mutex<Vec<TaskWarning>>>,
     takes_input: bool,
@@ -185,6 +206,12 @@ enum ExecOutcome {
     Task {
         exit_code: Option<i32>,
         message: String,
+        attempts: u32,
+    },
+    // The task was stopped for running longer than its timeout
+    TimedOut {
+        timeout: Duration,
+        attempts: u32,
     },
     // Task didn't execute normally due to a shutdown being initiated by another task
     Shutdown,
@@ -192,7 +219,7 @@ enum ExecOutcome {
 
 enum SuccessOutcome {
     CacheHit,
-    Run,
+    Run { attempts: u32 },
 }
 
 impl ExecContext {
@@ -223,7 +250,10 @@ impl ExecContext {
 
         // If the task resulted in an error, do not group in order to better highlight
         // the error.
-        let is_error = matches!(result, Ok(ExecOutcome::Task { .. }));
+        let is_error = matches!(
+            result,
+            Ok(ExecOutcome::Task { .. } | ExecOutcome::TimedOut { .. })
+        );
         let is_cache_hit = matches!(result, Ok(ExecOutcome::Success(SuccessOutcome::CacheHit)));
         let logs = match output_client.finish(is_error, is_cache_hit) {
             Ok(logs) => logs,
@@ -237,9 +267,10 @@ impl ExecContext {
 
         match result {
             Ok(ExecOutcome::Success(outcome)) => {
+                self.hash_tracker.insert_succeeded(self.task_id.clone());
                 let task_summary = match outcome {
                     SuccessOutcome::CacheHit => tracker.cached().await,
-                    SuccessOutcome::Run => tracker.build_succeeded(0).await,
+                    SuccessOutcome::Run { attempts } => tracker.build_succeeded(0, attempts).await,
                 };
                 callback.send(Ok(())).ok();
                 if let Some(client) = spaces_client {
@@ -248,36 +279,19 @@ impl ExecContext {
                     client.finish_task(info).await.ok();
                 }
             }
-            Ok(ExecOutcome::Task { exit_code, message }) => {
-                let task_summary = tracker.build_failed(exit_code, message).await;
-                callback
-                    .send(match self.continue_on_error {
-                        true => Ok(()),
-                        false => Err(StopExecution),
-                    })
-                    .ok();
-
-                match (spaces_client, self.continue_on_error) {
-                    // Nothing to do
-                    (None, true) => (),
-                    // Shut down manager
-                    (None, false) => self.manager.stop().await,
-                    // Send task
-                    (Some(client), true) => {
-                        let logs = logs.expect("spaced enabled logs should be collected");
-                        let info = self.spaces_task_info(self.task_id.clone(), task_summary, logs);
-                        client.finish_task(info).await.ok();
-                    }
-                    // Send task and shut down manager
-                    (Some(client), false) => {
-                        let logs = logs.unwrap_or_default();
-                        let info = self.spaces_task_info(self.task_id.clone(), task_summary, logs);
-                        // Ignore spaces result as that indicates handler is shut down and we are
-                        // unable to send information to spaces
-                        let (_spaces_result, _) =
-                            tokio::join!(client.finish_task(info), self.manager.stop());
-                    }
-                }
+            Ok(ExecOutcome::Task {
+                exit_code,
+                message,
+                attempts,
+            }) => {
+                let task_summary = tracker.build_failed(exit_code, message, attempts).await;
+                self.handle_task_failure(task_summary, logs, callback, spaces_client)
+                    .await;
+            }
+            Ok(ExecOutcome::TimedOut { timeout, attempts }) => {
+                let task_summary = tracker.timed_out(timeout, attempts).await;
+                self.handle_task_failure(task_summary, logs, callback, spaces_client)
+                    .await;
             }
             Ok(ExecOutcome::Shutdown) => {
                 tracker.cancel();
@@ -297,6 +311,43 @@ impl ExecContext {
         Ok(())
     }
 
+    async fn handle_task_failure(
+        &self,
+        task_summary: TaskExecutionSummary,
+        logs: Option<Vec<u8>>,
+        callback: oneshot::Sender<Result<(), StopExecution>>,
+        spaces_client: Option<SpacesTaskClient>,
+    ) {
+        callback
+            .send(match self.continue_on_error {
+                true => Ok(()),
+                false => Err(StopExecution),
+            })
+            .ok();
+
+        match (spaces_client, self.continue_on_error) {
+            // Nothing to do
+            (None, true) => (),
+            // Shut down manager
+            (None, false) => self.manager.stop().await,
+            // Send task
+            (Some(client), true) => {
+                let logs = logs.expect("spaced enabled logs should be collected");
+                let info = self.spaces_task_info(self.task_id.clone(), task_summary, logs);
+                client.finish_task(info).await.ok();
+            }
+            // Send task and shut down manager
+            (Some(client), false) => {
+                let logs = logs.unwrap_or_default();
+                let info = self.spaces_task_info(self.task_id.clone(), task_summary, logs);
+                // Ignore spaces result as that indicates handler is shut down and we are
+                // unable to send information to spaces
+                let (_spaces_result, _) =
+                    tokio::join!(client.finish_task(info), self.manager.stop());
+            }
+        }
+    }
+
     fn prefixed_ui<'a, W: Write>(
         &self,
         output_client: &'a TaskOutput<W>,
@@ -318,7 +369,6 @@ impl ExecContext {
         output_client: &TaskOutput<impl Write>,
         telemetry: &PackageTaskEventBuilder,
     ) -> Result<ExecOutcome, InternalError> {
-        let task_start = Instant::now();
         let mut prefixed_ui = self.prefixed_ui(output_client);
 
         if self.ui_mode.has_sender() {
@@ -361,45 +411,6 @@ impl ExecContext {
             }
         }
 
-        let cmd = self.cmd.clone();
-
-        let mut process = match self.manager.spawn(cmd, Duration::from_millis(500)) {
-            Some(Ok(child)) => child,
-            // Turbo was unable to spawn a process
-            Some(Err(e)) => {
-                // Note: we actually failed to spawn, but this matches the Go output
-                prefixed_ui.error(&format!("command finished with error: {e}"));
-                let error_string = e.to_string();
-                self.errors
-                    .lock()
-                    .expect("lock poisoned")
-                    .push(TaskError::from_spawn(self.task_id_for_display.clone(), e));
-                return Ok(ExecOutcome::Task {
-                    exit_code: None,
-                    message: error_string,
-                });
-            }
-            // Turbo is shutting down
-            None => {
-                return Ok(ExecOutcome::Shutdown);
-            }
-        };
-
-        if self.ui_mode.has_sender() && self.takes_input {
-            if let TaskOutput::UI(task) = output_client {
-                if let Some(stdin) = process.stdin() {
-                    task.set_stdin(stdin);
-                }
-            }
-        }
-
-        // Even if user does not have the TUI and cannot interact with a task, we keep
-        // stdin open for persistent tasks as some programs will shut down if stdin is
-        // closed.
-        if !self.takes_input && !self.manager.closing_stdin_ends_process() {
-            process.stdin();
-        }
-
         let mut stdout_writer = self
             .task_cache
             .output_writer(prefixed_ui.task_writer())
@@ -407,22 +418,150 @@ impl ExecContext {
                 telemetry.track_error(TrackedErrors::FailedToCaptureOutputs);
             })?;
 
-        let exit_status = match process.wait_with_piped_outputs(&mut stdout_writer).await {
-            Ok(Some(exit_status)) => exit_status,
-            Err(e) => {
-                telemetry.track_error(TrackedErrors::FailedToPipeOutputs);
-                return Err(e.into());
+        let mut attempts = 0;
+        let (process, exit_status, timed_out, task_duration) = loop {
+            attempts += 1;
+            let cmd = self.cmd.clone();
+            // Only the last attempt is timed, since failed attempts and the
+            // delays between them aren't needed to produce the outputs
+            let attempt_start = Instant::now();
+
+            let mut process = match self.manager.spawn(cmd, self.stop_timeout) {
+                Some(Ok(child)) => child,
+                // Turbo was unable to spawn a process
+                Some(Err(e)) => {
+                    // Note: we actually failed to spawn, but this matches the Go output
+                    prefixed_ui.error(&format!("command finished with error: {e}"));
+                    let error_string = e.to_string();
+                    self.errors
+                        .lock()
+                        .expect("lock poisoned")
+                        .push(TaskError::from_spawn(self.task_id_for_display.clone(), e));
+                    return Ok(ExecOutcome::Task {
+                        exit_code: None,
+                        message: error_string,
+                        attempts,
+                    });
+                }
+                // Turbo is shutting down
+                None => {
+                    return Ok(ExecOutcome::Shutdown);
+                }
+            };
+
+            if self.ui_mode.has_sender() && self.takes_input {
+                if let TaskOutput::UI(task) = output_client {
+                    if let Some(stdin) = process.stdin() {
+                        task.set_stdin(stdin);
+                    }
+                }
             }
-            Ok(None) => {
-                // TODO: how can this happen? we only update the
-                // exit status with Some and it is only initialized with
-                // None. Is it still running?
-                telemetry.track_error(TrackedErrors::UnknownChildExit);
-                error!("unable to determine why child exited");
-                return Err(InternalError::UnknownChildExit);
+
+            // Even if user does not have the TUI and cannot interact with a task, we keep
+            // stdin open for persistent tasks as some programs will shut down if stdin is
+            // closed.
+            if !self.takes_input && !self.manager.closing_stdin_ends_process() {
+                process.stdin();
             }
+
+            // Keep a handle to the child so it can be stopped while we're waiting on it
+            let mut timeout_handle = process.clone();
+            let kill_on_timeout = self.stop_timeout.is_zero();
+            let mut timed_out = None;
+            let wait_result = {
+                let wait = process.wait_with_piped_outputs(&mut stdout_writer);
+                tokio::pin!(wait);
+                match self.timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, &mut wait).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            timed_out = Some(timeout);
+                            // Keep piping outputs while the child shuts down so nothing
+                            // it writes on its way out is lost
+                            let (result, _) = tokio::join!(&mut wait, async {
+                                if kill_on_timeout {
+                                    timeout_handle.kill().await
+                                } else {
+                                    timeout_handle.stop().await
+                                }
+                            });
+                            result
+                        }
+                    },
+                    None => wait.await,
+                }
+            };
+
+            let exit_status = match wait_result {
+                Ok(Some(exit_status)) => exit_status,
+                Err(e) => {
+                    telemetry.track_error(TrackedErrors::FailedToPipeOutputs);
+                    return Err(e.into());
+                }
+                Ok(None) => {
+                    // TODO: how can this happen? we only update the
+                    // exit status with Some and it is only initialized with
+                    // None. Is it still running?
+                    telemetry.track_error(TrackedErrors::UnknownChildExit);
+                    error!("unable to determine why child exited");
+                    return Err(InternalError::UnknownChildExit);
+                }
+            };
+
+            let failure = match (timed_out, exit_status) {
+                (Some(timeout), _) => Some(TaskErrorCause::from_timeout(
+                    process.label().to_string(),
+                    timeout,
+                )),
+                (None, ChildExit::Finished(Some(code))) if code != 0 => Some(
+                    TaskErrorCause::from_execution(process.label().to_string(), code),
+                ),
+                _ => None,
+            };
+            if let Some(failure) = failure.filter(|_| attempts <= self.retries) {
+                // The notice goes through the log writer so the output of every attempt
+                // stays in the task logs
+                if let Err(e) = writeln!(
+                    stdout_writer,
+                    "{failure}, retrying (attempt {} of {})",
+                    attempts + 1,
+                    self.retries + 1
+                ) {
+                    error!("error writing logs: {e}");
+                }
+                if let Some(retry_delay) = self.retry_delay {
+                    // Shutting down shouldn't have to wait out the delay
+                    tokio::select! {
+                        _ = tokio::time::sleep(retry_delay) => {}
+                        _ = self.manager.closing() => return Ok(ExecOutcome::Shutdown),
+                    }
+                }
+                continue;
+            }
+
+            break (process, exit_status, timed_out, attempt_start.elapsed());
         };
-        let task_duration = task_start.elapsed();
+
+        // A timed out task is a failure regardless of how it responded to being stopped
+        if let Some(timeout) = timed_out {
+            if let Err(e) = stdout_writer.flush() {
+                error!("error flushing logs: {e}");
+            }
+            if let Err(e) = self.task_cache.on_error(&mut prefixed_ui) {
+                error!("error reading logs: {e}");
+            }
+            let error = TaskErrorCause::from_timeout(process.label().to_string(), timeout);
+            if self.continue_on_error {
+                prefixed_ui.warn(&format!("{error}, but continuing..."));
+            } else {
+                prefixed_ui.error(&format!("{error}"));
+            }
+            self.errors
+                .lock()
+                .expect("lock poisoned")
+                .push(TaskError::new(self.task_id_for_display.clone(), error));
+            return Ok(ExecOutcome::TimedOut { timeout, attempts });
+        }
 
         match exit_status {
             ChildExit::Finished(Some(0)) => {
@@ -447,7 +586,7 @@ impl ExecContext {
                 }
 
                 // Return success outcome
-                Ok(ExecOutcome::Success(SuccessOutcome::Run))
+                Ok(ExecOutcome::Success(SuccessOutcome::Run { attempts }))
             }
             ChildExit::Finished(Some(code)) => {
                 // If there was an error, flush the buffered output
@@ -471,6 +610,7 @@ impl ExecContext {
                 Ok(ExecOutcome::Task {
                     exit_code: Some(code),
                     message,
+                    attempts,
                 })
             }
             // The child exited in a way where we can't figure out how it finished so we assume it
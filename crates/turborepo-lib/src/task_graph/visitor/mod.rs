@@ -45,7 +45,7 @@ use crate::{
         task_id::TaskId,
         RunCache,
     },
-    task_hash::{self, PackageInputsHashes, TaskHashTrackerState, TaskHasher},
+    task_hash::{self, PackageInputsHashes, TaskHashTracker, TaskHashTrackerState, TaskHasher},
 };
 
 // This holds the whole world
@@ -192,9 +192,14 @@ impl<'a> Visitor<'a> {
         let concurrency = self.run_opts.concurrency as usize;
         let (node_sender, mut node_stream) = mpsc::channel(concurrency);
 
+        let mut execution_options = ExecutionOptions::new(false, concurrency)
+            .with_resource_limits(self.run_opts.resource_limits.clone());
+        if let Some(critical_paths) = self.run_tracker.critical_paths() {
+            execution_options = execution_options.with_critical_paths(critical_paths.clone());
+        }
         let engine_handle = {
             let engine = engine.clone();
-            tokio::spawn(engine.execute(ExecutionOptions::new(false, concurrency), node_sender))
+            tokio::spawn(engine.execute(execution_options, node_sender))
         };
         let mut tasks = FuturesUnordered::new();
         let errors = Arc::new(Mutex::new(Vec::new()));
@@ -242,7 +247,9 @@ impl<'a> Visitor<'a> {
             let task_env_mode = task_definition.env_mode.unwrap_or(self.global_env_mode);
             package_task_event.track_env_mode(&task_env_mode.to_string());
 
-            let dependency_set = engine.dependencies(&info).ok_or(Error::MissingDefinition)?;
+            let dependency_set = engine
+                .hash_dependencies(&info)
+                .ok_or(Error::MissingDefinition)?;
 
             let task_hash_telemetry = package_task_event.child();
             let task_hash = self.task_hasher.calculate_task_hash(
@@ -291,6 +298,7 @@ impl<'a> Visitor<'a> {
                         execution_env,
                         takes_input,
                         self.task_access.clone(),
+                        task_definition,
                     )?
                     else {
                         // TODO(gsoltis): if/when we fix https://github.com/vercel/turborepo/issues/937
@@ -534,6 +542,10 @@ impl<'a> Visitor<'a> {
         self.task_hasher.into_task_hash_tracker_state()
     }
 
+    pub fn task_hash_tracker(&self) -> TaskHashTracker {
+        self.task_hasher.task_hash_tracker()
+    }
+
     pub fn dry_run(&mut self) {
         self.dry = true;
         // No need to start a UI on dry run
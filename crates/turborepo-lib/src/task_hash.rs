@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{Arc, Mutex},
 };
 
@@ -22,7 +22,10 @@ use crate::{
     framework::infer_framework,
     hash::{FileHashes, LockFilePackages, TaskHashable, TurboHash},
     opts::RunOpts,
-    run::task_id::TaskId,
+    run::{
+        explain::{hashed_env_vars, TaskHashInputs},
+        task_id::TaskId,
+    },
     task_graph::TaskDefinition,
     DaemonClient, DaemonConnector,
 };
@@ -215,6 +218,19 @@ impl PackageInputsHashes {
             expanded_hashes,
         })
     }
+
+    /// The files that make up the inputs of a task, relative to its package
+    pub fn expanded_inputs(&self, task_id: &TaskId<'static>) -> Option<&FileHashes> {
+        self.expanded_hashes.get(task_id)
+    }
+
+    #[cfg(test)]
+    pub fn from_expanded_inputs(expanded_hashes: HashMap<TaskId<'static>, FileHashes>) -> Self {
+        Self {
+            hashes: HashMap::new(),
+            expanded_hashes,
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone)]
@@ -235,6 +251,10 @@ pub struct TaskHashTrackerState {
     package_task_cache: HashMap<TaskId<'static>, CacheHitMetadata>,
     #[serde(skip)]
     package_task_inputs_expanded_hashes: HashMap<TaskId<'static>, FileHashes>,
+    #[serde(skip)]
+    package_task_hash_inputs: HashMap<TaskId<'static>, TaskHashInputs>,
+    #[serde(skip)]
+    package_task_succeeded: HashSet<TaskId<'static>>,
 }
 
 /// Caches package-inputs hashes, and package-task hashes.
@@ -335,7 +355,10 @@ impl<'a> TaskHasher<'a> {
 
         let hashable_env_pairs = env_vars.all.to_hashable();
         let outputs = task_definition.hashable_outputs(task_id);
-        let task_dependency_hashes = self.calculate_dependency_hashes(dependency_set)?;
+        let dependency_hashes = self.calculate_dependency_hashes(dependency_set)?;
+        let mut task_dependency_hashes = dependency_hashes.values().cloned().collect::<Vec<_>>();
+        task_dependency_hashes.sort();
+        task_dependency_hashes.dedup();
         let external_deps_hash =
             is_monorepo.then(|| get_external_deps_hash(&workspace.transitive_dependencies));
 
@@ -353,6 +376,34 @@ impl<'a> TaskHasher<'a> {
         // We wrap in an Option to mimic Go's serialization of nullable values
         let optional_package_dir = (!is_root_package).then_some(package_dir);
 
+        let pass_through_env: &[String] = match task_env_mode {
+            EnvMode::Loose => &[],
+            EnvMode::Strict => task_definition
+                .pass_through_env
+                .as_deref()
+                .unwrap_or_default(),
+        };
+        // The hash is filled in once it's calculated
+        let mut hash_inputs = TaskHashInputs {
+            task_id: task_id.to_string(),
+            hash: String::new(),
+            package_dir: optional_package_dir.as_ref().map(|dir| dir.to_string()),
+            external_dependencies_hash: external_deps_hash.clone(),
+            dependencies: dependency_hashes,
+            outputs: outputs
+                .inclusions
+                .iter()
+                .cloned()
+                .chain(outputs.exclusions.iter().map(|glob| format!("!{glob}")))
+                .collect(),
+            pass_through_args: self.run_opts.pass_through_args.clone(),
+            env: task_definition.env.clone(),
+            env_vars: hashed_env_vars(env_vars.all.to_secret_hashable()),
+            pass_through_env: pass_through_env.to_vec(),
+            env_mode: task_env_mode,
+            framework: framework_slug.clone(),
+        };
+
         let task_hashable = TaskHashable {
             global_hash: self.global_hash,
             task_dependency_hashes,
@@ -373,12 +424,14 @@ impl<'a> TaskHasher<'a> {
         };
 
         let task_hash = task_hashable.calculate_task_hash();
+        hash_inputs.hash = task_hash.clone();
 
         self.task_hash_tracker.insert_hash(
             task_id.clone(),
             env_vars,
             task_hash.clone(),
             framework_slug,
+            hash_inputs,
         );
 
         Ok(task_hash)
@@ -392,12 +445,12 @@ impl<'a> TaskHasher<'a> {
     ///
     /// * `dependency_set`: The dependencies of the current task
     ///
-    /// returns: Result<Vec<String, Global>, Error>
+    /// returns: Result<BTreeMap<String, String>, Error> of task id to hash
     fn calculate_dependency_hashes(
         &self,
         dependency_set: HashSet<&TaskNode>,
-    ) -> Result<Vec<String>, Error> {
-        let mut dependency_hash_set = HashSet::new();
+    ) -> Result<BTreeMap<String, String>, Error> {
+        let mut dependency_hashes = BTreeMap::new();
 
         for dependency_task in dependency_set {
             let TaskNode::Task(dependency_task_id) = dependency_task else {
@@ -408,13 +461,10 @@ impl<'a> TaskHasher<'a> {
                 .task_hash_tracker
                 .hash(dependency_task_id)
                 .ok_or_else(|| Error::MissingDependencyTaskHash(dependency_task.to_string()))?;
-            dependency_hash_set.insert(dependency_hash.clone());
+            dependency_hashes.insert(dependency_task_id.to_string(), dependency_hash);
         }
 
-        let mut dependency_hash_list = dependency_hash_set.into_iter().collect::<Vec<_>>();
-        dependency_hash_list.sort();
-
-        Ok(dependency_hash_list)
+        Ok(dependency_hashes)
     }
 
     pub fn into_task_hash_tracker_state(self) -> TaskHashTrackerState {
@@ -571,8 +621,12 @@ impl TaskHashTracker {
         env_vars: DetailedMap,
         hash: String,
         framework_slug: Option<String>,
+        hash_inputs: TaskHashInputs,
     ) {
         let mut state = self.state.lock().expect("hash tracker mutex poisoned");
+        state
+            .package_task_hash_inputs
+            .insert(task_id.clone(), hash_inputs);
         state
             .package_task_env_vars
             .insert(task_id.clone(), env_vars);
@@ -584,6 +638,16 @@ impl TaskHashTracker {
         state.package_task_hashes.insert(task_id, hash);
     }
 
+    /// Records the hashes of tasks that were run separately, so that the
+    /// tasks depending on them are hashed the same as in a full run
+    pub fn insert_dependency_hashes(
+        &self,
+        hashes: impl IntoIterator<Item = (TaskId<'static>, String)>,
+    ) {
+        let mut state = self.state.lock().expect("hash tracker mutex poisoned");
+        state.package_task_hashes.extend(hashes);
+    }
+
     pub fn env_vars(&self, task_id: &TaskId) -> Option<DetailedMap> {
         let state = self.state.lock().expect("hash tracker mutex poisoned");
         state.package_task_env_vars.get(task_id).cloned()
@@ -618,6 +682,34 @@ impl TaskHashTracker {
         state.package_task_cache.insert(task_id, cache_status);
     }
 
+    /// Whether the task ran successfully, or was restored from the cache
+    pub fn succeeded(&self, task_id: &TaskId) -> bool {
+        let state = self.state.lock().expect("hash tracker mutex poisoned");
+        state.package_task_succeeded.contains(task_id)
+    }
+
+    pub fn insert_succeeded(&self, task_id: TaskId<'static>) {
+        let mut state = self.state.lock().expect("hash tracker mutex poisoned");
+        state.package_task_succeeded.insert(task_id);
+    }
+
+    /// The inputs of every task that has been hashed, along with the files
+    /// that were hashed for it
+    pub fn hash_inputs(&self) -> Vec<(TaskHashInputs, Option<FileHashes>)> {
+        let state = self.state.lock().expect("hash tracker mutex poisoned");
+        state
+            .package_task_hash_inputs
+            .iter()
+            .map(|(task_id, inputs)| {
+                let files = state
+                    .package_task_inputs_expanded_hashes
+                    .get(task_id)
+                    .cloned();
+                (inputs.clone(), files)
+            })
+            .collect()
+    }
+
     pub fn get_expanded_inputs(&self, task_id: &TaskId) -> Option<FileHashes> {
         let state = self.state.lock().expect("hash tracker mutex poisoned");
         state
@@ -353,7 +353,7 @@ fn root_turbo_json_from_scripts(scripts: &[String]) -> Result<TurboJson, Error>
 
 fn workspace_turbo_json_from_scripts(scripts: &[String]) -> Result<TurboJson, Error> {
     let mut turbo_json = TurboJson {
-        extends: Spanned::new(vec!["//".to_owned()]),
+        extends: Spanned::new(vec![Spanned::new("//".to_owned())]),
         ..Default::default()
     };
     for script in scripts {
@@ -3,6 +3,7 @@ use std::{
     fmt::Display,
     ops::{Deref, DerefMut},
     sync::Arc,
+    time::Duration,
 };
 
 use biome_deserialize_macros::Deserializable;
@@ -51,11 +52,48 @@ pub struct SpacesJson {
 pub struct TurboJson {
     text: Option<Arc<str>>,
     path: Option<Arc<str>>,
-    pub(crate) extends: Spanned<Vec<String>>,
+    pub(crate) extends: Spanned<Vec<Spanned<String>>>,
     pub(crate) global_deps: Vec<String>,
     pub(crate) global_env: Vec<String>,
     pub(crate) global_pass_through_env: Option<Vec<String>>,
     pub(crate) tasks: Pipeline,
+    pub(crate) tags: Vec<String>,
+    pub(crate) boundaries: Option<BoundariesConfig>,
+}
+
+/// Rules for `turbo boundaries` that are configured in the root turbo.json
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Deserializable)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundariesConfig {
+    // Rules that apply to packages with a given tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<BTreeMap<String, TagRules>>,
+    // Globs of files, relative to the repository root, whose diagnostics are
+    // suppressed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Deserializable)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRules {
+    // Restricts the packages that a package with this tag can depend on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Permissions>,
+    // Restricts the packages that can depend on a package with this tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependents: Option<Permissions>,
+}
+
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Deserializable)]
+#[serde(rename_all = "camelCase")]
+pub struct Permissions {
+    // If set, packages must have at least one of these tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+    // Packages must not have any of these tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deny: Option<Vec<String>>,
 }
 
 // Iterable is required to enumerate allowed keys
@@ -80,6 +118,36 @@ pub(crate) struct RawRemoteCacheOptions {
     enabled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     upload_timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) backend: Option<RemoteCacheBackendKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) bucket: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) prefix: Option<String>,
+}
+
+/// The kinds of remote cache that can be selected with `remoteCache.backend`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserializable)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteCacheBackendKind {
+    #[default]
+    Vercel,
+    S3,
+    Http,
+}
+
+impl Display for RemoteCacheBackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RemoteCacheBackendKind::Vercel => "vercel",
+            RemoteCacheBackendKind::S3 => "s3",
+            RemoteCacheBackendKind::Http => "http",
+        })
+    }
 }
 
 impl From<&RawRemoteCacheOptions> for ConfigurationOptions {
@@ -112,7 +180,7 @@ pub struct RawTurboJson {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental_spaces: Option<SpacesJson>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    extends: Option<Spanned<Vec<UnescapedString>>>,
+    extends: Option<Spanned<Vec<Spanned<UnescapedString>>>>,
     // Global root filesystem dependencies
     #[serde(skip_serializing_if = "Option::is_none")]
     global_dependencies: Option<Vec<Spanned<UnescapedString>>>,
@@ -143,6 +211,20 @@ pub struct RawTurboJson {
     pub env_mode: Option<EnvMode>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_dir: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_max_size: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_max_age: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_layout: Option<Spanned<UnescapedString>>,
+    // Total amount of each named resource that running tasks can use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_limits: Option<BTreeMap<String, u32>>,
+    // Tags used to group packages for `turbo boundaries` rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boundaries: Option<BoundariesConfig>,
 
     #[deserializable(rename = "//")]
     #[serde(skip)]
@@ -240,6 +322,16 @@ pub struct RawTaskDefinition {
     output_logs: Option<Spanned<OutputLogsMode>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     interactive: Option<Spanned<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout_grace_period: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retries: Option<Spanned<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_delay: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resources: Option<Spanned<BTreeMap<String, u32>>>,
     // TODO: Remove this once we have the ability to load task definitions directly
     // instead of deriving them from a TurboJson
     #[serde(skip)]
@@ -280,6 +372,11 @@ impl RawTaskDefinition {
         set_field!(self, other, env);
         set_field!(self, other, pass_through_env);
         set_field!(self, other, interactive);
+        set_field!(self, other, timeout);
+        set_field!(self, other, timeout_grace_period);
+        set_field!(self, other, retries);
+        set_field!(self, other, retry_delay);
+        set_field!(self, other, resources);
         set_field!(self, other, env_mode);
         set_field!(self, other, siblings);
     }
@@ -426,6 +523,27 @@ impl TryFrom<RawTaskDefinition> for TaskDefinition {
             })
             .transpose()?;
 
+        let timeout = raw_task
+            .timeout
+            .map(|timeout| {
+                // A zero timeout would kill the task as soon as it starts
+                let (span, text) = timeout.span_and_text("turbo.json");
+                let duration = parse_task_duration(timeout, "timeout")?;
+                if duration.is_zero() {
+                    return Err(Error::ZeroTaskTimeout { span, text });
+                }
+                Ok(duration)
+            })
+            .transpose()?;
+        let timeout_grace_period = raw_task
+            .timeout_grace_period
+            .map(|grace_period| parse_task_duration(grace_period, "timeoutGracePeriod"))
+            .transpose()?;
+        let retry_delay = raw_task
+            .retry_delay
+            .map(|retry_delay| parse_task_duration(retry_delay, "retryDelay"))
+            .transpose()?;
+
         let siblings = raw_task.siblings.map(|siblings| {
             siblings
                 .into_iter()
@@ -448,12 +566,30 @@ impl TryFrom<RawTaskDefinition> for TaskDefinition {
             persistent,
             interruptible: *interruptible,
             interactive,
+            timeout,
+            timeout_grace_period,
+            retries: raw_task.retries.map_or(0, |retries| retries.into_inner()),
+            retry_delay,
+            resources: raw_task
+                .resources
+                .map(|resources| resources.into_inner())
+                .unwrap_or_default(),
             env_mode: raw_task.env_mode,
             siblings,
         })
     }
 }
 
+fn parse_task_duration(
+    duration: Spanned<UnescapedString>,
+    field: &'static str,
+) -> Result<Duration, Error> {
+    humantime::parse_duration(duration.as_inner()).map_err(|_| {
+        let (span, text) = duration.span_and_text("turbo.json");
+        Error::InvalidTaskDuration { field, span, text }
+    })
+}
+
 impl RawTurboJson {
     pub(crate) fn read(
         repo_root: &AbsoluteSystemPath,
@@ -586,11 +722,18 @@ impl TryFrom<RawTurboJson> for TurboJson {
                 global_deps
             },
             tasks: raw_turbo.tasks.unwrap_or_default(),
+            tags: raw_turbo
+                .tags
+                .unwrap_or_default()
+                .into_iter()
+                .map(|tag| tag.into())
+                .collect(),
+            boundaries: raw_turbo.boundaries,
             // copy these over, we don't need any changes here.
             extends: raw_turbo
                 .extends
                 .unwrap_or_default()
-                .map(|s| s.into_iter().map(|s| s.into()).collect()),
+                .map(|s| s.into_iter().map(|s| s.map(|s| s.into())).collect()),
             // Spaces and Remote Cache config is handled through layered config
         })
     }
@@ -641,7 +784,7 @@ impl TurboJson {
     /// Adds a local proxy task to a workspace TurboJson
     pub fn with_proxy(&mut self, mfe_package_name: Option<&str>) {
         if self.extends.is_empty() {
-            self.extends = Spanned::new(vec!["//".into()]);
+            self.extends = Spanned::new(vec![Spanned::new("//".into())]);
         }
 
         self.tasks.insert(
@@ -661,7 +804,7 @@ impl TurboJson {
     /// Adds a sibling relationship from task to sibling
     pub fn with_sibling(&mut self, task: TaskName<'static>, sibling: &TaskName) {
         if self.extends.is_empty() {
-            self.extends = Spanned::new(vec!["//".into()]);
+            self.extends = Spanned::new(vec![Spanned::new("//".into())]);
         }
 
         let task_definition = self.tasks.entry(task).or_default();
@@ -696,10 +839,26 @@ pub fn validate_no_package_task_syntax(turbo_json: &TurboJson) -> Vec<Error> {
 
 pub fn validate_extends(turbo_json: &TurboJson) -> Vec<Error> {
     match turbo_json.extends.first() {
-        Some(package_name) if package_name != ROOT_PKG_NAME || turbo_json.extends.len() > 1 => {
-            let (span, text) = turbo_json.extends.span_and_text("turbo.json");
+        Some(package_name) if package_name.as_str() != ROOT_PKG_NAME => {
+            let (span, text) = package_name.span_and_text("turbo.json");
             vec![Error::ExtendFromNonRoot { span, text }]
         }
+        Some(_) => {
+            let mut seen = HashSet::new();
+            turbo_json
+                .extends
+                .iter()
+                .filter(|package_name| !seen.insert(package_name.as_str()))
+                .map(|package_name| {
+                    let (span, text) = package_name.span_and_text("turbo.json");
+                    Error::DuplicateExtends {
+                        name: package_name.to_string(),
+                        span,
+                        text,
+                    }
+                })
+                .collect()
+        }
         None => {
             let path = turbo_json
                 .path
@@ -720,7 +879,6 @@ pub fn validate_extends(turbo_json: &TurboJson) -> Vec<Error> {
                 text: NamedSource::new(path, text),
             }]
         }
-        _ => vec![],
     }
 }
 
@@ -756,6 +914,8 @@ fn gather_env_vars(
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use anyhow::Result;
     use biome_deserialize::json::deserialize_from_json_str;
     use biome_json_parser::JsonParserOptions;
@@ -764,7 +924,7 @@ mod tests {
     use test_case::test_case;
     use turborepo_unescape::UnescapedString;
 
-    use super::{RawTurboJson, Spanned, TurboJson, UIMode};
+    use super::{validate_extends, Permissions, RawTurboJson, Spanned, TurboJson, UIMode};
     use crate::{
         cli::OutputLogsMode,
         run::task_id::TaskName,
@@ -810,6 +970,11 @@ mod tests {
             persistent: Some(Spanned::new(true).with_range(278..282)),
             interactive: Some(Spanned::new(true).with_range(309..313)),
             interruptible: Some(Spanned::new(true).with_range(342..346)),
+            timeout: None,
+            timeout_grace_period: None,
+            retries: None,
+            retry_delay: None,
+            resources: None,
             env_mode: None,
             siblings: None,
         },
@@ -828,6 +993,11 @@ mod tests {
           persistent: true,
           interactive: true,
           interruptible: true,
+          timeout: None,
+          timeout_grace_period: None,
+          retries: 0,
+          retry_delay: None,
+          resources: BTreeMap::new(),
           env_mode: None,
           siblings: None,
         }
@@ -856,6 +1026,11 @@ mod tests {
             persistent: Some(Spanned::new(true).with_range(315..319)),
             interruptible: Some(Spanned::new(true).with_range(352..356)),
             interactive: None,
+            timeout: None,
+            timeout_grace_period: None,
+            retries: None,
+            retry_delay: None,
+            resources: None,
             env_mode: None,
             siblings: None,
         },
@@ -874,11 +1049,56 @@ mod tests {
             persistent: true,
             interruptible: true,
             interactive: false,
+            timeout: None,
+            timeout_grace_period: None,
+            retries: 0,
+            retry_delay: None,
+            resources: BTreeMap::new(),
             env_mode: None,
             siblings: None,
         }
       ; "full (windows)"
     )]
+    #[test_case(
+        r#"{ "timeout": "5m", "timeoutGracePeriod": "10s" }"#,
+        RawTaskDefinition {
+            timeout: Some(Spanned::<UnescapedString>::new("5m".into()).with_range(13..17)),
+            timeout_grace_period: Some(Spanned::<UnescapedString>::new("10s".into()).with_range(41..46)),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            timeout: Some(Duration::from_secs(300)),
+            timeout_grace_period: Some(Duration::from_secs(10)),
+            ..TaskDefinition::default()
+        }
+      ; "timeout"
+    )]
+    #[test_case(
+        r#"{ "retries": 2, "retryDelay": "1s" }"#,
+        RawTaskDefinition {
+            retries: Some(Spanned::new(2).with_range(13..14)),
+            retry_delay: Some(Spanned::<UnescapedString>::new("1s".into()).with_range(30..34)),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            retries: 2,
+            retry_delay: Some(Duration::from_secs(1)),
+            ..TaskDefinition::default()
+        }
+      ; "retries"
+    )]
+    #[test_case(
+        r#"{ "resources": { "memory": 4, "gpu": 1 } }"#,
+        RawTaskDefinition {
+            resources: Some(Spanned::new(BTreeMap::from([("memory".to_string(), 4), ("gpu".to_string(), 1)])).with_range(15..40)),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            resources: BTreeMap::from([("memory".to_string(), 4), ("gpu".to_string(), 1)]),
+            ..TaskDefinition::default()
+        }
+      ; "resources"
+    )]
     fn test_deserialize_task_definition(
         task_definition_content: &str,
         expected_raw_task_definition: RawTaskDefinition,
@@ -899,6 +1119,41 @@ mod tests {
         Ok(())
     }
 
+    #[test_case(r#"{ "timeout": "forever" }"# ; "invalid timeout")]
+    #[test_case(r#"{ "timeout": "5m", "timeoutGracePeriod": "10" }"# ; "grace period without unit")]
+    #[test_case(r#"{ "retries": 1, "retryDelay": "soon" }"# ; "invalid retry delay")]
+    fn test_invalid_task_timeout(task_definition_content: &str) {
+        let deserialized_result = deserialize_from_json_str(
+            task_definition_content,
+            JsonParserOptions::default().with_allow_comments(),
+            "turbo.json",
+        );
+        let raw_task_definition: RawTaskDefinition =
+            deserialized_result.into_deserialized().unwrap();
+        let result = TaskDefinition::try_from(raw_task_definition);
+        assert!(matches!(
+            result,
+            Err(crate::config::Error::InvalidTaskDuration { .. })
+        ));
+    }
+
+    #[test_case(r#"{ "timeout": "0s" }"# ; "zero seconds")]
+    #[test_case(r#"{ "timeout": "0ms" }"# ; "zero milliseconds")]
+    fn test_zero_task_timeout(task_definition_content: &str) {
+        let deserialized_result = deserialize_from_json_str(
+            task_definition_content,
+            JsonParserOptions::default().with_allow_comments(),
+            "turbo.json",
+        );
+        let raw_task_definition: RawTaskDefinition =
+            deserialized_result.into_deserialized().unwrap();
+        let result = TaskDefinition::try_from(raw_task_definition);
+        assert!(matches!(
+            result,
+            Err(crate::config::Error::ZeroTaskTimeout { .. })
+        ));
+    }
+
     #[test_case("[]", TaskOutputs::default() ; "empty")]
     #[test_case(r#"["target/**"]"#, TaskOutputs { inclusions: vec!["target/**".to_string()], exclusions: vec![] })]
     #[test_case(
@@ -1031,7 +1286,10 @@ mod tests {
     fn test_with_proxy_empty() {
         let mut json = TurboJson::default();
         json.with_proxy(None);
-        assert_eq!(json.extends.as_inner().as_slice(), &["//".to_string()]);
+        assert_eq!(
+            json.extends.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+            ["//"]
+        );
         assert!(json.tasks.contains_key(&TaskName::from("proxy")));
     }
 
@@ -1043,7 +1301,10 @@ mod tests {
             Spanned::new(RawTaskDefinition::default()),
         );
         json.with_proxy(None);
-        assert_eq!(json.extends.as_inner().as_slice(), &["//".to_string()]);
+        assert_eq!(
+            json.extends.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+            ["//"]
+        );
         assert!(json.tasks.contains_key(&TaskName::from("proxy")));
         assert!(json.tasks.contains_key(&TaskName::from("build")));
     }
@@ -1052,7 +1313,10 @@ mod tests {
     fn test_with_proxy_with_proxy_build() {
         let mut json = TurboJson::default();
         json.with_proxy(Some("my-proxy"));
-        assert_eq!(json.extends.as_inner().as_slice(), &["//".to_string()]);
+        assert_eq!(
+            json.extends.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+            ["//"]
+        );
         let proxy_task = json.tasks.get(&TaskName::from("proxy"));
         assert!(proxy_task.is_some());
         let proxy_task = proxy_task.unwrap().as_inner();
@@ -1100,4 +1364,67 @@ mod tests {
             &[Spanned::new(UnescapedString::from("api#server"))]
         );
     }
+
+    #[test_case(r#"{ "extends": ["//"] }"#, &[] ; "root")]
+    #[test_case(r#"{ "extends": ["//", "@acme/config"] }"#, &[] ; "shared config")]
+    #[test_case(
+        r#"{ "extends": ["@acme/config", "//"] }"#,
+        &["You must extend from the root of the workspace first."]
+        ; "root not first"
+    )]
+    #[test_case(
+        r#"{ "extends": ["//", "@acme/config", "@acme/config", "//"] }"#,
+        &["\"@acme/config\" is extended more than once.", "\"//\" is extended more than once."]
+        ; "duplicates"
+    )]
+    fn test_validate_extends(json: &str, expected: &[&str]) {
+        let json = TurboJson::try_from(RawTurboJson::parse(json, "turbo.json").unwrap()).unwrap();
+        let errors = validate_extends(&json)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(errors, expected);
+    }
+
+    #[test]
+    fn test_boundaries_config() {
+        let json = TurboJson::try_from(
+            RawTurboJson::parse(
+                r#"{
+                    "tags": ["ui", "internal"],
+                    "boundaries": {
+                        "tags": {
+                            "ui": { "dependencies": { "deny": ["server"] } },
+                            "internal": { "dependents": { "allow": ["internal"] } }
+                        },
+                        "ignore": ["packages/legacy/**"]
+                    }
+                }"#,
+                "turbo.json",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json.tags, ["ui", "internal"]);
+        let boundaries = json.boundaries.unwrap();
+        assert_eq!(
+            boundaries.ignore,
+            Some(vec!["packages/legacy/**".to_string()])
+        );
+        let rules = boundaries.tags.unwrap();
+        assert_eq!(
+            rules["ui"].dependencies,
+            Some(Permissions {
+                allow: None,
+                deny: Some(vec!["server".to_string()]),
+            })
+        );
+        assert_eq!(
+            rules["internal"].dependents,
+            Some(Permissions {
+                allow: Some(vec!["internal".to_string()]),
+                deny: None,
+            })
+        );
+    }
 }
@@ -100,22 +100,34 @@ impl WithMetadata for RawTurboJson {
     fn add_text(&mut self, text: Arc<str>) {
         self.span.add_text(text.clone());
         self.extends.add_text(text.clone());
+        if let Some(extends) = &mut self.extends {
+            extends.value.add_text(text.clone());
+        }
         self.global_dependencies.add_text(text.clone());
         self.global_env.add_text(text.clone());
         self.global_pass_through_env.add_text(text.clone());
         self.tasks.add_text(text.clone());
         self.cache_dir.add_text(text.clone());
+        self.cache_max_size.add_text(text.clone());
+        self.cache_max_age.add_text(text.clone());
+        self.cache_layout.add_text(text.clone());
         self.pipeline.add_text(text);
     }
 
     fn add_path(&mut self, path: Arc<str>) {
         self.span.add_path(path.clone());
         self.extends.add_path(path.clone());
+        if let Some(extends) = &mut self.extends {
+            extends.value.add_path(path.clone());
+        }
         self.global_dependencies.add_path(path.clone());
         self.global_env.add_path(path.clone());
         self.global_pass_through_env.add_path(path.clone());
         self.tasks.add_path(path.clone());
         self.cache_dir.add_path(path.clone());
+        self.cache_max_size.add_path(path.clone());
+        self.cache_max_age.add_path(path.clone());
+        self.cache_layout.add_path(path.clone());
         self.pipeline.add_path(path);
     }
 }
@@ -149,7 +161,12 @@ impl WithMetadata for RawTaskDefinition {
         self.interruptible.add_text(text.clone());
         self.outputs.add_text(text.clone());
         self.output_logs.add_text(text.clone());
-        self.interactive.add_text(text);
+        self.interactive.add_text(text.clone());
+        self.timeout.add_text(text.clone());
+        self.timeout_grace_period.add_text(text.clone());
+        self.retries.add_text(text.clone());
+        self.retry_delay.add_text(text.clone());
+        self.resources.add_text(text);
     }
 
     fn add_path(&mut self, path: Arc<str>) {
@@ -164,7 +181,12 @@ impl WithMetadata for RawTaskDefinition {
         self.interruptible.add_path(path.clone());
         self.outputs.add_path(path.clone());
         self.output_logs.add_path(path.clone());
-        self.interactive.add_path(path);
+        self.interactive.add_path(path.clone());
+        self.timeout.add_path(path.clone());
+        self.timeout_grace_period.add_path(path.clone());
+        self.retries.add_path(path.clone());
+        self.retry_delay.add_path(path.clone());
+        self.resources.add_path(path);
     }
 }
 
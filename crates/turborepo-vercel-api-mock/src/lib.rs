@@ -289,3 +289,55 @@ pub async fn start_test_server(port: u16) -> Result<()> {
 
     Ok(())
 }
+
+/// Starts a minimal object store that stands in for both S3-compatible and
+/// plain HTTP remote caches. Objects are stored with `PUT` at any path and
+/// served back with `GET` and `HEAD`, along with the `x-` headers they were
+/// uploaded with.
+pub async fn start_object_store_server(port: u16) -> Result<()> {
+    let get_objects_ref: Arc<Mutex<HashMap<String, (HeaderMap, Vec<u8>)>>> = Arc::default();
+    let put_objects_ref = get_objects_ref.clone();
+
+    let app = Router::new().route(
+        "/*key",
+        get(|Path(key): Path<String>| async move {
+            match get_objects_ref.lock().await.get(&key).cloned() {
+                Some((headers, body)) => (StatusCode::OK, headers, body),
+                None => (StatusCode::NOT_FOUND, HeaderMap::new(), Vec::new()),
+            }
+        })
+        .put(
+            |Path(key): Path<String>, headers: HeaderMap, body: Body| async move {
+                assert!(
+                    headers.get(CONTENT_LENGTH).is_some(),
+                    "expected to get content-length"
+                );
+
+                let stored_headers = headers
+                    .iter()
+                    .filter(|(name, _)| name.as_str().starts_with("x-"))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect::<HeaderMap>();
+
+                let mut buffer = Vec::new();
+                let mut body_stream = body.into_data_stream();
+                while let Some(item) = body_stream.next().await {
+                    buffer.extend_from_slice(&item.unwrap());
+                }
+
+                put_objects_ref
+                    .lock()
+                    .await
+                    .insert(key, (stored_headers, buffer));
+
+                StatusCode::OK
+            },
+        ),
+    );
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use std::process::Command;
+
+#[test]
+fn test_affected_by_inputs_dry_run() -> Result<(), anyhow::Error> {
+    let tempdir = tempfile::tempdir()?;
+    common::setup_fixture("basic_monorepo", "npm@10.5.0", tempdir.path())?;
+
+    Command::new("git")
+        .args(["checkout", "-b", "my-branch"])
+        .current_dir(tempdir.path())
+        .output()?;
+    std::fs::write(tempdir.path().join("packages/util/new.js"), "foo")?;
+
+    let output = assert_cmd::Command::cargo_bin("turbo")?
+        .args(["run", "build", "--affected-by-inputs", "--dry=json"])
+        .current_dir(tempdir.path())
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    println!("stderr: {}", stderr);
+
+    // `my-app` depends on `util`, but `my-app#build` doesn't depend on
+    // `util#build`, so only `util#build` is affected
+    let summary: serde_json::Value = serde_json::from_str(&stdout)?;
+    let tasks = summary["tasks"]
+        .as_array()
+        .expect("tasks should be an array")
+        .iter()
+        .map(|task| {
+            serde_json::json!({
+                "taskId": task["taskId"],
+                "affectedReason": task["affectedReason"],
+            })
+        })
+        .collect::<Vec<_>>();
+    insta::assert_json_snapshot!(tasks);
+
+    Ok(())
+}
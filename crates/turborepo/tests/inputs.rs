@@ -0,0 +1,21 @@
+mod common;
+
+#[test]
+fn test_inputs_suggest() -> Result<(), anyhow::Error> {
+    let tempdir = tempfile::tempdir()?;
+    common::setup_fixture("inputs_suggest", "npm@10.5.0", tempdir.path())?;
+
+    let output = assert_cmd::Command::cargo_bin("turbo")?
+        .args(["inputs", "suggest", "build", "--output", "json"])
+        .current_dir(tempdir.path())
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    println!("stderr: {}", stderr);
+
+    let inputs: serde_json::Value = serde_json::from_str(&stdout)?;
+    insta::assert_json_snapshot!(inputs);
+
+    Ok(())
+}
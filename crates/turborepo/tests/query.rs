@@ -12,6 +12,72 @@ fn test_query() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[test]
+fn test_tasks() -> Result<(), anyhow::Error> {
+    // Task hashes change whenever anything that goes into them does, so they're
+    // compared against a dry run in `test_task_hash_matches_dry_run` instead
+    insta::with_settings!({ filters => vec![(r#""hash": "[0-9a-f]{16}""#, r#""hash": "[task hash]""#)]}, {
+        check_json!(
+            "basic_monorepo",
+            "npm@10.5.0",
+            "query",
+            "get task definition" => "query { task(id: \"my-app#build\") { fullName definition { inputs outputs excludedOutputs env passThroughEnv persistent interruptible interactive cache } } }",
+            "get task hash" => "query { task(id: \"my-app#build\") { fullName hash expandedInputs { items { path hash } } cacheStatus { status source timeSaved } } }",
+            "get task that doesn't exist" => "query { task(id: \"util#doesnotexist\") { fullName } }",
+            "get tasks by name and package" => "query { tasks(filter: { name: \"build\", package: \"util\" }) { items { fullName definition { env } } } }",
+            "get persistent tasks" => "query { tasks(filter: { persistent: true }) { length items { fullName } } }",
+        );
+    });
+
+    Ok(())
+}
+
+#[test]
+fn test_task_hash_matches_dry_run() -> Result<(), anyhow::Error> {
+    let tempdir = tempfile::tempdir()?;
+    common::setup_fixture("basic_monorepo", "npm@10.5.0", tempdir.path())?;
+
+    let output = assert_cmd::Command::cargo_bin("turbo")?
+        .args(["run", "build", "--dry=json"])
+        .current_dir(tempdir.path())
+        .output()?;
+    let dry_run: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+
+    for task in dry_run["tasks"]
+        .as_array()
+        .expect("tasks should be an array")
+    {
+        let task_id = task["taskId"].as_str().expect("taskId should be a string");
+        let output = assert_cmd::Command::cargo_bin("turbo")?
+            .arg("query")
+            .arg(format!(
+                "query {{ task(id: \"{task_id}\") {{ hash expandedInputs {{ items {{ path hash }} \
+                 }} }} }}"
+            ))
+            .current_dir(tempdir.path())
+            .output()?;
+        let query: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        let query_task = &query["data"]["task"];
+
+        assert_eq!(query_task["hash"], task["hash"], "hash of {task_id}");
+        let expanded_inputs = query_task["expandedInputs"]["items"]
+            .as_array()
+            .expect("expandedInputs should be an array")
+            .iter()
+            .map(|input| (input["path"].clone(), input["hash"].clone()))
+            .collect::<Vec<_>>();
+        let inputs = task["inputs"]
+            .as_object()
+            .expect("inputs should be an object")
+            .iter()
+            .map(|(path, hash)| (serde_json::Value::from(path.as_str()), hash.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(expanded_inputs, inputs, "inputs of {task_id}");
+    }
+
+    Ok(())
+}
+
 #[cfg(not(windows))]
 #[test]
 fn test_double_symlink() -> Result<(), anyhow::Error> {
@@ -63,6 +129,14 @@ fn test_trace() -> Result<(), anyhow::Error> {
             "get `import_value_and_type.ts` with type dependencies" => "query { file(path: \"import_value_and_type.ts\") { path dependencies(importType: TYPES) { files { items { path } } } } }",
             "get `import_value_and_type.ts` with value dependencies" => "query { file(path: \"import_value_and_type.ts\") { path dependencies(importType: VALUES) { files { items { path } } } } }",
             "get `incorrect_extension.mjs` with dependencies" =>  "query { file(path: \"incorrect_extension.mjs\") { path dependencies(depth: 1) { files { items { path } } } } }",
+            "get `export_from.ts` with dependencies" => "query { file(path: \"export_from.ts\") { path dependencies { files { items { path } } } } }",
+            "get `require_calls.js` with dependencies" => "query { file(path: \"require_calls.js\") { path dependencies { files { items { path } } } } }",
+            "get `dynamic_import.ts` with dependencies" => "query { file(path: \"dynamic_import.ts\") { path dependencies { files { items { path } } errors { items { import } } } } }",
+            "get `dynamic_import.ts` with dependencies ignoring template imports" => "query { file(path: \"dynamic_import.ts\") { path dependencies(templateImports: IGNORE) { files { items { path } } errors { items { import } } } } }",
+            "get `assets.ts` with dependencies" => "query { file(path: \"assets.ts\") { path kind dependencies { files { items { path kind } } errors { items { message } } } } }",
+            "get `styles.css` with dependencies" => "query { file(path: \"styles.css\") { path kind dependencies { files { items { path kind } } errors { items { message } } } } }",
+            "get `card.vue` with dependencies" => "query { file(path: \"card.vue\") { path kind dependencies { files { items { path kind } } errors { items { message } } } } }",
+            "get `theme.scss` with dependencies" => "query { file(path: \"theme.scss\") { path kind dependencies { files { items { path kind } } errors { items { message } } } } }",
         );
 
         Ok(())
@@ -85,6 +159,22 @@ fn test_trace_on_monorepo() -> Result<(), anyhow::Error> {
     })
 }
 
+#[test]
+fn test_external_dependencies() -> Result<(), anyhow::Error> {
+    check_json!(
+        "external_dependencies",
+        "pnpm@8.0.0",
+        "query",
+        "get `web` with external dependencies" => "query { package(name: \"web\") { name externalDependencies { items { name specifier resolved { name version } } } } }",
+        "get `shared` with resolved dependencies" => "query { package(name: \"shared\") { name externalDependencies { items { name resolved { key version dependencies { items { name specifier } } } } } } }",
+        "get `is-number` usage" => "query { externalPackage(name: \"is-number\") { name versions { length items { version dependents { items { name } } } } } }",
+        "get unused external package" => "query { externalPackage(name: \"lodash\") { name versions { length } } }",
+        "get duplicate external packages" => "query { duplicateExternalPackages { length items { name versions { items { version } } } } }",
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_reverse_trace() -> Result<(), anyhow::Error> {
     check_json!(
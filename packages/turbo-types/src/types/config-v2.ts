@@ -47,11 +47,23 @@ export interface WorkspaceSchema extends BaseSchema {
    * and overrides with the keys provided
    * in your Workspace Configs.
    *
-   * Currently, only the "//" value is allowed.
+   * The first entry must be "//". Other workspace packages can be listed
+   * after it to share task configuration between packages. Their task
+   * definitions are applied in order, after the root and before this package.
    *
    * @defaultValue `["//"]`
    */
   extends: Array<string>;
+
+  /**
+   * Tags for this package that are used by the `boundaries` rules in the
+   * root `turbo.json`.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#tags
+   *
+   * @defaultValue `[]`
+   */
+  tags?: Array<string>;
 }
 
 export interface RootSchema extends BaseSchema {
@@ -135,6 +147,57 @@ export interface RootSchema extends BaseSchema {
    */
   cacheDir?: RelativeUnixPath;
 
+  /**
+   * Maximum size of the filesystem cache, e.g. `"10GB"`.
+   *
+   * Once exceeded, the least recently used artifacts are evicted.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#cachemaxsize
+   */
+  cacheMaxSize?: string;
+
+  /**
+   * Maximum age of an unused filesystem cache artifact, e.g. `"7d"`.
+   *
+   * Artifacts that haven't been written or restored within this window are evicted.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#cachemaxage
+   */
+  cacheMaxAge?: string;
+
+  /**
+   * How artifacts are written to the filesystem cache.
+   *
+   * - `"archive"`: Each task's outputs are stored as a compressed tarball.
+   * - `"deduplicated"`: File contents are stored once by content hash and shared between tasks.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#cachelayout
+   *
+   * @defaultValue `"archive"`
+   */
+  cacheLayout?: "archive" | "deduplicated";
+
+  /**
+   * The total amount of each named resource that running tasks can use,
+   * e.g. `{ "memory": 8 }`. Tasks declare how much of a resource they use
+   * with `resources`.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#resourcelimits
+   *
+   * @defaultValue `{}`
+   */
+  resourceLimits?: Record<string, number>;
+
+  /**
+   * Rules enforced by `turbo boundaries` that restrict which packages can
+   * depend on each other.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#boundaries
+   *
+   * @defaultValue `{}`
+   */
+  boundaries?: BoundariesConfig;
+
   /**
    * Turborepo runs a background process to pre-calculate some expensive operations. This standalone process (daemon) is a performance optimization, and not required for proper functioning of `turbo`.
    *
@@ -295,6 +358,93 @@ export interface Pipeline {
    * @defaultValue `false`
    */
   interruptible?: boolean;
+
+  /**
+   * The maximum amount of time the task is allowed to run (e.g. "10m").
+   * Tasks that run longer are stopped and marked as timed out.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#timeout
+   *
+   * @defaultValue no timeout
+   */
+  timeout?: string;
+
+  /**
+   * How long to wait for a timed out task to exit after interrupting it
+   * before killing it. Use "0s" to kill the task immediately.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#timeoutgraceperiod
+   *
+   * @defaultValue `"500ms"`
+   */
+  timeoutGracePeriod?: string;
+
+  /**
+   * The number of times a failed task is run again before it is reported
+   * as a failure.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#retries
+   *
+   * @defaultValue `0`
+   */
+  retries?: number;
+
+  /**
+   * How long to wait before retrying a failed task (e.g. "5s").
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#retrydelay
+   *
+   * @defaultValue no delay
+   */
+  retryDelay?: string;
+
+  /**
+   * The amount of each named resource the task uses while it runs, e.g.
+   * `{ "memory": 4 }`. Tasks only start once the resources they need are
+   * available within `resourceLimits`.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#resources
+   *
+   * @defaultValue `{}`
+   */
+  resources?: Record<string, number>;
+}
+
+export interface BoundariesConfig {
+  /**
+   * Rules that apply to packages with a given tag, keyed by the tag.
+   */
+  tags?: Record<string, TagRules>;
+
+  /**
+   * Globs of files, relative to the root of the repository, whose
+   * diagnostics are suppressed.
+   */
+  ignore?: Array<string>;
+}
+
+export interface TagRules {
+  /**
+   * Restricts the packages that a package with this tag can depend on.
+   */
+  dependencies?: Permissions;
+
+  /**
+   * Restricts the packages that can depend on a package with this tag.
+   */
+  dependents?: Permissions;
+}
+
+export interface Permissions {
+  /**
+   * If set, packages must have at least one of these tags.
+   */
+  allow?: Array<string>;
+
+  /**
+   * Packages must not have any of these tags.
+   */
+  deny?: Array<string>;
 }
 
 export interface RemoteCache {
@@ -357,6 +507,40 @@ export interface RemoteCache {
    * @defaultValue `60`
    */
   uploadTimeout?: number;
+  /**
+   * Where remote cache artifacts are stored.
+   *
+   * - `"vercel"`: The Vercel Remote Cache, or a server implementing its API at `apiUrl`.
+   * - `"s3"`: An S3-compatible object store. Requires `bucket` and `region`.
+   *   Credentials are read from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`,
+   *   and `AWS_SESSION_TOKEN`.
+   * - `"http"`: A server that stores artifacts with `PUT` and serves them
+   *   with `GET`, such as a WebDAV server. Requires `url`. A bearer token can be
+   *   provided with `TURBO_REMOTE_CACHE_HTTP_TOKEN`.
+   *
+   * The `s3` and `http` backends don't require linking the repository.
+   *
+   * @defaultValue `"vercel"`
+   */
+  backend?: "vercel" | "s3" | "http";
+  /**
+   * For the `http` backend, the URL that artifacts are stored under. For the
+   * `s3` backend, a custom endpoint for S3-compatible stores such as MinIO.
+   */
+  url?: string;
+  /**
+   * The bucket to store artifacts in when using the `s3` backend.
+   */
+  bucket?: string;
+  /**
+   * The region of the bucket when using the `s3` backend.
+   */
+  region?: string;
+  /**
+   * A prefix prepended to the key of every artifact when using the `s3`
+   * backend, e.g. `"turbo/"`.
+   */
+  prefix?: string;
 }
 
 export const isRootSchemaV2 = (schema: Schema): schema is RootSchema =>
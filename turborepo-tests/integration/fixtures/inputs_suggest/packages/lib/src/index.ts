@@ -0,0 +1,3 @@
+import { add } from "./math";
+
+export const sum = (values: number[]) => values.reduce(add, 0);
@@ -0,0 +1 @@
+export const subtract = (a: number, b: number) => a - b;
@@ -0,0 +1,7 @@
+import "./styles.css";
+import data from "./button.json";
+import logo from "./logo.svg";
+
+export const worker = new Worker(new URL("./worker.ts", import.meta.url));
+
+export { data, logo };
@@ -0,0 +1,7 @@
+const locale = process.env.LOCALE ?? "en";
+
+export async function load() {
+  const { default: foo } = await import("./foo");
+  const messages = await import(`./locales/${locale}.json`);
+  return { foo, messages };
+}
@@ -0,0 +1,2 @@
+export * from "./foo";
+export { bar } from "./bar";
@@ -0,0 +1,5 @@
+require("./foo");
+
+module.exports = {
+  barPath: require.resolve("./bar"),
+};
@@ -0,0 +1,3 @@
+self.onmessage = (event: MessageEvent) => {
+  self.postMessage(event.data);
+};
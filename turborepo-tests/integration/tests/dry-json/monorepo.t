@@ -38,6 +38,7 @@ Setup
     "id",
     "monorepo",
     "packages",
+    "schedule",
     "scm",
     "tasks",
     "turboVersion",
@@ -141,6 +141,18 @@ Setup
         }
       }
     ],
+    "schedule": [
+      {
+        "taskId": "build",
+        "estimatedDuration": 1000,
+        "criticalPath": 2000
+      },
+      {
+        "taskId": "test",
+        "estimatedDuration": 1000,
+        "criticalPath": 1000
+      }
+    ],
     "user": ".*", (re)
     "scm": {
       "type": "git",
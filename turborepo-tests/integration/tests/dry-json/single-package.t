@@ -85,6 +85,13 @@ Setup
         }
       }
     ],
+    "schedule": [
+      {
+        "taskId": "build",
+        "estimatedDuration": 1000,
+        "criticalPath": 1000
+      }
+    ],
     "user": ".*", (re)
     "scm": {
       "type": "git",
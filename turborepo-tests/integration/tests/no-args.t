@@ -9,6 +9,7 @@ Make sure exit code is 2 when no args are passed
   
   Commands:
     bin         Get the path to the Turbo binary
+    cache       Manage the local filesystem cache
     completion  Generate the autocompletion script for the specified shell
     daemon      Runs the Turborepo background daemon
     generate    Generate a new app / package
@@ -19,8 +20,10 @@ Make sure exit code is 2 when no args are passed
     login       Login to your Vercel account
     logout      Logout to your Vercel account
     info        Print debugging information
+    inputs      Suggest task inputs based on the files your source code imports
     prune       Prepare a subset of your monorepo
     run         Run tasks across projects in your monorepo
+    runs        Inspect the summaries of past runs saved with `--summarize`
     query       Query your monorepo using GraphQL. If no query is provided, spins up a GraphQL server with GraphiQL
     watch       Arguments used in run and watch
     unlink      Unlink the current directory from your Vercel organization and disable Remote Caching
@@ -80,6 +83,8 @@ Make sure exit code is 2 when no args are passed
             Set the number of concurrent cache operations (default 10) [default: 10]
         --dry-run [<DRY_RUN>]
             [possible values: text, json]
+        --explain[=<EXPLAIN>]
+            Explain why task hashes changed without running any tasks. Compares the inputs of each task against its last run, or against the inputs of a specific task hash when one is provided
         --graph [<GRAPH>]
             Generate a graph of the task execution and output to a file when a filename is specified (.svg, .png, .jpg, .pdf, .json, .html, .mermaid, .dot). Outputs dot graph to stdout when if no filename is provided
         --daemon
@@ -96,10 +101,20 @@ Make sure exit code is 2 when no args are passed
             Execute all tasks in parallel
         --cache-dir <CACHE_DIR>
             Override the filesystem cache directory
+        --cache-max-size <CACHE_MAX_SIZE>
+            Maximum size of the filesystem cache (e.g. "10GB"). Least recently used artifacts are evicted once the limit is exceeded
+        --cache-max-age <CACHE_MAX_AGE>
+            Maximum age of an unused filesystem cache artifact (e.g. "7d"). Older artifacts are evicted after the run
         --concurrency <CONCURRENCY>
             Limit the concurrency of task execution. Use 1 for serial (i.e. one-at-a-time) execution
+        --resource-limit <NAME=AMOUNT>
+            Limit the total amount of a resource used by running tasks (e.g. "memory=8"). Overrides the limit for that resource from `resourceLimits` in turbo.json
+        --critical-path [<BOOL>]
+            Specify whether or not to start tasks with the longest estimated critical path first, based on the durations of previous runs [default: true] [possible values: true, false]
         --continue
             Continue execution even if a task exits with an error or non-zero exit code. The default behavior is to bail
+        --task-timeout <TASK_TIMEOUT>
+            Maximum amount of time a task may run before it is stopped and marked as timed out (e.g. "10m"). Overrides `timeout` in turbo.json for all non-persistent tasks
         --single-package
             Run turbo in single-package mode
         --framework-inference [<BOOL>]
@@ -112,6 +127,8 @@ Make sure exit code is 2 when no args are passed
             Use the given selector to specify package(s) to act as entry points. The syntax mirrors pnpm's syntax, and additional documentation and examples can be found in turbo's documentation https://turbo.build/repo/docs/reference/command-line-reference/run#--filter
         --affected
             Run only tasks that are affected by changes between the current branch and `main`
+        --affected-by-inputs
+            Run only tasks whose inputs, or the inputs of the tasks they depend on, changed between the current branch and `main`
         --output-logs <OUTPUT_LOGS>
             Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) [possible values: full, none, hash-only, new-only, errors-only]
         --log-order <LOG_ORDER>
@@ -304,3 +304,23 @@ Setup
       }
     }
   }
+
+  $ ${TURBO} query "query { task(id: \"app-a#test\") { fullName definition { inputs outputs persistent cache } cacheStatus { status source } } }" | jq
+   WARNING  query command is experimental and may change in the future
+  {
+    "data": {
+      "task": {
+        "fullName": "app-a#test",
+        "definition": {
+          "inputs": [],
+          "outputs": [],
+          "persistent": false,
+          "cache": true
+        },
+        "cacheStatus": {
+          "status": "MISS",
+          "source": null
+        }
+      }
+    }
+  }
@@ -19,6 +19,7 @@ Validate that there was a failed task and exitCode is 1 (which is what we get fr
     "repoPath": "",
     "success": 0,
     "failed": 1,
+    "timedOut": 0,
     "cached": 0,
     "attempted": 1,
     "startTime": [0-9]+, (re)
@@ -101,6 +102,7 @@ success should be 1, and attempted should be 2
     "repoPath": "",
     "success": 1,
     "failed": 1,
+    "timedOut": 0,
     "cached": 0,
     "attempted": 2,
     "startTime": [0-9]+, (re)
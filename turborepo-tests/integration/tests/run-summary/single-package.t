@@ -25,7 +25,8 @@ Check
     "failed",
     "repoPath",
     "startTime",
-    "success"
+    "success",
+    "timedOut"
   ]
 
   $ cat $SUMMARY | jq 'keys'
@@ -135,7 +136,8 @@ Check Rust implementation
     "failed",
     "repoPath",
     "startTime",
-    "success"
+    "success",
+    "timedOut"
   ]
 
   $ cat $SUMMARY | jq 'keys'